@@ -43,6 +43,7 @@
 //! }
 //! ```
 
+use crate::analytics::pricing::PricingCatalog;
 use crate::db::Database;
 use crate::error::{Error, Result};
 use crate::types::{Message, Session, Thread};
@@ -905,6 +906,55 @@ impl AnalyticsEngine {
         db.get_thread_analytics(thread_id)?
             .ok_or_else(|| Error::Config("Failed to compute thread analytics".to_string()))
     }
+
+    /// Ensure thread cost metrics are computed and up-to-date.
+    ///
+    /// This method mirrors `ensure_thread_analytics`, but uses the
+    /// `core.cost` plugin and typed wrapper. Stored costs are also stale when
+    /// they were priced with a catalog other than `pricing`.
+    pub fn ensure_thread_cost(
+        &self,
+        thread_id: &str,
+        db: &Database,
+        pricing: &PricingCatalog,
+    ) -> Result<crate::analytics::CostMetrics> {
+        if let Some(existing) = db.get_thread_cost_metrics(thread_id)? {
+            let fingerprint = pricing.fingerprint();
+            if existing.pricing.as_deref() != Some(fingerprint.as_str()) {
+                tracing::debug!(thread_id, "Prices changed since thread cost, recomputing");
+            } else if let Some(last_msg_ts) = db.get_thread_last_activity(thread_id)? {
+                if existing.computed_at >= last_msg_ts {
+                    tracing::debug!(
+                        thread_id,
+                        computed_at = %existing.computed_at,
+                        "Using cached thread cost"
+                    );
+                    return Ok(existing);
+                }
+                tracing::debug!(
+                    thread_id,
+                    computed_at = %existing.computed_at,
+                    last_msg_ts = %last_msg_ts,
+                    "Thread cost is stale, recomputing"
+                );
+            } else {
+                return Ok(existing);
+            }
+        }
+
+        tracing::info!(thread_id, "Computing thread cost");
+
+        let thread = db
+            .get_thread(thread_id)?
+            .ok_or_else(|| Error::Config(format!("Thread not found: {}", thread_id)))?;
+
+        let messages = db.get_thread_messages(thread_id, 100_000)?;
+
+        self.run_thread_plugin("core.cost", &thread, &messages, db)?;
+
+        db.get_thread_cost_metrics(thread_id)?
+            .ok_or_else(|| Error::Config("Failed to compute thread cost".to_string()))
+    }
 }
 
 impl Default for AnalyticsEngine {
@@ -57,13 +57,37 @@ const FIRST_ORDER_METRICS: &[MetricDescriptor] = &[
         summary: "Total output tokens for the session.",
         description: "Sum of output tokens across all messages in the session.",
     },
+    MetricDescriptor {
+        plugin: "core.first_order",
+        entity_type: "session",
+        name: "tokens_cache_read",
+        value_type: MetricValueType::Integer,
+        summary: "Input tokens served from the prompt cache.",
+        description: "Sum of prompt-cache read tokens across all messages in the session.",
+    },
+    MetricDescriptor {
+        plugin: "core.first_order",
+        entity_type: "session",
+        name: "tokens_cache_write",
+        value_type: MetricValueType::Integer,
+        summary: "Input tokens written to the prompt cache.",
+        description: "Sum of prompt-cache write tokens across all messages in the session.",
+    },
+    MetricDescriptor {
+        plugin: "core.first_order",
+        entity_type: "session",
+        name: "tokens_reasoning",
+        value_type: MetricValueType::Integer,
+        summary: "Output tokens spent on reasoning.",
+        description: "Sum of reasoning tokens across the session (included in tokens_out).",
+    },
     MetricDescriptor {
         plugin: "core.first_order",
         entity_type: "session",
         name: "tokens_total",
         value_type: MetricValueType::Integer,
         summary: "Total tokens for the session.",
-        description: "Sum of input and output tokens across the session.",
+        description: "Sum of input, output and prompt-cache tokens across the session.",
     },
     MetricDescriptor {
         plugin: "core.first_order",
@@ -89,6 +113,14 @@ const FIRST_ORDER_METRICS: &[MetricDescriptor] = &[
         summary: "Total errors in the session.",
         description: "Count of messages classified as error events.",
     },
+    MetricDescriptor {
+        plugin: "core.first_order",
+        entity_type: "session",
+        name: "compaction_count",
+        value_type: MetricValueType::Integer,
+        summary: "Context compactions in the session.",
+        description: "Count of compaction messages, each summarizing the context before it.",
+    },
     MetricDescriptor {
         plugin: "core.first_order",
         entity_type: "session",
@@ -103,7 +135,50 @@ const FIRST_ORDER_METRICS: &[MetricDescriptor] = &[
         name: "tool_success_rate",
         value_type: MetricValueType::Float,
         summary: "Tool success rate for the session.",
-        description: "Ratio of successful tool calls to total tool calls.",
+        description: "Share of tool calls answered by a non-error result.",
+    },
+    MetricDescriptor {
+        plugin: "core.first_order",
+        entity_type: "session",
+        name: "tool_error_count",
+        value_type: MetricValueType::Integer,
+        summary: "Tool calls that failed.",
+        description: "Count of tool calls whose paired result is an error.",
+    },
+    MetricDescriptor {
+        plugin: "core.first_order",
+        entity_type: "session",
+        name: "tool_latency_avg_ms",
+        value_type: MetricValueType::Integer,
+        summary: "Average tool call latency in milliseconds.",
+        description: "Mean time from a tool call to its result; null if none answered.",
+    },
+];
+
+const GIT_METRICS: &[MetricDescriptor] = &[
+    MetricDescriptor {
+        plugin: "core.git",
+        entity_type: "session",
+        name: "git_commits",
+        value_type: MetricValueType::Integer,
+        summary: "Commits that landed files the session edited.",
+        description: "Non-merge commits in the session's repository, committed during or shortly after the session, touching a file it edited.",
+    },
+    MetricDescriptor {
+        plugin: "core.git",
+        entity_type: "session",
+        name: "git_files_committed",
+        value_type: MetricValueType::Integer,
+        summary: "Edited files that were committed.",
+        description: "Distinct files the session edited that appear in a correlated commit.",
+    },
+    MetricDescriptor {
+        plugin: "core.git",
+        entity_type: "session",
+        name: "git_shipped",
+        value_type: MetricValueType::Boolean,
+        summary: "Whether the session's edits landed in a commit.",
+        description: "True when at least one correlated commit was found.",
     },
 ];
 
@@ -114,7 +189,7 @@ const OUTCOME_METRICS: &[MetricDescriptor] = &[
         name: "outcome_success",
         value_type: MetricValueType::Boolean,
         summary: "Prototype boolean outcome for the session.",
-        description: "True when tool results were observed without error messages.",
+        description: "True when correlated commits landed the session's edits, or, without git data, when tool results were observed without error messages.",
     },
     MetricDescriptor {
         plugin: "core.outcome",
@@ -122,7 +197,7 @@ const OUTCOME_METRICS: &[MetricDescriptor] = &[
         name: "outcome_evidence_type",
         value_type: MetricValueType::Text,
         summary: "Reason code supporting the prototype outcome.",
-        description: "Categorical evidence (e.g. tool_result_no_errors, errors_only).",
+        description: "Categorical evidence (e.g. commits_landed, tool_result_no_errors, errors_only).",
     },
     MetricDescriptor {
         plugin: "core.outcome",
@@ -134,8 +209,47 @@ const OUTCOME_METRICS: &[MetricDescriptor] = &[
     },
 ];
 
+const COST_METRICS: &[MetricDescriptor] = &[
+    MetricDescriptor {
+        plugin: "core.cost",
+        entity_type: "session",
+        name: "cost_usd",
+        value_type: MetricValueType::Float,
+        summary: "Estimated cost of the session in USD.",
+        description: "Token usage priced with the pricing catalog for the session's backing model.",
+    },
+    MetricDescriptor {
+        plugin: "core.cost",
+        entity_type: "session",
+        name: "unpriced_tokens",
+        value_type: MetricValueType::Integer,
+        summary: "Tokens in the session that could not be priced.",
+        description: "Tokens excluded from cost_usd because the backing model has no known price.",
+    },
+    MetricDescriptor {
+        plugin: "core.cost",
+        entity_type: "thread",
+        name: "cost_usd",
+        value_type: MetricValueType::Float,
+        summary: "Estimated cost of the thread in USD.",
+        description: "Token usage priced with the pricing catalog for the session's backing model.",
+    },
+    MetricDescriptor {
+        plugin: "core.cost",
+        entity_type: "thread",
+        name: "unpriced_tokens",
+        value_type: MetricValueType::Integer,
+        summary: "Tokens in the thread that could not be priced.",
+        description: "Tokens excluded from cost_usd because the backing model has no known price.",
+    },
+];
+
 fn all_metrics_iter() -> impl Iterator<Item = &'static MetricDescriptor> {
-    FIRST_ORDER_METRICS.iter().chain(OUTCOME_METRICS.iter())
+    FIRST_ORDER_METRICS
+        .iter()
+        .chain(GIT_METRICS.iter())
+        .chain(OUTCOME_METRICS.iter())
+        .chain(COST_METRICS.iter())
 }
 
 /// List all registered metrics.
@@ -222,12 +336,20 @@ mod tests {
     #[test]
     fn test_list_metrics_for_plugin() {
         let metrics = list_metrics_for_plugin("core.first_order");
-        assert_eq!(metrics.len(), 8);
+        assert_eq!(metrics.len(), 14);
         assert!(metrics.iter().any(|m| m.name == "tokens_in"));
 
+        let git_metrics = list_metrics_for_plugin("core.git");
+        assert_eq!(git_metrics.len(), 3);
+        assert!(git_metrics.iter().any(|m| m.name == "git_shipped"));
+
         let outcome_metrics = list_metrics_for_plugin("core.outcome");
         assert_eq!(outcome_metrics.len(), 3);
         assert!(outcome_metrics.iter().any(|m| m.name == "outcome_success"));
+
+        let cost_metrics = list_metrics_for_plugin("core.cost");
+        assert_eq!(cost_metrics.len(), 4);
+        assert!(cost_metrics.iter().any(|m| m.entity_type == "thread"));
     }
 
     #[test]
@@ -22,6 +22,7 @@ pub mod engine;
 pub mod metrics_registry;
 pub mod personality;
 pub mod plugins;
+pub mod pricing;
 pub mod project;
 pub mod wrapped;
 
@@ -36,7 +37,10 @@ pub use metrics_registry::{
     list_metrics, list_metrics_for_entity, list_metrics_for_plugin, search_metrics,
     search_metrics_with_scoring, MetricDescriptor, MetricSearchResult, MetricValueType,
 };
-pub use plugins::{create_default_engine, create_default_engine_with_config};
+pub use plugins::{
+    create_default_engine, create_default_engine_with_config, create_engine_with_pricing,
+};
+pub use pricing::{CostEstimate, ModelPrice, PricingCatalog};
 
 // Session analytics struct
 use chrono::{DateTime, Utc};
@@ -71,7 +75,13 @@ pub struct FirstOrderSessionMetrics {
     pub tokens_in: i64,
     /// Total output tokens
     pub tokens_out: i64,
-    /// Total tokens (input + output)
+    /// Total input tokens served from the prompt cache
+    pub tokens_cache_read: i64,
+    /// Total input tokens written to the prompt cache
+    pub tokens_cache_write: i64,
+    /// Total reasoning tokens (part of `tokens_out`)
+    pub tokens_reasoning: i64,
+    /// Total tokens (input + output + cache reads and writes)
     pub tokens_total: i64,
     /// Total tool calls
     pub tool_call_count: i64,
@@ -79,10 +89,16 @@ pub struct FirstOrderSessionMetrics {
     pub tool_call_breakdown: std::collections::HashMap<String, i64>,
     /// Total error messages
     pub error_count: i64,
+    /// Times the context window was compacted
+    pub compaction_count: i64,
     /// Session duration in milliseconds
     pub duration_ms: i64,
-    /// Tool result count divided by tool call count
+    /// Share of tool calls that succeeded (see `core.first_order`)
     pub tool_success_rate: f64,
+    /// Tool calls whose result is an error
+    pub tool_error_count: i64,
+    /// Average time from a tool call to its result
+    pub tool_latency_avg_ms: Option<i64>,
     /// When these metrics were computed
     pub computed_at: DateTime<Utc>,
 }
@@ -115,10 +131,23 @@ pub struct ThreadAnalytics {
     pub computed_at: DateTime<Utc>,
 }
 
+/// Estimated cost of a session or thread.
+///
+/// Contains metrics from the `core.cost` plugin.
+#[derive(Debug, Clone)]
+pub struct CostMetrics {
+    /// Estimated cost and unpriced token count
+    pub estimate: CostEstimate,
+    /// [`PricingCatalog::fingerprint`] of the prices the estimate used
+    pub pricing: Option<String>,
+    /// When these metrics were computed
+    pub computed_at: DateTime<Utc>,
+}
+
 // Existing exports
 pub use dashboard::DashboardStats;
 pub use personality::Personality;
-pub use project::{ProjectRow, ProjectStats};
+pub use project::{list_projects, project_stats, ProjectRow, ProjectStats};
 pub use wrapped::{
     generate_wrapped, MarathonSession, ProjectRanking, StreakStats, TimePatterns, ToolRankings,
     TotalStats, TrendComparison, WrappedConfig, WrappedPeriod, WrappedStats,
@@ -144,3 +173,13 @@ pub fn ensure_thread_analytics(thread_id: &str, db: &Database) -> Result<ThreadA
     let engine = create_default_engine();
     engine.ensure_thread_analytics(thread_id, db)
 }
+
+/// Ensure thread cost metrics using the built-in plugins and `pricing`.
+pub fn ensure_thread_cost(
+    thread_id: &str,
+    db: &Database,
+    pricing: &PricingCatalog,
+) -> Result<CostMetrics> {
+    let engine = create_engine_with_pricing(pricing.clone());
+    engine.ensure_thread_cost(thread_id, db, pricing)
+}
@@ -0,0 +1,256 @@
+//! Cost Estimator
+//!
+//! Estimates what a session or thread would cost at list prices, using a
+//! [`PricingCatalog`]. Each message is priced by the model recorded on it
+//! (see [`Message::model_id`]), falling back to the session's backing model,
+//! at the rate in effect on the (UTC) day it was emitted.
+//!
+//! ## Metrics Produced
+//!
+//! For each session and thread:
+//!
+//! | Metric | Type | Description |
+//! |--------|------|-------------|
+//! | `cost_usd` | float | Estimated cost of the priced tokens in USD |
+//! | `unpriced_tokens` | integer | Tokens with no known price (unknown model) |
+//! | `pricing` | string | [`PricingCatalog::fingerprint`] of the prices used |
+
+use crate::analytics::engine::{AnalyticsContext, AnalyticsPlugin, AnalyticsTrigger, MetricOutput};
+use crate::analytics::pricing::{CostEstimate, PricingCatalog};
+use crate::db::TokenUsage;
+use crate::error::Result;
+use crate::types::{Message, Session, Thread};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+/// Plugin that estimates per-session cost from token usage and the pricing catalog.
+pub struct CostEstimator {
+    pricing: PricingCatalog,
+}
+
+impl CostEstimator {
+    /// Create an estimator using the built-in prices.
+    pub fn new() -> Self {
+        Self::with_pricing(PricingCatalog::builtin())
+    }
+
+    /// Create an estimator using `pricing`.
+    pub fn with_pricing(pricing: PricingCatalog) -> Self {
+        Self { pricing }
+    }
+
+    /// Estimate `messages`, pricing each at the model recorded on it, or at
+    /// `fallback_model_id` when it has none.
+    fn estimate(&self, fallback_model_id: Option<&str>, messages: &[Message]) -> CostEstimate {
+        let mut by_model_day: BTreeMap<(Option<&str>, NaiveDate), TokenUsage> = BTreeMap::new();
+        for msg in messages {
+            let model_id = msg.model_id().or(fallback_model_id);
+            let usage = by_model_day
+                .entry((model_id, msg.emitted_at.date_naive()))
+                .or_default();
+            usage.tokens_in += msg.tokens_in.unwrap_or(0) as i64;
+            usage.tokens_out += msg.tokens_out.unwrap_or(0) as i64;
+            usage.tokens_cache_read += msg.tokens_cache_read.unwrap_or(0) as i64;
+            usage.tokens_cache_write += msg.tokens_cache_write.unwrap_or(0) as i64;
+        }
+
+        let mut total = CostEstimate::default();
+        for ((model_id, day), usage) in &by_model_day {
+            total.add(self.pricing.estimate(*model_id, *day, usage));
+        }
+        total
+    }
+}
+
+impl Default for CostEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalyticsPlugin for CostEstimator {
+    fn name(&self) -> &str {
+        "core.cost"
+    }
+
+    fn triggers(&self) -> Vec<AnalyticsTrigger> {
+        vec![AnalyticsTrigger::OnDemand]
+    }
+
+    fn analyze_session(
+        &self,
+        session: &Session,
+        messages: &[Message],
+        _ctx: &AnalyticsContext,
+    ) -> Result<Vec<MetricOutput>> {
+        let cost = self.estimate(session.backing_model_id.as_deref(), messages);
+
+        Ok(vec![
+            MetricOutput::session(&session.id, "cost_usd", cost.usd.into()),
+            MetricOutput::session(&session.id, "unpriced_tokens", cost.unpriced_tokens.into()),
+            MetricOutput::session(&session.id, "pricing", self.pricing.fingerprint().into()),
+        ])
+    }
+
+    fn supports_thread_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze_thread(
+        &self,
+        thread: &Thread,
+        messages: &[Message],
+        ctx: &AnalyticsContext,
+    ) -> Result<Vec<MetricOutput>> {
+        let model_id = ctx
+            .db
+            .get_session(&thread.session_id)?
+            .and_then(|session| session.backing_model_id);
+        let cost = self.estimate(model_id.as_deref(), messages);
+
+        Ok(vec![
+            MetricOutput::thread(&thread.id, "cost_usd", cost.usd.into()),
+            MetricOutput::thread(&thread.id, "unpriced_tokens", cost.unpriced_tokens.into()),
+            MetricOutput::thread(&thread.id, "pricing", self.pricing.fingerprint().into()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::types::{Assistant, AuthorRole, MessageType, SessionStatus};
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde_json::json;
+
+    fn make_session(model: Option<&str>) -> Session {
+        let now = Utc::now();
+        Session {
+            id: "session-1".to_string(),
+            assistant: Assistant::ClaudeCode,
+            backing_model_id: model.map(|m| m.to_string()),
+            project_id: None,
+            started_at: now,
+            last_activity_at: Some(now),
+            status: SessionStatus::Active,
+            source_file_path: "source.jsonl".to_string(),
+            metadata: json!({}),
+        }
+    }
+
+    fn make_message(seq: i32, emitted_at: DateTime<Utc>, tokens_in: i32) -> Message {
+        Message {
+            id: seq as i64,
+            session_id: "session-1".to_string(),
+            thread_id: "thread-1".to_string(),
+            seq,
+            emitted_at,
+            observed_at: emitted_at,
+            author_role: AuthorRole::Assistant,
+            author_name: None,
+            message_type: MessageType::Response,
+            content: None,
+            content_type: None,
+            tool_name: None,
+            tool_input: None,
+            tool_result: None,
+            tool_call_id: None,
+            tokens_in: Some(tokens_in),
+            tokens_out: Some(0),
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
+            duration_ms: None,
+            source_file_path: "source.jsonl".to_string(),
+            source_offset: 0,
+            source_line: None,
+            record_id: None,
+            parent_record_id: None,
+            raw_data: json!({}),
+            metadata: json!({}),
+        }
+    }
+
+    fn metrics(outputs: Vec<MetricOutput>) -> std::collections::HashMap<String, serde_json::Value> {
+        outputs
+            .into_iter()
+            .map(|o| (o.metric_name, o.metric_value))
+            .collect()
+    }
+
+    #[test]
+    fn test_cost_uses_price_in_effect_per_day() {
+        let session = make_session(Some("google:gemini-2.5-flash"));
+        // One million input tokens on each side of the 2025-06-17 price change
+        let messages = vec![
+            make_message(
+                1,
+                Utc.with_ymd_and_hms(2025, 6, 16, 23, 0, 0).unwrap(),
+                1_000_000,
+            ),
+            make_message(
+                2,
+                Utc.with_ymd_and_hms(2025, 6, 17, 1, 0, 0).unwrap(),
+                1_000_000,
+            ),
+        ];
+
+        let db = Database::open_in_memory().expect("db");
+        db.migrate().expect("migrate");
+        let ctx = AnalyticsContext { db: &db };
+        let values = metrics(
+            CostEstimator::new()
+                .analyze_session(&session, &messages, &ctx)
+                .expect("analysis succeeds"),
+        );
+
+        let cost = values["cost_usd"].as_f64().unwrap();
+        assert!((cost - (0.15 + 0.3)).abs() < 1e-9, "{}", cost);
+        assert_eq!(values["unpriced_tokens"].as_i64(), Some(0));
+    }
+
+    #[test]
+    fn test_cost_uses_each_messages_model() {
+        let session = make_session(Some("google:gemini-2.5-flash"));
+        let day = Utc.with_ymd_and_hms(2025, 7, 1, 12, 0, 0).unwrap();
+        let mut switched = make_message(2, day, 1_000_000);
+        switched.metadata = json!({ "model": "google:gemini-2.5-pro" });
+        let messages = vec![make_message(1, day, 1_000_000), switched];
+
+        let db = Database::open_in_memory().expect("db");
+        db.migrate().expect("migrate");
+        let ctx = AnalyticsContext { db: &db };
+        let values = metrics(
+            CostEstimator::new()
+                .analyze_session(&session, &messages, &ctx)
+                .expect("analysis succeeds"),
+        );
+
+        // One million input tokens at the flash rate and one at the pro rate
+        let cost = values["cost_usd"].as_f64().unwrap();
+        assert!((cost - (0.3 + 1.25)).abs() < 1e-9, "{}", cost);
+        assert_eq!(
+            values["pricing"].as_str(),
+            Some(PricingCatalog::builtin().fingerprint().as_str())
+        );
+    }
+
+    #[test]
+    fn test_unknown_model_reports_unpriced_tokens() {
+        let session = make_session(None);
+        let messages = vec![make_message(1, Utc::now(), 500)];
+
+        let db = Database::open_in_memory().expect("db");
+        db.migrate().expect("migrate");
+        let ctx = AnalyticsContext { db: &db };
+        let values = metrics(
+            CostEstimator::new()
+                .analyze_session(&session, &messages, &ctx)
+                .expect("analysis succeeds"),
+        );
+
+        assert_eq!(values["cost_usd"].as_f64(), Some(0.0));
+        assert_eq!(values["unpriced_tokens"].as_i64(), Some(500));
+    }
+}
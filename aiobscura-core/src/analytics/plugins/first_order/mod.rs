@@ -1,7 +1,7 @@
 //! First-order session metrics plugin.
 
 use crate::analytics::engine::{AnalyticsContext, AnalyticsPlugin, AnalyticsTrigger, MetricOutput};
-use crate::types::{Message, MessageType, Session};
+use crate::types::{Message, MessageType, Session, ToolCallOutcome};
 use crate::Result;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
@@ -16,9 +16,13 @@ impl FirstOrderMetrics {
     fn compute_metrics(messages: &[Message]) -> FirstOrderSummary {
         let mut tokens_in: i64 = 0;
         let mut tokens_out: i64 = 0;
+        let mut tokens_cache_read: i64 = 0;
+        let mut tokens_cache_write: i64 = 0;
+        let mut tokens_reasoning: i64 = 0;
         let mut tool_call_count: i64 = 0;
         let mut tool_result_count: i64 = 0;
         let mut error_count: i64 = 0;
+        let mut compaction_count: i64 = 0;
         let mut tool_breakdown: HashMap<String, i64> = HashMap::new();
         let mut min_ts: Option<DateTime<Utc>> = None;
         let mut max_ts: Option<DateTime<Utc>> = None;
@@ -26,6 +30,9 @@ impl FirstOrderMetrics {
         for msg in messages {
             tokens_in += msg.tokens_in.unwrap_or(0) as i64;
             tokens_out += msg.tokens_out.unwrap_or(0) as i64;
+            tokens_cache_read += msg.tokens_cache_read.unwrap_or(0) as i64;
+            tokens_cache_write += msg.tokens_cache_write.unwrap_or(0) as i64;
+            tokens_reasoning += msg.tokens_reasoning.unwrap_or(0) as i64;
 
             match msg.message_type {
                 MessageType::ToolCall => {
@@ -42,6 +49,9 @@ impl FirstOrderMetrics {
                 MessageType::Error => {
                     error_count += 1;
                 }
+                MessageType::Compaction => {
+                    compaction_count += 1;
+                }
                 _ => {}
             }
 
@@ -55,21 +65,41 @@ impl FirstOrderMetrics {
             _ => 0,
         };
 
-        let tool_success_rate = if tool_call_count > 0 {
+        // Calls paired with their results by call id, when the assistant
+        // records one; otherwise fall back to comparing counts.
+        let outcomes = ToolCallOutcome::pair(messages);
+        let tool_success_rate = if !outcomes.is_empty() {
+            let succeeded = outcomes
+                .iter()
+                .filter(|o| o.is_error == Some(false))
+                .count();
+            succeeded as f64 / outcomes.len() as f64
+        } else if tool_call_count > 0 {
             tool_result_count as f64 / tool_call_count as f64
         } else {
             0.0
         };
+        let tool_error_count = outcomes.iter().filter(|o| o.is_error == Some(true)).count() as i64;
+        let latencies: Vec<i64> = outcomes.iter().filter_map(|o| o.latency_ms).collect();
+        let tool_latency_avg_ms =
+            (!latencies.is_empty()).then(|| latencies.iter().sum::<i64>() / latencies.len() as i64);
 
         FirstOrderSummary {
             tokens_in,
             tokens_out,
-            tokens_total: tokens_in + tokens_out,
+            tokens_cache_read,
+            tokens_cache_write,
+            tokens_reasoning,
+            // Reasoning tokens are already part of tokens_out
+            tokens_total: tokens_in + tokens_out + tokens_cache_read + tokens_cache_write,
             tool_call_count,
             tool_breakdown,
             error_count,
+            compaction_count,
             duration_ms,
             tool_success_rate,
+            tool_error_count,
+            tool_latency_avg_ms,
         }
     }
 }
@@ -100,6 +130,21 @@ impl AnalyticsPlugin for FirstOrderMetrics {
         Ok(vec![
             MetricOutput::session(&session.id, "tokens_in", metrics.tokens_in.into()),
             MetricOutput::session(&session.id, "tokens_out", metrics.tokens_out.into()),
+            MetricOutput::session(
+                &session.id,
+                "tokens_cache_read",
+                metrics.tokens_cache_read.into(),
+            ),
+            MetricOutput::session(
+                &session.id,
+                "tokens_cache_write",
+                metrics.tokens_cache_write.into(),
+            ),
+            MetricOutput::session(
+                &session.id,
+                "tokens_reasoning",
+                metrics.tokens_reasoning.into(),
+            ),
             MetricOutput::session(&session.id, "tokens_total", metrics.tokens_total.into()),
             MetricOutput::session(
                 &session.id,
@@ -112,12 +157,27 @@ impl AnalyticsPlugin for FirstOrderMetrics {
                 serde_json::to_value(metrics.tool_breakdown)?,
             ),
             MetricOutput::session(&session.id, "error_count", metrics.error_count.into()),
+            MetricOutput::session(
+                &session.id,
+                "compaction_count",
+                metrics.compaction_count.into(),
+            ),
             MetricOutput::session(&session.id, "duration_ms", metrics.duration_ms.into()),
             MetricOutput::session(
                 &session.id,
                 "tool_success_rate",
                 metrics.tool_success_rate.into(),
             ),
+            MetricOutput::session(
+                &session.id,
+                "tool_error_count",
+                metrics.tool_error_count.into(),
+            ),
+            MetricOutput::session(
+                &session.id,
+                "tool_latency_avg_ms",
+                metrics.tool_latency_avg_ms.into(),
+            ),
         ])
     }
 }
@@ -126,12 +186,18 @@ impl AnalyticsPlugin for FirstOrderMetrics {
 struct FirstOrderSummary {
     tokens_in: i64,
     tokens_out: i64,
+    tokens_cache_read: i64,
+    tokens_cache_write: i64,
+    tokens_reasoning: i64,
     tokens_total: i64,
     tool_call_count: i64,
     tool_breakdown: HashMap<String, i64>,
     error_count: i64,
+    compaction_count: i64,
     duration_ms: i64,
     tool_success_rate: f64,
+    tool_error_count: i64,
+    tool_latency_avg_ms: Option<i64>,
 }
 
 #[cfg(test)]
@@ -180,12 +246,18 @@ mod tests {
             tool_name: tool_name.map(|name| name.to_string()),
             tool_input: None,
             tool_result: None,
+            tool_call_id: None,
             tokens_in,
             tokens_out,
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
             duration_ms: None,
             source_file_path: "source.jsonl".to_string(),
             source_offset: 0,
             source_line: None,
+            record_id: None,
+            parent_record_id: None,
             raw_data: json!({}),
             metadata: json!({}),
         }
@@ -228,6 +300,14 @@ mod tests {
                 None,
                 None,
             ),
+            make_message(
+                5,
+                MessageType::Compaction,
+                start + Duration::seconds(2),
+                None,
+                None,
+                None,
+            ),
         ];
 
         let plugin = FirstOrderMetrics::new();
@@ -255,6 +335,10 @@ mod tests {
             Some(2)
         );
         assert_eq!(values.get("error_count").and_then(|v| v.as_i64()), Some(1));
+        assert_eq!(
+            values.get("compaction_count").and_then(|v| v.as_i64()),
+            Some(1)
+        );
         assert_eq!(
             values.get("duration_ms").and_then(|v| v.as_i64()),
             Some(2000)
@@ -273,4 +357,51 @@ mod tests {
         assert_eq!(breakdown.get("rg").and_then(|v| v.as_i64()), Some(1));
         assert_eq!(breakdown.get("cat").and_then(|v| v.as_i64()), Some(1));
     }
+
+    #[test]
+    fn test_first_order_pairs_tool_calls_by_id() {
+        let start = Utc::now();
+        let message = |seq, message_type, secs, call_id: &str| {
+            let mut msg = make_message(
+                seq,
+                message_type,
+                start + Duration::seconds(secs),
+                None,
+                None,
+                Some("Bash"),
+            );
+            msg.tool_call_id = Some(call_id.to_string());
+            msg
+        };
+        let messages = vec![
+            message(1, MessageType::ToolCall, 0, "a"),
+            message(2, MessageType::ToolCall, 0, "b"),
+            message(3, MessageType::ToolCall, 0, "c"),
+            message(4, MessageType::ToolResult, 1, "a"),
+            message(5, MessageType::Error, 3, "b"),
+            // A result for a call outside these messages is ignored
+            message(6, MessageType::ToolResult, 4, "z"),
+        ];
+
+        let summary = FirstOrderMetrics::compute_metrics(&messages);
+        assert_eq!(summary.tool_call_count, 3);
+        assert!((summary.tool_success_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(summary.tool_error_count, 1);
+        assert_eq!(summary.tool_latency_avg_ms, Some(2000));
+    }
+
+    #[test]
+    fn test_first_order_metrics_cache_tokens() {
+        let start = Utc::now();
+        let mut message = make_message(1, MessageType::Response, start, Some(10), Some(5), None);
+        message.tokens_cache_read = Some(900);
+        message.tokens_cache_write = Some(100);
+        message.tokens_reasoning = Some(3);
+
+        let summary = FirstOrderMetrics::compute_metrics(&[message]);
+        assert_eq!(summary.tokens_cache_read, 900);
+        assert_eq!(summary.tokens_cache_write, 100);
+        assert_eq!(summary.tokens_reasoning, 3);
+        assert_eq!(summary.tokens_total, 1015);
+    }
 }
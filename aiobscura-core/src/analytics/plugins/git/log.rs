@@ -0,0 +1,110 @@
+//! Reading commits with the `git` CLI
+
+use crate::ingest::project::git_output;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A commit and the files it touched.
+#[derive(Debug, Clone)]
+pub(super) struct Commit {
+    pub sha: String,
+    pub committed_at: DateTime<Utc>,
+    pub author_name: String,
+    pub author_email: String,
+    pub summary: String,
+    /// Paths relative to the repository root
+    pub files: Vec<String>,
+}
+
+/// Record and field separators in the `git log` format, which can't occur
+/// in commit metadata. `-z` ends the header and each file name with NUL, so
+/// paths are printed unquoted.
+const RECORD_SEP: char = '\u{1e}';
+const FIELD_SEP: char = '\u{1f}';
+const NAME_SEP: char = '\0';
+
+/// Root of the repository containing `dir`, or `None` if it isn't in one
+/// (or `git` isn't installed).
+pub(super) fn repo_root(dir: &Path) -> Option<PathBuf> {
+    git_output(dir, &["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+/// Non-merge commits on any local branch committed between `since` and
+/// `until`, oldest first.
+pub(super) fn commits_between(
+    root: &Path,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> std::io::Result<Vec<Commit>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "log",
+            "--branches",
+            "--no-merges",
+            "--reverse",
+            "--name-only",
+            "-z",
+        ])
+        .arg(format!("--since={}", since.to_rfc3339()))
+        .arg(format!("--until={}", until.to_rfc3339()))
+        .arg("--format=%x1e%H%x1f%cI%x1f%an%x1f%ae%x1f%s")
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let mut names = record.split(NAME_SEP);
+            let mut fields = names.next()?.trim_end_matches('\n').split(FIELD_SEP);
+            let sha = fields.next()?.to_string();
+            let committed_at = DateTime::parse_from_rfc3339(fields.next()?)
+                .ok()?
+                .with_timezone(&Utc);
+            Some(Commit {
+                sha,
+                committed_at,
+                author_name: fields.next().unwrap_or_default().to_string(),
+                author_email: fields.next().unwrap_or_default().to_string(),
+                summary: fields.next().unwrap_or_default().to_string(),
+                // The file list starts on a new line
+                files: names
+                    .map(|name| name.strip_prefix('\n').unwrap_or(name))
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log() {
+        let output = "\u{1e}abc123\u{1f}2025-12-06T10:00:00+01:00\u{1f}Ada\u{1f}ada@example.com\u{1f}Fix parser\0\nsrc/a.rs\0src/caf\u{e9} \"b\".rs\0\
+                      \u{1e}def456\u{1f}2025-12-06T11:00:00Z\u{1f}Ada\u{1f}ada@example.com\u{1f}Empty\0";
+        let commits = parse_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].sha, "abc123");
+        assert_eq!(
+            commits[0].committed_at.to_rfc3339(),
+            "2025-12-06T09:00:00+00:00"
+        );
+        assert_eq!(commits[0].summary, "Fix parser");
+        assert_eq!(commits[0].files, vec!["src/a.rs", "src/caf\u{e9} \"b\".rs"]);
+        assert!(commits[1].files.is_empty());
+    }
+}
@@ -0,0 +1,392 @@
+//! Git Correlation
+//!
+//! Links a session to the commits that landed its edits, answering "did this
+//! session ship anything". Uses the `git` CLI; sessions outside a repository
+//! (or without `git` installed) produce no metrics.
+//!
+//! ## Algorithm
+//!
+//! 1. Collect the files the session edited from Edit/Write/MultiEdit/
+//!    NotebookEdit tool inputs and Codex `apply_patch` patches.
+//! 2. Find the repository from the session's `cwd` (falling back to the
+//!    project path).
+//! 3. Walk the non-merge commits on every local branch committed between the
+//!    session start and [`COMMIT_GRACE_HOURS`] after its last activity.
+//! 4. Keep the commits that touch at least one edited file.
+//!
+//! Matching is by file, so a later commit by someone else to the same file
+//! inside the window also matches.
+//!
+//! ## Metrics Produced
+//!
+//! | Metric | Type | Description |
+//! |--------|------|-------------|
+//! | `git_commits` | integer | Commits touching files the session edited |
+//! | `git_files_committed` | integer | Edited files that appear in those commits |
+//! | `git_shipped` | boolean | Whether any commit landed the session's edits |
+//!
+//! The matched commits are stored in `session_commits`.
+
+mod log;
+
+use crate::analytics::engine::{AnalyticsContext, AnalyticsPlugin, AnalyticsTrigger, MetricOutput};
+use crate::error::Result;
+use crate::types::{Message, MessageType, Session, SessionCommit};
+use chrono::Duration;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// How long after a session's last activity a commit still counts.
+pub const COMMIT_GRACE_HOURS: i64 = 8;
+
+/// Prefixes of the lines naming files in an `apply_patch` patch.
+const PATCH_FILE_PREFIXES: &[&str] = &[
+    "*** Add File: ",
+    "*** Update File: ",
+    "*** Delete File: ",
+    "*** Move to: ",
+];
+
+/// Plugin that correlates sessions with git commits.
+pub struct GitCorrelator;
+
+impl GitCorrelator {
+    /// Create a new correlator.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Paths the session's tool calls wrote to, as given in the tool input.
+    fn edited_files(messages: &[Message]) -> BTreeSet<String> {
+        let mut files = BTreeSet::new();
+        for message in messages {
+            if message.message_type != MessageType::ToolCall {
+                continue;
+            }
+            let Some(input) = &message.tool_input else {
+                continue;
+            };
+            match message.tool_name.as_deref() {
+                Some("Edit" | "Write" | "MultiEdit" | "NotebookEdit" | "edit" | "write") => {
+                    let path = ["file_path", "filePath", "notebook_path"]
+                        .iter()
+                        .find_map(|key| input.get(key).and_then(|v| v.as_str()));
+                    files.extend(path.map(String::from));
+                }
+                Some("apply_patch") => {
+                    let patch = input
+                        .get("input")
+                        .or_else(|| input.get("patch"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default();
+                    files.extend(patch.lines().filter_map(|line| {
+                        PATCH_FILE_PREFIXES
+                            .iter()
+                            .find_map(|prefix| line.strip_prefix(prefix))
+                            .map(|path| path.trim().to_string())
+                    }));
+                }
+                _ => {}
+            }
+        }
+        files
+    }
+
+    /// Directory the session ran in: its `cwd`, else its project's path.
+    fn working_dir(session: &Session, ctx: &AnalyticsContext) -> Result<Option<PathBuf>> {
+        if let Some(cwd) = session.metadata.get("cwd").and_then(|v| v.as_str()) {
+            let cwd = PathBuf::from(cwd);
+            if cwd.is_dir() {
+                return Ok(Some(cwd));
+            }
+        }
+        let Some(project_id) = &session.project_id else {
+            return Ok(None);
+        };
+        Ok(ctx
+            .db
+            .get_project(project_id)?
+            .map(|project| project.path)
+            .filter(|path| path.is_dir()))
+    }
+
+    /// `path` relative to the repository root, resolving relative paths
+    /// against `cwd`.
+    ///
+    /// `git` reports the root with symlinks resolved, so the resolved forms
+    /// of the path are tried too.
+    fn relative_to_root(path: &str, cwd: &Path, root: &Path) -> Option<String> {
+        let path = cwd.join(path);
+        let resolved_parent = path
+            .parent()
+            .and_then(|parent| parent.canonicalize().ok())
+            .zip(path.file_name())
+            .map(|(parent, name)| parent.join(name));
+        [
+            Some(path.clone()),
+            path.canonicalize().ok(),
+            resolved_parent,
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|candidate| {
+            candidate
+                .strip_prefix(root)
+                .ok()
+                .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        })
+    }
+}
+
+impl Default for GitCorrelator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalyticsPlugin for GitCorrelator {
+    fn name(&self) -> &str {
+        "core.git"
+    }
+
+    fn triggers(&self) -> Vec<AnalyticsTrigger> {
+        vec![AnalyticsTrigger::OnDemand]
+    }
+
+    fn analyze_session(
+        &self,
+        session: &Session,
+        messages: &[Message],
+        ctx: &AnalyticsContext,
+    ) -> Result<Vec<MetricOutput>> {
+        let Some(cwd) = Self::working_dir(session, ctx)? else {
+            return Ok(vec![]);
+        };
+        let Some(root) = log::repo_root(&cwd) else {
+            return Ok(vec![]);
+        };
+
+        let edited: HashSet<String> = Self::edited_files(messages)
+            .iter()
+            .filter_map(|path| Self::relative_to_root(path, &cwd, &root))
+            .collect();
+
+        let mut commits = Vec::new();
+        if !edited.is_empty() {
+            let until = session.last_activity_at.unwrap_or(session.started_at)
+                + Duration::hours(COMMIT_GRACE_HOURS);
+            for commit in log::commits_between(&root, session.started_at, until)? {
+                let matched_files: Vec<String> = commit
+                    .files
+                    .into_iter()
+                    .filter(|file| edited.contains(file))
+                    .collect();
+                if matched_files.is_empty() {
+                    continue;
+                }
+                commits.push(SessionCommit {
+                    session_id: session.id.clone(),
+                    commit_sha: commit.sha,
+                    repo_path: root.clone(),
+                    committed_at: commit.committed_at,
+                    author_name: Some(commit.author_name).filter(|s| !s.is_empty()),
+                    author_email: Some(commit.author_email).filter(|s| !s.is_empty()),
+                    summary: commit.summary,
+                    matched_files,
+                });
+            }
+        }
+        ctx.db.replace_session_commits(&session.id, &commits)?;
+
+        let files_committed: HashSet<&String> =
+            commits.iter().flat_map(|c| &c.matched_files).collect();
+        Ok(vec![
+            MetricOutput::session(&session.id, "git_commits", commits.len().into()),
+            MetricOutput::session(
+                &session.id,
+                "git_files_committed",
+                files_committed.len().into(),
+            ),
+            MetricOutput::session(&session.id, "git_shipped", (!commits.is_empty()).into()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::plugins::test_support::store_session;
+    use crate::types::{Assistant, AuthorRole, SessionStatus};
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde_json::json;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str], date: DateTime<Utc>) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .env("GIT_AUTHOR_DATE", date.to_rfc3339())
+            .env("GIT_COMMITTER_DATE", date.to_rfc3339())
+            .status()
+            .expect("git should run");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn commit_file(dir: &Path, file: &str, summary: &str, date: DateTime<Utc>) {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, summary).unwrap();
+        git(dir, &["add", file], date);
+        git(dir, &["commit", "-q", "-m", summary], date);
+    }
+
+    fn make_session(cwd: &Path, start: DateTime<Utc>) -> Session {
+        Session {
+            id: "session-git".to_string(),
+            assistant: Assistant::ClaudeCode,
+            backing_model_id: None,
+            project_id: None,
+            started_at: start,
+            last_activity_at: Some(start + Duration::hours(1)),
+            status: SessionStatus::Stale,
+            source_file_path: "source.jsonl".to_string(),
+            metadata: json!({"cwd": cwd.to_string_lossy()}),
+        }
+    }
+
+    fn make_tool_call(tool_name: &str, tool_input: serde_json::Value) -> Message {
+        let now = Utc::now();
+        Message {
+            id: 1,
+            session_id: "session-git".to_string(),
+            thread_id: "thread-1".to_string(),
+            seq: 1,
+            emitted_at: now,
+            observed_at: now,
+            author_role: AuthorRole::Assistant,
+            author_name: None,
+            message_type: MessageType::ToolCall,
+            content: None,
+            content_type: None,
+            tool_name: Some(tool_name.to_string()),
+            tool_input: Some(tool_input),
+            tool_result: None,
+            tool_call_id: None,
+            tokens_in: None,
+            tokens_out: None,
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
+            duration_ms: None,
+            source_file_path: "source.jsonl".to_string(),
+            source_offset: 0,
+            source_line: None,
+            record_id: None,
+            parent_record_id: None,
+            raw_data: json!({}),
+            metadata: json!({}),
+        }
+    }
+
+    #[test]
+    fn test_edited_files_from_edits_and_patches() {
+        let messages = vec![
+            make_tool_call("Edit", json!({"file_path": "/repo/src/a.rs"})),
+            make_tool_call("Write", json!({"file_path": "/repo/src/b.rs"})),
+            make_tool_call(
+                "apply_patch",
+                json!({"input": "*** Begin Patch\n*** Update File: src/c.rs\n@@\n-x\n+y\n*** Add File: src/d.rs\n+z\n*** End Patch"}),
+            ),
+            make_tool_call("Read", json!({"file_path": "/repo/src/e.rs"})),
+        ];
+        let files: Vec<String> = GitCorrelator::edited_files(&messages).into_iter().collect();
+        assert_eq!(
+            files,
+            vec!["/repo/src/a.rs", "/repo/src/b.rs", "src/c.rs", "src/d.rs"]
+        );
+    }
+
+    #[test]
+    fn test_links_commits_that_touch_edited_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        let start = Utc.with_ymd_and_hms(2025, 12, 6, 10, 0, 0).unwrap();
+
+        git(repo, &["init", "-q"], start);
+        commit_file(
+            repo,
+            "src/a.rs",
+            "Before the session",
+            start - Duration::hours(1),
+        );
+        commit_file(
+            repo,
+            "src/a.rs",
+            "Land the edit",
+            start + Duration::minutes(30),
+        );
+        commit_file(
+            repo,
+            "docs/notes.md",
+            "Unrelated",
+            start + Duration::minutes(40),
+        );
+        commit_file(repo, "src/lib.rs", "Patch", start + Duration::hours(2));
+        commit_file(repo, "src/a.rs", "Next week", start + Duration::days(7));
+
+        let session = make_session(repo, start);
+        let messages = vec![
+            make_tool_call(
+                "Edit",
+                json!({"file_path": repo.join("src/a.rs").to_string_lossy()}),
+            ),
+            make_tool_call(
+                "apply_patch",
+                json!({"input": "*** Begin Patch\n*** Update File: src/lib.rs\n*** End Patch"}),
+            ),
+        ];
+
+        let db = crate::db::Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        store_session(&db, &session);
+        let ctx = AnalyticsContext { db: &db };
+
+        let outputs = GitCorrelator::new()
+            .analyze_session(&session, &messages, &ctx)
+            .unwrap();
+        let value = |name: &str| {
+            outputs
+                .iter()
+                .find(|m| m.metric_name == name)
+                .map(|m| m.metric_value.clone())
+                .unwrap()
+        };
+        assert_eq!(value("git_commits"), json!(2));
+        assert_eq!(value("git_files_committed"), json!(2));
+        assert_eq!(value("git_shipped"), json!(true));
+
+        let commits = db.get_session_commits("session-git").unwrap();
+        let summaries: Vec<&str> = commits.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["Land the edit", "Patch"]);
+        assert_eq!(commits[0].matched_files, vec!["src/a.rs"]);
+        assert_eq!(commits[0].author_name.as_deref(), Some("Test"));
+        assert_eq!(commits[0].committed_at, start + Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_no_metrics_outside_a_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let session = make_session(temp_dir.path(), Utc::now());
+        let db = crate::db::Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let ctx = AnalyticsContext { db: &db };
+
+        let outputs = GitCorrelator::new()
+            .analyze_session(&session, &[], &ctx)
+            .unwrap();
+        assert!(outputs.is_empty());
+    }
+}
@@ -6,6 +6,10 @@
 //! ## Built-in Plugins
 //!
 //! - [`edit_churn`]: Tracks file modification patterns and churn ratio
+//! - [`first_order`]: Token, tool call, error and duration totals
+//! - [`git`]: Commits that landed the session's edits
+//! - [`outcome`]: Session outcome signals
+//! - [`cost`]: Estimated cost from a [`PricingCatalog`]
 //!
 //! ## Creating Custom Plugins
 //!
@@ -14,10 +18,16 @@
 //!
 //! Or use [`create_default_engine`] to get an engine with all built-in plugins.
 
+pub mod cost;
 pub mod edit_churn;
 pub mod first_order;
+pub mod git;
 pub mod outcome;
 
+#[cfg(test)]
+mod test_support;
+
+use super::pricing::PricingCatalog;
 use super::AnalyticsEngine;
 
 /// Create an engine with all built-in plugins registered.
@@ -31,23 +41,32 @@ use super::AnalyticsEngine;
 /// println!("Registered plugins: {:?}", engine.plugin_names());
 /// ```
 pub fn create_default_engine() -> AnalyticsEngine {
-    let mut engine = AnalyticsEngine::new();
-    engine.register(Box::new(edit_churn::EditChurnAnalyzer::new()));
-    engine.register(Box::new(first_order::FirstOrderMetrics::new()));
-    engine.register(Box::new(outcome::OutcomeMetrics::new()));
-    engine
+    create_engine_with_pricing(PricingCatalog::builtin())
 }
 
-/// Create an engine with built-in plugins and timeout settings from config.
+/// Create an engine with built-in plugins, timeout settings and model prices
+/// from config.
 pub fn create_default_engine_with_config(
     config: &crate::config::AnalyticsConfig,
 ) -> AnalyticsEngine {
-    let mut engine = create_default_engine();
+    let mut engine = create_engine_with_pricing(PricingCatalog::from_config(config));
     engine.set_default_timeout_ms(config.timeout_ms);
     engine.set_plugin_timeouts_ms(config.plugin_timeouts.clone());
     engine
 }
 
+/// Create an engine with all built-in plugins, estimating costs with `pricing`.
+pub fn create_engine_with_pricing(pricing: PricingCatalog) -> AnalyticsEngine {
+    let mut engine = AnalyticsEngine::new();
+    engine.register(Box::new(edit_churn::EditChurnAnalyzer::new()));
+    engine.register(Box::new(first_order::FirstOrderMetrics::new()));
+    // Before core.outcome, which reads the commits it links
+    engine.register(Box::new(git::GitCorrelator::new()));
+    engine.register(Box::new(outcome::OutcomeMetrics::new()));
+    engine.register(Box::new(cost::CostEstimator::with_pricing(pricing)));
+    engine
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,9 +85,11 @@ mod tests {
             names.contains(&"core.first_order"),
             "Should include first_order plugin"
         );
+        assert!(names.contains(&"core.git"), "Should include git plugin");
         assert!(
             names.contains(&"core.outcome"),
             "Should include outcome plugin"
         );
+        assert!(names.contains(&"core.cost"), "Should include cost plugin");
     }
 }
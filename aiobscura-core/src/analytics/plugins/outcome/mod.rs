@@ -2,6 +2,10 @@
 //!
 //! Captures coarse session outcomes in `plugin_metrics` until a first-class
 //! outcome model is introduced.
+//!
+//! Commits linked by `core.git` are the strongest signal: a session whose
+//! edits landed counts as a success. Without them the outcome is guessed
+//! from tool results and errors.
 
 use crate::analytics::engine::{AnalyticsContext, AnalyticsPlugin, AnalyticsTrigger, MetricOutput};
 use crate::types::{Message, MessageType, Session};
@@ -34,8 +38,9 @@ impl AnalyticsPlugin for OutcomeMetrics {
         &self,
         session: &Session,
         messages: &[Message],
-        _ctx: &AnalyticsContext,
+        ctx: &AnalyticsContext,
     ) -> Result<Vec<MetricOutput>> {
+        let commit_count = ctx.db.get_session_commits(&session.id)?.len();
         let error_count = messages
             .iter()
             .filter(|m| matches!(m.message_type, MessageType::Error))
@@ -45,8 +50,10 @@ impl AnalyticsPlugin for OutcomeMetrics {
             .filter(|m| matches!(m.message_type, MessageType::ToolResult))
             .count();
 
-        let success = tool_result_count > 0 && error_count == 0;
-        let evidence_type = if success {
+        let success = commit_count > 0 || (tool_result_count > 0 && error_count == 0);
+        let evidence_type = if commit_count > 0 {
+            "commits_landed"
+        } else if success {
             "tool_result_no_errors"
         } else if tool_result_count > 0 {
             "tool_result_with_errors"
@@ -55,7 +62,10 @@ impl AnalyticsPlugin for OutcomeMetrics {
         } else {
             "insufficient_signal"
         };
-        let notes = format!("tool_results={} errors={}", tool_result_count, error_count);
+        let notes = format!(
+            "commits={} tool_results={} errors={}",
+            commit_count, tool_result_count, error_count
+        );
 
         Ok(vec![
             MetricOutput::session(&session.id, "outcome_success", success.into()),
@@ -68,6 +78,7 @@ impl AnalyticsPlugin for OutcomeMetrics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analytics::plugins::test_support::store_session;
     use crate::types::{Assistant, AuthorRole, SessionStatus};
     use chrono::Utc;
     use serde_json::json;
@@ -104,12 +115,18 @@ mod tests {
             tool_name: None,
             tool_input: None,
             tool_result: None,
+            tool_call_id: None,
             tokens_in: None,
             tokens_out: None,
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
             duration_ms: None,
             source_file_path: "source.jsonl".to_string(),
             source_offset: 0,
             source_line: None,
+            record_id: None,
+            parent_record_id: None,
             raw_data: json!({}),
             metadata: json!({}),
         }
@@ -146,4 +163,42 @@ mod tests {
             Some("tool_result_no_errors")
         );
     }
+
+    #[test]
+    fn outcome_success_when_commits_landed() {
+        let plugin = OutcomeMetrics::new();
+        let session = make_session();
+        let messages = vec![make_message(1, MessageType::Error)];
+
+        let db = crate::db::Database::open_in_memory().expect("db");
+        db.migrate().expect("migrate");
+        store_session(&db, &session);
+        db.replace_session_commits(
+            &session.id,
+            &[crate::types::SessionCommit {
+                session_id: session.id.clone(),
+                commit_sha: "abc123".to_string(),
+                repo_path: "/repo".into(),
+                committed_at: Utc::now(),
+                author_name: None,
+                author_email: None,
+                summary: "Fix".to_string(),
+                matched_files: vec!["src/a.rs".to_string()],
+            }],
+        )
+        .expect("commits");
+        let ctx = AnalyticsContext { db: &db };
+
+        let outputs = plugin
+            .analyze_session(&session, &messages, &ctx)
+            .expect("analysis should succeed");
+        let evidence = outputs
+            .iter()
+            .find(|o| o.metric_name == "outcome_evidence_type")
+            .map(|o| o.metric_value.clone());
+        assert_eq!(evidence, Some(serde_json::json!("commits_landed")));
+        assert!(outputs
+            .iter()
+            .any(|o| o.metric_name == "outcome_success" && o.metric_value == true));
+    }
 }
@@ -0,0 +1,23 @@
+//! Helpers shared by the plugins' tests
+
+use crate::db::Database;
+use crate::types::{Checkpoint, FileType, Session, SourceFile};
+use chrono::Utc;
+
+/// Store the session, and the source file it references.
+pub(super) fn store_session(db: &Database, session: &Session) {
+    db.upsert_source_file(&SourceFile {
+        path: session.source_file_path.clone().into(),
+        file_type: FileType::Jsonl,
+        assistant: session.assistant,
+        agent: None,
+        created_at: Utc::now(),
+        modified_at: Utc::now(),
+        size_bytes: 0,
+        last_parsed_at: None,
+        checkpoint: Checkpoint::ByteOffset { offset: 0 },
+        parser_version: 1,
+    })
+    .expect("source file");
+    db.upsert_session(session).expect("session");
+}
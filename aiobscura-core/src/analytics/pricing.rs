@@ -0,0 +1,370 @@
+//! Model pricing and cost estimation.
+//!
+//! A [`PricingCatalog`] maps [`BackingModel`](crate::types::BackingModel) ids
+//! to per-token rates. It ships with list prices for common models and can be
+//! extended or overridden with `[[analytics.pricing]]` sections in
+//! `config.toml`:
+//!
+//! ```toml
+//! [[analytics.pricing]]
+//! model = "anthropic:claude-opus-4-5"
+//! input = 5.0
+//! output = 25.0
+//! cache_read = 0.5
+//! cache_write = 6.25
+//! effective_from = "2025-11-24"
+//! ```
+//!
+//! Rates are USD per million tokens. `model` matches a backing model id
+//! exactly or as a prefix, so `"openai:gpt-5"` also prices
+//! `"openai:gpt-5-codex"`; the longest matching prefix wins, and configured
+//! prices win over built-in ones of the same length.
+//!
+//! Costs are estimates: they use list prices and ignore batch discounts,
+//! long-context tiers and free allowances.
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::AnalyticsConfig;
+use crate::db::{ModelUsage, TokenUsage};
+
+/// Price of one model over an optional date range.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelPrice {
+    /// Backing model id or id prefix, e.g. "anthropic:claude-sonnet-4"
+    pub model: String,
+    /// USD per million uncached input tokens
+    pub input: f64,
+    /// USD per million output tokens (including reasoning)
+    pub output: f64,
+    /// USD per million input tokens read from the prompt cache
+    /// (defaults to the input rate)
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+    /// USD per million input tokens written to the prompt cache
+    /// (defaults to the input rate)
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+    /// First day this price applies (inclusive)
+    #[serde(default)]
+    pub effective_from: Option<NaiveDate>,
+    /// Day this price stops applying (exclusive)
+    #[serde(default)]
+    pub effective_until: Option<NaiveDate>,
+}
+
+impl ModelPrice {
+    fn new(model: &str, input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self {
+            model: model.to_string(),
+            input,
+            output,
+            cache_read: Some(cache_read),
+            cache_write: Some(cache_write),
+            effective_from: None,
+            effective_until: None,
+        }
+    }
+
+    fn between(mut self, from: Option<NaiveDate>, until: Option<NaiveDate>) -> Self {
+        self.effective_from = from;
+        self.effective_until = until;
+        self
+    }
+
+    /// Whether this price covers `model_id` on `date`.
+    pub fn applies_to(&self, model_id: &str, date: NaiveDate) -> bool {
+        model_id.starts_with(&self.model)
+            && self.effective_from.is_none_or(|from| date >= from)
+            && self.effective_until.is_none_or(|until| date < until)
+    }
+
+    /// Cost in USD of `usage` at this price.
+    pub fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        let per_token = |rate: f64| rate / 1_000_000.0;
+        usage.tokens_in as f64 * per_token(self.input)
+            + usage.tokens_out as f64 * per_token(self.output)
+            + usage.tokens_cache_read as f64 * per_token(self.cache_read.unwrap_or(self.input))
+            + usage.tokens_cache_write as f64 * per_token(self.cache_write.unwrap_or(self.input))
+    }
+}
+
+/// Estimated cost of some token usage.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CostEstimate {
+    /// Estimated cost in USD of the priced tokens
+    pub usd: f64,
+    /// Tokens whose model is unknown or has no price
+    pub unpriced_tokens: i64,
+}
+
+impl CostEstimate {
+    /// Add another estimate to this one.
+    pub fn add(&mut self, other: CostEstimate) {
+        self.usd += other.usd;
+        self.unpriced_tokens += other.unpriced_tokens;
+    }
+
+    /// Format for display (e.g., "$12.34").
+    ///
+    /// A trailing "+" marks a lower bound (some tokens could not be priced);
+    /// "n/a" means nothing could be priced.
+    pub fn display(&self) -> String {
+        if self.usd == 0.0 && self.unpriced_tokens > 0 {
+            return "n/a".to_string();
+        }
+        let amount = if self.usd >= 1_000.0 {
+            format!("${:.1}K", self.usd / 1_000.0)
+        } else {
+            format!("${:.2}", self.usd)
+        };
+        if self.unpriced_tokens > 0 {
+            format!("{}+", amount)
+        } else {
+            amount
+        }
+    }
+}
+
+/// Catalog of model prices used for cost estimates.
+#[derive(Debug, Clone)]
+pub struct PricingCatalog {
+    /// Configured prices first, then built-in ones
+    prices: Vec<ModelPrice>,
+}
+
+impl Default for PricingCatalog {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl PricingCatalog {
+    /// Catalog with only the built-in list prices.
+    pub fn builtin() -> Self {
+        Self {
+            prices: builtin_prices(),
+        }
+    }
+
+    /// Built-in prices plus `overrides`, which take precedence.
+    pub fn with_overrides(overrides: &[ModelPrice]) -> Self {
+        let mut prices = overrides.to_vec();
+        prices.extend(builtin_prices());
+        Self { prices }
+    }
+
+    /// Catalog for the `[[analytics.pricing]]` sections of `config`.
+    pub fn from_config(config: &AnalyticsConfig) -> Self {
+        Self::with_overrides(&config.pricing)
+    }
+
+    /// All prices, configured ones first.
+    pub fn prices(&self) -> &[ModelPrice] {
+        &self.prices
+    }
+
+    /// Short hash of every price, stored with computed costs so they are
+    /// recomputed when the prices change.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{:?}", self.prices).as_bytes());
+        format!("{:x}", hasher.finalize())[..16].to_string()
+    }
+
+    /// Find the price for `model_id` on `date`.
+    pub fn price_for(&self, model_id: &str, date: NaiveDate) -> Option<&ModelPrice> {
+        let mut best: Option<&ModelPrice> = None;
+        for price in self.prices.iter().filter(|p| p.applies_to(model_id, date)) {
+            // Strictly longer so that earlier (configured) prices win ties
+            if best.is_none_or(|b| price.model.len() > b.model.len()) {
+                best = Some(price);
+            }
+        }
+        best
+    }
+
+    /// Estimate the cost of `usage` by `model_id` on `date`.
+    pub fn estimate(
+        &self,
+        model_id: Option<&str>,
+        date: NaiveDate,
+        usage: &TokenUsage,
+    ) -> CostEstimate {
+        match model_id.and_then(|id| self.price_for(id, date)) {
+            Some(price) => CostEstimate {
+                usd: price.cost_usd(usage),
+                unpriced_tokens: 0,
+            },
+            None => CostEstimate {
+                usd: 0.0,
+                unpriced_tokens: usage.total(),
+            },
+        }
+    }
+
+    /// Estimate the total cost of per-model, per-day usage rows.
+    pub fn estimate_all(&self, rows: &[ModelUsage]) -> CostEstimate {
+        let mut total = CostEstimate::default();
+        for row in rows {
+            total.add(self.estimate(row.backing_model_id.as_deref(), row.day, &row.usage));
+        }
+        total
+    }
+}
+
+/// List prices in USD per million tokens.
+fn builtin_prices() -> Vec<ModelPrice> {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+    vec![
+        // Anthropic: cache reads at 0.1x input, 5-minute cache writes at 1.25x
+        ModelPrice::new("anthropic:claude-opus-4", 15.0, 75.0, 1.5, 18.75),
+        ModelPrice::new("anthropic:claude-opus-4-5", 5.0, 25.0, 0.5, 6.25),
+        ModelPrice::new("anthropic:claude-sonnet-4", 3.0, 15.0, 0.3, 3.75),
+        ModelPrice::new("anthropic:claude-haiku-4-5", 1.0, 5.0, 0.1, 1.25),
+        ModelPrice::new("anthropic:claude-3-opus", 15.0, 75.0, 1.5, 18.75),
+        ModelPrice::new("anthropic:claude-3-7-sonnet", 3.0, 15.0, 0.3, 3.75),
+        ModelPrice::new("anthropic:claude-3-5-sonnet", 3.0, 15.0, 0.3, 3.75),
+        ModelPrice::new("anthropic:claude-3-5-haiku", 0.8, 4.0, 0.08, 1.0),
+        // OpenAI: cached input is discounted, cache writes are not billed
+        ModelPrice::new("openai:gpt-5", 1.25, 10.0, 0.125, 0.0),
+        ModelPrice::new("openai:gpt-5-mini", 0.25, 2.0, 0.025, 0.0),
+        ModelPrice::new("openai:gpt-5-nano", 0.05, 0.4, 0.005, 0.0),
+        ModelPrice::new("openai:gpt-4.1", 2.0, 8.0, 0.5, 0.0),
+        ModelPrice::new("openai:gpt-4.1-mini", 0.4, 1.6, 0.1, 0.0),
+        ModelPrice::new("openai:gpt-4o", 2.5, 10.0, 1.25, 0.0),
+        ModelPrice::new("openai:gpt-4o-mini", 0.15, 0.6, 0.075, 0.0),
+        ModelPrice::new("openai:o3", 2.0, 8.0, 0.5, 0.0),
+        ModelPrice::new("openai:o3-mini", 1.1, 4.4, 0.55, 0.0),
+        ModelPrice::new("openai:o4-mini", 1.1, 4.4, 0.275, 0.0),
+        // Google: prompts up to 200K tokens
+        ModelPrice::new("google:gemini-2.5-pro", 1.25, 10.0, 0.31, 1.25),
+        ModelPrice::new("google:gemini-2.5-flash", 0.15, 0.6, 0.0375, 0.15)
+            .between(None, date(2025, 6, 17)),
+        ModelPrice::new("google:gemini-2.5-flash", 0.3, 2.5, 0.075, 0.3)
+            .between(date(2025, 6, 17), None),
+        ModelPrice::new("google:gemini-2.5-flash-lite", 0.1, 0.4, 0.025, 0.1),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn usage(tokens_in: i64, tokens_out: i64) -> TokenUsage {
+        TokenUsage {
+            tokens_in,
+            tokens_out,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let catalog = PricingCatalog::builtin();
+        let date = day(2025, 12, 1);
+
+        let opus_45 = catalog
+            .price_for("anthropic:claude-opus-4-5-20251101", date)
+            .unwrap();
+        assert_eq!(opus_45.model, "anthropic:claude-opus-4-5");
+
+        let opus_41 = catalog
+            .price_for("anthropic:claude-opus-4-1-20250805", date)
+            .unwrap();
+        assert_eq!(opus_41.model, "anthropic:claude-opus-4");
+
+        let codex = catalog.price_for("openai:gpt-5-codex", date).unwrap();
+        assert_eq!(codex.model, "openai:gpt-5");
+
+        assert!(catalog.price_for("ollama:llama3", date).is_none());
+    }
+
+    #[test]
+    fn test_effective_dates() {
+        let catalog = PricingCatalog::builtin();
+
+        let before = catalog
+            .price_for("google:gemini-2.5-flash", day(2025, 6, 16))
+            .unwrap();
+        assert_eq!(before.input, 0.15);
+
+        let after = catalog
+            .price_for("google:gemini-2.5-flash", day(2025, 6, 17))
+            .unwrap();
+        assert_eq!(after.input, 0.3);
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let catalog = PricingCatalog::with_overrides(&[ModelPrice {
+            model: "anthropic:claude-sonnet-4".to_string(),
+            input: 1.0,
+            output: 2.0,
+            cache_read: None,
+            cache_write: None,
+            effective_from: None,
+            effective_until: None,
+        }]);
+
+        let price = catalog
+            .price_for("anthropic:claude-sonnet-4-5-20250929", day(2025, 10, 1))
+            .unwrap();
+        assert_eq!(price.input, 1.0);
+
+        // Cache rates default to the input rate
+        let cost = price.cost_usd(&TokenUsage {
+            tokens_cache_read: 1_000_000,
+            tokens_cache_write: 1_000_000,
+            ..Default::default()
+        });
+        assert!((cost - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cost_includes_cache_tokens() {
+        let catalog = PricingCatalog::builtin();
+        let estimate = catalog.estimate(
+            Some("anthropic:claude-sonnet-4-20250514"),
+            day(2025, 8, 1),
+            &TokenUsage {
+                tokens_in: 1_000_000,
+                tokens_out: 1_000_000,
+                tokens_cache_read: 1_000_000,
+                tokens_cache_write: 1_000_000,
+            },
+        );
+        assert!((estimate.usd - (3.0 + 15.0 + 0.3 + 3.75)).abs() < 1e-9);
+        assert_eq!(estimate.unpriced_tokens, 0);
+    }
+
+    #[test]
+    fn test_unknown_model_is_unpriced() {
+        let catalog = PricingCatalog::builtin();
+        let estimate = catalog.estimate(None, day(2025, 8, 1), &usage(100, 50));
+        assert_eq!(estimate.usd, 0.0);
+        assert_eq!(estimate.unpriced_tokens, 150);
+        assert_eq!(estimate.display(), "n/a");
+    }
+
+    #[test]
+    fn test_cost_display() {
+        let mut estimate = CostEstimate {
+            usd: 12.345,
+            unpriced_tokens: 0,
+        };
+        assert_eq!(estimate.display(), "$12.35");
+
+        estimate.add(CostEstimate {
+            usd: 2_000.0,
+            unpriced_tokens: 10,
+        });
+        assert_eq!(estimate.display(), "$2.0K+");
+    }
+}
@@ -3,9 +3,13 @@
 //! Provides aggregate statistics and insights at the project level,
 //! designed for the Project view in the TUI.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 
-use crate::db::{FileStats, ToolStats};
+use super::pricing::{CostEstimate, PricingCatalog};
+use crate::db::{Database, FileStats, ToolStats};
+use crate::Result;
 
 /// Row for project list display (lightweight, for table view).
 #[derive(Debug, Clone)]
@@ -20,8 +24,10 @@ pub struct ProjectRow {
     pub session_count: i64,
     /// When the project was last active
     pub last_activity: Option<DateTime<Utc>>,
-    /// Total tokens used (in + out)
+    /// Total tokens used (in + out + cache reads and writes)
     pub total_tokens: i64,
+    /// Estimated cost (filled in by [`list_projects`])
+    pub cost: CostEstimate,
 }
 
 /// Detailed statistics for a single project.
@@ -49,6 +55,14 @@ pub struct ProjectStats {
     pub tokens_in: i64,
     /// Total output tokens
     pub tokens_out: i64,
+    /// Total input tokens served from the prompt cache
+    pub tokens_cache_read: i64,
+    /// Total input tokens written to the prompt cache
+    pub tokens_cache_write: i64,
+    /// Total reasoning tokens (part of `tokens_out`)
+    pub tokens_reasoning: i64,
+    /// Estimated cost (filled in by [`project_stats`])
+    pub cost: CostEstimate,
 
     // Work patterns
     /// Tool usage statistics
@@ -78,9 +92,9 @@ pub struct ProjectStats {
 }
 
 impl ProjectStats {
-    /// Returns the total token count (in + out).
+    /// Returns the total token count (in + out + cache reads and writes).
     pub fn total_tokens(&self) -> i64 {
-        self.tokens_in + self.tokens_out
+        self.tokens_in + self.tokens_out + self.tokens_cache_read + self.tokens_cache_write
     }
 
     /// Returns the peak activity hour (0-23).
@@ -106,3 +120,37 @@ impl ProjectStats {
         }
     }
 }
+
+/// List all projects with summary stats and estimated costs.
+pub fn list_projects(db: &Database, pricing: &PricingCatalog) -> Result<Vec<ProjectRow>> {
+    let mut rows = db.list_projects_with_stats()?;
+
+    let mut costs: HashMap<String, CostEstimate> = HashMap::new();
+    for usage in db.get_model_usage(None, None)? {
+        if let Some(project_id) = usage.project_id {
+            costs.entry(project_id).or_default().add(pricing.estimate(
+                usage.backing_model_id.as_deref(),
+                usage.day,
+                &usage.usage,
+            ));
+        }
+    }
+
+    for row in &mut rows {
+        row.cost = costs.get(&row.id).copied().unwrap_or_default();
+    }
+    Ok(rows)
+}
+
+/// Get detailed stats for a single project, including its estimated cost.
+pub fn project_stats(
+    db: &Database,
+    project_id: &str,
+    pricing: &PricingCatalog,
+) -> Result<Option<ProjectStats>> {
+    let Some(mut stats) = db.get_project_stats(project_id)? else {
+        return Ok(None);
+    };
+    stats.cost = pricing.estimate_all(&db.get_model_usage(Some(project_id), None)?);
+    Ok(Some(stats))
+}
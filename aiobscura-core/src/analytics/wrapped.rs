@@ -4,7 +4,8 @@
 
 use chrono::{DateTime, Datelike, Local, Utc};
 
-use super::Personality;
+use super::pricing::PricingCatalog;
+use super::{CostEstimate, Personality};
 
 /// Time period for wrapped statistics.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -117,6 +118,8 @@ pub struct WrappedConfig {
     pub top_tools_count: usize,
     /// Number of top projects to include
     pub top_projects_count: usize,
+    /// Model prices used for the cost estimate
+    pub pricing: PricingCatalog,
 }
 
 impl Default for WrappedConfig {
@@ -126,6 +129,7 @@ impl Default for WrappedConfig {
             include_trends: true,
             top_tools_count: 5,
             top_projects_count: 5,
+            pricing: PricingCatalog::builtin(),
         }
     }
 }
@@ -172,6 +176,14 @@ pub struct TotalStats {
     pub tokens_in: i64,
     /// Total output tokens
     pub tokens_out: i64,
+    /// Total input tokens served from the prompt cache
+    pub tokens_cache_read: i64,
+    /// Total input tokens written to the prompt cache
+    pub tokens_cache_write: i64,
+    /// Total reasoning tokens (part of `tokens_out`)
+    pub tokens_reasoning: i64,
+    /// Estimated cost (filled in by [`generate_wrapped`])
+    pub cost: CostEstimate,
     /// Total tool calls
     pub tool_calls: i64,
     /// Number of plans created/used
@@ -185,9 +197,9 @@ pub struct TotalStats {
 }
 
 impl TotalStats {
-    /// Total tokens (in + out).
+    /// Total tokens (in + out + cache reads and writes).
     pub fn total_tokens(&self) -> i64 {
-        self.tokens_in + self.tokens_out
+        self.tokens_in + self.tokens_out + self.tokens_cache_read + self.tokens_cache_write
     }
 
     /// Format total tokens for display (e.g., "14.2M").
@@ -438,7 +450,10 @@ pub fn generate_wrapped(
     let end = period.end();
 
     // Get all the raw data from the database
-    let totals = db.get_wrapped_totals(start, end)?;
+    let mut totals = db.get_wrapped_totals(start, end)?;
+    totals.cost = config
+        .pricing
+        .estimate_all(&db.get_model_usage(None, Some((start, end)))?);
     let tool_rankings_raw = db.get_wrapped_tool_rankings(start, end, config.top_tools_count)?;
     let hourly_distribution = db.get_wrapped_hourly_distribution(start, end)?;
     let daily_distribution = db.get_wrapped_daily_distribution(start, end)?;
@@ -505,9 +520,12 @@ pub fn generate_wrapped(
         let prev_start = prev_period.start();
         let prev_end = prev_period.end();
 
-        if let Ok(prev_totals) = db.get_wrapped_totals(prev_start, prev_end) {
+        if let Ok(mut prev_totals) = db.get_wrapped_totals(prev_start, prev_end) {
             // Only include trends if there's previous data
             if prev_totals.sessions > 0 {
+                prev_totals.cost = config
+                    .pricing
+                    .estimate_all(&db.get_model_usage(None, Some((prev_start, prev_end)))?);
                 Some(TrendComparison {
                     sessions_delta_pct: TrendComparison::calc_delta(
                         totals.sessions,
@@ -582,6 +600,19 @@ mod tests {
         assert_eq!(stats.duration_display(), "312h 45m");
     }
 
+    #[test]
+    fn test_total_stats_include_cache_tokens() {
+        let stats = TotalStats {
+            tokens_in: 1_000,
+            tokens_out: 500,
+            tokens_cache_read: 90_000,
+            tokens_cache_write: 8_000,
+            tokens_reasoning: 200,
+            ..Default::default()
+        };
+        assert_eq!(stats.total_tokens(), 99_500);
+    }
+
     #[test]
     fn test_trend_delta() {
         assert_eq!(TrendComparison::calc_delta(123, 100), 23.0);
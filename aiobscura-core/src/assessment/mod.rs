@@ -441,12 +441,18 @@ mod tests {
             tool_name: None,
             tool_input: None,
             tool_result: None,
+            tool_call_id: None,
             tokens_in: None,
             tokens_out: None,
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
             duration_ms: None,
             source_file_path: "src.jsonl".to_string(),
             source_offset: 0,
             source_line: None,
+            record_id: None,
+            parent_record_id: None,
             raw_data: json!({}),
             metadata: json!({}),
         }
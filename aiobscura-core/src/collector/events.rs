@@ -70,6 +70,7 @@ fn map_message_type(mt: &MessageType) -> String {
         MessageType::Plan => "message",
         MessageType::Summary => "message",
         MessageType::Context => "message",
+        MessageType::Compaction => "message",
         MessageType::Error => "error",
     }
     .to_string()
@@ -98,6 +99,7 @@ fn map_message_type_for_data(mt: &MessageType) -> String {
         MessageType::Plan => "plan",
         MessageType::Summary => "summary",
         MessageType::Context => "context",
+        MessageType::Compaction => "compaction",
         MessageType::Error => "error",
     }
     .to_string()
@@ -130,6 +132,15 @@ fn build_message_data(msg: &Message) -> serde_json::Value {
             "input_tokens": tokens_in,
             "output_tokens": msg.tokens_out.unwrap_or(0),
         });
+        for (key, value) in [
+            ("cache_read_input_tokens", msg.tokens_cache_read),
+            ("cache_creation_input_tokens", msg.tokens_cache_write),
+            ("reasoning_output_tokens", msg.tokens_reasoning),
+        ] {
+            if let Some(value) = value {
+                data["token_usage"][key] = value.into();
+            }
+        }
     }
 
     // Preserve raw_data for lossless transmission
@@ -260,12 +271,18 @@ mod tests {
             tool_name: None,
             tool_input: None,
             tool_result: None,
+            tool_call_id: None,
             tokens_in: Some(10),
             tokens_out: None,
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
             duration_ms: None,
             source_file_path: "/test/path".to_string(),
             source_offset: 0,
             source_line: Some(1),
+            record_id: None,
+            parent_record_id: None,
             raw_data: serde_json::json!({}),
             metadata: serde_json::json!({}),
         }
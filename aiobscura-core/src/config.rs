@@ -7,9 +7,11 @@
 //! - Data: `$XDG_DATA_HOME/aiobscura/` (~/.local/share/aiobscura/)
 //! - State/Logs: `$XDG_STATE_HOME/aiobscura/` (~/.local/state/aiobscura/)
 
+use crate::analytics::ModelPrice;
 use crate::error::{Error, Result};
+use crate::types::{Assistant, FileType};
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Returns a best-effort home directory path.
 fn home_dir() -> PathBuf {
@@ -19,6 +21,14 @@ fn home_dir() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from("."))
 }
 
+/// Expand a leading `~` to the home directory.
+pub(crate) fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home_dir().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
 /// Returns XDG_CONFIG_HOME or ~/.config
 fn xdg_config_home() -> PathBuf {
     std::env::var("XDG_CONFIG_HOME")
@@ -55,6 +65,10 @@ pub struct Config {
     #[serde(default)]
     pub agents: AgentOverrides,
 
+    /// Ingestion configuration
+    #[serde(default)]
+    pub ingest: IngestConfig,
+
     /// Logging configuration
     #[serde(default)]
     pub logging: LoggingConfig,
@@ -62,6 +76,18 @@ pub struct Config {
     /// Catsyphon collector configuration (optional)
     #[serde(default)]
     pub collector: CollectorConfig,
+
+    /// OpenTelemetry receiver configuration
+    #[serde(default)]
+    pub otlp: OtlpConfig,
+
+    /// Project identity overrides
+    #[serde(default)]
+    pub projects: ProjectsConfig,
+
+    /// How long raw message data is kept
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
 /// LLM provider configuration
@@ -122,6 +148,10 @@ pub struct AnalyticsConfig {
     /// Per-plugin timeout overrides
     #[serde(default)]
     pub plugin_timeouts: std::collections::HashMap<String, u64>,
+
+    /// Model prices that extend or override the built-in pricing catalog
+    #[serde(default)]
+    pub pricing: Vec<ModelPrice>,
 }
 
 impl Default for AnalyticsConfig {
@@ -132,6 +162,7 @@ impl Default for AnalyticsConfig {
             timeout_ms: default_plugin_timeout(),
             disabled_plugins: vec![],
             plugin_timeouts: std::collections::HashMap::new(),
+            pricing: vec![],
         }
     }
 }
@@ -152,6 +183,14 @@ fn default_llm_timeout_secs() -> u64 {
     30
 }
 
+/// Ingestion configuration
+#[derive(Debug, Deserialize, Default)]
+pub struct IngestConfig {
+    /// Number of files to parse concurrently during sync (0 = one per CPU)
+    #[serde(default)]
+    pub parallelism: usize,
+}
+
 /// Override paths for agent directories
 #[derive(Debug, Deserialize, Default)]
 pub struct AgentOverrides {
@@ -163,6 +202,297 @@ pub struct AgentOverrides {
     pub aider_path: Option<PathBuf>,
     /// Override path for Cursor data
     pub cursor_path: Option<PathBuf>,
+    /// Override path for Gemini CLI data
+    pub gemini_path: Option<PathBuf>,
+    /// Declarative JSONL parsers for agents without a built-in parser
+    /// (`[[agents.custom]]` sections)
+    #[serde(default)]
+    pub custom: Vec<CustomAgentConfig>,
+    /// Out-of-process parsers (`[[agents.plugin]]` sections)
+    #[serde(default)]
+    pub plugin: Vec<PluginAgentConfig>,
+    /// Additional data roots for built-in assistants (`[[agents.root]]` sections)
+    #[serde(default)]
+    pub root: Vec<AgentRootConfig>,
+}
+
+impl AgentOverrides {
+    /// Configured replacement for an assistant's default root, if any.
+    pub fn path_override(&self, assistant: Assistant) -> Option<PathBuf> {
+        let path = match assistant {
+            Assistant::ClaudeCode => &self.claude_code_path,
+            Assistant::Codex => &self.codex_path,
+            Assistant::Aider => &self.aider_path,
+            Assistant::Cursor => &self.cursor_path,
+            Assistant::Gemini => &self.gemini_path,
+            Assistant::Custom => &None,
+        };
+        path.as_deref().map(expand_home)
+    }
+}
+
+/// An additional data root for a built-in assistant.
+///
+/// Each assistant is always read from its default root (or its `*_path`
+/// override), labeled `default`. Every `[[agents.root]]` adds another, so
+/// one assistant can be read from several profiles, containers or mounts:
+///
+/// ```toml
+/// [[agents.root]]
+/// assistant = "claude_code"
+/// path = "~/.claude-work"      # a CLAUDE_CONFIG_DIR profile
+/// label = "work"
+///
+/// [[agents.root]]
+/// assistant = "claude_code"
+/// path = "~/containers/api/home/.claude"
+/// label = "api"
+/// host = "api-devcontainer"
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct AgentRootConfig {
+    /// Built-in assistant whose logs live under the root
+    pub assistant: Assistant,
+    /// Root directory (`~` is expanded)
+    pub path: PathBuf,
+    /// Label recorded on sessions read from this root
+    pub label: String,
+    /// Machine or container the root belongs to (defaults to this host's name)
+    pub host: Option<String>,
+}
+
+impl AgentRootConfig {
+    /// Root directory with a leading `~` expanded to the home directory.
+    pub fn expanded_path(&self) -> PathBuf {
+        expand_home(&self.path)
+    }
+}
+
+/// A JSONL agent log described in configuration instead of code.
+///
+/// Each line of a matching file is one record. Fields are located with
+/// [JSON pointers](https://www.rfc-editor.org/rfc/rfc6901) (e.g. `/message/text`).
+///
+/// ```toml
+/// [[agents.custom]]
+/// name = "buildbot"
+/// root = "~/.buildbot"
+///
+/// [[agents.custom.sources]]
+/// pattern = "sessions/*.jsonl"
+///
+/// [agents.custom.fields]
+/// session_id = "/session"
+/// timestamp = "/ts"
+/// role = "/role"
+/// content = "/text"
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct CustomAgentConfig {
+    /// Agent name, stored in session metadata and used to namespace session IDs
+    pub name: String,
+    /// Root directory of the agent's logs (`~` is expanded)
+    pub root: PathBuf,
+    /// Glob patterns relative to `root`
+    pub sources: Vec<CustomSourceConfig>,
+    /// JSON pointers locating record fields
+    #[serde(default)]
+    pub fields: CustomFieldMappings,
+    /// Raw role value -> author role (`human`, `caller`, `assistant`, `agent`,
+    /// `tool`, `system`). Merged over the defaults `user = "human"`,
+    /// `assistant = "assistant"`, `tool = "tool"` and `system = "system"`.
+    #[serde(default)]
+    pub roles: std::collections::HashMap<String, String>,
+    /// Provider prefix for backing model IDs (defaults to the agent name)
+    pub model_provider: Option<String>,
+}
+
+/// A glob pattern for a custom agent's log files.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CustomSourceConfig {
+    /// Glob pattern relative to the agent root (e.g. "sessions/*.jsonl")
+    pub pattern: String,
+    /// Human-readable description for logging
+    pub description: Option<String>,
+}
+
+/// JSON pointers into each JSONL record. Unset fields are left empty.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CustomFieldMappings {
+    /// Session ID (defaults to the file stem)
+    pub session_id: Option<String>,
+    /// RFC 3339 string or epoch seconds/milliseconds
+    pub timestamp: Option<String>,
+    /// Author role, mapped through [`CustomAgentConfig::roles`]
+    pub role: Option<String>,
+    /// Message text
+    pub content: Option<String>,
+    /// Tool name; a record with a tool name is a tool call
+    pub tool_name: Option<String>,
+    /// Tool input (object, or a string holding JSON)
+    pub tool_input: Option<String>,
+    /// Tool result; a record with a tool result is a tool result
+    pub tool_result: Option<String>,
+    /// Input token count
+    pub tokens_in: Option<String>,
+    /// Output token count
+    pub tokens_out: Option<String>,
+    /// Model name
+    pub model: Option<String>,
+    /// Working directory, used to create the project
+    pub cwd: Option<String>,
+}
+
+impl CustomFieldMappings {
+    /// All configured pointers with their field names.
+    pub fn pointers(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("session_id", &self.session_id),
+            ("timestamp", &self.timestamp),
+            ("role", &self.role),
+            ("content", &self.content),
+            ("tool_name", &self.tool_name),
+            ("tool_input", &self.tool_input),
+            ("tool_result", &self.tool_result),
+            ("tokens_in", &self.tokens_in),
+            ("tokens_out", &self.tokens_out),
+            ("model", &self.model),
+            ("cwd", &self.cwd),
+        ]
+        .into_iter()
+        .filter_map(|(name, ptr)| ptr.as_deref().map(|p| (name, p)))
+    }
+}
+
+impl CustomAgentConfig {
+    /// Validate configuration, returning error message if invalid
+    pub fn validate(&self) -> Result<()> {
+        let prefix = format!("agents.custom[{}]", self.name);
+        if self.name.trim().is_empty() {
+            return Err(Error::Config(
+                "agents.custom.name must not be empty".to_string(),
+            ));
+        }
+        if self.sources.is_empty() {
+            return Err(Error::Config(format!(
+                "{}.sources must list at least one pattern",
+                prefix
+            )));
+        }
+        for (field, pointer) in self.fields.pointers() {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return Err(Error::Config(format!(
+                    "{}.fields.{} must be a JSON pointer starting with '/', got {:?}",
+                    prefix, field, pointer
+                )));
+            }
+        }
+        for (raw, role) in &self.roles {
+            if role.parse::<crate::types::AuthorRole>().is_err() {
+                return Err(Error::Config(format!(
+                    "{}.roles.{}: unknown author role {:?}",
+                    prefix, raw, role
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Root directory with a leading `~` expanded to the home directory.
+    pub fn expanded_root(&self) -> PathBuf {
+        expand_home(&self.root)
+    }
+}
+
+/// An external executable that parses an agent's logs.
+///
+/// For each new or changed source file the command is run once, with the
+/// parse context as JSON on stdin, and must print a parse result as JSON on
+/// stdout. See `docs/parser-plugins.md` for the protocol.
+///
+/// ```toml
+/// [[agents.plugin]]
+/// name = "shipit"
+/// command = "/usr/local/bin/shipit-aiobscura"
+/// args = ["--quiet"]
+/// root = "~/.shipit"
+///
+/// [[agents.plugin.sources]]
+/// pattern = "runs/*.json"
+/// file_type = "json"
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct PluginAgentConfig {
+    /// Agent name, stored in session metadata
+    pub name: String,
+    /// Executable to run (looked up on `PATH` if not a path)
+    pub command: PathBuf,
+    /// Extra arguments passed to the command
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Root directory of the agent's logs (`~` is expanded)
+    pub root: PathBuf,
+    /// Glob patterns relative to `root`
+    pub sources: Vec<PluginSourceConfig>,
+    /// Seconds to wait for the command before killing it
+    #[serde(default = "default_plugin_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// A glob pattern for a plugin agent's source files.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PluginSourceConfig {
+    /// Glob pattern relative to the agent root (e.g. "runs/*.json")
+    pub pattern: String,
+    /// Checkpoint strategy for matching files (`jsonl`, `json`, `markdown`, `sqlite`)
+    #[serde(default = "default_plugin_file_type")]
+    pub file_type: FileType,
+    /// Human-readable description for logging
+    pub description: Option<String>,
+}
+
+fn default_plugin_timeout_secs() -> u64 {
+    30
+}
+
+fn default_plugin_file_type() -> FileType {
+    FileType::Jsonl
+}
+
+impl PluginAgentConfig {
+    /// Validate configuration, returning error message if invalid
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Config(
+                "agents.plugin.name must not be empty".to_string(),
+            ));
+        }
+        let prefix = format!("agents.plugin[{}]", self.name);
+        if self.command.as_os_str().is_empty() {
+            return Err(Error::Config(format!(
+                "{}.command must not be empty",
+                prefix
+            )));
+        }
+        if self.sources.is_empty() {
+            return Err(Error::Config(format!(
+                "{}.sources must list at least one pattern",
+                prefix
+            )));
+        }
+        if self.timeout_secs == 0 {
+            return Err(Error::Config(format!(
+                "{}.timeout_secs must be greater than 0",
+                prefix
+            )));
+        }
+        Ok(())
+    }
+
+    /// Root directory with a leading `~` expanded to the home directory.
+    pub fn expanded_root(&self) -> PathBuf {
+        expand_home(&self.root)
+    }
 }
 
 /// Logging configuration
@@ -300,6 +630,108 @@ fn default_collector_max_retries() -> usize {
     3
 }
 
+/// OpenTelemetry (OTLP/HTTP) receiver configuration
+///
+/// When enabled, `aiobscura-sync --watch` accepts the telemetry Claude Code
+/// and Codex export and stores it alongside their sessions.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OtlpConfig {
+    /// Enable/disable the receiver
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Loopback address to listen on
+    #[serde(default = "default_otlp_listen")]
+    pub listen: String,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_otlp_listen(),
+        }
+    }
+}
+
+fn default_otlp_listen() -> String {
+    "127.0.0.1:4318".to_string()
+}
+
+/// Project identity overrides
+///
+/// Sessions are grouped into projects by git repository (see
+/// `docs/projects.md`). These mappings cover what git can't tell us:
+///
+/// ```toml
+/// [projects.aliases]
+/// # Sessions recorded before the checkout moved belong to the new location
+/// "~/src/old-name" = "~/src/new-name"
+///
+/// [projects.merge]
+/// # Sessions resolved to the first project id are filed under the second
+/// "3f2a9c1b0d4e5f67" = "a1b2c3d4e5f60718"
+/// ```
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProjectsConfig {
+    /// Directory prefix rewrites applied before resolving a project (`~` is expanded)
+    #[serde(default)]
+    pub aliases: std::collections::HashMap<String, String>,
+
+    /// Project id redirects applied after resolving a project
+    #[serde(default)]
+    pub merge: std::collections::HashMap<String, String>,
+}
+
+/// How long raw message data is kept
+///
+/// `aiobscura-sync --prune` drops `raw_data` and tool result bodies of
+/// messages older than the retention period, keeping their normalized rows
+/// and everything derived from them. A project's period overrides its
+/// assistant's, which overrides `days`; without any, data is kept forever.
+///
+/// ```toml
+/// [retention]
+/// days = 365
+///
+/// [retention.assistants]
+/// codex = 90
+///
+/// [retention.projects]
+/// # Keyed by project id
+/// "3f2a9c1b0d4e5f67" = 30
+/// ```
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RetentionConfig {
+    /// Days to keep raw data for, unless overridden below
+    #[serde(default)]
+    pub days: Option<u32>,
+
+    /// Per-assistant retention in days
+    #[serde(default)]
+    pub assistants: std::collections::HashMap<Assistant, u32>,
+
+    /// Per-project retention in days, keyed by project id
+    #[serde(default)]
+    pub projects: std::collections::HashMap<String, u32>,
+}
+
+impl RetentionConfig {
+    /// Whether no retention period is configured.
+    pub fn is_empty(&self) -> bool {
+        self.days.is_none() && self.assistants.is_empty() && self.projects.is_empty()
+    }
+
+    /// Retention period in days for a session, or `None` to keep it forever.
+    pub fn days_for(&self, assistant: Assistant, project_id: Option<&str>) -> Option<u32> {
+        project_id
+            .and_then(|id| self.projects.get(id))
+            .or_else(|| self.assistants.get(&assistant))
+            .copied()
+            .or(self.days)
+    }
+}
+
 impl Config {
     /// Load configuration from the default path
     pub fn load() -> Result<Self> {
@@ -359,6 +791,28 @@ impl Config {
         Self::state_dir().join("aiobscura.log")
     }
 
+    /// Returns the Unix socket `aiobscura hook` delivers hook events to
+    ///
+    /// `$XDG_STATE_HOME/aiobscura/hook.sock` (~/.local/state/aiobscura/hook.sock)
+    pub fn hook_socket_path() -> PathBuf {
+        Self::state_dir().join("hook.sock")
+    }
+
+    /// Returns the spool file hook events are appended to when nothing is
+    /// listening on the hook socket
+    ///
+    /// `$XDG_STATE_HOME/aiobscura/hooks.jsonl` (~/.local/state/aiobscura/hooks.jsonl)
+    pub fn hook_spool_path() -> PathBuf {
+        Self::state_dir().join("hooks.jsonl")
+    }
+
+    /// Returns the directory imported archives are unpacked into
+    ///
+    /// `$XDG_DATA_HOME/aiobscura/imports/` (~/.local/share/aiobscura/imports/)
+    pub fn imports_dir() -> PathBuf {
+        Self::data_dir().join("imports")
+    }
+
     /// Ensure XDG base directory environment variables are set.
     ///
     /// This is mainly for CLI binaries that want explicit, stable path behavior
@@ -416,6 +870,42 @@ level = "debug"
         assert_eq!(config.logging.level, "debug");
     }
 
+    #[test]
+    fn test_parse_pricing_config() {
+        let toml = r#"
+[[analytics.pricing]]
+model = "openai:gpt-5"
+input = 1.0
+output = 8.0
+effective_from = "2026-01-01"
+
+[[analytics.pricing]]
+model = "ollama:"
+input = 0.0
+output = 0.0
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+
+        let pricing = &config.analytics.pricing;
+        assert_eq!(pricing.len(), 2);
+        assert_eq!(pricing[0].model, "openai:gpt-5");
+        assert_eq!(
+            pricing[0].effective_from,
+            chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+        );
+        assert_eq!(pricing[0].cache_read, None);
+        assert_eq!(pricing[1].output, 0.0);
+    }
+
+    #[test]
+    fn test_parse_ingest_config() {
+        let config: Config = toml::from_str("[ingest]\nparallelism = 4\n").unwrap();
+        assert_eq!(config.ingest.parallelism, 4);
+
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.ingest.parallelism, 0);
+    }
+
     #[test]
     fn test_llm_provider_endpoints() {
         assert_eq!(
@@ -483,4 +973,115 @@ batch_size = 30
         assert_eq!(config.collector.batch_size, 30);
         assert!(config.collector.is_ready());
     }
+
+    #[test]
+    fn test_parse_otlp_config() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(!config.otlp.enabled);
+        assert_eq!(config.otlp.listen, "127.0.0.1:4318");
+
+        let config: Config = toml::from_str("[otlp]\nenabled = true\n").unwrap();
+        assert!(config.otlp.enabled);
+        assert_eq!(config.otlp.listen, "127.0.0.1:4318");
+    }
+
+    #[test]
+    fn test_parse_projects_config() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.projects.aliases.is_empty());
+        assert!(config.projects.merge.is_empty());
+
+        let toml = r#"
+[projects.aliases]
+"~/src/old" = "~/src/new"
+
+[projects.merge]
+"aaaa" = "bbbb"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.projects.aliases["~/src/old"], "~/src/new");
+        assert_eq!(config.projects.merge["aaaa"], "bbbb");
+    }
+
+    #[test]
+    fn test_parse_retention_config() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.retention.is_empty());
+        assert_eq!(config.retention.days_for(Assistant::Codex, None), None);
+
+        let toml = r#"
+[retention]
+days = 365
+
+[retention.assistants]
+codex = 90
+
+[retention.projects]
+"aaaa" = 30
+"#;
+        let retention = toml::from_str::<Config>(toml).unwrap().retention;
+        assert_eq!(retention.days_for(Assistant::ClaudeCode, None), Some(365));
+        assert_eq!(retention.days_for(Assistant::Codex, Some("bbbb")), Some(90));
+        assert_eq!(retention.days_for(Assistant::Codex, Some("aaaa")), Some(30));
+    }
+
+    #[test]
+    fn test_parse_agent_roots() {
+        let toml = r#"
+[agents]
+codex_path = "/opt/codex"
+
+[[agents.root]]
+assistant = "claude_code"
+path = "/profiles/work/.claude"
+label = "work"
+
+[[agents.root]]
+assistant = "claude_code"
+path = "/containers/api/.claude"
+label = "api"
+host = "api-devcontainer"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.agents.path_override(Assistant::Codex),
+            Some(PathBuf::from("/opt/codex"))
+        );
+        assert_eq!(config.agents.path_override(Assistant::ClaudeCode), None);
+        assert_eq!(config.agents.root.len(), 2);
+        assert_eq!(config.agents.root[0].assistant, Assistant::ClaudeCode);
+        assert_eq!(config.agents.root[0].host, None);
+        assert_eq!(config.agents.root[1].label, "api");
+        assert_eq!(
+            config.agents.root[1].host.as_deref(),
+            Some("api-devcontainer")
+        );
+    }
+
+    #[test]
+    fn test_parse_plugin_config() {
+        let toml = r#"
+[[agents.plugin]]
+name = "shipit"
+command = "shipit-aiobscura"
+root = "/var/log/shipit"
+
+[[agents.plugin.sources]]
+pattern = "runs/*.json"
+file_type = "json"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let plugin = &config.agents.plugin[0];
+        assert_eq!(plugin.name, "shipit");
+        assert!(plugin.args.is_empty());
+        assert_eq!(plugin.timeout_secs, 30);
+        assert_eq!(plugin.sources[0].file_type, FileType::Json);
+        assert!(plugin.validate().is_ok());
+
+        let no_sources = PluginAgentConfig {
+            sources: vec![],
+            ..plugin.clone()
+        };
+        assert!(no_sources.validate().is_err());
+    }
 }
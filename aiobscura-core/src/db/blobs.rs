@@ -0,0 +1,319 @@
+//! Content-addressed storage for embedded images
+//!
+//! Assistants log screenshots as base64 payloads inside the JSON records kept
+//! in `messages.raw_data`, and tool results that return screenshots carry them
+//! in `messages.tool_result` too. Before a message is stored,
+//! [`BlobStore::externalize_message`] decodes each payload into
+//! `blobs/<first two hex digits>/<rest of the SHA-256>` next to the database
+//! file and leaves a [`BlobRef`] in its place, so identical images are stored
+//! once and the database only holds the reference.
+//!
+//! An inline payload is any object of the form
+//! `{"type": "base64", "media_type": "...", "data": "..."}`, wherever it sits
+//! in the record. Parsers mark the image an image message shows with a JSON
+//! pointer into `raw_data` in `metadata.image_pointer`; the stored message
+//! gets the resolved reference as `metadata.image` instead.
+//!
+//! `message_blobs` lists the blobs each message references. Blobs no row
+//! references any more, after a reparse or pruning, are deleted by
+//! [`Database::remove_unreferenced_blobs`](super::Database::remove_unreferenced_blobs).
+
+use crate::error::Result;
+use crate::types::BlobRef;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Directory of blobs keyed by the SHA-256 of their content.
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    /// Use `root` as the blob directory. It is created on first write.
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The blob directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the blob with this hash lives (whether or not it exists).
+    ///
+    /// Returns `None` unless `sha256` is 64 lowercase hex digits.
+    pub fn path(&self, sha256: &str) -> Option<PathBuf> {
+        let valid = sha256.len() == 64
+            && sha256
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        valid.then(|| self.root.join(&sha256[..2]).join(&sha256[2..]))
+    }
+
+    /// Store `bytes`, returning their hash. Storing the same bytes again is a
+    /// no-op.
+    pub fn put(&self, bytes: &[u8]) -> Result<String> {
+        let sha256 = hex::encode(Sha256::digest(bytes));
+        let path = self
+            .path(&sha256)
+            .expect("hex-encoded SHA-256 is a valid key");
+        if path.exists() {
+            return Ok(sha256);
+        }
+
+        let dir = path.parent().expect("blob path has a parent");
+        std::fs::create_dir_all(dir)?;
+        // Write then rename, so readers never see a partial blob
+        let tmp = dir.join(format!("{}.{}.tmp", &sha256[2..], std::process::id()));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(sha256)
+    }
+
+    /// Read a blob, or `None` if it is not stored.
+    pub fn get(&self, sha256: &str) -> Result<Option<Vec<u8>>> {
+        let Some(path) = self.path(sha256) else {
+            return Ok(None);
+        };
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Hashes of the stored blobs, each with when it was written.
+    pub fn list(&self) -> Result<Vec<(String, SystemTime)>> {
+        let mut blobs = Vec::new();
+        let shards = match std::fs::read_dir(&self.root) {
+            Ok(shards) => shards,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(blobs),
+            Err(e) => return Err(e.into()),
+        };
+        for shard in shards {
+            let shard = shard?;
+            if !shard.file_type()?.is_dir() {
+                continue;
+            }
+            let prefix = shard.file_name().to_string_lossy().to_string();
+            for entry in std::fs::read_dir(shard.path())? {
+                let entry = entry?;
+                let sha256 = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+                // Skips temporary files of writes in progress
+                if self.path(&sha256).is_none() {
+                    continue;
+                }
+                blobs.push((sha256, entry.metadata()?.modified()?));
+            }
+        }
+        Ok(blobs)
+    }
+
+    /// Delete a blob. Deleting one that is not stored is a no-op.
+    pub fn remove(&self, sha256: &str) -> Result<()> {
+        let Some(path) = self.path(sha256) else {
+            return Ok(());
+        };
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        // Fails while the shard still holds other blobs
+        let _ = std::fs::remove_dir(path.parent().expect("blob path has a parent"));
+        Ok(())
+    }
+
+    /// Move the inline payloads of a message's raw data and tool result into
+    /// the store, and resolve `metadata.image_pointer` into `metadata.image`.
+    ///
+    /// Returns the blobs the message references, or `None` when there was
+    /// nothing to move.
+    pub fn externalize_message(
+        &self,
+        raw_data: &mut serde_json::Value,
+        tool_result: &mut Option<String>,
+        metadata: &mut serde_json::Value,
+    ) -> Result<Option<Vec<BlobRef>>> {
+        let tool_result_value = tool_result.as_deref().and_then(parse_inline_payloads);
+        if tool_result_value.is_none() && !has_inline_payloads(raw_data) {
+            return Ok(None);
+        }
+
+        let mut blobs = self.externalize(raw_data)?;
+        if let Some(mut value) = tool_result_value {
+            blobs.extend(self.externalize(&mut value)?);
+            *tool_result = Some(value.to_string());
+        }
+        let pointer = metadata
+            .as_object_mut()
+            .and_then(|metadata| metadata.remove("image_pointer"));
+        let image = pointer
+            .as_ref()
+            .and_then(|pointer| pointer.as_str())
+            .and_then(|pointer| raw_data.pointer(pointer))
+            .and_then(|source| serde_json::from_value::<BlobRef>(source.clone()).ok());
+        if let Some(image) = image {
+            metadata["image"] = serde_json::to_value(image)?;
+        }
+        Ok(Some(blobs))
+    }
+
+    /// Move every inline base64 payload in `value` into the store, replacing
+    /// each with its [`BlobRef`] tagged `"type": "blob"`.
+    ///
+    /// Payloads that do not decode are left in place.
+    pub fn externalize(&self, value: &mut serde_json::Value) -> Result<Vec<BlobRef>> {
+        let mut refs = Vec::new();
+        self.externalize_into(value, &mut refs)?;
+        Ok(refs)
+    }
+
+    fn externalize_into(
+        &self,
+        value: &mut serde_json::Value,
+        refs: &mut Vec<BlobRef>,
+    ) -> Result<()> {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some((media_type, data)) = inline_payload(map) {
+                    if let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(data) {
+                        let blob = BlobRef {
+                            sha256: self.put(&bytes)?,
+                            media_type: media_type.to_string(),
+                            size: bytes.len() as u64,
+                        };
+                        let mut replacement = serde_json::to_value(&blob)?;
+                        replacement["type"] = "blob".into();
+                        *value = replacement;
+                        refs.push(blob);
+                        return Ok(());
+                    }
+                }
+                for child in map.values_mut() {
+                    self.externalize_into(child, refs)?;
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for child in items {
+                    self.externalize_into(child, refs)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Whether `value` holds any inline base64 payload.
+pub fn has_inline_payloads(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => {
+            inline_payload(map).is_some() || map.values().any(has_inline_payloads)
+        }
+        serde_json::Value::Array(items) => items.iter().any(has_inline_payloads),
+        _ => false,
+    }
+}
+
+/// A tool result holding inline base64 payloads, such as the content blocks
+/// of a screenshot, parsed as JSON. `None` for text and for JSON without
+/// payloads.
+pub fn parse_inline_payloads(text: &str) -> Option<serde_json::Value> {
+    if !text.contains("\"base64\"") {
+        return None;
+    }
+    serde_json::from_str(text).ok().filter(has_inline_payloads)
+}
+
+fn inline_payload(map: &serde_json::Map<String, serde_json::Value>) -> Option<(&str, &str)> {
+    if map.get("type")?.as_str()? != "base64" {
+        return None;
+    }
+    Some((map.get("media_type")?.as_str()?, map.get("data")?.as_str()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_put_is_content_addressed() {
+        let temp = TempDir::new().unwrap();
+        let store = BlobStore::new(temp.path().join("blobs"));
+
+        let sha256 = store.put(b"pixels").unwrap();
+        assert_eq!(store.put(b"pixels").unwrap(), sha256);
+        assert_eq!(store.get(&sha256).unwrap().as_deref(), Some(&b"pixels"[..]));
+        assert!(store
+            .path(&sha256)
+            .unwrap()
+            .starts_with(temp.path().join("blobs").join(&sha256[..2])));
+
+        assert_eq!(store.get(&"0".repeat(64)).unwrap(), None);
+        assert_eq!(store.path("../../etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_externalize_replaces_payloads() {
+        let temp = TempDir::new().unwrap();
+        let store = BlobStore::new(temp.path().to_path_buf());
+        let data = base64::engine::general_purpose::STANDARD.encode(b"png bytes");
+        let mut record = serde_json::json!({
+            "message": {"content": [
+                {"type": "text", "text": "look"},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": data}},
+                {"type": "tool_result", "content": [
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": data}},
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "%%%"}}
+                ]}
+            ]}
+        });
+        assert!(has_inline_payloads(&record));
+
+        let refs = store.externalize(&mut record).unwrap();
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0], refs[1]);
+        assert_eq!(refs[0].size, 9);
+        assert_eq!(
+            store.get(&refs[0].sha256).unwrap().as_deref(),
+            Some(&b"png bytes"[..])
+        );
+
+        let source = record.pointer("/message/content/1/source").unwrap();
+        assert_eq!(source["type"], "blob");
+        assert_eq!(source["sha256"], refs[0].sha256.as_str());
+        assert!(source.get("data").is_none());
+        // Undecodable payloads stay inline
+        assert!(has_inline_payloads(&record));
+    }
+
+    #[test]
+    fn test_list_and_remove() {
+        let temp = TempDir::new().unwrap();
+        let store = BlobStore::new(temp.path().join("blobs"));
+        assert!(store.list().unwrap().is_empty());
+
+        let kept = store.put(b"kept").unwrap();
+        let removed = store.put(b"removed").unwrap();
+        let mut listed: Vec<String> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|(sha, _)| sha)
+            .collect();
+        listed.sort();
+        let mut expected = vec![kept.clone(), removed.clone()];
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        store.remove(&removed).unwrap();
+        store.remove(&removed).unwrap();
+        assert_eq!(store.get(&removed).unwrap(), None);
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+}
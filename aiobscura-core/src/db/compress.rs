@@ -0,0 +1,230 @@
+//! zstd compression for the bulky message columns
+//!
+//! `messages.raw_data` and `messages.tool_result` hold whole log records and
+//! tool output (file reads, command output), most of the database's size.
+//! Values of at least [`COMPRESS_MIN_BYTES`] are stored as zstd frames in
+//! BLOBs; shorter ones stay TEXT, so the storage class tells them apart.
+//! [`Database`](super::Database) decompresses when reading messages, and the
+//! SQL function `aiobscura_text(value)` does the same inside SQL, e.g. for
+//! ad hoc queries.
+//!
+//! A dictionary trained on local data compresses the many small, similar
+//! records much better. Trained dictionaries are kept in
+//! `compression_dictionaries`; new values use the latest one, and each frame
+//! records the id of the dictionary it needs, so older frames stay readable.
+
+use crate::error::Result;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, Connection, Row, RowIndex};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+/// Values shorter than this are stored as they are: a frame's overhead
+/// outweighs the saving.
+pub(crate) const COMPRESS_MIN_BYTES: usize = 256;
+
+/// zstd level: fast to compress, and reads decompress at the same speed at
+/// any level.
+const LEVEL: i32 = 3;
+
+/// Maximum size of a trained dictionary (zstd's recommended default).
+const DICTIONARY_SIZE: usize = 112_640;
+
+/// Decoder for every dictionary loaded in this process, by zstd dictionary
+/// id. Rows are decoded without a handle on their database, so this is
+/// shared by all of them; ids are derived from the dictionary's content.
+static DECODERS: LazyLock<RwLock<HashMap<u32, Arc<DecoderDictionary<'static>>>>> =
+    LazyLock::new(Default::default);
+
+/// Register `aiobscura_text(value)`, which returns a compressed value as
+/// text and any other value unchanged.
+pub(crate) fn register_functions(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "aiobscura_text",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| match ctx.get_raw(0) {
+            ValueRef::Blob(frame) => decode(frame)
+                .map(Value::Text)
+                .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e))),
+            value => Ok(Value::from(value)),
+        },
+    )
+}
+
+/// The stored form of `text`: a zstd frame if it is long enough and
+/// compresses, otherwise the text itself.
+pub(crate) fn encode(text: String, dictionary: Option<&EncoderDictionary<'_>>) -> Result<Value> {
+    if text.len() < COMPRESS_MIN_BYTES {
+        return Ok(Value::Text(text));
+    }
+    let frame = match dictionary {
+        Some(dictionary) => zstd::bulk::Compressor::with_prepared_dictionary(dictionary)?
+            .compress(text.as_bytes())?,
+        None => zstd::bulk::compress(text.as_bytes(), LEVEL)?,
+    };
+    if frame.len() < text.len() {
+        Ok(Value::Blob(frame))
+    } else {
+        Ok(Value::Text(text))
+    }
+}
+
+/// Decompress a frame written by [`encode`].
+pub(crate) fn decode(frame: &[u8]) -> std::io::Result<String> {
+    let mut text = String::new();
+    match zstd::zstd_safe::get_dict_id_from_frame(frame) {
+        Some(id) => {
+            let decoder = DECODERS
+                .read()
+                .ok()
+                .and_then(|decoders| decoders.get(&id.get()).cloned())
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("compression dictionary {} is not loaded", id),
+                    )
+                })?;
+            zstd::stream::read::Decoder::with_prepared_dictionary(frame, &decoder)?
+                .read_to_string(&mut text)?;
+        }
+        None => {
+            zstd::stream::read::Decoder::new(frame)?.read_to_string(&mut text)?;
+        }
+    }
+    Ok(text)
+}
+
+/// Read a column that may be compressed as text.
+pub(crate) fn column_text<I: RowIndex>(row: &Row, idx: I) -> rusqlite::Result<Option<String>> {
+    match row.get_ref(idx)? {
+        ValueRef::Null => Ok(None),
+        ValueRef::Blob(frame) => decode(frame).map(Some).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e))
+        }),
+        ValueRef::Text(text) => String::from_utf8(text.to_vec()).map(Some).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        }),
+        other => Err(rusqlite::Error::InvalidColumnType(
+            0,
+            "compressed text".to_string(),
+            other.data_type(),
+        )),
+    }
+}
+
+/// Train a dictionary on sample values.
+pub(crate) fn train(samples: &[Vec<u8>]) -> Result<Vec<u8>> {
+    Ok(zstd::dict::from_samples(samples, DICTIONARY_SIZE)?)
+}
+
+/// The zstd id of a trained dictionary.
+pub(crate) fn dictionary_id(dictionary: &[u8]) -> Option<u32> {
+    zstd::zstd_safe::get_dict_id_from_dict(dictionary).map(|id| id.get())
+}
+
+/// A database's trained dictionaries, loaded as they are added.
+#[derive(Default)]
+pub(crate) struct Dictionaries {
+    /// Row id of the last dictionary loaded
+    loaded_upto: AtomicI64,
+    /// Encoder for the latest dictionary, used for new values
+    active: RwLock<Option<Arc<EncoderDictionary<'static>>>>,
+}
+
+impl Dictionaries {
+    /// Load dictionaries added since the last call, e.g. by another process.
+    pub(crate) fn refresh(&self, conn: &Connection) -> Result<()> {
+        let loaded_upto = self.loaded_upto.load(Ordering::Acquire);
+        let mut stmt = match conn.prepare_cached(
+            "SELECT id, dictionary FROM compression_dictionaries WHERE id > ?1 ORDER BY id",
+        ) {
+            Ok(stmt) => stmt,
+            // Not migrated yet
+            Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+                if message.starts_with("no such table") =>
+            {
+                return Ok(())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let rows = stmt
+            .query_map(params![loaded_upto], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        for (id, dictionary) in rows {
+            self.load(&dictionary);
+            self.loaded_upto.fetch_max(id, Ordering::AcqRel);
+        }
+        Ok(())
+    }
+
+    /// Make `dictionary` readable and use it for new values.
+    pub(crate) fn load(&self, dictionary: &[u8]) {
+        if let Some(id) = dictionary_id(dictionary) {
+            if let Ok(mut decoders) = DECODERS.write() {
+                decoders
+                    .entry(id)
+                    .or_insert_with(|| Arc::new(DecoderDictionary::copy(dictionary)));
+            }
+        }
+        if let Ok(mut active) = self.active.write() {
+            *active = Some(Arc::new(EncoderDictionary::copy(dictionary, LEVEL)));
+        }
+    }
+
+    /// Encoder for new values, if a dictionary was trained.
+    pub(crate) fn active(&self) -> Option<Arc<EncoderDictionary<'static>>> {
+        self.active.read().ok().and_then(|active| active.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(i: usize) -> String {
+        format!(
+            r#"{{"type":"assistant","uuid":"{i:08}","message":{{"role":"assistant","content":[{{"type":"text","text":"Step {i}: reading the configuration and running the tests again"}}]}},"cwd":"/Users/alice/src/app","gitBranch":"main","version":"2.0.31","timestamp":"2025-12-06T10:00:00.000Z"}}"#
+        )
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let short = "tiny".to_string();
+        assert_eq!(encode(short.clone(), None).unwrap(), Value::Text(short));
+
+        let long = "ls -la\n".repeat(100);
+        let Value::Blob(frame) = encode(long.clone(), None).unwrap() else {
+            panic!("long text should be compressed");
+        };
+        assert!(frame.len() < long.len() / 5);
+        assert_eq!(decode(&frame).unwrap(), long);
+    }
+
+    #[test]
+    fn test_dictionary_roundtrip() {
+        let samples: Vec<Vec<u8>> = (0..2000).map(|i| sample(i).into_bytes()).collect();
+        let dictionary = train(&samples).unwrap();
+        let dictionaries = Dictionaries::default();
+        dictionaries.load(&dictionary);
+        let encoder = dictionaries.active().unwrap();
+
+        let text = sample(99_999);
+        let Value::Blob(with_dictionary) = encode(text.clone(), Some(&encoder)).unwrap() else {
+            panic!("record should be compressed");
+        };
+        let without = match encode(text.clone(), None).unwrap() {
+            Value::Blob(frame) => frame.len(),
+            _ => text.len(),
+        };
+        assert!(with_dictionary.len() < without);
+        assert_eq!(decode(&with_dictionary).unwrap(), text);
+    }
+}
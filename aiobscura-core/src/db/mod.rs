@@ -5,10 +5,16 @@
 //! - Repository pattern for queries
 //! - Checkpoint tracking for incremental ingestion
 
+pub mod blobs;
+mod compress;
+mod pool;
 pub mod repo;
 pub mod schema;
 
+pub use blobs::BlobStore;
 pub use repo::{
-    AssistantHealth, CollectorPublishState, Database, EnvironmentHealth, FileStats, NewAssessment,
-    SessionFilter, SessionSummary, ThreadMetadata, ThreadSummary, TokenUsage, ToolStats,
+    AssistantHealth, CollectorPublishState, CompressionSummary, Database, EnvironmentHealth,
+    ExportFilter, ExportWatermark, FileStats, ModelUsage, NewAssessment, ParseFailureGroup,
+    PruneSummary, SearchFilter, SearchHit, SessionFilter, SessionSummary, ThreadMetadata,
+    ThreadSummary, TokenUsage, ToolStats, SNIPPET_MATCH_END, SNIPPET_MATCH_START,
 };
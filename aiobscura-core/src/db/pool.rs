@@ -0,0 +1,115 @@
+//! Read-only connections for queries
+//!
+//! The database runs in WAL mode, where readers see the last committed state
+//! and never wait for the writer. [`Database`](super::Database) keeps one
+//! writer connection behind a mutex and hands queries a connection from a
+//! [`ReaderPool`] instead, so a slow view or a long `sync_all` does not hold
+//! up the other.
+//!
+//! Readers are opened on first use and returned to the pool when dropped.
+
+use crate::error::{Error, Result};
+use parking_lot::ReentrantMutexGuard;
+use rusqlite::{Connection, OpenFlags};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Idle readers kept open for reuse. Concurrent queries beyond this open
+/// extra connections that are closed when they finish.
+const MAX_IDLE_READERS: usize = 4;
+
+/// Open `path` for reading only.
+pub(crate) fn open_read_only(path: &Path) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_URI
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.execute_batch("PRAGMA cache_size = -16000;")?;
+    super::compress::register_functions(&conn)?;
+    Ok(conn)
+}
+
+/// Read-only connections to one database file.
+#[derive(Debug)]
+pub(crate) struct ReaderPool {
+    path: PathBuf,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl ReaderPool {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Take an idle reader, or open a new one.
+    pub(crate) fn get(&self) -> Result<PooledConnection<'_>> {
+        let idle = self.lock_idle()?.pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => open_read_only(&self.path)?,
+        };
+        Ok(PooledConnection {
+            pool: self,
+            conn: Some(conn),
+        })
+    }
+
+    fn lock_idle(&self) -> Result<MutexGuard<'_, Vec<Connection>>> {
+        self.idle
+            .lock()
+            .map_err(|e| Error::Config(format!("reader pool lock poisoned: {e}")))
+    }
+}
+
+/// A reader borrowed from a [`ReaderPool`].
+pub(crate) struct PooledConnection<'a> {
+    pool: &'a ReaderPool,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("connection is taken only on drop")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        if let Ok(mut idle) = self.pool.idle.lock() {
+            if idle.len() < MAX_IDLE_READERS {
+                idle.push(conn);
+            }
+        }
+    }
+}
+
+/// Connection a query runs on: a pooled reader, or the writer when the
+/// database has no readers or the caller holds the writer.
+pub(crate) enum ReadConnection<'a> {
+    Reader(PooledConnection<'a>),
+    Writer(ReentrantMutexGuard<'a, Connection>),
+}
+
+impl Deref for ReadConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            Self::Reader(conn) => conn,
+            Self::Writer(conn) => conn,
+        }
+    }
+}
@@ -2,12 +2,18 @@
 //!
 //! Provides query and insert operations for all entity types.
 
+use super::blobs::BlobStore;
+use super::compress::{self, Dictionaries};
+use super::pool::{self, ReadConnection, ReaderPool};
 use crate::error::{Error, Result};
+use crate::ingest::FailedRecord;
 use crate::types::*;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use parking_lot::{ReentrantMutex, ReentrantMutexGuard};
 use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
 
 /// Agent spawn info for linking threads to Task tool calls.
 #[derive(Debug, Clone)]
@@ -39,6 +45,45 @@ pub struct TokenUsage {
     pub tokens_in: i64,
     /// Total output tokens
     pub tokens_out: i64,
+    /// Total input tokens served from the prompt cache
+    pub tokens_cache_read: i64,
+    /// Total input tokens written to the prompt cache
+    pub tokens_cache_write: i64,
+}
+
+impl TokenUsage {
+    /// Total tokens (in + out + cache reads and writes).
+    pub fn total(&self) -> i64 {
+        self.tokens_in + self.tokens_out + self.tokens_cache_read + self.tokens_cache_write
+    }
+}
+
+/// Token usage for one project, backing model and day, used for cost estimates.
+#[derive(Debug, Clone)]
+pub struct ModelUsage {
+    /// Project the sessions belong to (if known)
+    pub project_id: Option<String>,
+    /// Backing model of the messages (if known)
+    pub backing_model_id: Option<String>,
+    /// UTC day the messages were emitted
+    pub day: NaiveDate,
+    /// Tokens used
+    pub usage: TokenUsage,
+}
+
+/// Parse failures that share an error, for spotting format changes.
+#[derive(Debug, Clone)]
+pub struct ParseFailureGroup {
+    /// Assistant whose parser failed
+    pub assistant: Assistant,
+    /// Error with record positions (e.g. "at line 1 column 5") removed
+    pub error: String,
+    /// Number of failed records
+    pub count: usize,
+    /// Number of distinct source files
+    pub files: usize,
+    /// Most recent failure
+    pub last_recorded_at: DateTime<Utc>,
 }
 
 /// File modification statistics for a thread.
@@ -69,6 +114,10 @@ pub struct SessionSummary {
     pub message_count: i64,
     /// Model name (if known)
     pub model_name: Option<String>,
+    /// Label of the data root the session was read from
+    pub source_root: Option<String>,
+    /// Host label of the data root the session was read from
+    pub source_host: Option<String>,
 }
 
 /// Thread summary with session and project context for list views.
@@ -84,6 +133,43 @@ pub struct ThreadSummary {
     pub message_count: i64,
 }
 
+/// A message matching a full-text search, with where it was said.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    /// Message database ID
+    pub message_id: i64,
+    /// Session the message belongs to
+    pub session_id: String,
+    /// Thread the message belongs to
+    pub thread_id: String,
+    /// Type of the thread (main, agent, background)
+    pub thread_type: ThreadType,
+    /// Project the session belongs to (if known)
+    pub project_id: Option<String>,
+    /// Project display name (if known)
+    pub project_name: Option<String>,
+    /// Which assistant produced the message
+    pub assistant: Assistant,
+    /// Type of the message
+    pub message_type: MessageType,
+    /// Author role
+    pub author_role: AuthorRole,
+    /// Tool name (for tool calls and results)
+    pub tool_name: Option<String>,
+    /// When the message was emitted
+    pub emitted_at: DateTime<Utc>,
+    /// Excerpt around the match, with matched terms wrapped in
+    /// [`SNIPPET_MATCH_START`] and [`SNIPPET_MATCH_END`]
+    pub snippet: String,
+    /// BM25 relevance; lower is more relevant
+    pub rank: f64,
+}
+
+/// Marks the start of a matched term in [`SearchHit::snippet`].
+pub const SNIPPET_MATCH_START: &str = "\u{ab}";
+/// Marks the end of a matched term in [`SearchHit::snippet`].
+pub const SNIPPET_MATCH_END: &str = "\u{bb}";
+
 /// Metadata for a thread detail view.
 #[derive(Debug, Clone)]
 pub struct ThreadMetadata {
@@ -168,17 +254,52 @@ pub struct NewAssessment<'a> {
     pub prompt_hash: Option<&'a str>,
 }
 
-/// Database handle with connection pooling (single connection for now)
+/// Database handle with one writer connection and a pool of readers
+///
+/// Writes go through a single writer connection. Queries run on read-only
+/// connections from a pool, so they neither wait for nor block ingestion.
+/// In-memory databases have no readers and run everything on the writer.
+///
+/// The writer lock is reentrant: a thread holding it, e.g. for an open
+/// [`Database::savepoint`], can keep writing and querying through it.
 pub struct Database {
-    conn: Mutex<Connection>,
+    conn: ReentrantMutex<Connection>,
+    readers: Option<ReaderPool>,
+    dictionaries: Dictionaries,
+    blobs: Option<BlobStore>,
 }
 
 impl Database {
-    /// Acquire the shared database connection or return a typed error.
-    fn lock_conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
-        self.conn
-            .lock()
-            .map_err(|e| Error::Config(format!("database connection lock poisoned: {e}")))
+    /// Acquire the shared database connection.
+    fn lock_conn(&self) -> Result<ReentrantMutexGuard<'_, Connection>> {
+        Ok(self.conn.lock())
+    }
+
+    /// Connection for a query: a pooled reader, or the writer while the
+    /// calling thread holds it, e.g. with a savepoint open.
+    fn read_conn(&self) -> Result<ReadConnection<'_>> {
+        let conn = match &self.readers {
+            Some(readers) if !self.conn.is_owned_by_current_thread() => {
+                ReadConnection::Reader(readers.get()?)
+            }
+            _ => ReadConnection::Writer(self.lock_conn()?),
+        };
+        // Messages may be compressed with a dictionary another process trained
+        self.dictionaries.refresh(&conn)?;
+        Ok(conn)
+    }
+
+    fn with_connections(
+        conn: Connection,
+        readers: Option<ReaderPool>,
+        blobs: Option<BlobStore>,
+    ) -> Self {
+        Self {
+            conn: ReentrantMutex::new(conn),
+            readers,
+            dictionaries: Dictionaries::default(),
+            blobs,
+        }
     }
 
     /// Open or create a database at the given path
@@ -199,32 +320,137 @@ impl Database {
             PRAGMA cache_size = -64000;  -- 64MB cache
             ",
         )?;
+        compress::register_functions(&conn)?;
 
-        Ok(Self {
-            conn: Mutex::new(conn),
-        })
+        Ok(Self::with_connections(
+            conn,
+            Some(ReaderPool::new(path.clone())),
+            path.parent().map(|dir| BlobStore::new(dir.join("blobs"))),
+        ))
+    }
+
+    /// Open an existing database without write access.
+    ///
+    /// For processes that only view data while another process ingests.
+    /// Every write fails, and the schema must already be migrated.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        Ok(Self::with_connections(
+            pool::open_read_only(path)?,
+            Some(ReaderPool::new(path.to_path_buf())),
+            path.parent().map(|dir| BlobStore::new(dir.join("blobs"))),
+        ))
     }
 
     /// Open an in-memory database (for testing)
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
         conn.execute("PRAGMA foreign_keys = ON", [])?;
-        Ok(Self {
-            conn: Mutex::new(conn),
-        })
+        compress::register_functions(&conn)?;
+        Ok(Self::with_connections(conn, None, None))
     }
 
     /// Run migrations on this database
+    ///
+    /// Upgrading from before compression compresses the stored messages, in
+    /// batches, and vacuums the file, which can take a while on a large
+    /// database.
     pub fn migrate(&self) -> Result<()> {
-        let conn = self.lock_conn()?;
-        super::schema::run_migrations(&conn)
+        let previous = {
+            let conn = self.lock_conn()?;
+            let previous = super::schema::get_schema_version(&conn)?;
+            super::schema::run_migrations(&conn)?;
+            previous
+        };
+
+        if previous > 0 && previous < super::schema::FTS_CONTENTLESS_VERSION {
+            self.index_compressed_tool_results()?;
+        }
+        if previous > 0 && previous < super::schema::COMPRESSION_VERSION {
+            let summary = self.compress_messages(false)?;
+            if summary.rows > 0 {
+                self.vacuum()?;
+            }
+            tracing::info!(
+                rows = summary.rows,
+                bytes_before = summary.bytes_before,
+                bytes_after = summary.bytes_after,
+                "Compressed stored messages"
+            );
+        }
+        Ok(())
+    }
+
+    /// Add the compressed tool results stored before the full-text index
+    /// became contentless to the index, which the migration could not
+    /// decompress.
+    fn index_compressed_tool_results(&self) -> Result<()> {
+        let mut last_id = 0i64;
+        loop {
+            let (fetched, next_id) = self.savepoint(|| {
+                let conn = self.lock_conn()?;
+                self.dictionaries.refresh(&conn)?;
+                let rows = {
+                    let mut stmt = conn.prepare(
+                        r#"
+                        SELECT id, tool_result FROM messages
+                        WHERE id > ?1 AND typeof(tool_result) = 'blob'
+                        ORDER BY id LIMIT ?2
+                        "#,
+                    )?;
+                    let rows = stmt
+                        .query_map(params![last_id, COMPRESS_BATCH_SIZE as i64], |row| {
+                            Ok((row.get::<_, i64>(0)?, compress::column_text(row, 1)?))
+                        })?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    rows
+                };
+                for (id, tool_result) in &rows {
+                    Self::reindex_tool_result(&conn, *id, tool_result.as_deref())?;
+                }
+                Ok((rows.len(), rows.last().map(|row| row.0)))
+            })?;
+
+            match next_id {
+                Some(id) if fetched == COMPRESS_BATCH_SIZE => last_id = id,
+                _ => break,
+            }
+        }
+        Ok(())
     }
 
     /// Get the underlying connection (for advanced use)
-    pub fn connection(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+    pub fn connection(&self) -> Result<ReentrantMutexGuard<'_, Connection>> {
         self.lock_conn()
     }
 
+    /// Run `f` inside a savepoint, committing its writes together or rolling
+    /// them back if it fails.
+    ///
+    /// Savepoints nest, so a batch of writes can wrap smaller units that are
+    /// each rolled back on their own. Outside a transaction the outermost
+    /// savepoint opens one, which saves a commit per statement.
+    ///
+    /// The writer stays locked until the savepoint ends, so other threads'
+    /// writes wait instead of joining it (and being rolled back with it).
+    pub fn savepoint<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let conn = self.lock_conn()?;
+        conn.execute_batch("SAVEPOINT aiobscura")?;
+        match f() {
+            Ok(value) => {
+                conn.execute_batch("RELEASE aiobscura")?;
+                Ok(value)
+            }
+            Err(e) => {
+                if let Err(rollback_err) =
+                    conn.execute_batch("ROLLBACK TO aiobscura; RELEASE aiobscura")
+                {
+                    tracing::warn!(error = %rollback_err, "Failed to roll back savepoint");
+                }
+                Err(e)
+            }
+        }
+    }
+
     fn decode_error(field: &str, value: &str, err: impl std::fmt::Display) -> rusqlite::Error {
         rusqlite::Error::FromSqlConversionFailure(
             0,
@@ -302,7 +528,7 @@ impl Database {
 
     /// Get a project by ID
     pub fn get_project(&self, id: &str) -> Result<Option<Project>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         conn.query_row("SELECT * FROM projects WHERE id = ?", [id], |row| {
             Self::row_to_project(row)
         })
@@ -312,7 +538,7 @@ impl Database {
 
     /// Get a project by path
     pub fn get_project_by_path(&self, path: &Path) -> Result<Option<Project>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         conn.query_row(
             "SELECT * FROM projects WHERE path = ?",
             [path.to_string_lossy().to_string()],
@@ -341,6 +567,112 @@ impl Database {
         })
     }
 
+    /// Move every session of project `from` into project `to` and delete
+    /// `from`, in one transaction.
+    ///
+    /// `to` keeps its path and name and takes the earlier creation time and
+    /// later activity of the two. Returns the number of sessions moved.
+    pub fn merge_projects(&self, from: &str, to: &str) -> Result<usize> {
+        if from == to {
+            return Ok(0);
+        }
+
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+
+            for id in [from, to] {
+                let exists: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM projects WHERE id = ?)",
+                    [id],
+                    |row| row.get(0),
+                )?;
+                if !exists {
+                    return Err(Error::ProjectNotFound(id.to_string()));
+                }
+            }
+
+            let moved = conn.execute(
+                "UPDATE sessions SET project_id = ?2 WHERE project_id = ?1",
+                params![from, to],
+            )?;
+            conn.execute(
+                r#"
+                UPDATE projects SET
+                    created_at = MIN(created_at, (SELECT created_at FROM projects WHERE id = ?1)),
+                    last_activity_at = CASE
+                        WHEN last_activity_at IS NULL
+                            THEN (SELECT last_activity_at FROM projects WHERE id = ?1)
+                        ELSE MAX(last_activity_at,
+                                 COALESCE((SELECT last_activity_at FROM projects WHERE id = ?1), ''))
+                    END
+                WHERE id = ?2
+                "#,
+                params![from, to],
+            )?;
+            conn.execute(
+                "DELETE FROM plugin_metrics WHERE entity_type = 'project' AND entity_id = ?",
+                [from],
+            )?;
+            conn.execute("DELETE FROM projects WHERE id = ?", [from])?;
+
+            Ok(moved)
+        })
+    }
+
+    /// Projects stored before project identity resolution, which still
+    /// need re-resolving (see schema version 23).
+    pub fn list_unresolved_projects(&self) -> Result<Vec<Project>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare("SELECT * FROM projects WHERE resolved = 0 ORDER BY path")?;
+        let projects = stmt
+            .query_map([], Self::row_to_project)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(projects)
+    }
+
+    /// Mark every project as resolved.
+    pub fn mark_projects_resolved(&self) -> Result<()> {
+        let conn = self.lock_conn()?;
+        conn.execute("UPDATE projects SET resolved = 1 WHERE resolved = 0", [])?;
+        Ok(())
+    }
+
+    /// Give project `from` the id, path, name and metadata of `to`, which
+    /// must not exist yet, and move its sessions along.
+    ///
+    /// Its creation time and activity are kept. Returns the number of
+    /// sessions moved.
+    pub fn rekey_project(&self, from: &str, to: &Project) -> Result<usize> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            // Sessions reference the old id until the end of the transaction
+            conn.execute_batch("PRAGMA defer_foreign_keys = ON")?;
+
+            let renamed = conn.execute(
+                "UPDATE projects SET id = ?2, path = ?3, name = ?4, metadata = ?5 WHERE id = ?1",
+                params![
+                    from,
+                    to.id,
+                    to.path.to_string_lossy().to_string(),
+                    to.name,
+                    to.metadata.to_string(),
+                ],
+            )?;
+            if renamed == 0 {
+                return Err(Error::ProjectNotFound(from.to_string()));
+            }
+            let moved = conn.execute(
+                "UPDATE sessions SET project_id = ?2 WHERE project_id = ?1",
+                params![from, to.id],
+            )?;
+            conn.execute(
+                "DELETE FROM plugin_metrics WHERE entity_type = 'project' AND entity_id = ?",
+                [from],
+            )?;
+            Ok(moved)
+        })
+    }
+
     // ============================================
     // BackingModel operations
     // ============================================
@@ -370,7 +702,7 @@ impl Database {
 
     /// Get a backing model by ID
     pub fn get_backing_model(&self, id: &str) -> Result<Option<BackingModel>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         conn.query_row(
             "SELECT * FROM backing_models WHERE id = ?",
             [id],
@@ -410,9 +742,9 @@ impl Database {
                 "byte_offset".to_string(),
                 serde_json::json!({"offset": offset}),
             ),
-            Checkpoint::ContentHash { hash } => (
+            Checkpoint::ContentHash { hash, records } => (
                 "content_hash".to_string(),
-                serde_json::json!({"hash": hash}),
+                serde_json::json!({"hash": hash, "records": records}),
             ),
             Checkpoint::DatabaseCursor {
                 table,
@@ -432,14 +764,17 @@ impl Database {
         conn.execute(
             r#"
             INSERT INTO source_files (path, file_type, assistant, created_at, modified_at,
-                                       size_bytes, last_parsed_at, checkpoint_type, checkpoint_data)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                                       size_bytes, last_parsed_at, checkpoint_type, checkpoint_data,
+                                       parser_version, agent)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
             ON CONFLICT(path) DO UPDATE SET
+                agent = excluded.agent,
                 modified_at = excluded.modified_at,
                 size_bytes = excluded.size_bytes,
                 last_parsed_at = excluded.last_parsed_at,
                 checkpoint_type = excluded.checkpoint_type,
-                checkpoint_data = excluded.checkpoint_data
+                checkpoint_data = excluded.checkpoint_data,
+                parser_version = excluded.parser_version
             "#,
             params![
                 file.path.to_string_lossy().to_string(),
@@ -451,6 +786,8 @@ impl Database {
                 file.last_parsed_at.map(|t| t.to_rfc3339()),
                 checkpoint_type,
                 checkpoint_data.to_string(),
+                file.parser_version,
+                file.agent,
             ],
         )?;
         Ok(())
@@ -458,7 +795,7 @@ impl Database {
 
     /// Get a source file by path
     pub fn get_source_file(&self, path: &str) -> Result<Option<SourceFile>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         conn.query_row(
             "SELECT * FROM source_files WHERE path = ?",
             [path],
@@ -468,6 +805,112 @@ impl Database {
         .map_err(Error::from)
     }
 
+    /// List all source files, ordered by path
+    pub fn list_source_files(&self) -> Result<Vec<SourceFile>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare("SELECT * FROM source_files ORDER BY path")?;
+        let files = stmt
+            .query_map([], Self::row_to_source_file)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(files)
+    }
+
+    /// Paths of source files with messages whose raw data was pruned.
+    pub fn list_pruned_source_files(&self) -> Result<Vec<String>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn
+            .prepare("SELECT path FROM source_files WHERE pruned_at IS NOT NULL ORDER BY path")?;
+        let paths = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(paths)
+    }
+
+    /// List `(session_id, source_file_path)` pairs linking each session to
+    /// the files its records came from.
+    ///
+    /// A session can span several files (e.g. Claude Code agent files share
+    /// their parent's session ID).
+    pub fn list_session_source_links(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, source_file_path FROM sessions
+            UNION
+            SELECT DISTINCT session_id, source_file_path FROM messages
+            "#,
+        )?;
+        let links = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(links)
+    }
+
+    /// Delete sessions with everything derived from them, plus the given
+    /// source file records, so the files are ingested from scratch next sync.
+    ///
+    /// Removes the sessions' threads, messages, plans and agent spawns, and
+    /// their analytics (plugin metrics, session metrics, assessments) so they
+    /// are recomputed. Every file the sessions came from must be in
+    /// `source_paths` (see [`Self::list_session_source_links`]). The sessions
+    /// are recorded in `reparsed_sessions` for dataset exports.
+    pub fn delete_ingested_data(
+        &self,
+        session_ids: &[String],
+        source_paths: &[String],
+    ) -> Result<()> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+
+            for session_id in session_ids {
+                conn.execute(
+                    r#"
+                    DELETE FROM plugin_metrics
+                    WHERE (entity_type = 'session' AND entity_id = ?1)
+                       OR (entity_type = 'thread'
+                           AND entity_id IN (SELECT id FROM threads WHERE session_id = ?1))
+                    "#,
+                    [session_id],
+                )?;
+                for table in [
+                    "session_metrics",
+                    "assessments",
+                    "agent_spawns",
+                    "session_plans",
+                    "session_commits",
+                    "plans",
+                    "message_blobs",
+                    "messages",
+                    "threads",
+                ] {
+                    conn.execute(
+                        &format!("DELETE FROM {} WHERE session_id = ?", table),
+                        [session_id],
+                    )?;
+                }
+                conn.execute(
+                    "UPDATE sessions SET continues_session_id = NULL WHERE continues_session_id = ?",
+                    [session_id],
+                )?;
+                conn.execute("DELETE FROM sessions WHERE id = ?", [session_id])?;
+                conn.execute(
+                    "INSERT INTO reparsed_sessions (session_id, reparsed_at) VALUES (?, ?)",
+                    params![session_id, Utc::now().to_rfc3339()],
+                )?;
+            }
+
+            for path in source_paths {
+                conn.execute("DELETE FROM source_files WHERE path = ?", [path])?;
+                conn.execute(
+                    "DELETE FROM parse_failures WHERE source_file_path = ?",
+                    [path],
+                )?;
+            }
+
+            Ok(())
+        })
+    }
+
     fn row_to_source_file(row: &Row) -> rusqlite::Result<SourceFile> {
         let path_str: String = row.get("path")?;
         let file_type_str: String = row.get("file_type")?;
@@ -478,6 +921,8 @@ impl Database {
         let last_parsed_str: Option<String> = row.get("last_parsed_at")?;
         let checkpoint_type: Option<String> = row.get("checkpoint_type")?;
         let checkpoint_data_str: Option<String> = row.get("checkpoint_data")?;
+        let parser_version: u32 = row.get("parser_version")?;
+        let agent: Option<String> = row.get("agent")?;
 
         let checkpoint = match checkpoint_type.as_deref() {
             Some("byte_offset") => {
@@ -510,8 +955,10 @@ impl Database {
                 let hash = data.get("hash").and_then(|v| v.as_str()).ok_or_else(|| {
                     Self::decode_error("source_files.checkpoint_data", raw, "missing hash")
                 })?;
+                let records = data.get("records").and_then(|v| v.as_u64()).unwrap_or(0);
                 Checkpoint::ContentHash {
                     hash: hash.to_string(),
+                    records,
                 }
             }
             Some("database_cursor") => {
@@ -559,6 +1006,7 @@ impl Database {
             path: PathBuf::from(path_str),
             file_type: Self::parse_enum_field("source_files.file_type", &file_type_str)?,
             assistant: Self::parse_enum_field("source_files.assistant", &assistant_str)?,
+            agent,
             created_at: Self::parse_rfc3339_field("source_files.created_at", &created_at_str)?,
             modified_at: Self::parse_rfc3339_field("source_files.modified_at", &modified_at_str)?,
             size_bytes: size_bytes as u64,
@@ -567,102 +1015,596 @@ impl Database {
                 last_parsed_str,
             )?,
             checkpoint,
+            parser_version,
         })
     }
 
-    // ============================================
-    // Session operations
-    // ============================================
-
-    /// Insert or update a session
-    pub fn upsert_session(&self, session: &Session) -> Result<()> {
+    /// Record a snapshot imported from `root` under `label`.
+    ///
+    /// Importing the same root again updates its label.
+    pub fn record_import(&self, root: &Path, label: &str) -> Result<()> {
         let conn = self.lock_conn()?;
         conn.execute(
             r#"
-            INSERT INTO sessions (id, assistant, backing_model_id, project_id, started_at,
-                                  last_activity_at, status, source_file_path, metadata)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-            ON CONFLICT(id) DO UPDATE SET
-                backing_model_id = excluded.backing_model_id,
-                project_id = COALESCE(excluded.project_id, sessions.project_id),
-                last_activity_at = excluded.last_activity_at,
-                status = excluded.status,
-                metadata = excluded.metadata
+            INSERT INTO imports (root, label, imported_at)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(root) DO UPDATE SET
+                label = excluded.label,
+                imported_at = excluded.imported_at
             "#,
-            params![
-                session.id,
-                session.assistant.as_str(),
-                session.backing_model_id,
-                session.project_id,
-                session.started_at.to_rfc3339(),
-                session.last_activity_at.map(|t| t.to_rfc3339()),
-                session.status.as_str(),
-                session.source_file_path,
-                session.metadata.to_string(),
-            ],
+            params![root.to_string_lossy(), label, Utc::now().to_rfc3339()],
         )?;
         Ok(())
     }
 
-    /// Get a session by ID
-    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
-        let conn = self.lock_conn()?;
-        conn.query_row("SELECT * FROM sessions WHERE id = ?", [id], |row| {
-            Self::row_to_session(row)
-        })
-        .optional()
-        .map_err(Error::from)
+    /// List imported snapshots as (root, label), ordered by root
+    pub fn list_imports(&self) -> Result<Vec<(PathBuf, String)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare("SELECT root, label FROM imports ORDER BY root")?;
+        let imports = stmt
+            .query_map([], |row| {
+                Ok((PathBuf::from(row.get::<_, String>(0)?), row.get(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(imports)
     }
 
-    /// List sessions with optional filtering
-    pub fn list_sessions(&self, filter: &SessionFilter) -> Result<Vec<Session>> {
-        let conn = self.lock_conn()?;
-
-        let mut sql = String::from("SELECT * FROM sessions WHERE 1=1");
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
-
-        if let Some(assistant) = &filter.assistant {
-            sql.push_str(" AND assistant = ?");
-            params.push(Box::new(assistant.as_str().to_string()));
-        }
-
-        if let Some(status) = &filter.status {
-            sql.push_str(" AND status = ?");
-            params.push(Box::new(status.as_str().to_string()));
-        }
+    // ============================================
+    // Parse failure operations
+    // ============================================
 
-        if let Some(project_id) = &filter.project_id {
-            sql.push_str(" AND project_id = ?");
-            params.push(Box::new(project_id.clone()));
+    /// Record source records a parser could not interpret.
+    ///
+    /// A record that fails again at the same offset replaces the earlier entry.
+    pub fn insert_parse_failures(
+        &self,
+        source_file_path: &str,
+        assistant: Assistant,
+        parser_version: u32,
+        records: &[FailedRecord],
+    ) -> Result<()> {
+        let conn = self.lock_conn()?;
+        let recorded_at = Utc::now().to_rfc3339();
+        for record in records {
+            conn.execute(
+                r#"
+                INSERT INTO parse_failures (source_file_path, assistant, byte_offset, line_number,
+                                            raw_text, error, parser_version, recorded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                ON CONFLICT(source_file_path, byte_offset) DO UPDATE SET
+                    line_number = excluded.line_number,
+                    raw_text = excluded.raw_text,
+                    error = excluded.error,
+                    parser_version = excluded.parser_version,
+                    recorded_at = excluded.recorded_at
+                "#,
+                params![
+                    source_file_path,
+                    assistant.as_str(),
+                    record.offset as i64,
+                    record.line,
+                    record.raw,
+                    record.error,
+                    parser_version,
+                    recorded_at,
+                ],
+            )?;
         }
+        Ok(())
+    }
 
-        if let Some(since) = &filter.since {
-            sql.push_str(" AND started_at >= ?");
-            params.push(Box::new(since.to_rfc3339()));
-        }
+    /// List parse failures, most recent first, optionally only those of
+    /// one assistant or of the files of one custom `agent`.
+    pub fn list_parse_failures(
+        &self,
+        assistant: Option<Assistant>,
+        agent: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ParseFailure>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT * FROM parse_failures
+            WHERE (?1 IS NULL OR assistant = ?1) AND {agent}
+            ORDER BY recorded_at DESC, source_file_path, byte_offset
+            LIMIT ?3
+            "#,
+            agent = FAILURE_AGENT_SQL
+        ))?;
+        let failures = stmt
+            .query_map(
+                params![assistant.map(|a| a.as_str()), agent, limit as i64],
+                Self::row_to_parse_failure,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(failures)
+    }
 
-        sql.push_str(" ORDER BY last_activity_at DESC NULLS LAST");
+    /// Group parse failures by assistant and error, largest groups first,
+    /// filtered like [`Self::list_parse_failures`].
+    pub fn summarize_parse_failures(
+        &self,
+        assistant: Option<Assistant>,
+        agent: Option<&str>,
+    ) -> Result<Vec<ParseFailureGroup>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT assistant, {cause} AS cause, COUNT(*), COUNT(DISTINCT source_file_path),
+                   MAX(recorded_at)
+            FROM parse_failures
+            WHERE (?1 IS NULL OR assistant = ?1) AND {agent}
+            GROUP BY assistant, cause
+            ORDER BY COUNT(*) DESC, cause
+            "#,
+            cause = ERROR_CAUSE_SQL,
+            agent = FAILURE_AGENT_SQL
+        ))?;
+        let groups = stmt
+            .query_map(params![assistant.map(|a| a.as_str()), agent], |row| {
+                Ok(ParseFailureGroup {
+                    assistant: Self::parse_enum_field("assistant", &row.get::<_, String>(0)?)?,
+                    error: row.get(1)?,
+                    count: row.get::<_, i64>(2)? as usize,
+                    files: row.get::<_, i64>(3)? as usize,
+                    last_recorded_at: Self::parse_rfc3339_field(
+                        "recorded_at",
+                        &row.get::<_, String>(4)?,
+                    )?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(groups)
+    }
 
-        if let Some(limit) = filter.limit {
-            sql.push_str(&format!(" LIMIT {}", limit));
-        }
+    /// Paths of source files with recorded parse failures.
+    pub fn list_parse_failure_files(&self) -> Result<Vec<String>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT source_file_path FROM parse_failures ORDER BY source_file_path",
+        )?;
+        let paths = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(paths)
+    }
 
-        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    fn row_to_parse_failure(row: &Row) -> rusqlite::Result<ParseFailure> {
+        let assistant_str: String = row.get("assistant")?;
+        let recorded_at_str: String = row.get("recorded_at")?;
+        let byte_offset: i64 = row.get("byte_offset")?;
+        Ok(ParseFailure {
+            id: row.get("id")?,
+            source_file_path: row.get("source_file_path")?,
+            assistant: Self::parse_enum_field("parse_failures.assistant", &assistant_str)?,
+            byte_offset: byte_offset as u64,
+            line_number: row.get("line_number")?,
+            raw_text: row.get("raw_text")?,
+            error: row.get("error")?,
+            parser_version: row.get("parser_version")?,
+            recorded_at: Self::parse_rfc3339_field("parse_failures.recorded_at", &recorded_at_str)?,
+        })
+    }
 
-        let mut stmt = conn.prepare(&sql)?;
-        let sessions = stmt
-            .query_map(params_refs.as_slice(), Self::row_to_session)?
-            .collect::<std::result::Result<Vec<_>, _>>()?;
+    // ============================================
+    // Telemetry operations
+    // ============================================
 
-        Ok(sessions)
+    /// Store events received from an OpenTelemetry exporter.
+    pub fn insert_telemetry_events(&self, events: &[TelemetryEvent]) -> Result<()> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            for event in events {
+                conn.execute(
+                    r#"
+                    INSERT INTO telemetry_events (session_id, assistant, name, emitted_at, received_at,
+                                                  model, tool_name, decision, duration_ms, cost_usd,
+                                                  attributes)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                    params![
+                        event.session_id,
+                        event.assistant.map(|a| a.as_str()),
+                        event.name,
+                        event.emitted_at.to_rfc3339(),
+                        event.received_at.to_rfc3339(),
+                        event.model,
+                        event.tool_name,
+                        event.decision,
+                        event.duration_ms,
+                        event.cost_usd,
+                        event.attributes.to_string(),
+                    ],
+                )?;
+            }
+            Ok(())
+        })
     }
 
-    /// List sessions for a project with summary stats for TUI display.
-    ///
+    /// Store metric data points received from an OpenTelemetry exporter.
+    pub fn insert_telemetry_metrics(&self, metrics: &[TelemetryMetric]) -> Result<()> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            for metric in metrics {
+                conn.execute(
+                    r#"
+                    INSERT INTO telemetry_metrics (session_id, assistant, name, unit, value,
+                                                   temporality, monotonic, started_at,
+                                                   observed_at, received_at, attributes)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                    params![
+                        metric.session_id,
+                        metric.assistant.map(|a| a.as_str()),
+                        metric.name,
+                        metric.unit,
+                        metric.value,
+                        metric.temporality.map(|t| t.as_str()),
+                        metric.monotonic,
+                        metric.started_at.map(|t| t.to_rfc3339()),
+                        metric.observed_at.to_rfc3339(),
+                        metric.received_at.to_rfc3339(),
+                        metric.attributes.to_string(),
+                    ],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Telemetry events for a session, oldest first.
+    pub fn get_session_telemetry_events(&self, session_id: &str) -> Result<Vec<TelemetryEvent>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM telemetry_events WHERE session_id = ? ORDER BY emitted_at ASC, id ASC",
+        )?;
+        let events = stmt
+            .query_map([session_id], Self::row_to_telemetry_event)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    /// Telemetry metric data points for a session, oldest first.
+    pub fn get_session_telemetry_metrics(&self, session_id: &str) -> Result<Vec<TelemetryMetric>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM telemetry_metrics WHERE session_id = ? ORDER BY observed_at ASC, id ASC",
+        )?;
+        let metrics = stmt
+            .query_map([session_id], Self::row_to_telemetry_metric)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(metrics)
+    }
+
+    /// Total of a sum metric over a session, e.g. `cost.usage`.
+    ///
+    /// Delta points are added up. A cumulative series (same attributes and
+    /// start time) already holds its running total, so only its latest
+    /// point counts. Gauges and points without a temporality are ignored.
+    pub fn telemetry_metric_total(&self, session_id: &str, name: &str) -> Result<f64> {
+        let conn = self.read_conn()?;
+        let total = conn.query_row(
+            r#"
+            SELECT COALESCE(SUM(value), 0) FROM telemetry_metrics m
+            WHERE session_id = ?1 AND name = ?2
+              AND (temporality = 'delta'
+                   OR (temporality = 'cumulative' AND id = (
+                           SELECT id FROM telemetry_metrics latest
+                           WHERE latest.session_id = m.session_id
+                             AND latest.name = m.name
+                             AND latest.temporality = 'cumulative'
+                             AND latest.attributes = m.attributes
+                             AND latest.started_at IS m.started_at
+                           ORDER BY latest.observed_at DESC, latest.id DESC
+                           LIMIT 1)))
+            "#,
+            params![session_id, name],
+            |row| row.get(0),
+        )?;
+        Ok(total)
+    }
+
+    fn row_to_telemetry_event(row: &Row) -> rusqlite::Result<TelemetryEvent> {
+        let assistant_str: Option<String> = row.get("assistant")?;
+        let emitted_at_str: String = row.get("emitted_at")?;
+        let received_at_str: String = row.get("received_at")?;
+        let attributes_str: String = row.get("attributes")?;
+        Ok(TelemetryEvent {
+            id: row.get("id")?,
+            session_id: row.get("session_id")?,
+            assistant: assistant_str
+                .map(|a| Self::parse_enum_field("telemetry_events.assistant", &a))
+                .transpose()?,
+            name: row.get("name")?,
+            emitted_at: Self::parse_rfc3339_field("telemetry_events.emitted_at", &emitted_at_str)?,
+            received_at: Self::parse_rfc3339_field(
+                "telemetry_events.received_at",
+                &received_at_str,
+            )?,
+            model: row.get("model")?,
+            tool_name: row.get("tool_name")?,
+            decision: row.get("decision")?,
+            duration_ms: row.get("duration_ms")?,
+            cost_usd: row.get("cost_usd")?,
+            attributes: Self::parse_json_field("telemetry_events.attributes", &attributes_str)?,
+        })
+    }
+
+    fn row_to_telemetry_metric(row: &Row) -> rusqlite::Result<TelemetryMetric> {
+        let assistant_str: Option<String> = row.get("assistant")?;
+        let observed_at_str: String = row.get("observed_at")?;
+        let received_at_str: String = row.get("received_at")?;
+        let attributes_str: String = row.get("attributes")?;
+        let temporality_str: Option<String> = row.get("temporality")?;
+        let started_at_str: Option<String> = row.get("started_at")?;
+        Ok(TelemetryMetric {
+            id: row.get("id")?,
+            session_id: row.get("session_id")?,
+            assistant: assistant_str
+                .map(|a| Self::parse_enum_field("telemetry_metrics.assistant", &a))
+                .transpose()?,
+            name: row.get("name")?,
+            unit: row.get("unit")?,
+            value: row.get("value")?,
+            temporality: temporality_str
+                .map(|t| Self::parse_enum_field("telemetry_metrics.temporality", &t))
+                .transpose()?,
+            monotonic: row.get("monotonic")?,
+            started_at: Self::parse_optional_rfc3339_field(
+                "telemetry_metrics.started_at",
+                started_at_str,
+            )?,
+            observed_at: Self::parse_rfc3339_field(
+                "telemetry_metrics.observed_at",
+                &observed_at_str,
+            )?,
+            received_at: Self::parse_rfc3339_field(
+                "telemetry_metrics.received_at",
+                &received_at_str,
+            )?,
+            attributes: Self::parse_json_field("telemetry_metrics.attributes", &attributes_str)?,
+        })
+    }
+
+    // ============================================
+    // Hook event operations
+    // ============================================
+
+    /// Store events pushed by assistant hooks.
+    pub fn insert_hook_events(&self, events: &[HookEvent]) -> Result<()> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            for event in events {
+                conn.execute(
+                    r#"
+                    INSERT INTO hook_events (session_id, event_name, tool_name, tool_use_id, message,
+                                             received_at, payload)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    "#,
+                    params![
+                        event.session_id,
+                        event.event_name,
+                        event.tool_name,
+                        event.tool_use_id,
+                        event.message,
+                        event.received_at.to_rfc3339(),
+                        event.payload.to_string(),
+                    ],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Hook events for a session, oldest first.
+    pub fn get_session_hook_events(&self, session_id: &str) -> Result<Vec<HookEvent>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM hook_events WHERE session_id = ? ORDER BY received_at ASC, id ASC",
+        )?;
+        let events = stmt
+            .query_map([session_id], Self::row_to_hook_event)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    fn row_to_hook_event(row: &Row) -> rusqlite::Result<HookEvent> {
+        let received_at_str: String = row.get("received_at")?;
+        let payload_str: String = row.get("payload")?;
+        Ok(HookEvent {
+            id: row.get("id")?,
+            session_id: row.get("session_id")?,
+            event_name: row.get("event_name")?,
+            tool_name: row.get("tool_name")?,
+            tool_use_id: row.get("tool_use_id")?,
+            message: row.get("message")?,
+            received_at: Self::parse_rfc3339_field("hook_events.received_at", &received_at_str)?,
+            payload: Self::parse_json_field("hook_events.payload", &payload_str)?,
+        })
+    }
+
+    // ============================================
+    // Git correlation operations
+    // ============================================
+
+    /// Replace the commits correlated with a session.
+    pub fn replace_session_commits(
+        &self,
+        session_id: &str,
+        commits: &[SessionCommit],
+    ) -> Result<()> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            conn.execute(
+                "DELETE FROM session_commits WHERE session_id = ?",
+                [session_id],
+            )?;
+            let correlated_at = Utc::now().to_rfc3339();
+            for commit in commits {
+                conn.execute(
+                    r#"
+                    INSERT INTO session_commits (session_id, commit_sha, repo_path, committed_at,
+                                                 author_name, author_email, summary, matched_files,
+                                                 correlated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                    "#,
+                    params![
+                        session_id,
+                        commit.commit_sha,
+                        commit.repo_path.to_string_lossy(),
+                        commit.committed_at.to_rfc3339(),
+                        commit.author_name,
+                        commit.author_email,
+                        commit.summary,
+                        serde_json::to_string(&commit.matched_files)?,
+                        correlated_at,
+                    ],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Commits correlated with a session, oldest first.
+    pub fn get_session_commits(&self, session_id: &str) -> Result<Vec<SessionCommit>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM session_commits WHERE session_id = ? ORDER BY committed_at ASC",
+        )?;
+        let commits = stmt
+            .query_map([session_id], Self::row_to_session_commit)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(commits)
+    }
+
+    fn row_to_session_commit(row: &Row) -> rusqlite::Result<SessionCommit> {
+        let repo_path: String = row.get("repo_path")?;
+        let committed_at_str: String = row.get("committed_at")?;
+        let matched_files_str: String = row.get("matched_files")?;
+        Ok(SessionCommit {
+            session_id: row.get("session_id")?,
+            commit_sha: row.get("commit_sha")?,
+            repo_path: PathBuf::from(repo_path),
+            committed_at: Self::parse_rfc3339_field(
+                "session_commits.committed_at",
+                &committed_at_str,
+            )?,
+            author_name: row.get("author_name")?,
+            author_email: row.get("author_email")?,
+            summary: row.get("summary")?,
+            matched_files: serde_json::from_str(&matched_files_str).map_err(|e| {
+                Self::decode_error("session_commits.matched_files", &matched_files_str, e)
+            })?,
+        })
+    }
+    // ============================================
+    // Session operations
+    // ============================================
+
+    /// Insert or update a session
+    pub fn upsert_session(&self, session: &Session) -> Result<()> {
+        let conn = self.lock_conn()?;
+        conn.execute(
+            r#"
+            INSERT INTO sessions (id, assistant, backing_model_id, project_id, started_at,
+                                  last_activity_at, status, source_file_path, metadata)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT(id) DO UPDATE SET
+                backing_model_id = excluded.backing_model_id,
+                project_id = COALESCE(excluded.project_id, sessions.project_id),
+                last_activity_at = excluded.last_activity_at,
+                status = excluded.status,
+                metadata = excluded.metadata
+            "#,
+            params![
+                session.id,
+                session.assistant.as_str(),
+                session.backing_model_id,
+                session.project_id,
+                session.started_at.to_rfc3339(),
+                session.last_activity_at.map(|t| t.to_rfc3339()),
+                session.status.as_str(),
+                session.source_file_path,
+                session.metadata.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get a session by ID
+    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        let conn = self.read_conn()?;
+        conn.query_row("SELECT * FROM sessions WHERE id = ?", [id], |row| {
+            Self::row_to_session(row)
+        })
+        .optional()
+        .map_err(Error::from)
+    }
+
+    /// List sessions with optional filtering
+    pub fn list_sessions(&self, filter: &SessionFilter) -> Result<Vec<Session>> {
+        let conn = self.read_conn()?;
+
+        let mut sql = String::from("SELECT * FROM sessions WHERE 1=1");
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+
+        if let Some(assistant) = &filter.assistant {
+            sql.push_str(" AND assistant = ?");
+            params.push(Box::new(assistant.as_str().to_string()));
+        }
+
+        if let Some(agent) = &filter.agent {
+            sql.push_str(" AND json_extract(metadata, '$.agent') = ?");
+            params.push(Box::new(agent.clone()));
+        }
+
+        if let Some(status) = &filter.status {
+            sql.push_str(" AND status = ?");
+            params.push(Box::new(status.as_str().to_string()));
+        }
+
+        if let Some(project_id) = &filter.project_id {
+            sql.push_str(" AND project_id = ?");
+            params.push(Box::new(project_id.clone()));
+        }
+
+        if let Some(source_root) = &filter.source_root {
+            sql.push_str(" AND json_extract(metadata, '$.source_root') = ?");
+            params.push(Box::new(source_root.clone()));
+        }
+
+        if let Some(source_host) = &filter.source_host {
+            sql.push_str(" AND json_extract(metadata, '$.source_host') = ?");
+            params.push(Box::new(source_host.clone()));
+        }
+
+        if let Some(since) = &filter.since {
+            sql.push_str(" AND started_at >= ?");
+            params.push(Box::new(since.to_rfc3339()));
+        }
+
+        sql.push_str(" ORDER BY last_activity_at DESC NULLS LAST");
+
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = conn.prepare(&sql)?;
+        let sessions = stmt
+            .query_map(params_refs.as_slice(), Self::row_to_session)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(sessions)
+    }
+
+    /// List sessions for a project with summary stats for TUI display.
+    ///
     /// Returns sessions with pre-computed thread count, message count, and model name
     /// to avoid N+1 queries when rendering session lists.
     pub fn list_project_sessions(&self, project_id: &str) -> Result<Vec<SessionSummary>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         // Join sessions with aggregated stats from threads and messages
         let mut stmt = conn.prepare(
@@ -674,7 +1616,9 @@ impl Database {
                 s.last_activity_at,
                 COUNT(DISTINCT t.id) as thread_count,
                 COUNT(m.id) as message_count,
-                bm.display_name as model_name
+                bm.display_name as model_name,
+                json_extract(s.metadata, '$.source_root') as source_root,
+                json_extract(s.metadata, '$.source_host') as source_host
             FROM sessions s
             LEFT JOIN threads t ON t.session_id = s.id
             LEFT JOIN messages m ON m.session_id = s.id
@@ -702,6 +1646,8 @@ impl Database {
                     thread_count: row.get(4)?,
                     message_count: row.get(5)?,
                     model_name: row.get(6)?,
+                    source_root: row.get(7)?,
+                    source_host: row.get(8)?,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -762,7 +1708,7 @@ impl Database {
 
     /// Get threads for a session
     pub fn get_session_threads(&self, session_id: &str) -> Result<Vec<Thread>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt =
             conn.prepare("SELECT * FROM threads WHERE session_id = ? ORDER BY started_at ASC")?;
 
@@ -775,7 +1721,7 @@ impl Database {
 
     /// List all threads with message counts and session/project context.
     pub fn list_threads_with_counts(&self) -> Result<Vec<ThreadSummary>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             r#"
             SELECT
@@ -812,7 +1758,7 @@ impl Database {
 
     /// Get a single thread by ID
     pub fn get_thread(&self, thread_id: &str) -> Result<Option<Thread>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let thread = conn
             .query_row("SELECT * FROM threads WHERE id = ?", [thread_id], |row| {
                 Self::row_to_thread(row)
@@ -933,7 +1879,7 @@ impl Database {
     ///
     /// Returns the session ID and spawning message seq for linking agent threads.
     pub fn get_agent_spawn(&self, agent_id: &str) -> Result<Option<AgentSpawnInfo>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             "SELECT agent_id, session_id, spawning_message_seq FROM agent_spawns WHERE agent_id = ?",
         )?;
@@ -951,20 +1897,140 @@ impl Database {
         Ok(result)
     }
 
+    // ============================================
+    // Session continuation
+    // ============================================
+
+    /// Link a session to the one it continues, and any session continuing it.
+    ///
+    /// A resumed session (`--continue`/`--resume`) lives in a new file whose
+    /// first record follows a record of the earlier session. `stored` are the
+    /// messages just stored for the session, and `from_start` says whether
+    /// they begin with its first record. Links are resolved in both
+    /// directions, so it does not matter which file is ingested first: the
+    /// first record's parent is looked up only when it was just stored, and
+    /// only the records just stored are looked up as parents of other
+    /// sessions.
+    pub fn link_continued_sessions(
+        &self,
+        session_id: &str,
+        stored: &[Message],
+        from_start: bool,
+    ) -> Result<()> {
+        let records: HashSet<&str> = stored
+            .iter()
+            .filter(|m| m.session_id == session_id)
+            .filter_map(|m| m.record_id.as_deref())
+            .collect();
+        let conn = self.lock_conn()?;
+
+        let first_parent = stored
+            .iter()
+            .filter(|m| m.session_id == session_id && m.record_id.is_some())
+            .min_by_key(|m| m.seq)
+            .and_then(|m| m.parent_record_id.as_deref())
+            .filter(|parent| from_start && !records.contains(parent));
+        if let Some(parent) = first_parent {
+            conn.execute(
+                r#"
+                UPDATE sessions SET continues_session_id = (
+                    SELECT session_id FROM messages
+                    WHERE record_id = ?2 AND session_id != ?1
+                    LIMIT 1
+                )
+                WHERE id = ?1 AND continues_session_id IS NULL
+                "#,
+                params![session_id, parent],
+            )?;
+        }
+
+        let mut continuing = conn.prepare_cached(
+            r#"
+            UPDATE sessions SET continues_session_id = ?1
+            WHERE continues_session_id IS NULL
+              AND id IN (
+                SELECT m.session_id
+                FROM messages m
+                WHERE m.parent_record_id = ?2
+                  AND m.session_id != ?1
+                  AND NOT EXISTS (SELECT 1 FROM messages own
+                                  WHERE own.record_id = ?2
+                                    AND own.session_id = m.session_id)
+              )
+            "#,
+        )?;
+        for record in records {
+            continuing.execute(params![session_id, record])?;
+        }
+        Ok(())
+    }
+
+    /// Get the session a session was continued from, if any.
+    pub fn get_continued_session_id(&self, session_id: &str) -> Result<Option<String>> {
+        let conn = self.read_conn()?;
+        let result = conn
+            .query_row(
+                "SELECT continues_session_id FROM sessions WHERE id = ?",
+                [session_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(result.flatten())
+    }
+
     // ============================================
     // Message operations
     // ============================================
 
     /// Insert a message
+    ///
+    /// Inline images are moved to the blob store first (see
+    /// [`blobs`](super::blobs)).
     pub fn insert_message(&self, message: &Message) -> Result<i64> {
-        let conn = self.lock_conn()?;
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            self.dictionaries.refresh(&conn)?;
+            let id = self.insert_message_row(&conn, message)?;
+            Ok(id)
+        })
+    }
+
+    /// Insert multiple messages in a transaction
+    pub fn insert_messages(&self, messages: &[Message]) -> Result<()> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            self.dictionaries.refresh(&conn)?;
+            // A savepoint, so this also works inside [`Self::savepoint`]
+
+            for message in messages {
+                self.insert_message_row(&conn, message)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn insert_message_row(&self, conn: &Connection, message: &Message) -> Result<i64> {
+        let (message, blobs) = match self.externalize_images(message)? {
+            Some((stored, blobs)) => (std::borrow::Cow::Owned(stored), blobs),
+            None => (std::borrow::Cow::Borrowed(message), Vec::new()),
+        };
+        let dictionary = self.dictionaries.active();
+        let tool_result = message
+            .tool_result
+            .clone()
+            .map(|text| compress::encode(text, dictionary.as_deref()))
+            .transpose()?;
+        let raw_data = compress::encode(message.raw_data.to_string(), dictionary.as_deref())?;
+
         conn.execute(
             r#"
             INSERT INTO messages (session_id, thread_id, seq, emitted_at, observed_at, author_role, author_name,
                                   message_type, content, content_type, tool_name, tool_input, tool_result,
-                                  tokens_in, tokens_out, duration_ms, source_file_path,
-                                  source_offset, source_line, raw_data, metadata)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)
+                                  tool_call_id, tokens_in, tokens_out, tokens_cache_read, tokens_cache_write,
+                                  tokens_reasoning, duration_ms, source_file_path,
+                                  source_offset, source_line, raw_data, metadata, record_id, parent_record_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27)
             "#,
             params![
                 message.session_id,
@@ -979,67 +2045,95 @@ impl Database {
                 message.content_type.as_ref().map(|ct| ct.to_string()),
                 message.tool_name,
                 message.tool_input.as_ref().map(|v| v.to_string()),
-                message.tool_result,
+                tool_result,
+                message.tool_call_id,
                 message.tokens_in,
                 message.tokens_out,
+                message.tokens_cache_read,
+                message.tokens_cache_write,
+                message.tokens_reasoning,
                 message.duration_ms,
                 message.source_file_path,
                 message.source_offset,
                 message.source_line,
-                message.raw_data.to_string(),
+                raw_data,
                 message.metadata.to_string(),
+                message.record_id,
+                message.parent_record_id,
             ],
         )?;
-        Ok(conn.last_insert_rowid())
+        let id = conn.last_insert_rowid();
+        // The index gets the text; the row may hold it compressed
+        conn.execute(
+            "INSERT INTO messages_fts(rowid, content, tool_input, tool_result) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                id,
+                message.content,
+                message.tool_input.as_ref().map(|v| v.to_string()),
+                message.tool_result
+            ],
+        )?;
+        Self::insert_message_blobs(conn, id, &message.session_id, &blobs)?;
+        Ok(id)
     }
 
-    /// Insert multiple messages in a transaction
-    pub fn insert_messages(&self, messages: &[Message]) -> Result<()> {
-        let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
-
-        for message in messages {
-            tx.execute(
-                r#"
-                INSERT INTO messages (session_id, thread_id, seq, emitted_at, observed_at, author_role, author_name,
-                                      message_type, content, content_type, tool_name, tool_input, tool_result,
-                                      tokens_in, tokens_out, duration_ms, source_file_path,
-                                      source_offset, source_line, raw_data, metadata)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)
-                "#,
+    /// Re-index message `id` with a new tool result, which the full-text
+    /// index cannot read from the row when it is compressed.
+    fn reindex_tool_result(conn: &Connection, id: i64, tool_result: Option<&str>) -> Result<()> {
+        conn.execute("DELETE FROM messages_fts WHERE rowid = ?1", params![id])?;
+        conn.execute(
+            r#"
+            INSERT INTO messages_fts(rowid, content, tool_input, tool_result)
+            SELECT id, content, tool_input, ?2 FROM messages WHERE id = ?1
+            "#,
+            params![id, tool_result],
+        )?;
+        Ok(())
+    }
+
+    fn insert_message_blobs(
+        conn: &Connection,
+        message_id: i64,
+        session_id: &str,
+        blobs: &[BlobRef],
+    ) -> Result<()> {
+        for blob in blobs {
+            conn.execute(
+                r#"
+                INSERT OR IGNORE INTO message_blobs (message_id, session_id, sha256, media_type, size)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
                 params![
-                    message.session_id,
-                    message.thread_id,
-                    message.seq,
-                    message.emitted_at.to_rfc3339(),
-                    message.observed_at.to_rfc3339(),
-                    message.author_role.as_str(),
-                    message.author_name,
-                    message.message_type.as_str(),
-                    message.content,
-                    message.content_type.as_ref().map(|ct| ct.to_string()),
-                    message.tool_name,
-                    message.tool_input.as_ref().map(|v| v.to_string()),
-                    message.tool_result,
-                    message.tokens_in,
-                    message.tokens_out,
-                    message.duration_ms,
-                    message.source_file_path,
-                    message.source_offset,
-                    message.source_line,
-                    message.raw_data.to_string(),
-                    message.metadata.to_string(),
+                    message_id,
+                    session_id,
+                    blob.sha256,
+                    blob.media_type,
+                    blob.size as i64
                 ],
             )?;
         }
-
-        tx.commit()?;
         Ok(())
     }
 
+    /// The message as stored: inline images moved to the blob store and the
+    /// image it shows resolved into `metadata.image`, with the blobs it
+    /// references. `None` when there is nothing to move.
+    fn externalize_images(&self, message: &Message) -> Result<Option<(Message, Vec<BlobRef>)>> {
+        let Some(store) = &self.blobs else {
+            return Ok(None);
+        };
+        let mut stored = message.clone();
+        let blobs = store.externalize_message(
+            &mut stored.raw_data,
+            &mut stored.tool_result,
+            &mut stored.metadata,
+        )?;
+        Ok(blobs.map(|blobs| (stored, blobs)))
+    }
+
     /// Get messages for a session
     pub fn get_session_messages(&self, session_id: &str, limit: usize) -> Result<Vec<Message>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             "SELECT * FROM messages WHERE session_id = ? ORDER BY emitted_at ASC LIMIT ?",
         )?;
@@ -1055,7 +2149,7 @@ impl Database {
 
     /// Count messages for a session
     pub fn count_session_messages(&self, session_id: &str) -> Result<i64> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM messages WHERE session_id = ?",
             [session_id],
@@ -1066,7 +2160,7 @@ impl Database {
 
     /// Count messages for a thread
     pub fn count_thread_messages(&self, thread_id: &str) -> Result<i64> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM messages WHERE thread_id = ?",
             [thread_id],
@@ -1077,7 +2171,7 @@ impl Database {
 
     /// Get messages for a thread
     pub fn get_thread_messages(&self, thread_id: &str, limit: usize) -> Result<Vec<Message>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt =
             conn.prepare("SELECT * FROM messages WHERE thread_id = ? ORDER BY seq ASC LIMIT ?")?;
 
@@ -1090,13 +2184,52 @@ impl Database {
         Ok(messages)
     }
 
+    /// Get the tool calls in a session, paired with their results.
+    pub fn get_session_tool_calls(&self, session_id: &str) -> Result<Vec<ToolCallOutcome>> {
+        let messages = self.get_tool_messages(session_id)?;
+        Ok(ToolCallOutcome::pair(&messages))
+    }
+
+    /// Get the tool calls in a thread, paired with their results.
+    pub fn get_thread_tool_calls(&self, thread_id: &str) -> Result<Vec<ToolCallOutcome>> {
+        let Some(thread) = self.get_thread(thread_id)? else {
+            return Ok(Vec::new());
+        };
+        let messages = self.get_tool_messages(&thread.session_id)?;
+        let thread_calls: HashSet<i64> = messages
+            .iter()
+            .filter(|m| m.thread_id == thread_id)
+            .map(|m| m.id)
+            .collect();
+        Ok(ToolCallOutcome::pair(&messages)
+            .into_iter()
+            .filter(|outcome| thread_calls.contains(&outcome.call_message_id))
+            .collect())
+    }
+
+    /// Tool calls and results carrying a `tool_call_id`, in emission order.
+    fn get_tool_messages(&self, session_id: &str) -> Result<Vec<Message>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT * FROM messages
+            WHERE session_id = ? AND tool_call_id IS NOT NULL
+            ORDER BY emitted_at ASC, id ASC
+            "#,
+        )?;
+        let messages = stmt
+            .query_map([session_id], Self::row_to_message)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(messages)
+    }
+
     /// Get a message from the main thread by session and sequence number.
     pub fn get_main_thread_message_by_seq(
         &self,
         session_id: &str,
         seq: i64,
     ) -> Result<Option<Message>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         Ok(conn
             .query_row(
                 r#"
@@ -1117,7 +2250,7 @@ impl Database {
 
     /// Get the last sequence number for a thread
     pub fn get_last_message_seq(&self, thread_id: &str) -> Result<Option<i32>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         conn.query_row(
             "SELECT MAX(seq) FROM messages WHERE thread_id = ?",
             [thread_id],
@@ -1130,7 +2263,7 @@ impl Database {
 
     /// Get the last activity timestamp for a thread.
     pub fn get_thread_last_activity(&self, thread_id: &str) -> Result<Option<DateTime<Utc>>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let result: Option<String> = conn
             .query_row(
                 "SELECT last_activity_at FROM threads WHERE id = ?",
@@ -1152,7 +2285,7 @@ impl Database {
     /// Used by TUI to detect when new data has been synced.
     /// Note: Uses observed_at since we want to detect new ingestions, not event times.
     pub fn get_latest_message_ts(&self) -> Result<Option<DateTime<Utc>>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let result: Option<String> = conn
             .query_row("SELECT MAX(observed_at) FROM messages", [], |row| {
                 row.get(0)
@@ -1171,7 +2304,7 @@ impl Database {
     /// Get the latest message timestamp for a specific session.
     /// Used for analytics freshness checking.
     pub fn get_session_last_message_ts(&self, session_id: &str) -> Result<Option<DateTime<Utc>>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let result: Option<String> = conn
             .query_row(
                 "SELECT MAX(emitted_at) FROM messages WHERE session_id = ?",
@@ -1194,7 +2327,7 @@ impl Database {
     /// Used by the collector to find newly ingested messages for publishing.
     /// Returns messages ordered by observed_at ascending.
     pub fn get_messages_since(&self, after: DateTime<Utc>, limit: usize) -> Result<Vec<Message>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -1205,9 +2338,10 @@ impl Database {
                 message_type,
                 content, content_type,
                 tool_name, tool_input, tool_result,
-                tokens_in, tokens_out, duration_ms,
+                tokens_in, tokens_out, tokens_cache_read, tokens_cache_write,
+                tokens_reasoning, duration_ms,
                 source_file_path, source_offset, source_line,
-                raw_data, metadata
+                raw_data, metadata, tool_call_id, record_id, parent_record_id
             FROM messages
             WHERE observed_at > ?
             ORDER BY observed_at ASC
@@ -1248,19 +2382,24 @@ impl Database {
                         .get::<_, Option<String>>(12)?
                         .map(|s| Self::parse_json_field("messages.tool_input", &s))
                         .transpose()?,
-                    tool_result: row.get(13)?,
+                    tool_result: compress::column_text(row, 13)?,
+                    tool_call_id: row.get(25)?,
                     tokens_in: row.get(14)?,
                     tokens_out: row.get(15)?,
-                    duration_ms: row.get(16)?,
-                    source_file_path: row.get(17)?,
-                    source_offset: row.get(18)?,
-                    source_line: row.get(19)?,
-                    raw_data: row
-                        .get::<_, Option<String>>(20)?
+                    tokens_cache_read: row.get(16)?,
+                    tokens_cache_write: row.get(17)?,
+                    tokens_reasoning: row.get(18)?,
+                    duration_ms: row.get(19)?,
+                    source_file_path: row.get(20)?,
+                    source_offset: row.get(21)?,
+                    source_line: row.get(22)?,
+                    record_id: row.get(26)?,
+                    parent_record_id: row.get(27)?,
+                    raw_data: compress::column_text(row, 23)?
                         .and_then(|s| serde_json::from_str(&s).ok())
                         .unwrap_or_else(|| serde_json::json!({})),
                     metadata: row
-                        .get::<_, Option<String>>(21)?
+                        .get::<_, Option<String>>(24)?
                         .and_then(|s| serde_json::from_str(&s).ok())
                         .unwrap_or_else(|| serde_json::json!({})),
                 })
@@ -1348,13 +2487,19 @@ impl Database {
 
         let mut tokens_in: i64 = 0;
         let mut tokens_out: i64 = 0;
+        let mut tokens_cache_read: i64 = 0;
+        let mut tokens_cache_write: i64 = 0;
+        let mut tokens_reasoning: i64 = 0;
         let mut tokens_total: i64 = 0;
         let mut tool_call_count: i64 = 0;
         let mut tool_call_breakdown: std::collections::HashMap<String, i64> =
             std::collections::HashMap::new();
         let mut error_count: i64 = 0;
+        let mut compaction_count: i64 = 0;
         let mut duration_ms: i64 = 0;
         let mut tool_success_rate: f64 = 0.0;
+        let mut tool_error_count: i64 = 0;
+        let mut tool_latency_avg_ms: Option<i64> = None;
         let mut computed_at = chrono::Utc::now();
 
         for metric in &first_order_metrics {
@@ -1365,6 +2510,15 @@ impl Database {
                 "tokens_out" => {
                     tokens_out = metric.metric_value.as_i64().unwrap_or(0);
                 }
+                "tokens_cache_read" => {
+                    tokens_cache_read = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "tokens_cache_write" => {
+                    tokens_cache_write = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "tokens_reasoning" => {
+                    tokens_reasoning = metric.metric_value.as_i64().unwrap_or(0);
+                }
                 "tokens_total" => {
                     tokens_total = metric.metric_value.as_i64().unwrap_or(0);
                 }
@@ -1382,57 +2536,1101 @@ impl Database {
                 "error_count" => {
                     error_count = metric.metric_value.as_i64().unwrap_or(0);
                 }
+                "compaction_count" => {
+                    compaction_count = metric.metric_value.as_i64().unwrap_or(0);
+                }
                 "duration_ms" => {
                     duration_ms = metric.metric_value.as_i64().unwrap_or(0);
                 }
                 "tool_success_rate" => {
                     tool_success_rate = metric.metric_value.as_f64().unwrap_or(0.0);
                 }
+                "tool_error_count" => {
+                    tool_error_count = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "tool_latency_avg_ms" => {
+                    tool_latency_avg_ms = metric.metric_value.as_i64();
+                }
                 _ => {}
             }
             computed_at = metric.computed_at;
         }
 
-        Ok(Some(crate::analytics::FirstOrderSessionMetrics {
-            tokens_in,
-            tokens_out,
-            tokens_total,
-            tool_call_count,
-            tool_call_breakdown,
-            error_count,
-            duration_ms,
-            tool_success_rate,
-            computed_at,
-        }))
+        Ok(Some(crate::analytics::FirstOrderSessionMetrics {
+            tokens_in,
+            tokens_out,
+            tokens_cache_read,
+            tokens_cache_write,
+            tokens_reasoning,
+            tokens_total,
+            tool_call_count,
+            tool_call_breakdown,
+            error_count,
+            compaction_count,
+            duration_ms,
+            tool_success_rate,
+            tool_error_count,
+            tool_latency_avg_ms,
+            computed_at,
+        }))
+    }
+
+    /// Get all metrics for a thread across all plugins.
+    pub fn get_thread_plugin_metrics(
+        &self,
+        thread_id: &str,
+    ) -> Result<Vec<crate::types::PluginMetric>> {
+        let conn = self.read_conn()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, plugin_name, entity_type, entity_id, metric_name, metric_value, computed_at
+            FROM plugin_metrics
+            WHERE entity_type = 'thread' AND entity_id = ?
+            ORDER BY plugin_name, metric_name
+            "#,
+        )?;
+
+        let metrics = stmt
+            .query_map([thread_id], |row| {
+                let computed_at_str: String = row.get(6)?;
+                let metric_value = Self::parse_metric_value(row.get_ref(5)?);
+                Ok(crate::types::PluginMetric {
+                    id: row.get(0)?,
+                    plugin_name: row.get(1)?,
+                    entity_type: row.get(2)?,
+                    entity_id: row.get(3)?,
+                    metric_name: row.get(4)?,
+                    metric_value,
+                    computed_at: Self::parse_rfc3339_field(
+                        "plugin_metrics.computed_at",
+                        &computed_at_str,
+                    )?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(metrics)
+    }
+
+    /// Get thread cost metrics from plugin_metrics table.
+    /// Returns None if the `core.cost` plugin has not run on this thread.
+    pub fn get_thread_cost_metrics(
+        &self,
+        thread_id: &str,
+    ) -> Result<Option<crate::analytics::CostMetrics>> {
+        let metrics = self.get_thread_plugin_metrics(thread_id)?;
+
+        let cost_metrics: Vec<_> = metrics
+            .iter()
+            .filter(|m| m.plugin_name == "core.cost")
+            .collect();
+
+        if cost_metrics.is_empty() {
+            return Ok(None);
+        }
+
+        let mut estimate = crate::analytics::CostEstimate::default();
+        let mut pricing = None;
+        let mut computed_at = chrono::Utc::now();
+
+        for metric in &cost_metrics {
+            match metric.metric_name.as_str() {
+                "cost_usd" => {
+                    estimate.usd = metric.metric_value.as_f64().unwrap_or(0.0);
+                }
+                "unpriced_tokens" => {
+                    estimate.unpriced_tokens = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "pricing" => {
+                    pricing = metric.metric_value.as_str().map(str::to_string);
+                }
+                _ => {}
+            }
+            computed_at = metric.computed_at;
+        }
+
+        Ok(Some(crate::analytics::CostMetrics {
+            estimate,
+            pricing,
+            computed_at,
+        }))
+    }
+
+    /// Get pre-computed thread analytics from plugin_metrics table.
+    /// Returns None if no analytics have been computed for this thread.
+    pub fn get_thread_analytics(
+        &self,
+        thread_id: &str,
+    ) -> Result<Option<crate::analytics::ThreadAnalytics>> {
+        let metrics = self.get_thread_plugin_metrics(thread_id)?;
+
+        // Filter to edit_churn plugin metrics
+        let edit_churn_metrics: Vec<_> = metrics
+            .iter()
+            .filter(|m| m.plugin_name == "core.edit_churn")
+            .collect();
+
+        if edit_churn_metrics.is_empty() {
+            return Ok(None);
+        }
+
+        // Extract each metric value
+        let mut edit_count: i64 = 0;
+        let mut unique_files: i64 = 0;
+        let mut churn_ratio: f64 = 0.0;
+        let mut high_churn_files: Vec<String> = Vec::new();
+        let mut high_churn_threshold: f64 = 0.0;
+        let mut burst_edit_files: std::collections::HashMap<String, i64> =
+            std::collections::HashMap::new();
+        let mut burst_edit_count: i64 = 0;
+        let mut lines_changed: i64 = 0;
+        let mut first_try_rate: f64 = 0.0;
+        let mut computed_at = chrono::Utc::now();
+
+        for metric in &edit_churn_metrics {
+            match metric.metric_name.as_str() {
+                "edit_count" => {
+                    edit_count = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "unique_files" => {
+                    unique_files = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "churn_ratio" => {
+                    churn_ratio = metric.metric_value.as_f64().unwrap_or(0.0);
+                }
+                "high_churn_files" => {
+                    if let Some(arr) = metric.metric_value.as_array() {
+                        high_churn_files = arr
+                            .iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect();
+                    }
+                }
+                "high_churn_threshold" => {
+                    high_churn_threshold = metric.metric_value.as_f64().unwrap_or(0.0);
+                }
+                "burst_edit_files" => {
+                    if let Some(obj) = metric.metric_value.as_object() {
+                        burst_edit_files = obj
+                            .iter()
+                            .map(|(k, v)| (k.clone(), v.as_i64().unwrap_or(0)))
+                            .collect();
+                    }
+                }
+                "burst_edit_count" => {
+                    burst_edit_count = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "lines_changed" => {
+                    lines_changed = metric.metric_value.as_i64().unwrap_or(0);
+                }
+                "first_try_rate" => {
+                    first_try_rate = metric.metric_value.as_f64().unwrap_or(0.0);
+                }
+                _ => {}
+            }
+            // Use the computed_at from any metric (they should all be the same)
+            computed_at = metric.computed_at;
+        }
+
+        Ok(Some(crate::analytics::ThreadAnalytics {
+            edit_count,
+            unique_files,
+            churn_ratio,
+            high_churn_files,
+            high_churn_threshold,
+            burst_edit_files,
+            burst_edit_count,
+            lines_changed,
+            first_try_rate,
+            computed_at,
+        }))
+    }
+
+    fn row_to_message(row: &Row) -> rusqlite::Result<Message> {
+        let author_role_str: String = row.get("author_role")?;
+        let message_type_str: String = row.get("message_type")?;
+        let emitted_at_str: String = row.get("emitted_at")?;
+        let observed_at_str: String = row.get("observed_at")?;
+        let content_type_str: Option<String> = row.get("content_type")?;
+        let tool_input_str: Option<String> = row.get("tool_input")?;
+        let raw_data_str = compress::column_text(row, "raw_data")?.unwrap_or_default();
+        let metadata_str: String = row.get("metadata")?;
+
+        Ok(Message {
+            id: row.get("id")?,
+            session_id: row.get("session_id")?,
+            thread_id: row.get("thread_id")?,
+            seq: row.get("seq")?,
+            emitted_at: Self::parse_rfc3339_field("messages.emitted_at", &emitted_at_str)?,
+            observed_at: Self::parse_rfc3339_field("messages.observed_at", &observed_at_str)?,
+            author_role: Self::parse_enum_field("messages.author_role", &author_role_str)?,
+            author_name: row.get("author_name")?,
+            message_type: Self::parse_enum_field("messages.message_type", &message_type_str)?,
+            content: row.get("content")?,
+            content_type: content_type_str
+                .map(|s| Self::parse_enum_field("messages.content_type", &s))
+                .transpose()?,
+            tool_name: row.get("tool_name")?,
+            tool_input: Self::parse_optional_json_field("messages.tool_input", tool_input_str)?,
+            tool_result: compress::column_text(row, "tool_result")?,
+            tool_call_id: row.get("tool_call_id")?,
+            tokens_in: row.get("tokens_in")?,
+            tokens_out: row.get("tokens_out")?,
+            tokens_cache_read: row.get("tokens_cache_read")?,
+            tokens_cache_write: row.get("tokens_cache_write")?,
+            tokens_reasoning: row.get("tokens_reasoning")?,
+            duration_ms: row.get("duration_ms")?,
+            source_file_path: row.get("source_file_path")?,
+            source_offset: row.get("source_offset")?,
+            source_line: row.get("source_line")?,
+            record_id: row.get("record_id")?,
+            parent_record_id: row.get("parent_record_id")?,
+            raw_data: Self::parse_json_field("messages.raw_data", &raw_data_str)?,
+            metadata: Self::parse_json_field("messages.metadata", &metadata_str)?,
+        })
+    }
+
+    // ============================================
+    // Blob operations
+    // ============================================
+
+    /// The blob store next to the database file (`None` in memory, where
+    /// images stay inline).
+    pub fn blob_store(&self) -> Option<&BlobStore> {
+        self.blobs.as_ref()
+    }
+
+    /// Read a stored blob by its SHA-256.
+    pub fn get_blob(&self, sha256: &str) -> Result<Option<Vec<u8>>> {
+        match &self.blobs {
+            Some(store) => store.get(sha256),
+            None => Ok(None),
+        }
+    }
+
+    /// Path of a stored blob, if it exists.
+    pub fn blob_path(&self, sha256: &str) -> Option<PathBuf> {
+        self.blobs
+            .as_ref()
+            .and_then(|store| store.path(sha256))
+            .filter(|path| path.is_file())
+    }
+
+    /// Blobs referenced by a message's raw record, images nested in tool
+    /// results included.
+    pub fn get_message_blobs(&self, message_id: i64) -> Result<Vec<BlobRef>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT sha256, media_type, size FROM message_blobs WHERE message_id = ? ORDER BY rowid",
+        )?;
+        let blobs = stmt
+            .query_map([message_id], |row| {
+                Ok(BlobRef {
+                    sha256: row.get(0)?,
+                    media_type: row.get(1)?,
+                    size: row.get::<_, i64>(2)? as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(blobs)
+    }
+
+    /// Move images still stored inline, in messages stored before the blob
+    /// store or before tool results were included, to the blob store.
+    /// Returns the number of messages rewritten.
+    ///
+    /// Works in batches, each committed on its own, like
+    /// [`Self::compress_messages`]. The file only shrinks after
+    /// [`Self::vacuum`].
+    pub fn externalize_stored_images(&self) -> Result<u64> {
+        let Some(store) = &self.blobs else {
+            return Ok(0);
+        };
+
+        let mut rewritten = 0;
+        let mut last_id = 0i64;
+        loop {
+            let (fetched, next_id) = self.savepoint(|| {
+                let conn = self.lock_conn()?;
+                self.dictionaries.refresh(&conn)?;
+                let dictionary = self.dictionaries.active();
+
+                let rows = {
+                    let mut stmt = conn.prepare(
+                        r#"
+                        SELECT id, session_id, raw_data, tool_result, metadata FROM messages
+                        WHERE id > ?1 AND pruned_at IS NULL
+                        ORDER BY id LIMIT ?2
+                        "#,
+                    )?;
+                    let rows = stmt
+                        .query_map(params![last_id, COMPRESS_BATCH_SIZE as i64], |row| {
+                            Ok((
+                                row.get::<_, i64>(0)?,
+                                row.get::<_, String>(1)?,
+                                compress::column_text(row, 2)?,
+                                compress::column_text(row, 3)?,
+                                row.get::<_, Option<String>>(4)?,
+                            ))
+                        })?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    rows
+                };
+
+                for (id, session_id, raw_data, tool_result, metadata) in &rows {
+                    let mentions_base64 = |text: &Option<String>| {
+                        text.as_deref().is_some_and(|t| t.contains("\"base64\""))
+                    };
+                    if !mentions_base64(raw_data) && !mentions_base64(tool_result) {
+                        continue;
+                    }
+                    let mut raw_data: serde_json::Value = raw_data
+                        .as_deref()
+                        .and_then(|text| serde_json::from_str(text).ok())
+                        .unwrap_or_default();
+                    let mut tool_result = tool_result.clone();
+                    let mut metadata: serde_json::Value = metadata
+                        .as_deref()
+                        .and_then(|text| serde_json::from_str(text).ok())
+                        .unwrap_or_else(|| serde_json::json!({}));
+                    let Some(blobs) =
+                        store.externalize_message(&mut raw_data, &mut tool_result, &mut metadata)?
+                    else {
+                        continue;
+                    };
+
+                    conn.execute(
+                        "UPDATE messages SET raw_data = ?2, tool_result = ?3, metadata = ?4 WHERE id = ?1",
+                        params![
+                            id,
+                            compress::encode(raw_data.to_string(), dictionary.as_deref())?,
+                            tool_result
+                                .clone()
+                                .map(|text| compress::encode(text, dictionary.as_deref()))
+                                .transpose()?,
+                            metadata.to_string()
+                        ],
+                    )?;
+                    Self::reindex_tool_result(&conn, *id, tool_result.as_deref())?;
+                    Self::insert_message_blobs(&conn, *id, session_id, &blobs)?;
+                    rewritten += 1;
+                }
+                Ok((rows.len(), rows.last().map(|row| row.0)))
+            })?;
+
+            match next_id {
+                Some(id) if fetched == COMPRESS_BATCH_SIZE => last_id = id,
+                _ => break,
+            }
+        }
+        Ok(rewritten)
+    }
+
+    /// Delete blobs no message references any more, such as the images of
+    /// reparsed or pruned messages. Returns the number deleted.
+    ///
+    /// Blobs written in the last hour are kept, since another process may
+    /// have stored one for a message it has not committed yet.
+    pub fn remove_unreferenced_blobs(&self) -> Result<u64> {
+        let Some(store) = &self.blobs else {
+            return Ok(0);
+        };
+        let referenced: HashSet<String> = {
+            let conn = self.read_conn()?;
+            let mut stmt = conn.prepare("SELECT DISTINCT sha256 FROM message_blobs")?;
+            let referenced = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            referenced
+        };
+
+        let cutoff = std::time::SystemTime::now() - BLOB_GRACE_PERIOD;
+        let mut removed = 0;
+        for (sha256, written_at) in store.list()? {
+            if written_at < cutoff && !referenced.contains(&sha256) {
+                store.remove(&sha256)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    // ============================================
+    // Search
+    // ============================================
+
+    /// Full-text search over message content, tool inputs and tool results.
+    ///
+    /// `query` is free text: a message matches when it contains every word
+    /// (stemmed, case-insensitive), and `word*` matches by prefix. Hits are
+    /// ranked by BM25, most relevant first.
+    pub fn search_messages(&self, query: &str, filter: &SearchFilter) -> Result<Vec<SearchHit>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.read_conn()?;
+
+        let mut sql = r#"
+            SELECT
+                m.id,
+                m.session_id,
+                m.thread_id,
+                t.thread_type,
+                s.project_id,
+                p.name,
+                s.assistant,
+                m.message_type,
+                m.author_role,
+                m.tool_name,
+                m.emitted_at,
+                m.content,
+                m.tool_input,
+                m.tool_result,
+                bm25(messages_fts)
+            FROM messages_fts
+            JOIN messages m ON m.id = messages_fts.rowid
+            JOIN threads t ON t.id = m.thread_id
+            JOIN sessions s ON s.id = m.session_id
+            LEFT JOIN projects p ON p.id = s.project_id
+            WHERE messages_fts MATCH ?
+            "#
+        .to_string();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(fts_query)];
+
+        if let Some(assistant) = &filter.assistant {
+            sql.push_str(" AND s.assistant = ?");
+            params.push(Box::new(assistant.as_str().to_string()));
+        }
+
+        if let Some(project_id) = &filter.project_id {
+            sql.push_str(" AND s.project_id = ?");
+            params.push(Box::new(project_id.clone()));
+        }
+
+        if let Some(session_id) = &filter.session_id {
+            sql.push_str(" AND m.session_id = ?");
+            params.push(Box::new(session_id.clone()));
+        }
+
+        if let Some(message_type) = &filter.message_type {
+            sql.push_str(" AND m.message_type = ?");
+            params.push(Box::new(message_type.as_str().to_string()));
+        }
+
+        if let Some(since) = &filter.since {
+            sql.push_str(" AND m.emitted_at >= ?");
+            params.push(Box::new(since.to_rfc3339()));
+        }
+
+        sql.push_str(&format!(
+            " ORDER BY bm25(messages_fts) LIMIT {}",
+            filter.limit.unwrap_or(50)
+        ));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = conn.prepare(&sql)?;
+        let hits = stmt
+            .query_map(params_refs.as_slice(), |row| {
+                let thread_type: String = row.get(3)?;
+                let assistant: String = row.get(6)?;
+                let message_type: String = row.get(7)?;
+                let author_role: String = row.get(8)?;
+                let emitted_at: String = row.get(10)?;
+                // The index is contentless, so excerpts come from the message
+                let content: Option<String> = row.get(11)?;
+                let tool_input: Option<String> = row.get(12)?;
+                let tool_result = compress::column_text(row, 13)?;
+                Ok(SearchHit {
+                    message_id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    thread_id: row.get(2)?,
+                    thread_type: Self::parse_enum_field("thread_type", &thread_type)?,
+                    project_id: row.get(4)?,
+                    project_name: row.get(5)?,
+                    assistant: Self::parse_enum_field("assistant", &assistant)?,
+                    message_type: Self::parse_enum_field("message_type", &message_type)?,
+                    author_role: Self::parse_enum_field("author_role", &author_role)?,
+                    tool_name: row.get(9)?,
+                    emitted_at: Self::parse_rfc3339_field("emitted_at", &emitted_at)?,
+                    snippet: search_snippet(
+                        query,
+                        &[
+                            content.as_deref(),
+                            tool_input.as_deref(),
+                            tool_result.as_deref(),
+                        ],
+                    ),
+                    rank: row.get(14)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(hits)
+    }
+
+    // ============================================
+    // Retention
+    // ============================================
+
+    /// Drop `raw_data` and tool result bodies of each session's messages
+    /// emitted before its cutoff, keeping the normalized rows.
+    ///
+    /// Pruned messages keep `null` as their raw data and only the image they
+    /// show in `message_blobs`, and their source files are marked pruned, so
+    /// they are never reparsed from scratch. With `dry_run`, only counts what
+    /// would be dropped. The file only shrinks after [`Self::vacuum`].
+    pub fn prune_raw_data(
+        &self,
+        cutoffs: &[(String, DateTime<Utc>)],
+        dry_run: bool,
+    ) -> Result<PruneSummary> {
+        self.savepoint(|| {
+            let conn = self.lock_conn()?;
+            let pruned_at = Utc::now().to_rfc3339();
+            let mut summary = PruneSummary::default();
+            let mut source_files: BTreeSet<String> = BTreeSet::new();
+
+            for (session_id, cutoff) in cutoffs {
+                let cutoff = cutoff.to_rfc3339();
+                let (messages, bytes): (i64, i64) = conn.query_row(
+                    r#"
+                    SELECT COUNT(*),
+                           COALESCE(SUM(LENGTH(raw_data) + COALESCE(LENGTH(tool_result), 0)), 0)
+                    FROM messages
+                    WHERE session_id = ?1 AND emitted_at < ?2 AND pruned_at IS NULL
+                    "#,
+                    params![session_id, cutoff],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                if messages == 0 {
+                    continue;
+                }
+                summary.messages += messages as u64;
+                summary.bytes += bytes as u64;
+                summary.sessions += 1;
+
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT DISTINCT source_file_path FROM messages
+                    WHERE session_id = ?1 AND emitted_at < ?2 AND pruned_at IS NULL
+                    "#,
+                )?;
+                for path in stmt.query_map(params![session_id, cutoff], |row| row.get(0))? {
+                    source_files.insert(path?);
+                }
+                drop(stmt);
+
+                if !dry_run {
+                    // Pruned records no longer reference their images, except
+                    // the one an image message shows
+                    conn.execute(
+                        r#"
+                        DELETE FROM message_blobs
+                        WHERE message_id IN (
+                                  SELECT id FROM messages
+                                  WHERE session_id = ?1 AND emitted_at < ?2 AND pruned_at IS NULL)
+                          AND sha256 IS NOT (
+                                  SELECT json_extract(metadata, '$.image.sha256') FROM messages
+                                  WHERE messages.id = message_blobs.message_id)
+                        "#,
+                        params![session_id, cutoff],
+                    )?;
+                    // Re-index the messages whose tool result goes, without it
+                    conn.execute(
+                        r#"
+                        DELETE FROM messages_fts
+                        WHERE rowid IN (
+                            SELECT id FROM messages
+                            WHERE session_id = ?1 AND emitted_at < ?2 AND pruned_at IS NULL
+                              AND tool_result IS NOT NULL)
+                        "#,
+                        params![session_id, cutoff],
+                    )?;
+                    conn.execute(
+                        r#"
+                        INSERT INTO messages_fts(rowid, content, tool_input)
+                        SELECT id, content, tool_input FROM messages
+                        WHERE session_id = ?1 AND emitted_at < ?2 AND pruned_at IS NULL
+                          AND tool_result IS NOT NULL
+                        "#,
+                        params![session_id, cutoff],
+                    )?;
+                    conn.execute(
+                        r#"
+                        UPDATE messages SET raw_data = 'null', tool_result = NULL, pruned_at = ?3
+                        WHERE session_id = ?1 AND emitted_at < ?2 AND pruned_at IS NULL
+                        "#,
+                        params![session_id, cutoff, pruned_at],
+                    )?;
+                }
+            }
+
+            summary.source_files = source_files.len() as u64;
+            if !dry_run {
+                for path in &source_files {
+                    conn.execute(
+                        "UPDATE source_files SET pruned_at = ?2 WHERE path = ?1 AND pruned_at IS NULL",
+                        params![path, pruned_at],
+                    )?;
+                }
+            }
+
+            Ok(summary)
+        })
+    }
+
+    /// Rebuild the database file, returning space freed by deletes and
+    /// pruning to the filesystem.
+    pub fn vacuum(&self) -> Result<()> {
+        let conn = self.lock_conn()?;
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        Ok(())
+    }
+
+    // ============================================
+    // Compression
+    // ============================================
+
+    /// Train a compression dictionary on recent messages and compress new
+    /// values with it. Returns its zstd dictionary id, or `None` when there
+    /// are too few messages to train on.
+    ///
+    /// Stored values keep their encoding until [`Self::compress_messages`]
+    /// recompresses them.
+    pub fn train_compression_dictionary(&self) -> Result<Option<u32>> {
+        let mut samples: Vec<Vec<u8>> = Vec::new();
+        {
+            let conn = self.read_conn()?;
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT raw_data, tool_result FROM messages
+                WHERE pruned_at IS NULL
+                ORDER BY id DESC
+                LIMIT ?1
+                "#,
+            )?;
+            let mut rows = stmt.query([DICTIONARY_SAMPLE_ROWS])?;
+            let mut total = 0;
+            while let Some(row) = rows.next()? {
+                for idx in 0..2 {
+                    if let Some(text) = compress::column_text(row, idx)? {
+                        // Long tool output would crowd out the small records
+                        let mut sample = text.into_bytes();
+                        sample.truncate(DICTIONARY_SAMPLE_MAX_BYTES);
+                        total += sample.len();
+                        samples.push(sample);
+                    }
+                }
+                if total >= DICTIONARY_SAMPLE_TOTAL_BYTES {
+                    break;
+                }
+            }
+        }
+        if samples.len() < MIN_DICTIONARY_SAMPLES {
+            return Ok(None);
+        }
+
+        let dictionary = compress::train(&samples)?;
+        let Some(dict_id) = compress::dictionary_id(&dictionary) else {
+            return Ok(None);
+        };
+        let conn = self.lock_conn()?;
+        conn.execute(
+            r#"
+            INSERT OR IGNORE INTO compression_dictionaries (dict_id, dictionary, samples, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![
+                dict_id,
+                dictionary,
+                samples.len() as i64,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        self.dictionaries.refresh(&conn)?;
+        Ok(Some(dict_id))
+    }
+
+    /// Compress stored `raw_data` and tool results that are not compressed
+    /// yet, such as those stored before compression.
+    ///
+    /// Works in batches, each committed on its own, so an interrupted run
+    /// picks up where it stopped. With `recompress`, values that are already
+    /// compressed are recompressed with the current dictionary too. The file
+    /// only shrinks after [`Self::vacuum`].
+    pub fn compress_messages(&self, recompress: bool) -> Result<CompressionSummary> {
+        let mut condition = String::from(
+            r#"
+            (typeof(raw_data) = 'text' AND length(CAST(raw_data AS BLOB)) >= ?2)
+            OR (typeof(tool_result) = 'text' AND length(CAST(tool_result AS BLOB)) >= ?2)
+            "#,
+        );
+        if recompress {
+            condition.push_str(" OR typeof(raw_data) = 'blob' OR typeof(tool_result) = 'blob'");
+        }
+        let sql = format!(
+            "SELECT id, raw_data, tool_result FROM messages WHERE id > ?1 AND ({}) ORDER BY id LIMIT ?3",
+            condition
+        );
+
+        let mut summary = CompressionSummary::default();
+        let mut last_id = 0i64;
+        loop {
+            let rows = self.savepoint(|| {
+                let conn = self.lock_conn()?;
+                self.dictionaries.refresh(&conn)?;
+                let dictionary = self.dictionaries.active();
+
+                let rows = {
+                    let mut stmt = conn.prepare(&sql)?;
+                    let rows = stmt
+                        .query_map(
+                            params![
+                                last_id,
+                                compress::COMPRESS_MIN_BYTES as i64,
+                                COMPRESS_BATCH_SIZE as i64
+                            ],
+                            |row| {
+                                Ok((
+                                    row.get::<_, i64>(0)?,
+                                    StoredText::read(row, 1)?,
+                                    StoredText::read(row, 2)?,
+                                ))
+                            },
+                        )?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    rows
+                };
+
+                for (id, raw_data, tool_result) in &rows {
+                    let mut changed = false;
+                    for (column, stored) in [("raw_data", raw_data), ("tool_result", tool_result)] {
+                        let Some(stored) = stored else {
+                            continue;
+                        };
+                        let value = compress::encode(stored.text.clone(), dictionary.as_deref())?;
+                        let size = match &value {
+                            rusqlite::types::Value::Blob(frame) => frame.len(),
+                            rusqlite::types::Value::Text(text) => text.len(),
+                            _ => 0,
+                        };
+                        if !stored.compressed && !matches!(value, rusqlite::types::Value::Blob(_)) {
+                            continue;
+                        }
+                        conn.execute(
+                            &format!("UPDATE messages SET {} = ?2 WHERE id = ?1", column),
+                            params![id, value],
+                        )?;
+                        summary.bytes_before += stored.size as u64;
+                        summary.bytes_after += size as u64;
+                        changed = true;
+                    }
+                    if changed {
+                        summary.rows += 1;
+                    }
+                }
+                Ok(rows)
+            })?;
+
+            match rows.last() {
+                Some((id, _, _)) if rows.len() == COMPRESS_BATCH_SIZE => last_id = *id,
+                _ => break,
+            }
+        }
+        Ok(summary)
+    }
+
+    // ============================================
+    // Dataset export
+    // ============================================
+
+    /// How far the database reaches right now, to bound an export.
+    pub fn export_watermark(&self) -> Result<ExportWatermark> {
+        let conn = self.read_conn()?;
+        let (message_id, assessment_id, computed_at, reparse_id) = conn.query_row(
+            r#"
+            SELECT (SELECT COALESCE(MAX(id), 0) FROM messages),
+                   (SELECT COALESCE(MAX(id), 0) FROM assessments),
+                   (SELECT MAX(computed_at) FROM plugin_metrics),
+                   (SELECT COALESCE(MAX(id), 0) FROM reparsed_sessions)
+            "#,
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            },
+        )?;
+        Ok(ExportWatermark {
+            message_id,
+            assessment_id,
+            reparse_id,
+            metrics_computed_at: Self::parse_optional_rfc3339_field(
+                "plugin_metrics.computed_at",
+                computed_at,
+            )?,
+        })
+    }
+
+    /// Conditions on sessions `s` selecting those in the export's scope,
+    /// and with `changed`, only those with messages new since the last export.
+    fn export_session_scope(
+        filter: &ExportFilter,
+        changed: bool,
+        params: &mut Vec<Box<dyn rusqlite::ToSql>>,
+    ) -> String {
+        let mut sql = String::from("1=1");
+        if let Some(project_id) = &filter.project_id {
+            sql.push_str(" AND s.project_id = ?");
+            params.push(Box::new(project_id.clone()));
+        }
+        if let Some(since) = &filter.since {
+            sql.push_str(" AND COALESCE(s.last_activity_at, s.started_at) >= ?");
+            params.push(Box::new(since.to_rfc3339()));
+        }
+        if let (true, Some(after)) = (changed, &filter.after) {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM messages m WHERE m.session_id = s.id AND m.id > ? AND m.id <= ?)",
+            );
+            params.push(Box::new(after.message_id));
+            params.push(Box::new(filter.upto.message_id));
+        }
+        sql
+    }
+
+    /// Projects of the sessions in an export.
+    pub fn export_projects(&self, filter: &ExportFilter) -> Result<Vec<Project>> {
+        let conn = self.read_conn()?;
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+        let scope = Self::export_session_scope(filter, true, &mut params);
+        let sql = format!(
+            "SELECT * FROM projects WHERE id IN (SELECT s.project_id FROM sessions s WHERE {scope}) \
+             ORDER BY created_at, id"
+        );
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let projects = stmt
+            .query_map(params_refs.as_slice(), Self::row_to_project)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(projects)
+    }
+
+    /// Sessions in an export; incrementally, those with new messages.
+    pub fn export_sessions(&self, filter: &ExportFilter) -> Result<Vec<Session>> {
+        let conn = self.read_conn()?;
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+        let scope = Self::export_session_scope(filter, true, &mut params);
+        let sql = format!("SELECT s.* FROM sessions s WHERE {scope} ORDER BY s.started_at, s.id");
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let sessions = stmt
+            .query_map(params_refs.as_slice(), Self::row_to_session)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
+    /// Threads of the sessions in an export; incrementally, those with new
+    /// messages.
+    pub fn export_threads(&self, filter: &ExportFilter) -> Result<Vec<Thread>> {
+        let conn = self.read_conn()?;
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+        let mut sql = format!(
+            "SELECT t.* FROM threads t JOIN sessions s ON s.id = t.session_id WHERE {}",
+            Self::export_session_scope(filter, false, &mut params)
+        );
+        if let Some(after) = &filter.after {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM messages m WHERE m.thread_id = t.id AND m.id > ? AND m.id <= ?)",
+            );
+            params.push(Box::new(after.message_id));
+            params.push(Box::new(filter.upto.message_id));
+        }
+        sql.push_str(" ORDER BY t.started_at, t.id");
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let threads = stmt
+            .query_map(params_refs.as_slice(), Self::row_to_thread)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(threads)
+    }
+
+    /// Next page of messages in an export, in id order, after message
+    /// `after_id`. Raw data is not read.
+    pub fn export_messages(
+        &self,
+        filter: &ExportFilter,
+        after_id: i64,
+        limit: usize,
+    ) -> Result<Vec<Message>> {
+        let conn = self.read_conn()?;
+        let after_id = after_id.max(filter.after.as_ref().map_or(0, |w| w.message_id));
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> =
+            vec![Box::new(after_id), Box::new(filter.upto.message_id)];
+        let mut sql = String::from(
+            r#"
+            SELECT m.id, m.session_id, m.thread_id, m.seq, m.emitted_at, m.observed_at,
+                   m.author_role, m.author_name, m.message_type, m.content, m.content_type,
+                   m.tool_name, m.tool_input, m.tool_result, m.tool_call_id,
+                   m.tokens_in, m.tokens_out, m.tokens_cache_read, m.tokens_cache_write,
+                   m.tokens_reasoning, m.duration_ms, m.source_file_path, m.source_offset,
+                   m.source_line, m.record_id, m.parent_record_id,
+                   'null' AS raw_data, m.metadata
+            FROM messages m
+            WHERE m.id > ? AND m.id <= ?
+            "#,
+        );
+        if filter.project_id.is_some() || filter.since.is_some() {
+            sql.push_str(&format!(
+                " AND m.session_id IN (SELECT s.id FROM sessions s WHERE {})",
+                Self::export_session_scope(filter, false, &mut params)
+            ));
+        }
+        if let Some(since) = &filter.since {
+            sql.push_str(" AND m.emitted_at >= ?");
+            params.push(Box::new(since.to_rfc3339()));
+        }
+        sql.push_str(&format!(" ORDER BY m.id LIMIT {}", limit));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let messages = stmt
+            .query_map(params_refs.as_slice(), Self::row_to_message)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(messages)
+    }
+
+    /// Sessions in an incremental export's scope that were reparsed since
+    /// the last one, including those the reparse did not rebuild.
+    pub fn export_reparsed_sessions(&self, filter: &ExportFilter) -> Result<Vec<String>> {
+        let Some(after) = &filter.after else {
+            return Ok(Vec::new());
+        };
+        let conn = self.read_conn()?;
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> =
+            vec![Box::new(after.reparse_id), Box::new(filter.upto.reparse_id)];
+        let mut sql = String::from(
+            "SELECT DISTINCT r.session_id FROM reparsed_sessions r WHERE r.id > ? AND r.id <= ?",
+        );
+        if filter.project_id.is_some() || filter.since.is_some() {
+            sql.push_str(&format!(
+                " AND (r.session_id IN (SELECT s.id FROM sessions s WHERE {}) \
+                 OR r.session_id NOT IN (SELECT id FROM sessions))",
+                Self::export_session_scope(filter, false, &mut params)
+            ));
+        }
+        sql.push_str(" ORDER BY r.session_id");
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let sessions = stmt
+            .query_map(params_refs.as_slice(), |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
+    /// Assessments of the sessions in an export, added since the last one.
+    pub fn export_assessments(
+        &self,
+        filter: &ExportFilter,
+    ) -> Result<Vec<crate::types::Assessment>> {
+        let conn = self.read_conn()?;
+        let after_id = filter.after.as_ref().map_or(0, |w| w.assessment_id);
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> =
+            vec![Box::new(after_id), Box::new(filter.upto.assessment_id)];
+        let sql = format!(
+            r#"
+            SELECT a.id, a.session_id, a.assessor, a.model, a.assessed_at, a.scores,
+                   a.raw_response, a.prompt_hash
+            FROM assessments a
+            JOIN sessions s ON s.id = a.session_id
+            WHERE a.id > ? AND a.id <= ? AND {}
+            ORDER BY a.id
+            "#,
+            Self::export_session_scope(filter, false, &mut params)
+        );
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let assessments = stmt
+            .query_map(params_refs.as_slice(), |row| {
+                let assessed_at_str: String = row.get(4)?;
+                let scores_raw: String = row.get(5)?;
+                Ok(crate::types::Assessment {
+                    id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    assessor: row.get(2)?,
+                    model: row.get(3)?,
+                    assessed_at: Self::parse_rfc3339_field(
+                        "assessments.assessed_at",
+                        &assessed_at_str,
+                    )?,
+                    scores: Self::parse_json_field("assessments.scores", &scores_raw)?,
+                    raw_response: row.get(6)?,
+                    prompt_hash: row.get(7)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(assessments)
     }
 
-    /// Get all metrics for a thread across all plugins.
-    pub fn get_thread_plugin_metrics(
+    /// Plugin metrics of the sessions and threads in an export, computed
+    /// since the last one. Global metrics are included unless the export is
+    /// limited to a project.
+    pub fn export_plugin_metrics(
         &self,
-        thread_id: &str,
+        filter: &ExportFilter,
     ) -> Result<Vec<crate::types::PluginMetric>> {
-        let conn = self.lock_conn()?;
-
-        let mut stmt = conn.prepare(
+        let conn = self.read_conn()?;
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+        let session_scope = Self::export_session_scope(filter, false, &mut params);
+        let thread_scope = Self::export_session_scope(filter, false, &mut params);
+        let mut sql = format!(
             r#"
             SELECT id, plugin_name, entity_type, entity_id, metric_name, metric_value, computed_at
             FROM plugin_metrics
-            WHERE entity_type = 'thread' AND entity_id = ?
-            ORDER BY plugin_name, metric_name
-            "#,
-        )?;
+            WHERE ((entity_type = 'session'
+                    AND entity_id IN (SELECT s.id FROM sessions s WHERE {session_scope}))
+                OR (entity_type = 'thread'
+                    AND entity_id IN (SELECT t.id FROM threads t
+                                      JOIN sessions s ON s.id = t.session_id
+                                      WHERE {thread_scope}))
+                OR (entity_type NOT IN ('session', 'thread') AND ?))
+            "#
+        );
+        params.push(Box::new(filter.project_id.is_none()));
+        if let Some(computed_at) = filter.after.as_ref().and_then(|w| w.metrics_computed_at) {
+            sql.push_str(" AND computed_at > ?");
+            params.push(Box::new(computed_at.to_rfc3339()));
+        }
+        match filter.upto.metrics_computed_at {
+            Some(computed_at) => {
+                sql.push_str(" AND computed_at <= ?");
+                params.push(Box::new(computed_at.to_rfc3339()));
+            }
+            None => sql.push_str(" AND 0"),
+        }
+        sql.push_str(" ORDER BY computed_at, id");
 
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
         let metrics = stmt
-            .query_map([thread_id], |row| {
+            .query_map(params_refs.as_slice(), |row| {
                 let computed_at_str: String = row.get(6)?;
-                let metric_value = Self::parse_metric_value(row.get_ref(5)?);
                 Ok(crate::types::PluginMetric {
                     id: row.get(0)?,
                     plugin_name: row.get(1)?,
                     entity_type: row.get(2)?,
                     entity_id: row.get(3)?,
                     metric_name: row.get(4)?,
-                    metric_value,
+                    metric_value: Self::parse_metric_value(row.get_ref(5)?),
                     computed_at: Self::parse_rfc3339_field(
                         "plugin_metrics.computed_at",
                         &computed_at_str,
@@ -1440,138 +3638,9 @@ impl Database {
                 })
             })?
             .collect::<rusqlite::Result<Vec<_>>>()?;
-
         Ok(metrics)
     }
 
-    /// Get pre-computed thread analytics from plugin_metrics table.
-    /// Returns None if no analytics have been computed for this thread.
-    pub fn get_thread_analytics(
-        &self,
-        thread_id: &str,
-    ) -> Result<Option<crate::analytics::ThreadAnalytics>> {
-        let metrics = self.get_thread_plugin_metrics(thread_id)?;
-
-        // Filter to edit_churn plugin metrics
-        let edit_churn_metrics: Vec<_> = metrics
-            .iter()
-            .filter(|m| m.plugin_name == "core.edit_churn")
-            .collect();
-
-        if edit_churn_metrics.is_empty() {
-            return Ok(None);
-        }
-
-        // Extract each metric value
-        let mut edit_count: i64 = 0;
-        let mut unique_files: i64 = 0;
-        let mut churn_ratio: f64 = 0.0;
-        let mut high_churn_files: Vec<String> = Vec::new();
-        let mut high_churn_threshold: f64 = 0.0;
-        let mut burst_edit_files: std::collections::HashMap<String, i64> =
-            std::collections::HashMap::new();
-        let mut burst_edit_count: i64 = 0;
-        let mut lines_changed: i64 = 0;
-        let mut first_try_rate: f64 = 0.0;
-        let mut computed_at = chrono::Utc::now();
-
-        for metric in &edit_churn_metrics {
-            match metric.metric_name.as_str() {
-                "edit_count" => {
-                    edit_count = metric.metric_value.as_i64().unwrap_or(0);
-                }
-                "unique_files" => {
-                    unique_files = metric.metric_value.as_i64().unwrap_or(0);
-                }
-                "churn_ratio" => {
-                    churn_ratio = metric.metric_value.as_f64().unwrap_or(0.0);
-                }
-                "high_churn_files" => {
-                    if let Some(arr) = metric.metric_value.as_array() {
-                        high_churn_files = arr
-                            .iter()
-                            .filter_map(|v| v.as_str().map(String::from))
-                            .collect();
-                    }
-                }
-                "high_churn_threshold" => {
-                    high_churn_threshold = metric.metric_value.as_f64().unwrap_or(0.0);
-                }
-                "burst_edit_files" => {
-                    if let Some(obj) = metric.metric_value.as_object() {
-                        burst_edit_files = obj
-                            .iter()
-                            .map(|(k, v)| (k.clone(), v.as_i64().unwrap_or(0)))
-                            .collect();
-                    }
-                }
-                "burst_edit_count" => {
-                    burst_edit_count = metric.metric_value.as_i64().unwrap_or(0);
-                }
-                "lines_changed" => {
-                    lines_changed = metric.metric_value.as_i64().unwrap_or(0);
-                }
-                "first_try_rate" => {
-                    first_try_rate = metric.metric_value.as_f64().unwrap_or(0.0);
-                }
-                _ => {}
-            }
-            // Use the computed_at from any metric (they should all be the same)
-            computed_at = metric.computed_at;
-        }
-
-        Ok(Some(crate::analytics::ThreadAnalytics {
-            edit_count,
-            unique_files,
-            churn_ratio,
-            high_churn_files,
-            high_churn_threshold,
-            burst_edit_files,
-            burst_edit_count,
-            lines_changed,
-            first_try_rate,
-            computed_at,
-        }))
-    }
-
-    fn row_to_message(row: &Row) -> rusqlite::Result<Message> {
-        let author_role_str: String = row.get("author_role")?;
-        let message_type_str: String = row.get("message_type")?;
-        let emitted_at_str: String = row.get("emitted_at")?;
-        let observed_at_str: String = row.get("observed_at")?;
-        let content_type_str: Option<String> = row.get("content_type")?;
-        let tool_input_str: Option<String> = row.get("tool_input")?;
-        let raw_data_str: String = row.get("raw_data")?;
-        let metadata_str: String = row.get("metadata")?;
-
-        Ok(Message {
-            id: row.get("id")?,
-            session_id: row.get("session_id")?,
-            thread_id: row.get("thread_id")?,
-            seq: row.get("seq")?,
-            emitted_at: Self::parse_rfc3339_field("messages.emitted_at", &emitted_at_str)?,
-            observed_at: Self::parse_rfc3339_field("messages.observed_at", &observed_at_str)?,
-            author_role: Self::parse_enum_field("messages.author_role", &author_role_str)?,
-            author_name: row.get("author_name")?,
-            message_type: Self::parse_enum_field("messages.message_type", &message_type_str)?,
-            content: row.get("content")?,
-            content_type: content_type_str
-                .map(|s| Self::parse_enum_field("messages.content_type", &s))
-                .transpose()?,
-            tool_name: row.get("tool_name")?,
-            tool_input: Self::parse_optional_json_field("messages.tool_input", tool_input_str)?,
-            tool_result: row.get("tool_result")?,
-            tokens_in: row.get("tokens_in")?,
-            tokens_out: row.get("tokens_out")?,
-            duration_ms: row.get("duration_ms")?,
-            source_file_path: row.get("source_file_path")?,
-            source_offset: row.get("source_offset")?,
-            source_line: row.get("source_line")?,
-            raw_data: Self::parse_json_field("messages.raw_data", &raw_data_str)?,
-            metadata: Self::parse_json_field("messages.metadata", &metadata_str)?,
-        })
-    }
-
     // ============================================
     // Plan operations
     // ============================================
@@ -1633,7 +3702,7 @@ impl Database {
 
     /// Get the latest version of a plan by slug
     pub fn get_plan_by_slug(&self, slug: &str) -> Result<Option<Plan>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let result = conn
             .query_row(
                 r#"
@@ -1674,7 +3743,7 @@ impl Database {
 
     /// Get all plan slugs for a session
     pub fn get_plan_slugs_for_session(&self, session_id: &str) -> Result<Vec<String>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             "SELECT plan_slug FROM session_plans WHERE session_id = ? ORDER BY first_used_at",
         )?;
@@ -1702,7 +3771,7 @@ impl Database {
 
     /// List plans for a project (latest version per plan slug).
     pub fn list_project_plans(&self, project_id: &str) -> Result<Vec<Plan>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             r#"
             SELECT
@@ -1759,7 +3828,7 @@ impl Database {
 
     /// Count sessions by status
     pub fn count_sessions_by_status(&self) -> Result<std::collections::HashMap<String, i64>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare("SELECT status, COUNT(*) FROM sessions GROUP BY status")?;
 
         let counts: std::collections::HashMap<String, i64> = stmt
@@ -1775,7 +3844,7 @@ impl Database {
 
     /// Count total messages
     pub fn count_messages(&self) -> Result<i64> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM messages", [], |r| r.get(0))?;
         Ok(count)
     }
@@ -1819,7 +3888,7 @@ impl Database {
         session_id: &str,
         assessor: &str,
     ) -> Result<Option<crate::types::Assessment>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             r#"
             SELECT id, session_id, assessor, model, assessed_at, scores, raw_response, prompt_hash
@@ -1898,7 +3967,7 @@ impl Database {
         entity_type: &str,
         entity_id: Option<&str>,
     ) -> Result<Vec<crate::types::PluginMetric>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -1955,7 +4024,7 @@ impl Database {
         &self,
         session_id: &str,
     ) -> Result<Vec<crate::types::PluginMetric>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -2019,7 +4088,7 @@ impl Database {
         plugin_name: &str,
         limit: usize,
     ) -> Result<Vec<crate::analytics::PluginRunResult>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -2059,7 +4128,7 @@ impl Database {
     ///
     /// Returns (success_count, error_count, avg_duration_ms) for each plugin.
     pub fn get_plugin_stats(&self) -> Result<Vec<(String, i64, i64, f64)>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -2094,7 +4163,7 @@ impl Database {
 
     /// Get the source file path for a session
     pub fn get_session_source_path(&self, session_id: &str) -> Result<Option<String>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let path: Option<String> = conn
             .query_row(
                 "SELECT source_file_path FROM sessions WHERE id = ?",
@@ -2107,7 +4176,7 @@ impl Database {
 
     /// Get the backing model display name for a session
     pub fn get_session_model_name(&self, session_id: &str) -> Result<Option<String>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let name: Option<String> = conn
             .query_row(
                 r#"
@@ -2125,7 +4194,7 @@ impl Database {
 
     /// Get session metadata (cwd, git_branch, etc.)
     pub fn get_session_metadata(&self, session_id: &str) -> Result<Option<serde_json::Value>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let metadata: Option<String> = conn
             .query_row(
                 "SELECT metadata FROM sessions WHERE id = ?",
@@ -2141,7 +4210,7 @@ impl Database {
 
     /// Count agent threads for a session
     pub fn count_session_agents(&self, session_id: &str) -> Result<i64> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM threads WHERE session_id = ? AND thread_type = 'agent'",
             [session_id],
@@ -2152,7 +4221,7 @@ impl Database {
 
     /// Count plans for a session
     pub fn count_session_plans(&self, session_id: &str) -> Result<i64> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM session_plans WHERE session_id = ?",
             [session_id],
@@ -2163,7 +4232,7 @@ impl Database {
 
     /// Get tool usage statistics for a thread
     pub fn get_thread_tool_stats(&self, thread_id: &str) -> Result<ToolStats> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         // Get total tool calls
         let total_calls: i64 = conn.query_row(
@@ -2196,7 +4265,7 @@ impl Database {
 
     /// Get file modification statistics for a thread (from Edit/Write tool_input)
     pub fn get_thread_file_stats(&self, thread_id: &str) -> Result<FileStats> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         // Query all Edit/Write tool calls and extract file_path from JSON
         let mut stmt = conn.prepare(
@@ -2225,7 +4294,7 @@ impl Database {
 
         // Sort by count descending
         let mut breakdown: Vec<(String, i64)> = file_counts.into_iter().collect();
-        breakdown.sort_by(|a, b| b.1.cmp(&a.1));
+        breakdown.sort_by_key(|b| std::cmp::Reverse(b.1));
 
         Ok(FileStats {
             total_files,
@@ -2245,7 +4314,7 @@ impl Database {
         }
 
         let result: Option<ThreadMetadataRow> = {
-            let conn = self.lock_conn()?;
+            let conn = self.read_conn()?;
             conn.query_row(
                 r#"
                 SELECT
@@ -2325,7 +4394,7 @@ impl Database {
         &self,
         session_id: &str,
     ) -> Result<Option<(DateTime<Utc>, Option<DateTime<Utc>>)>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let result: Option<(String, Option<String>)> = conn
             .query_row(
                 "SELECT started_at, last_activity_at FROM sessions WHERE id = ?",
@@ -2363,7 +4432,7 @@ impl Database {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<crate::analytics::TotalStats> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -2385,11 +4454,21 @@ impl Database {
         )?;
 
         // Tokens and tool calls from messages
-        let (tokens_in, tokens_out, tool_calls): (i64, i64, i64) = conn.query_row(
+        let (
+            tokens_in,
+            tokens_out,
+            tokens_cache_read,
+            tokens_cache_write,
+            tokens_reasoning,
+            tool_calls,
+        ): (i64, i64, i64, i64, i64, i64) = conn.query_row(
             r#"
                 SELECT
                     COALESCE(SUM(tokens_in), 0),
                     COALESCE(SUM(tokens_out), 0),
+                    COALESCE(SUM(tokens_cache_read), 0),
+                    COALESCE(SUM(tokens_cache_write), 0),
+                    COALESCE(SUM(tokens_reasoning), 0),
                     COALESCE(SUM(CASE WHEN message_type = 'tool_call' THEN 1 ELSE 0 END), 0)
                 FROM messages m
                 JOIN threads t ON m.thread_id = t.id
@@ -2397,7 +4476,16 @@ impl Database {
                 WHERE s.started_at >= ? AND s.started_at < ?
                 "#,
             [&start_str, &end_str],
-            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            |r| {
+                Ok((
+                    r.get(0)?,
+                    r.get(1)?,
+                    r.get(2)?,
+                    r.get(3)?,
+                    r.get(4)?,
+                    r.get(5)?,
+                ))
+            },
         )?;
 
         // Plans
@@ -2458,6 +4546,10 @@ impl Database {
             total_duration_secs,
             tokens_in,
             tokens_out,
+            tokens_cache_read,
+            tokens_cache_write,
+            tokens_reasoning,
+            cost: Default::default(),
             tool_calls,
             plans,
             agents_spawned,
@@ -2473,7 +4565,7 @@ impl Database {
         end: DateTime<Utc>,
         limit: usize,
     ) -> Result<Vec<(String, i64)>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -2507,7 +4599,7 @@ impl Database {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<[i64; 24]> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -2544,7 +4636,7 @@ impl Database {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<[i64; 7]> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -2582,7 +4674,7 @@ impl Database {
         end: DateTime<Utc>,
         limit: usize,
     ) -> Result<Vec<crate::analytics::ProjectRanking>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -2591,7 +4683,10 @@ impl Database {
             SELECT
                 COALESCE(p.name, '(no project)') as name,
                 COUNT(DISTINCT s.id) as sessions,
-                COALESCE(SUM(m.tokens_in + m.tokens_out), 0) as tokens,
+                COALESCE(SUM(
+                    COALESCE(m.tokens_in, 0) + COALESCE(m.tokens_out, 0)
+                    + COALESCE(m.tokens_cache_read, 0) + COALESCE(m.tokens_cache_write, 0)
+                ), 0) as tokens,
                 COALESCE(SUM(
                     CASE WHEN s.last_activity_at IS NOT NULL
                     THEN (julianday(s.last_activity_at) - julianday(s.started_at)) * 86400
@@ -2641,7 +4736,7 @@ impl Database {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<Option<crate::analytics::MarathonSession>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -2659,7 +4754,10 @@ impl Database {
                         MAX(m.emitted_at) as last_msg,
                         (julianday(MAX(m.emitted_at)) - julianday(MIN(m.emitted_at))) * 86400 as duration_secs,
                         COUNT(CASE WHEN m.message_type = 'tool_call' THEN 1 END) as tool_calls,
-                        COALESCE(SUM(m.tokens_in + m.tokens_out), 0) as tokens
+                        COALESCE(SUM(
+                            COALESCE(m.tokens_in, 0) + COALESCE(m.tokens_out, 0)
+                            + COALESCE(m.tokens_cache_read, 0) + COALESCE(m.tokens_cache_write, 0)
+                        ), 0) as tokens
                     FROM messages m
                     JOIN threads t ON m.thread_id = t.id
                     JOIN sessions s ON t.session_id = s.id
@@ -2717,7 +4815,7 @@ impl Database {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<crate::analytics::StreakStats> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -2808,13 +4906,85 @@ impl Database {
         })
     }
 
+    // ============================================
+    // Cost Queries
+    // ============================================
+
+    /// Get token usage grouped by project, backing model and day.
+    ///
+    /// Each message counts toward the model recorded on it (see
+    /// [`Message::model_id`]), or its session's backing model.
+    ///
+    /// Optionally restricted to one project and/or to sessions started in
+    /// `[start, end)`. Price the rows with
+    /// [`PricingCatalog`](crate::analytics::PricingCatalog).
+    pub fn get_model_usage(
+        &self,
+        project_id: Option<&str>,
+        started: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> Result<Vec<ModelUsage>> {
+        let conn = self.read_conn()?;
+        let (start_str, end_str) = match started {
+            Some((start, end)) => (Some(start.to_rfc3339()), Some(end.to_rfc3339())),
+            None => (None, None),
+        };
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                s.project_id,
+                COALESCE(json_extract(m.metadata, '$.model'), s.backing_model_id) as model,
+                date(m.emitted_at) as day,
+                COALESCE(SUM(m.tokens_in), 0),
+                COALESCE(SUM(m.tokens_out), 0),
+                COALESCE(SUM(m.tokens_cache_read), 0),
+                COALESCE(SUM(m.tokens_cache_write), 0)
+            FROM messages m
+            JOIN threads t ON m.thread_id = t.id
+            JOIN sessions s ON t.session_id = s.id
+            WHERE (?1 IS NULL OR s.project_id = ?1)
+              AND (?2 IS NULL OR s.started_at >= ?2)
+              AND (?3 IS NULL OR s.started_at < ?3)
+              AND (m.tokens_in IS NOT NULL OR m.tokens_out IS NOT NULL
+                   OR m.tokens_cache_read IS NOT NULL OR m.tokens_cache_write IS NOT NULL)
+            GROUP BY s.project_id, model, day
+            "#,
+        )?;
+
+        let rows = stmt
+            .query_map(params![project_id, start_str, end_str], |row| {
+                let day: String = row.get(2)?;
+                let day = NaiveDate::parse_from_str(&day, "%Y-%m-%d").map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        2,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?;
+                Ok(ModelUsage {
+                    project_id: row.get(0)?,
+                    backing_model_id: row.get(1)?,
+                    day,
+                    usage: TokenUsage {
+                        tokens_in: row.get(3)?,
+                        tokens_out: row.get(4)?,
+                        tokens_cache_read: row.get(5)?,
+                        tokens_cache_write: row.get(6)?,
+                    },
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(rows)
+    }
+
     // ============================================
     // Project Analytics Queries (for TUI Project View)
     // ============================================
 
     /// List all projects with summary stats for the project list view.
     pub fn list_projects_with_stats(&self) -> Result<Vec<crate::analytics::ProjectRow>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -2824,7 +4994,10 @@ impl Database {
                 p.path,
                 COUNT(DISTINCT s.id) as session_count,
                 MAX(s.last_activity_at) as last_activity,
-                COALESCE(SUM(m.tokens_in + m.tokens_out), 0) as total_tokens
+                COALESCE(SUM(
+                    COALESCE(m.tokens_in, 0) + COALESCE(m.tokens_out, 0)
+                    + COALESCE(m.tokens_cache_read, 0) + COALESCE(m.tokens_cache_write, 0)
+                ), 0) as total_tokens
             FROM projects p
             LEFT JOIN sessions s ON s.project_id = p.id
             LEFT JOIN threads t ON t.session_id = s.id
@@ -2848,6 +5021,7 @@ impl Database {
                     session_count: row.get(3)?,
                     last_activity,
                     total_tokens: row.get(5)?,
+                    cost: Default::default(),
                 })
             })?
             .collect::<rusqlite::Result<Vec<_>>>()?;
@@ -2860,7 +5034,7 @@ impl Database {
         &self,
         project_id: &str,
     ) -> Result<Option<crate::analytics::ProjectStats>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         // First, get the project info
         let project_info: Option<(String, String, String)> = conn
@@ -2923,18 +5097,27 @@ impl Database {
         )?;
 
         // Token usage
-        let (tokens_in, tokens_out): (i64, i64) = conn.query_row(
+        let (tokens_in, tokens_out, tokens_cache_read, tokens_cache_write, tokens_reasoning): (
+            i64,
+            i64,
+            i64,
+            i64,
+            i64,
+        ) = conn.query_row(
             r#"
                 SELECT
                     COALESCE(SUM(m.tokens_in), 0),
-                    COALESCE(SUM(m.tokens_out), 0)
+                    COALESCE(SUM(m.tokens_out), 0),
+                    COALESCE(SUM(m.tokens_cache_read), 0),
+                    COALESCE(SUM(m.tokens_cache_write), 0),
+                    COALESCE(SUM(m.tokens_reasoning), 0)
                 FROM messages m
                 JOIN threads t ON m.thread_id = t.id
                 JOIN sessions s ON t.session_id = s.id
                 WHERE s.project_id = ?
                 "#,
             [project_id],
-            |r| Ok((r.get(0)?, r.get(1)?)),
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
         )?;
 
         // Tool stats
@@ -2997,7 +5180,7 @@ impl Database {
 
         let total_files = file_counts.len() as i64;
         let mut file_breakdown: Vec<(String, i64)> = file_counts.into_iter().collect();
-        file_breakdown.sort_by(|a, b| b.1.cmp(&a.1));
+        file_breakdown.sort_by_key(|b| std::cmp::Reverse(b.1));
 
         let file_stats = FileStats {
             total_files,
@@ -3098,6 +5281,10 @@ impl Database {
             total_duration_secs: total_duration_secs as i64,
             tokens_in,
             tokens_out,
+            tokens_cache_read,
+            tokens_cache_write,
+            tokens_reasoning,
+            cost: Default::default(),
             tool_stats,
             file_stats,
             agents_spawned,
@@ -3116,7 +5303,7 @@ impl Database {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<crate::analytics::personality::UsageProfile> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
 
@@ -3251,7 +5438,7 @@ impl Database {
     ///
     /// Returns aggregate stats, activity heatmap (last 28 days), streaks, and patterns.
     pub fn get_dashboard_stats(&self) -> Result<crate::analytics::DashboardStats> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         // 1. Get aggregate totals
         let (project_count, session_count, total_tokens): (i64, i64, i64) = conn.query_row(
@@ -3259,7 +5446,13 @@ impl Database {
                 SELECT
                     (SELECT COUNT(*) FROM projects),
                     (SELECT COUNT(*) FROM sessions),
-                    COALESCE((SELECT SUM(tokens_in + tokens_out) FROM messages), 0)
+                    COALESCE((
+                        SELECT SUM(
+                            COALESCE(tokens_in, 0) + COALESCE(tokens_out, 0)
+                            + COALESCE(tokens_cache_read, 0) + COALESCE(tokens_cache_write, 0)
+                        )
+                        FROM messages
+                    ), 0)
                 "#,
             [],
             |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
@@ -3370,7 +5563,7 @@ impl Database {
         &self,
         since_minutes: i64,
     ) -> Result<Vec<crate::types::ActiveSession>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -3424,7 +5617,7 @@ impl Database {
     /// Returns message count, token totals, agent count, and tool call count
     /// for messages within the specified time window.
     pub fn get_live_stats(&self, since_minutes: i64) -> Result<crate::types::LiveStats> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let since_param = format!("-{}", since_minutes);
 
         // Get message count, token totals, and tool call count
@@ -3432,7 +5625,10 @@ impl Database {
             r#"
             SELECT
                 COUNT(*) as total_messages,
-                COALESCE(SUM(COALESCE(tokens_in, 0) + COALESCE(tokens_out, 0)), 0) as total_tokens,
+                COALESCE(SUM(
+                    COALESCE(tokens_in, 0) + COALESCE(tokens_out, 0)
+                    + COALESCE(tokens_cache_read, 0) + COALESCE(tokens_cache_write, 0)
+                ), 0) as total_tokens,
                 COALESCE(SUM(CASE WHEN message_type = 'tool_call' THEN 1 ELSE 0 END), 0) as total_tool_calls
             FROM messages
             WHERE emitted_at >= datetime('now', ? || ' minutes')
@@ -3473,7 +5669,7 @@ impl Database {
         &self,
         limit: usize,
     ) -> Result<Vec<crate::types::MessageWithContext>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -3536,7 +5732,7 @@ impl Database {
 
     /// Get the database file size in bytes.
     pub fn get_database_size(&self) -> Result<u64> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
         let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
@@ -3555,7 +5751,7 @@ impl Database {
     /// Get environment health stats for each assistant.
     /// Returns a list of (assistant, file_count, total_size_bytes, last_parsed_at).
     pub fn get_assistant_source_stats(&self) -> Result<Vec<AssistantSourceStats>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -3593,7 +5789,7 @@ impl Database {
 
     /// Get total counts for environment overview.
     pub fn get_total_counts(&self) -> Result<(i64, i64, i64)> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
 
         let session_count: i64 =
             conn.query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))?;
@@ -3642,7 +5838,7 @@ impl Database {
         &self,
         session_id: &str,
     ) -> Result<Option<CollectorPublishState>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let result = conn
             .query_row(
                 r#"
@@ -3716,7 +5912,7 @@ impl Database {
     /// Returns sessions where there are messages with seq > last_published_seq.
     /// Used for crash recovery to resume publishing.
     pub fn get_incomplete_publish_states(&self) -> Result<Vec<CollectorPublishState>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             r#"
             SELECT ps.session_id, ps.last_published_seq, ps.last_published_at,
@@ -3770,7 +5966,7 @@ impl Database {
         last_published_seq: i64,
         limit: usize,
     ) -> Result<Vec<Message>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             r#"
             SELECT
@@ -3780,9 +5976,10 @@ impl Database {
                 message_type,
                 content, content_type,
                 tool_name, tool_input, tool_result,
-                tokens_in, tokens_out, duration_ms,
+                tokens_in, tokens_out, tokens_cache_read, tokens_cache_write,
+                tokens_reasoning, duration_ms,
                 source_file_path, source_offset, source_line,
-                raw_data, metadata
+                raw_data, metadata, tool_call_id, record_id, parent_record_id
             FROM messages
             WHERE session_id = ?1 AND seq > ?2
             ORDER BY seq ASC
@@ -3818,7 +6015,7 @@ impl Database {
     ///
     /// Returns all sessions that are currently being tracked for publishing.
     pub fn get_active_publish_states(&self) -> Result<Vec<CollectorPublishState>> {
-        let conn = self.lock_conn()?;
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             r#"
             SELECT session_id, last_published_seq, last_published_at,
@@ -3865,16 +6062,278 @@ impl Database {
 pub struct SessionFilter {
     /// Filter by assistant type
     pub assistant: Option<Assistant>,
+    /// Filter by the custom agent or parser plugin sessions were read by
+    pub agent: Option<String>,
     /// Filter by status
     pub status: Option<SessionStatus>,
     /// Filter by project ID
     pub project_id: Option<String>,
+    /// Filter by the label of the data root sessions were read from
+    pub source_root: Option<String>,
+    /// Filter by the host label of the data root sessions were read from
+    pub source_host: Option<String>,
     /// Filter sessions started after this time
     pub since: Option<DateTime<Utc>>,
     /// Maximum number of sessions to return
     pub limit: Option<usize>,
 }
 
+/// Filter for full-text message search
+#[derive(Debug, Default)]
+pub struct SearchFilter {
+    /// Only messages from this assistant
+    pub assistant: Option<Assistant>,
+    /// Only messages from sessions of this project
+    pub project_id: Option<String>,
+    /// Only messages from this session
+    pub session_id: Option<String>,
+    /// Only messages of this type
+    pub message_type: Option<MessageType>,
+    /// Only messages emitted at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum number of hits to return (default 50)
+    pub limit: Option<usize>,
+}
+
+/// What [`Database::prune_raw_data`] dropped, or would drop.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    /// Messages whose raw data was dropped
+    pub messages: u64,
+    /// Sessions with pruned messages
+    pub sessions: u64,
+    /// Source files newly marked pruned
+    pub source_files: u64,
+    /// Bytes of raw data and tool results dropped, before `VACUUM`
+    pub bytes: u64,
+}
+
+/// What [`Database::compress_messages`] compressed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompressionSummary {
+    /// Messages with a value rewritten
+    pub rows: u64,
+    /// Stored size of the rewritten values before
+    pub bytes_before: u64,
+    /// Stored size of the rewritten values after
+    pub bytes_after: u64,
+}
+
+/// How far a dataset export reached: the next incremental export starts
+/// after it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportWatermark {
+    /// Highest message id
+    pub message_id: i64,
+    /// Highest assessment id
+    pub assessment_id: i64,
+    /// Latest plugin metric computation
+    pub metrics_computed_at: Option<DateTime<Utc>>,
+    /// Highest `reparsed_sessions` id
+    #[serde(default)]
+    pub reparse_id: i64,
+}
+
+/// Rows covered by a dataset export
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Only sessions of this project
+    pub project_id: Option<String>,
+    /// Only sessions active, and messages emitted, at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only rows added or changed after this watermark (incremental export)
+    pub after: Option<ExportWatermark>,
+    /// Only rows up to this watermark, from [`Database::export_watermark`]
+    pub upto: ExportWatermark,
+}
+
+/// How long a blob no message references is kept, in case its message is
+/// about to be committed (see [`Database::remove_unreferenced_blobs`]).
+const BLOB_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Messages rewritten per transaction by [`Database::compress_messages`].
+const COMPRESS_BATCH_SIZE: usize = 500;
+
+/// Fewest values a compression dictionary is trained on.
+const MIN_DICTIONARY_SAMPLES: usize = 1000;
+
+/// Most recent messages sampled to train a compression dictionary.
+const DICTIONARY_SAMPLE_ROWS: i64 = 20_000;
+
+/// Longest sample, and total sample size, used to train a dictionary.
+const DICTIONARY_SAMPLE_MAX_BYTES: usize = 16 * 1024;
+const DICTIONARY_SAMPLE_TOTAL_BYTES: usize = 8 * 1024 * 1024;
+
+/// A possibly compressed column value, decoded.
+struct StoredText {
+    text: String,
+    /// Bytes the value takes in the database
+    size: usize,
+    compressed: bool,
+}
+
+impl StoredText {
+    fn read(row: &Row, idx: usize) -> rusqlite::Result<Option<Self>> {
+        let (size, compressed) = match row.get_ref(idx)? {
+            rusqlite::types::ValueRef::Blob(frame) => (frame.len(), true),
+            rusqlite::types::ValueRef::Text(text) => (text.len(), false),
+            _ => return Ok(None),
+        };
+        Ok(compress::column_text(row, idx)?.map(|text| Self {
+            text,
+            size,
+            compressed,
+        }))
+    }
+}
+
+/// Words of a search query, each with whether it is a prefix (`word*`).
+fn query_words(text: &str) -> Vec<(&str, bool)> {
+    text.split_whitespace()
+        .filter_map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) => (stem, true),
+                None => (word, false),
+            };
+            (!word.is_empty()).then_some((word, prefix))
+        })
+        .collect()
+}
+
+/// Turn free text into an FTS5 query matching messages with every word.
+///
+/// Words are quoted, so punctuation and FTS5 keywords are matched literally;
+/// a trailing `*` keeps its meaning as a prefix match. Returns `None` when
+/// there is nothing to search for.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = query_words(text)
+        .into_iter()
+        .map(|(word, prefix)| {
+            format!(
+                "\"{}\"{}",
+                word.replace('"', "\"\""),
+                if prefix { "*" } else { "" }
+            )
+        })
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Words in a [`SearchHit::snippet`].
+const SNIPPET_WORDS: usize = 16;
+
+/// Excerpt of the first of `columns` matching `query`: up to
+/// [`SNIPPET_WORDS`] words from just before the first match, with matched
+/// words marked.
+///
+/// Words are split like the index's `unicode61` tokenizer; its porter
+/// stemming is approximated by comparing words with common suffixes removed.
+fn search_snippet(query: &str, columns: &[Option<&str>]) -> String {
+    let terms: Vec<(String, bool)> = query_words(query)
+        .into_iter()
+        .map(|(word, prefix)| {
+            let word = word.to_lowercase();
+            if prefix {
+                (word, true)
+            } else {
+                (rough_stem(&word).to_string(), false)
+            }
+        })
+        .collect();
+    let matches = |word: &str| {
+        let word = word.to_lowercase();
+        terms.iter().any(|(term, prefix)| {
+            !term.is_empty()
+                && if *prefix {
+                    word.starts_with(term.as_str())
+                } else {
+                    rough_stem(&word).starts_with(term.as_str())
+                }
+        })
+    };
+
+    let texts: Vec<&str> = columns.iter().flatten().copied().collect();
+    let words_of = |text: &str| -> Vec<(usize, usize)> {
+        let mut words = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices() {
+            match (c.is_alphanumeric(), start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    words.push((s, i));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            words.push((s, text.len()));
+        }
+        words
+    };
+
+    let Some((text, words, first)) = texts
+        .iter()
+        .filter_map(|text| {
+            let words = words_of(text);
+            let first = words.iter().position(|&(s, e)| matches(&text[s..e]))?;
+            Some((*text, words, first))
+        })
+        .next()
+        .or_else(|| texts.first().map(|text| (*text, words_of(text), 0)))
+    else {
+        return String::new();
+    };
+    if words.is_empty() {
+        return String::new();
+    }
+
+    let start = first
+        .saturating_sub(3)
+        .min(words.len().saturating_sub(SNIPPET_WORDS));
+    let end = (start + SNIPPET_WORDS).min(words.len());
+    let mut snippet = String::new();
+    let mut pos = if start == 0 { 0 } else { words[start].0 };
+    if start > 0 {
+        snippet.push('…');
+    }
+    for &(s, e) in &words[start..end] {
+        snippet.push_str(&text[pos..s]);
+        if matches(&text[s..e]) {
+            snippet.push_str(SNIPPET_MATCH_START);
+            snippet.push_str(&text[s..e]);
+            snippet.push_str(SNIPPET_MATCH_END);
+        } else {
+            snippet.push_str(&text[s..e]);
+        }
+        pos = e;
+    }
+    if end == words.len() {
+        snippet.push_str(&text[pos..]);
+    } else {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// `word` without a common English suffix, for matching stemmed terms.
+fn rough_stem(word: &str) -> &str {
+    ["ing", "ed", "es", "e", "s"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix).filter(|stem| stem.len() >= 3))
+        .unwrap_or(word)
+}
+
+/// A parse failure's error without serde's trailing position (" at line 1
+/// column 5"), so failures with the same cause group together.
+const ERROR_CAUSE_SQL: &str = "CASE WHEN error GLOB '* at line [0-9]* column [0-9]*' \
+     THEN substr(error, 1, instr(error, ' at line ') - 1) ELSE error END";
+
+/// Matches parse failures of the source files of the custom agent bound to
+/// `?2`, or every failure when it is NULL.
+const FAILURE_AGENT_SQL: &str = "(?2 IS NULL OR source_file_path IN \
+     (SELECT path FROM source_files WHERE agent = ?2))";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3884,11 +6343,13 @@ mod tests {
             path: PathBuf::from("/path/to/source.jsonl"),
             file_type: FileType::Jsonl,
             assistant: Assistant::ClaudeCode,
+            agent: None,
             created_at: Utc::now(),
             modified_at: Utc::now(),
             size_bytes: 1024,
             last_parsed_at: None,
             checkpoint: Checkpoint::ByteOffset { offset: 0 },
+            parser_version: 1,
         }
     }
 
@@ -3950,12 +6411,18 @@ mod tests {
             tool_name: None,
             tool_input: None,
             tool_result: None,
+            tool_call_id: None,
             tokens_in: Some(100),
             tokens_out: None,
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
             duration_ms: None,
             source_file_path: "/path/to/source.jsonl".to_string(),
             source_offset: 0,
             source_line: Some(1),
+            record_id: None,
+            parent_record_id: None,
             raw_data: serde_json::json!({"type": "prompt"}),
             metadata: serde_json::json!({}),
         }
@@ -3985,6 +6452,470 @@ mod tests {
         assert_eq!(sessions.len(), 1);
     }
 
+    #[test]
+    fn test_queries_use_readers() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("test.db");
+        let db = Database::open(&path).unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+        db.upsert_session(&create_test_session()).unwrap();
+
+        // A reader sees committed data and does not wait for the writer
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        let db = &db;
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                let writer = db.connection().unwrap();
+                writer
+                    .execute_batch("BEGIN; DELETE FROM sessions;")
+                    .unwrap();
+                locked_tx.send(()).unwrap();
+                done_rx.recv().unwrap();
+                writer.execute_batch("ROLLBACK").unwrap();
+            });
+            locked_rx.recv().unwrap();
+            assert!(db.get_session("test-session-1").unwrap().is_some());
+            done_tx.send(()).unwrap();
+        });
+
+        // Inside a savepoint, queries see its uncommitted writes
+        db.savepoint(|| {
+            db.connection()?
+                .execute("DELETE FROM sessions WHERE id = 'test-session-1'", [])?;
+            assert!(db.get_session("test-session-1")?.is_none());
+            Err::<(), _>(Error::Config("roll back".to_string()))
+        })
+        .unwrap_err();
+        assert!(db.get_session("test-session-1").unwrap().is_some());
+
+        let read_only = Database::open_read_only(&path).unwrap();
+        assert!(read_only.get_session("test-session-1").unwrap().is_some());
+        assert!(read_only.upsert_session(&create_test_session()).is_err());
+    }
+
+    #[test]
+    fn test_savepoint_keeps_other_threads_writes_out() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open(&temp.path().join("test.db")).unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+
+        // Another thread's write waits for the savepoint, so rolling the
+        // savepoint back does not undo it
+        let (opened_tx, opened_rx) = std::sync::mpsc::channel();
+        let db = &db;
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                opened_rx.recv().unwrap();
+                db.upsert_session(&create_test_session()).unwrap();
+            });
+            db.savepoint(|| {
+                opened_tx.send(()).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                db.connection()?.execute("DELETE FROM source_files", [])?;
+                Err::<(), _>(Error::Config("roll back".to_string()))
+            })
+            .unwrap_err();
+        });
+
+        assert!(db.get_session("test-session-1").unwrap().is_some());
+        assert_eq!(db.list_source_files().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_prune_raw_data() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+        let session = create_test_session();
+        db.upsert_session(&session).unwrap();
+        let thread = create_test_thread(&session.id);
+        db.insert_thread(&thread).unwrap();
+
+        let mut old = create_test_message(&session.id, &thread.id, 1);
+        old.emitted_at = Utc::now() - chrono::Duration::days(60);
+        old.tool_result = Some("file listing".to_string());
+        let recent = create_test_message(&session.id, &thread.id, 2);
+        db.insert_messages(&[old, recent]).unwrap();
+
+        let cutoffs = vec![(session.id.clone(), Utc::now() - chrono::Duration::days(30))];
+        let estimate = db.prune_raw_data(&cutoffs, true).unwrap();
+        assert_eq!(estimate.messages, 1);
+        assert_eq!(estimate.source_files, 1);
+        assert!(estimate.bytes > 0);
+        assert!(db.list_pruned_source_files().unwrap().is_empty());
+
+        assert_eq!(db.prune_raw_data(&cutoffs, false).unwrap(), estimate);
+        let messages = db.get_session_messages(&session.id, 10).unwrap();
+        assert!(messages[0].raw_data.is_null());
+        assert_eq!(messages[0].tool_result, None);
+        assert_eq!(messages[0].content.as_deref(), Some("Hello"));
+        assert!(!messages[1].raw_data.is_null());
+        assert_eq!(
+            db.list_pruned_source_files().unwrap(),
+            vec!["/path/to/source.jsonl".to_string()]
+        );
+
+        // Already pruned messages are not counted again
+        assert_eq!(
+            db.prune_raw_data(&cutoffs, false).unwrap(),
+            PruneSummary::default()
+        );
+    }
+
+    #[test]
+    fn test_messages_are_stored_compressed() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+        let session = create_test_session();
+        db.upsert_session(&session).unwrap();
+        let thread = create_test_thread(&session.id);
+        db.insert_thread(&thread).unwrap();
+
+        let listing = "drwxr-xr-x  src\n-rw-r--r--  Cargo.toml\n".repeat(50);
+        let mut result = create_test_message(&session.id, &thread.id, 1);
+        result.message_type = MessageType::ToolResult;
+        result.tool_result = Some(format!("{listing}kubernetes"));
+        result.raw_data = serde_json::json!({"toolUseResult": {"stdout": listing}});
+        db.insert_message(&result).unwrap();
+        db.insert_message(&create_test_message(&session.id, &thread.id, 2))
+            .unwrap();
+
+        let storage_classes = |db: &Database| -> Vec<(String, String)> {
+            let conn = db.connection().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT typeof(raw_data), typeof(tool_result) FROM messages ORDER BY seq")
+                .unwrap();
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap()
+        };
+        assert_eq!(
+            storage_classes(&db),
+            vec![
+                ("blob".to_string(), "blob".to_string()),
+                ("text".to_string(), "null".to_string())
+            ]
+        );
+
+        // Readers and the full-text index see the original values
+        let messages = db.get_session_messages(&session.id, 10).unwrap();
+        assert_eq!(messages[0].tool_result, result.tool_result);
+        assert_eq!(messages[0].raw_data, result.raw_data);
+        let hits = db
+            .search_messages("kubernetes", &SearchFilter::default())
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].snippet.contains("\u{ab}kubernetes\u{bb}"));
+
+        // The index keeps no copy of the text
+        let indexed: Option<String> = db
+            .connection()
+            .unwrap()
+            .query_row("SELECT tool_result FROM messages_fts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, None);
+
+        // The index is rebuilt contentless on upgrade, compressed tool
+        // results included
+        db.connection()
+            .unwrap()
+            .execute_batch("DELETE FROM messages_fts; PRAGMA user_version = 25;")
+            .unwrap();
+        db.migrate().unwrap();
+        let hits = db
+            .search_messages("kubernetes", &SearchFilter::default())
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+
+        // Rows stored before compression are compressed on upgrade
+        {
+            let conn = db.connection().unwrap();
+            conn.execute_batch(
+                r#"
+                UPDATE messages SET raw_data = aiobscura_text(raw_data),
+                                    tool_result = aiobscura_text(tool_result);
+                -- Undo the later migrations that cannot run twice
+                ALTER TABLE telemetry_metrics DROP COLUMN temporality;
+                ALTER TABLE telemetry_metrics DROP COLUMN monotonic;
+                ALTER TABLE telemetry_metrics DROP COLUMN started_at;
+                ALTER TABLE projects DROP COLUMN resolved;
+                ALTER TABLE source_files DROP COLUMN agent;
+                PRAGMA user_version = 19;
+                "#,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            storage_classes(&db)[0],
+            ("text".to_string(), "text".to_string())
+        );
+        db.migrate().unwrap();
+        assert_eq!(
+            storage_classes(&db)[0],
+            ("blob".to_string(), "blob".to_string())
+        );
+        assert_eq!(
+            db.get_session_messages(&session.id, 10).unwrap()[0].tool_result,
+            result.tool_result
+        );
+        assert_eq!(
+            db.search_messages("kubernetes", &SearchFilter::default())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            db.compress_messages(false).unwrap(),
+            CompressionSummary::default()
+        );
+    }
+
+    #[test]
+    fn test_sqlite_shell_can_delete_compressed_messages() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("data.db");
+        let db = Database::open(&path).unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+        let session = create_test_session();
+        db.upsert_session(&session).unwrap();
+        let thread = create_test_thread(&session.id);
+        db.insert_thread(&thread).unwrap();
+        let mut result = create_test_message(&session.id, &thread.id, 1);
+        result.message_type = MessageType::ToolResult;
+        result.tool_result = Some(format!(
+            "{}kubernetes",
+            "-rw-r--r--  Cargo.toml\n".repeat(50)
+        ));
+        db.insert_message(&result).unwrap();
+        let mut prompt = create_test_message(&session.id, &thread.id, 2);
+        prompt.content = Some("deploy to staging".to_string());
+        db.insert_message(&prompt).unwrap();
+
+        // A connection without aiobscura's SQL functions, like sqlite3's
+        let shell = Connection::open(&path).unwrap();
+        shell
+            .execute_batch(
+                r#"
+                UPDATE messages SET content = 'deploy to production' WHERE seq = 2;
+                DELETE FROM messages WHERE seq = 1;
+                "#,
+            )
+            .unwrap();
+        drop(shell);
+
+        assert!(db
+            .search_messages("kubernetes", &SearchFilter::default())
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .search_messages("staging", &SearchFilter::default())
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.search_messages("production", &SearchFilter::default())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_compression_dictionary() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+        let session = create_test_session();
+        db.upsert_session(&session).unwrap();
+        let thread = create_test_thread(&session.id);
+        db.insert_thread(&thread).unwrap();
+
+        let message = |seq: i32| {
+            let mut message = create_test_message(&session.id, &thread.id, seq);
+            message.raw_data = serde_json::json!({
+                "type": "assistant",
+                "uuid": format!("{seq:08}"),
+                "cwd": "/Users/alice/src/app",
+                "message": {"role": "assistant", "content": format!("Step {seq}: reading the configuration and running the test suite again")},
+                "gitBranch": "main",
+                "version": "2.0.31",
+                "timestamp": format!("2025-12-06T10:{:02}:{:02}.000Z", seq / 60 % 60, seq % 60),
+                "sessionId": "5f1c2a9e-7b3d-4c8e-9a6f-2d1b0e4c7a93",
+            });
+            message
+        };
+        assert_eq!(db.train_compression_dictionary().unwrap(), None);
+        let messages: Vec<Message> = (0..1200).map(message).collect();
+        db.insert_messages(&messages).unwrap();
+
+        let dict_id = db.train_compression_dictionary().unwrap().unwrap();
+        let summary = db.compress_messages(true).unwrap();
+        assert_eq!(summary.rows, 1200);
+        assert!(summary.bytes_after < summary.bytes_before);
+
+        db.insert_message(&message(1200)).unwrap();
+        let stored = db.get_session_messages(&session.id, 2000).unwrap();
+        assert_eq!(stored.len(), 1201);
+        assert_eq!(stored[1200].raw_data, message(1200).raw_data);
+
+        let conn = db.connection().unwrap();
+        let frame: Vec<u8> = conn
+            .query_row(
+                "SELECT raw_data FROM messages WHERE seq = 1200",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            zstd::zstd_safe::get_dict_id_from_frame(&frame).map(|id| id.get()),
+            Some(dict_id)
+        );
+    }
+
+    #[test]
+    fn test_search_messages() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+        let session = create_test_session();
+        db.upsert_session(&session).unwrap();
+        let thread = create_test_thread(&session.id);
+        db.insert_thread(&thread).unwrap();
+
+        let mut prompt = create_test_message(&session.id, &thread.id, 1);
+        prompt.content = Some("The OAuth refresh token expires too early".to_string());
+        let mut call = create_test_message(&session.id, &thread.id, 2);
+        call.message_type = MessageType::ToolCall;
+        call.content = None;
+        call.tool_name = Some("Edit".to_string());
+        call.tool_input = Some(serde_json::json!({"file_path": "src/auth/oauth.rs"}));
+        let mut result = create_test_message(&session.id, &thread.id, 3);
+        result.message_type = MessageType::ToolResult;
+        result.content = None;
+        result.tool_result = Some("Fixed refreshing of expired tokens".to_string());
+        db.insert_messages(&[prompt, call, result]).unwrap();
+
+        let hits = db
+            .search_messages("oauth refresh", &SearchFilter::default())
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, session.id);
+        assert_eq!(hits[0].thread_type, ThreadType::Main);
+        assert_eq!(hits[0].message_type, MessageType::Prompt);
+        assert!(hits[0].snippet.contains("\u{ab}OAuth\u{bb}"));
+
+        // Stemmed, across columns, and punctuation is matched literally
+        assert_eq!(
+            db.search_messages("refreshing", &SearchFilter::default())
+                .unwrap()
+                .len(),
+            2
+        );
+        let hits = db
+            .search_messages("auth/oauth.rs", &SearchFilter::default())
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].tool_name.as_deref(), Some("Edit"));
+        assert_eq!(
+            db.search_messages("expire*", &SearchFilter::default())
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let filter = SearchFilter {
+            message_type: Some(MessageType::ToolResult),
+            ..Default::default()
+        };
+        assert_eq!(db.search_messages("token", &filter).unwrap().len(), 1);
+        assert!(db
+            .search_messages("  \" ", &SearchFilter::default())
+            .unwrap()
+            .is_empty());
+
+        // Deleted messages leave the index
+        db.delete_ingested_data(std::slice::from_ref(&session.id), &[])
+            .unwrap();
+        assert!(db
+            .search_messages("oauth", &SearchFilter::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search_snippet() {
+        let text = format!(
+            "{}the token expired {}",
+            "one two ".repeat(10),
+            "end ".repeat(20)
+        );
+        let snippet = search_snippet("expire", &[None, Some(&text)]);
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("two the token \u{ab}expired\u{bb} end"));
+        assert_eq!(snippet.split_whitespace().count(), SNIPPET_WORDS);
+
+        assert_eq!(
+            search_snippet("oauth*", &[Some("Fix OAuth2 login")]),
+            "Fix \u{ab}OAuth2\u{bb} login"
+        );
+    }
+
+    #[test]
+    fn test_merge_projects() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&create_test_source_file()).unwrap();
+
+        let now = Utc::now();
+        for (id, created_at, last_activity_at) in [
+            ("old", now - chrono::Duration::days(10), now),
+            (
+                "new",
+                now - chrono::Duration::days(1),
+                now - chrono::Duration::days(1),
+            ),
+        ] {
+            db.upsert_project(&Project {
+                id: id.to_string(),
+                path: PathBuf::from(format!("/src/{}", id)),
+                name: Some(id.to_string()),
+                created_at,
+                last_activity_at: Some(last_activity_at),
+                metadata: serde_json::json!({}),
+            })
+            .unwrap();
+        }
+        let mut session = create_test_session();
+        session.project_id = Some("old".to_string());
+        db.upsert_session(&session).unwrap();
+
+        assert_eq!(db.merge_projects("old", "new").unwrap(), 1);
+
+        assert!(db.get_project("old").unwrap().is_none());
+        let merged = db.get_project("new").unwrap().unwrap();
+        assert_eq!(merged.path, PathBuf::from("/src/new"));
+        assert_eq!(
+            merged.created_at.timestamp(),
+            (now - chrono::Duration::days(10)).timestamp()
+        );
+        assert_eq!(
+            merged.last_activity_at.unwrap().timestamp(),
+            now.timestamp()
+        );
+        let session = db.get_session(&session.id).unwrap().unwrap();
+        assert_eq!(session.project_id.as_deref(), Some("new"));
+
+        assert!(matches!(
+            db.merge_projects("old", "new"),
+            Err(Error::ProjectNotFound(id)) if id == "old"
+        ));
+    }
+
     #[test]
     fn test_message_insert_and_query() {
         let db = Database::open_in_memory().unwrap();
@@ -4257,4 +7188,64 @@ mod tests {
             Some(0.8)
         );
     }
+
+    #[test]
+    fn test_summarize_parse_failures_groups_by_error() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        let record = |offset: u64, error: &str| FailedRecord {
+            offset,
+            line: Some(offset as i32),
+            raw: "{".to_string(),
+            error: error.to_string(),
+        };
+        db.insert_parse_failures(
+            "/a.jsonl",
+            Assistant::ClaudeCode,
+            1,
+            &[
+                record(1, "JSON parse error: EOF at line 1 column 1"),
+                record(2, "JSON parse error: EOF at line 1 column 7"),
+            ],
+        )
+        .unwrap();
+        db.insert_parse_failures(
+            "/b.jsonl",
+            Assistant::ClaudeCode,
+            1,
+            &[record(1, "JSON parse error: EOF at line 1 column 3")],
+        )
+        .unwrap();
+        db.insert_parse_failures(
+            "/c.jsonl",
+            Assistant::Codex,
+            2,
+            &[
+                record(1, "deserialization error: unknown variant"),
+                record(2, "unexpected token at line breaks"),
+            ],
+        )
+        .unwrap();
+
+        let groups = db.summarize_parse_failures(None, None).unwrap();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].assistant, Assistant::ClaudeCode);
+        assert_eq!(groups[0].error, "JSON parse error: EOF");
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].files, 2);
+
+        // Only a trailing position is stripped
+        let codex = db
+            .summarize_parse_failures(Some(Assistant::Codex), None)
+            .unwrap();
+        assert_eq!(codex.len(), 2);
+        assert_eq!(codex[0].count, 1);
+        assert_eq!(codex[0].error, "deserialization error: unknown variant");
+        assert_eq!(codex[1].error, "unexpected token at line breaks");
+        assert_eq!(
+            db.list_parse_failure_files().unwrap(),
+            vec!["/a.jsonl", "/b.jsonl", "/c.jsonl"]
+        );
+    }
 }
@@ -5,7 +5,13 @@
 use rusqlite::Connection;
 
 /// Current schema version
-pub const SCHEMA_VERSION: i32 = 8;
+pub const SCHEMA_VERSION: i32 = 26;
+
+/// First schema version that stores `raw_data` and `tool_result` compressed
+pub const COMPRESSION_VERSION: i32 = 20;
+
+/// First schema version whose full-text index is contentless
+pub const FTS_CONTENTLESS_VERSION: i32 = 26;
 
 /// SQL migrations, indexed by version number
 const MIGRATIONS: &[&str] = &[
@@ -524,6 +530,312 @@ const MIGRATIONS: &[&str] = &[
 
     CREATE INDEX IF NOT EXISTS idx_collector_publish_status ON collector_publish_state(status);
     "#,
+    // Version 9: Add prompt-cache and reasoning token counts to messages
+    r#"
+    ALTER TABLE messages ADD COLUMN tokens_cache_read INTEGER;
+    ALTER TABLE messages ADD COLUMN tokens_cache_write INTEGER;
+    ALTER TABLE messages ADD COLUMN tokens_reasoning INTEGER;
+
+    -- Backfill Claude Code messages from the usage block kept in raw_data.
+    -- Codex token counts live in separate token_count records and are only
+    -- picked up when sessions are re-ingested.
+    UPDATE messages SET
+        tokens_cache_read = json_extract(raw_data, '$.message.usage.cache_read_input_tokens'),
+        tokens_cache_write = json_extract(raw_data, '$.message.usage.cache_creation_input_tokens')
+    WHERE tokens_in IS NOT NULL
+      AND session_id IN (SELECT id FROM sessions WHERE assistant = 'claude_code');
+
+    -- Recompute first-order metrics so they include the new counts
+    DELETE FROM plugin_metrics WHERE plugin_name = 'core.first_order';
+    "#,
+    // Version 10: Record which parser version ingested each source file
+    r#"
+    ALTER TABLE source_files ADD COLUMN parser_version INTEGER NOT NULL DEFAULT 1;
+    "#,
+    // Version 11: Quarantine records that parsers could not interpret
+    r#"
+    CREATE TABLE IF NOT EXISTS parse_failures (
+        id               INTEGER PRIMARY KEY AUTOINCREMENT,
+        source_file_path TEXT NOT NULL,
+        assistant        TEXT NOT NULL,
+        byte_offset      INTEGER NOT NULL,
+        line_number      INTEGER,
+        raw_text         TEXT NOT NULL,
+        error            TEXT NOT NULL,
+        parser_version   INTEGER NOT NULL,
+        recorded_at      DATETIME NOT NULL,
+
+        UNIQUE(source_file_path, byte_offset)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_parse_failures_assistant ON parse_failures(assistant);
+    "#,
+    // Version 12: Link tool calls to their results
+    r#"
+    ALTER TABLE messages ADD COLUMN tool_call_id TEXT;
+
+    -- Parsers kept the call id in metadata before it got a column
+    UPDATE messages
+    SET tool_call_id = COALESCE(json_extract(metadata, '$.tool_use_id'),
+                                json_extract(metadata, '$.call_id'))
+    WHERE message_type IN ('tool_call', 'tool_result', 'error')
+      AND json_valid(metadata);
+
+    CREATE INDEX IF NOT EXISTS idx_messages_tool_call ON messages(session_id, tool_call_id)
+        WHERE tool_call_id IS NOT NULL;
+    "#,
+    // Version 13: Keep the record tree so branches and continued sessions can be traced
+    r#"
+    ALTER TABLE messages ADD COLUMN record_id TEXT;
+    ALTER TABLE messages ADD COLUMN parent_record_id TEXT;
+    ALTER TABLE sessions ADD COLUMN continues_session_id TEXT REFERENCES sessions(id);
+
+    -- Claude Code records carry their ids in raw_data
+    UPDATE messages SET
+        record_id = json_extract(raw_data, '$.uuid'),
+        parent_record_id = COALESCE(json_extract(raw_data, '$.parentUuid'),
+                                    json_extract(raw_data, '$.logicalParentUuid'))
+    WHERE session_id IN (SELECT id FROM sessions WHERE assistant = 'claude_code')
+      AND json_valid(raw_data);
+
+    CREATE INDEX IF NOT EXISTS idx_messages_record ON messages(record_id)
+        WHERE record_id IS NOT NULL;
+    CREATE INDEX IF NOT EXISTS idx_messages_parent_record ON messages(parent_record_id)
+        WHERE parent_record_id IS NOT NULL;
+
+    -- A session continues another when a record follows one stored under the other
+    UPDATE sessions SET continues_session_id = (
+        SELECT parent.session_id
+        FROM messages m
+        JOIN messages parent ON parent.record_id = m.parent_record_id
+        WHERE m.session_id = sessions.id
+          AND parent.session_id != sessions.id
+          AND NOT EXISTS (SELECT 1 FROM messages own
+                          WHERE own.session_id = sessions.id
+                            AND own.record_id = m.parent_record_id)
+        ORDER BY m.seq
+        LIMIT 1
+    );
+    "#,
+    // Version 14: OpenTelemetry events and metrics from the OTLP receiver.
+    // session_id is not a foreign key: telemetry often arrives before the
+    // session's logs are synced.
+    r#"
+    CREATE TABLE IF NOT EXISTS telemetry_events (
+        id               INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id       TEXT,
+        assistant        TEXT,
+        name             TEXT NOT NULL,
+        emitted_at       DATETIME NOT NULL,
+        received_at      DATETIME NOT NULL,
+        model            TEXT,
+        tool_name        TEXT,
+        decision         TEXT,
+        duration_ms      INTEGER,
+        cost_usd         REAL,
+        attributes       JSON NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_telemetry_events_session
+        ON telemetry_events(session_id, emitted_at);
+    CREATE INDEX IF NOT EXISTS idx_telemetry_events_name ON telemetry_events(name);
+
+    CREATE TABLE IF NOT EXISTS telemetry_metrics (
+        id               INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id       TEXT,
+        assistant        TEXT,
+        name             TEXT NOT NULL,
+        unit             TEXT,
+        value            REAL NOT NULL,
+        observed_at      DATETIME NOT NULL,
+        received_at      DATETIME NOT NULL,
+        attributes       JSON NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_telemetry_metrics_session
+        ON telemetry_metrics(session_id, name);
+    "#,
+    // Version 15: events pushed by assistant hooks (`aiobscura hook`).
+    // Like telemetry, hooks fire before the session's logs are synced, so
+    // session_id is not a foreign key.
+    r#"
+    CREATE TABLE IF NOT EXISTS hook_events (
+        id               INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id       TEXT NOT NULL,
+        event_name       TEXT NOT NULL,
+        tool_name        TEXT,
+        tool_use_id      TEXT,
+        message          TEXT,
+        received_at      DATETIME NOT NULL,
+        payload          JSON NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_hook_events_session
+        ON hook_events(session_id, received_at);
+    CREATE INDEX IF NOT EXISTS idx_hook_events_name ON hook_events(event_name);
+    "#,
+    // Version 16: commits correlated with sessions by the core.git plugin
+    r#"
+    CREATE TABLE IF NOT EXISTS session_commits (
+        session_id       TEXT NOT NULL REFERENCES sessions(id),
+        commit_sha       TEXT NOT NULL,
+        repo_path        TEXT NOT NULL,
+        committed_at     DATETIME NOT NULL,
+        author_name      TEXT,
+        author_email     TEXT,
+        summary          TEXT NOT NULL,
+        matched_files    JSON NOT NULL,
+        correlated_at    DATETIME NOT NULL,
+        PRIMARY KEY (session_id, commit_sha)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_session_commits_sha ON session_commits(commit_sha);
+    "#,
+    // Version 17: images moved out of raw_data into the blob store
+    r#"
+    CREATE TABLE IF NOT EXISTS message_blobs (
+        message_id       INTEGER NOT NULL REFERENCES messages(id),
+        session_id       TEXT NOT NULL REFERENCES sessions(id),
+        sha256           TEXT NOT NULL,
+        media_type       TEXT NOT NULL,
+        size             INTEGER NOT NULL,
+        PRIMARY KEY (message_id, sha256)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_message_blobs_sha ON message_blobs(sha256);
+    CREATE INDEX IF NOT EXISTS idx_message_blobs_session ON message_blobs(session_id);
+    "#,
+    // Version 18: full-text index over message bodies, kept in step by triggers
+    r#"
+    CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+        content,
+        tool_input,
+        tool_result,
+        content = 'messages',
+        content_rowid = 'id',
+        tokenize = 'porter unicode61'
+    );
+
+    CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+        INSERT INTO messages_fts(rowid, content, tool_input, tool_result)
+        VALUES (new.id, new.content, new.tool_input, new.tool_result);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+        INSERT INTO messages_fts(messages_fts, rowid, content, tool_input, tool_result)
+        VALUES ('delete', old.id, old.content, old.tool_input, old.tool_result);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS messages_fts_update
+    AFTER UPDATE OF content, tool_input, tool_result ON messages BEGIN
+        INSERT INTO messages_fts(messages_fts, rowid, content, tool_input, tool_result)
+        VALUES ('delete', old.id, old.content, old.tool_input, old.tool_result);
+        INSERT INTO messages_fts(rowid, content, tool_input, tool_result)
+        VALUES (new.id, new.content, new.tool_input, new.tool_result);
+    END;
+
+    -- Index messages stored before this version
+    INSERT INTO messages_fts(messages_fts) VALUES ('rebuild');
+    "#,
+    // Version 19: retention pruning drops raw_data and tool result bodies
+    r#"
+    ALTER TABLE messages ADD COLUMN pruned_at DATETIME;
+    ALTER TABLE source_files ADD COLUMN pruned_at DATETIME;
+    "#,
+    // Version 20: zstd-compressed raw_data and tool_result (see db/compress.rs).
+    // Rows stored before it are compressed by `Database::migrate`.
+    r#"
+    CREATE TABLE IF NOT EXISTS compression_dictionaries (
+        id               INTEGER PRIMARY KEY AUTOINCREMENT,
+        dict_id          INTEGER NOT NULL UNIQUE,   -- zstd dictionary id, recorded in each frame
+        dictionary       BLOB NOT NULL,
+        samples          INTEGER NOT NULL,
+        created_at       DATETIME NOT NULL
+    );
+    "#,
+    // Version 21: snapshots imported with `aiobscura-sync --import`, so
+    // reparsing can read their files again
+    r#"
+    CREATE TABLE IF NOT EXISTS imports (
+        root             TEXT PRIMARY KEY,
+        label            TEXT NOT NULL,
+        imported_at      DATETIME NOT NULL
+    );
+    "#,
+    // Version 22: how OTLP sums accumulate, so cumulative points are not
+    // added up. Points stored before it have no temporality.
+    r#"
+    ALTER TABLE telemetry_metrics ADD COLUMN temporality TEXT;
+    ALTER TABLE telemetry_metrics ADD COLUMN monotonic INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE telemetry_metrics ADD COLUMN started_at DATETIME;
+    "#,
+    // Version 23: projects stored before project identity resolution keyed
+    // by the hash of the raw cwd. `IngestCoordinator` re-resolves them once.
+    r#"
+    ALTER TABLE projects ADD COLUMN resolved INTEGER NOT NULL DEFAULT 1;
+    UPDATE projects SET resolved = 0;
+    "#,
+    // Version 24: sessions deleted to be ingested again, so incremental
+    // dataset exports can tell readers their earlier rows are out of date
+    r#"
+    CREATE TABLE IF NOT EXISTS reparsed_sessions (
+        id               INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id       TEXT NOT NULL,
+        reparsed_at      DATETIME NOT NULL
+    );
+    "#,
+    // Version 25: the custom agent or parser plugin that reads each source
+    // file, taken from the sessions stored before it
+    r#"
+    ALTER TABLE source_files ADD COLUMN agent TEXT;
+    UPDATE source_files SET agent = (
+        SELECT json_extract(s.metadata, '$.agent') FROM sessions s
+        WHERE s.source_file_path = source_files.path
+          AND json_extract(s.metadata, '$.agent') IS NOT NULL
+        LIMIT 1
+    )
+    WHERE assistant = 'custom';
+    "#,
+    // Version 26: the full-text index becomes contentless, so it adds no
+    // second copy of the text; Database indexes each message as it inserts
+    // it (its tool result may be stored compressed), and search excerpts are
+    // built from the messages. Deletes and edits of content or tool_input
+    // are kept in step by triggers that need no aiobscura SQL functions, so
+    // they also work in the sqlite3 shell; an edit there drops a compressed
+    // tool result from the index. `Database::migrate` indexes the compressed
+    // tool results stored before this version.
+    r#"
+    DROP TRIGGER IF EXISTS messages_fts_insert;
+    DROP TRIGGER IF EXISTS messages_fts_delete;
+    DROP TRIGGER IF EXISTS messages_fts_update;
+    DROP TABLE IF EXISTS messages_fts;
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+        content,
+        tool_input,
+        tool_result,
+        content = '',
+        contentless_delete = 1,
+        tokenize = 'porter unicode61'
+    );
+
+    CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+        DELETE FROM messages_fts WHERE rowid = old.id;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS messages_fts_update
+    AFTER UPDATE OF content, tool_input ON messages BEGIN
+        DELETE FROM messages_fts WHERE rowid = old.id;
+        INSERT INTO messages_fts(rowid, content, tool_input, tool_result)
+        VALUES (new.id, new.content, new.tool_input,
+                CASE WHEN typeof(new.tool_result) = 'text' THEN new.tool_result END);
+    END;
+
+    INSERT INTO messages_fts(rowid, content, tool_input, tool_result)
+    SELECT id, content, tool_input,
+           CASE WHEN typeof(tool_result) = 'text' THEN tool_result END
+    FROM messages;
+    "#,
 ];
 
 /// Run all pending migrations
@@ -603,6 +915,15 @@ mod tests {
             "session_plans",
             "plan_versions",
             "collector_publish_state",
+            "parse_failures",
+            "telemetry_events",
+            "telemetry_metrics",
+            "hook_events",
+            "session_commits",
+            "message_blobs",
+            "messages_fts",
+            "compression_dictionaries",
+            "imports",
         ];
 
         for table in tables {
@@ -617,6 +938,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tool_call_id_backfilled_from_metadata() {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..11] {
+            conn.execute_batch(migration).unwrap();
+        }
+        conn.execute("PRAGMA user_version = 11", []).unwrap();
+        // Rows without their session, thread and source file are enough here
+        conn.execute("PRAGMA foreign_keys = OFF", []).unwrap();
+        for (message_type, metadata) in [
+            ("tool_call", r#"{"tool_use_id":"toolu_1"}"#),
+            ("tool_result", r#"{"call_id":"call_1"}"#),
+            ("response", r#"{"call_id":"call_2"}"#),
+        ] {
+            conn.execute(
+                r#"
+                INSERT INTO messages (session_id, thread_id, seq, emitted_at, observed_at,
+                                      author_role, message_type, source_file_path,
+                                      source_offset, raw_data, metadata)
+                VALUES ('s', 't', 1, '', '', 'assistant', ?1, 'f', 0, '{}', ?2)
+                "#,
+                [message_type, metadata],
+            )
+            .unwrap();
+        }
+
+        run_migrations(&conn).unwrap();
+
+        let ids: Vec<Option<String>> = conn
+            .prepare("SELECT tool_call_id FROM messages ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            ids,
+            vec![
+                Some("toolu_1".to_string()),
+                Some("call_1".to_string()),
+                None
+            ]
+        );
+    }
+
     #[test]
     fn test_foreign_keys() {
         let conn = Connection::open_in_memory().unwrap();
@@ -33,6 +33,10 @@ pub enum Error {
     #[error("session not found: {0}")]
     SessionNotFound(String),
 
+    /// Project not found
+    #[error("project not found: {0}")]
+    ProjectNotFound(String),
+
     /// Plan not found
     #[error("plan not found: {0}")]
     PlanNotFound(String),
@@ -40,6 +44,26 @@ pub enum Error {
     /// Collector/API error
     #[error("collector error: {0}")]
     Collector(String),
+
+    /// OTLP telemetry error
+    #[error("OTLP error: {0}")]
+    Otlp(String),
+
+    /// Hook event error
+    #[error("hook error: {0}")]
+    Hook(String),
+
+    /// Offline import error
+    #[error("import error: {0}")]
+    Import(String),
+
+    /// Dataset export error
+    #[error("export error: {0}")]
+    Export(String),
+
+    /// Reparse error
+    #[error("reparse error: {0}")]
+    Reparse(String),
 }
 
 /// Result type alias for aiobscura-core
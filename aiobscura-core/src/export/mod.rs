@@ -0,0 +1,603 @@
+//! Dataset export for analysis outside aiobscura
+//!
+//! The database schema is internal and changes through migrations. [`export`]
+//! writes the normalized tables instead, in a schema of their own that is
+//! versioned by [`SCHEMA_VERSION`] and documented in `docs/export.md`.
+//!
+//! ## Layout
+//!
+//! ```text
+//! <dir>/
+//! ├── manifest.json              # schema version, filters, watermark, parts
+//! ├── messages/
+//! │   ├── part-00001.parquet
+//! │   └── part-00002.parquet     # appended by an incremental export
+//! └── projects/, sessions/, threads/, plugin_metrics/, assessments/
+//! ```
+//!
+//! Each table is a directory of part files, which Parquet readers load as
+//! one dataset. An incremental export adds a part with the rows newer than
+//! the manifest's [`ExportWatermark`]: new messages and assessments, plugin
+//! metrics computed since, and the projects, sessions and threads those new
+//! messages belong to. Those entities are appended again with their current
+//! values, so readers keep the row with the latest `last_activity_at` per id.
+//!
+//! A reparse stores a session's messages again under new ids. The part
+//! lists such sessions in [`ExportPart::reparsed_sessions`], so readers can
+//! drop the rows earlier parts hold for them.
+
+mod tables;
+mod writer;
+
+pub use tables::{Column, ColumnType, Table, TABLES};
+
+use crate::db::{Database, ExportFilter, ExportWatermark};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tables::Cell;
+use writer::TableWriter;
+
+/// Version of the exported schema. Bumped whenever a table or column changes
+/// in a way readers would notice.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// File describing an export directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Messages read and written per batch.
+const MESSAGE_BATCH_SIZE: usize = 10_000;
+
+/// File format of an export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Parquet,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    /// File extension of the part files.
+    pub fn extension(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "parquet" => Ok(ExportFormat::Parquet),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!("unknown export format: {} (parquet, csv)", s)),
+        }
+    }
+}
+
+/// What to export, and how.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Only sessions of this project
+    pub project_id: Option<String>,
+    /// Only sessions active, and messages emitted, at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Include message text, tool inputs and results, and assessor responses
+    pub content: bool,
+    /// Append to an existing export instead of starting a new one
+    pub incremental: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            project_id: None,
+            since: None,
+            content: true,
+            incremental: false,
+        }
+    }
+}
+
+/// Contents of `manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub schema_version: u32,
+    pub format: ExportFormat,
+    pub project_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub content: bool,
+    /// Where the next incremental export starts
+    pub watermark: ExportWatermark,
+    /// Every export written to the directory, oldest first
+    pub parts: Vec<ExportPart>,
+    /// Columns of each table, for readers that check the schema (rewritten
+    /// on every export)
+    #[serde(default, skip_deserializing)]
+    pub tables: BTreeMap<String, Vec<Column>>,
+}
+
+impl Manifest {
+    /// Read the manifest of an export directory, if it holds one.
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+            Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        // Written last and replaced atomically: a failed export leaves the
+        // previous manifest, and its part is overwritten by the next run
+        let tmp = dir.join(format!("{MANIFEST_FILE}.tmp"));
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, dir.join(MANIFEST_FILE))?;
+        Ok(())
+    }
+}
+
+/// One export run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportPart {
+    /// Part number, also in the part files' names
+    pub part: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Rows written per table
+    pub rows: BTreeMap<String, u64>,
+    /// Sessions reparsed since the previous part, whose rows in earlier
+    /// parts are out of date
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reparsed_sessions: Vec<String>,
+}
+
+/// Write the dataset to `dir`, or with `options.incremental`, append what
+/// is new since the export already there. Returns the part written.
+pub fn export(db: &Database, dir: &Path, options: &ExportOptions) -> Result<ExportPart> {
+    let previous = Manifest::load(dir)?;
+    if let Some(manifest) = &previous {
+        check_compatible(manifest, options)?;
+    }
+
+    let upto = db.export_watermark()?;
+    let filter = ExportFilter {
+        project_id: options.project_id.clone(),
+        since: options.since,
+        after: previous.as_ref().map(|m| m.watermark.clone()),
+        upto: upto.clone(),
+    };
+    let part_number = previous
+        .as_ref()
+        .and_then(|m| m.parts.last())
+        .map_or(1, |part| part.part + 1);
+
+    let mut part = PartWriter {
+        dir,
+        format: options.format,
+        part: part_number,
+        rows: BTreeMap::new(),
+    };
+
+    let projects = db.export_projects(&filter)?;
+    part.write_table(tables::PROJECTS, projects.iter().map(tables::project_row))?;
+    let sessions = db.export_sessions(&filter)?;
+    part.write_table(tables::SESSIONS, sessions.iter().map(tables::session_row))?;
+    let threads = db.export_threads(&filter)?;
+    part.write_table(tables::THREADS, threads.iter().map(tables::thread_row))?;
+    part.write_messages(db, &filter, options.content)?;
+    let metrics = db.export_plugin_metrics(&filter)?;
+    part.write_table(
+        tables::PLUGIN_METRICS,
+        metrics.iter().map(tables::plugin_metric_row),
+    )?;
+    let assessments = db.export_assessments(&filter)?;
+    part.write_table(
+        tables::ASSESSMENTS,
+        assessments
+            .iter()
+            .map(|assessment| tables::assessment_row(assessment, options.content)),
+    )?;
+
+    let exported = ExportPart {
+        part: part_number,
+        exported_at: Utc::now(),
+        rows: part.rows,
+        reparsed_sessions: db.export_reparsed_sessions(&filter)?,
+    };
+    let mut parts = previous.map(|m| m.parts).unwrap_or_default();
+    parts.push(exported.clone());
+    Manifest {
+        schema_version: SCHEMA_VERSION,
+        format: options.format,
+        project_id: options.project_id.clone(),
+        since: options.since,
+        content: options.content,
+        watermark: upto,
+        parts,
+        tables: TABLES
+            .iter()
+            .map(|table| (table.name.to_string(), table.columns.to_vec()))
+            .collect(),
+    }
+    .save(dir)?;
+
+    tracing::info!(
+        dir = %dir.display(),
+        part = exported.part,
+        rows = ?exported.rows,
+        "Exported dataset"
+    );
+    Ok(exported)
+}
+
+/// An incremental export must continue the existing one as it was started;
+/// anything else needs a fresh directory.
+fn check_compatible(manifest: &Manifest, options: &ExportOptions) -> Result<()> {
+    if !options.incremental {
+        return Err(Error::Export(
+            "directory already holds an export; append to it incrementally or use a new directory"
+                .to_string(),
+        ));
+    }
+    let mismatch = if manifest.schema_version != SCHEMA_VERSION {
+        Some(format!(
+            "schema version {} (now {})",
+            manifest.schema_version, SCHEMA_VERSION
+        ))
+    } else if manifest.format != options.format {
+        Some(format!("format {}", manifest.format))
+    } else if manifest.project_id != options.project_id {
+        Some(format!(
+            "project filter {}",
+            manifest.project_id.as_deref().unwrap_or("(none)")
+        ))
+    } else if manifest.since != options.since {
+        Some(format!(
+            "since filter {}",
+            manifest
+                .since
+                .map_or("(none)".to_string(), |since| since.to_rfc3339())
+        ))
+    } else if manifest.content != options.content {
+        Some(if manifest.content {
+            "message content".to_string()
+        } else {
+            "no message content".to_string()
+        })
+    } else {
+        None
+    };
+    match mismatch {
+        Some(mismatch) => Err(Error::Export(format!(
+            "existing export was written with {mismatch}; start a new directory to change it"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Files of one export part.
+struct PartWriter<'a> {
+    dir: &'a Path,
+    format: ExportFormat,
+    part: u32,
+    rows: BTreeMap<String, u64>,
+}
+
+impl PartWriter<'_> {
+    fn path(&self, table: &Table) -> Result<PathBuf> {
+        let dir = self.dir.join(table.name);
+        fs::create_dir_all(&dir)?;
+        Ok(dir.join(format!("part-{:05}.{}", self.part, self.format.extension())))
+    }
+
+    /// Open the table's file, unless there is nothing to write: every table
+    /// gets a file in the first part, later parts only where rows were added.
+    fn open(&self, table: Table, rows: usize) -> Result<Option<TableWriter>> {
+        if rows == 0 && self.part > 1 {
+            return Ok(None);
+        }
+        TableWriter::create(&self.path(&table)?, self.format, table).map(Some)
+    }
+
+    fn write_table(&mut self, table: Table, rows: impl Iterator<Item = Vec<Cell>>) -> Result<()> {
+        let rows: Vec<Vec<Cell>> = rows.collect();
+        if let Some(mut writer) = self.open(table, rows.len())? {
+            writer.write(&rows)?;
+            writer.finish()?;
+        }
+        self.rows.insert(table.name.to_string(), rows.len() as u64);
+        Ok(())
+    }
+
+    /// Messages are streamed in batches rather than loaded at once.
+    fn write_messages(
+        &mut self,
+        db: &Database,
+        filter: &ExportFilter,
+        content: bool,
+    ) -> Result<()> {
+        let table = tables::MESSAGES;
+        let mut writer: Option<TableWriter> = None;
+        let mut written = 0u64;
+        let mut after_id = 0;
+        loop {
+            let messages = db.export_messages(filter, after_id, MESSAGE_BATCH_SIZE)?;
+            let Some(last) = messages.last() else {
+                break;
+            };
+            after_id = last.id;
+
+            if writer.is_none() {
+                writer = self.open(table, messages.len())?;
+            }
+            let rows: Vec<Vec<Cell>> = messages
+                .iter()
+                .map(|message| tables::message_row(message, content))
+                .collect();
+            if let Some(writer) = writer.as_mut() {
+                writer.write(&rows)?;
+            }
+            written += rows.len() as u64;
+
+            if messages.len() < MESSAGE_BATCH_SIZE {
+                break;
+            }
+        }
+
+        match writer {
+            Some(writer) => writer.finish()?,
+            None => {
+                if let Some(writer) = self.open(table, 0)? {
+                    writer.finish()?;
+                }
+            }
+        }
+        self.rows.insert(table.name.to_string(), written);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        AuthorRole, Checkpoint, Message, MessageType, Session, SessionStatus, SourceFile, Thread,
+        ThreadType,
+    };
+    use crate::{Assistant, FileType};
+    use tempfile::TempDir;
+
+    fn setup() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&SourceFile {
+            path: "/tmp/session.jsonl".into(),
+            file_type: FileType::Jsonl,
+            assistant: Assistant::ClaudeCode,
+            agent: None,
+            created_at: Utc::now(),
+            modified_at: Utc::now(),
+            size_bytes: 0,
+            last_parsed_at: None,
+            checkpoint: Checkpoint::None,
+            parser_version: 1,
+        })
+        .unwrap();
+        store_session(&db);
+        db
+    }
+
+    fn store_session(db: &Database) {
+        db.upsert_session(&Session {
+            id: "s1".to_string(),
+            assistant: Assistant::ClaudeCode,
+            backing_model_id: None,
+            project_id: None,
+            started_at: Utc::now(),
+            last_activity_at: Some(Utc::now()),
+            status: SessionStatus::Active,
+            source_file_path: "/tmp/session.jsonl".to_string(),
+            metadata: serde_json::json!({}),
+        })
+        .unwrap();
+        db.insert_thread(&Thread {
+            id: "t1".to_string(),
+            session_id: "s1".to_string(),
+            thread_type: ThreadType::Main,
+            parent_thread_id: None,
+            spawned_by_message_id: None,
+            started_at: Utc::now(),
+            ended_at: None,
+            last_activity_at: None,
+            metadata: serde_json::json!({}),
+        })
+        .unwrap();
+    }
+
+    fn message(seq: i32, content: &str) -> Message {
+        Message {
+            id: 0,
+            session_id: "s1".to_string(),
+            thread_id: "t1".to_string(),
+            seq,
+            emitted_at: Utc::now(),
+            observed_at: Utc::now(),
+            author_role: AuthorRole::Human,
+            author_name: None,
+            message_type: MessageType::Prompt,
+            content: Some(content.to_string()),
+            content_type: None,
+            tool_name: None,
+            tool_input: None,
+            tool_result: None,
+            tool_call_id: None,
+            tokens_in: Some(10),
+            tokens_out: None,
+            tokens_cache_read: None,
+            tokens_cache_write: None,
+            tokens_reasoning: None,
+            duration_ms: None,
+            source_file_path: "/tmp/session.jsonl".to_string(),
+            source_offset: i64::from(seq),
+            source_line: None,
+            record_id: None,
+            parent_record_id: None,
+            raw_data: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    fn read_csv(path: &Path) -> Vec<Vec<String>> {
+        csv::Reader::from_path(path)
+            .unwrap()
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_csv_export() {
+        let db = setup();
+        db.insert_messages(&[message(1, "first"), message(2, "second")])
+            .unwrap();
+        let dir = TempDir::new().unwrap();
+        let options = ExportOptions {
+            format: ExportFormat::Csv,
+            incremental: true,
+            ..Default::default()
+        };
+
+        let first = export(&db, dir.path(), &options).unwrap();
+        assert_eq!(first.part, 1);
+        assert_eq!(first.rows["messages"], 2);
+        assert_eq!(first.rows["sessions"], 1);
+        let rows = read_csv(&dir.path().join("messages/part-00001.csv"));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][9], "first");
+        for table in TABLES {
+            assert!(dir.path().join(table.name).join("part-00001.csv").is_file());
+        }
+
+        // Nothing new: an empty part with no files
+        let second = export(&db, dir.path(), &options).unwrap();
+        assert_eq!(second.part, 2);
+        assert!(second.rows.values().all(|&rows| rows == 0));
+        assert!(!dir.path().join("messages/part-00002.csv").exists());
+
+        db.insert_message(&message(3, "third")).unwrap();
+        let third = export(&db, dir.path(), &options).unwrap();
+        assert_eq!(third.rows["messages"], 1);
+        assert_eq!(third.rows["sessions"], 1);
+        assert_eq!(third.rows["threads"], 1);
+        let rows = read_csv(&dir.path().join("messages/part-00003.csv"));
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][9], "third");
+
+        let manifest = Manifest::load(dir.path()).unwrap().unwrap();
+        assert_eq!(manifest.schema_version, SCHEMA_VERSION);
+        assert_eq!(manifest.parts.len(), 3);
+        assert_eq!(manifest.watermark, db.export_watermark().unwrap());
+    }
+
+    #[test]
+    fn test_incremental_export_lists_reparsed_sessions() {
+        let db = setup();
+        db.insert_messages(&[message(1, "first")]).unwrap();
+        let dir = TempDir::new().unwrap();
+        let options = ExportOptions {
+            format: ExportFormat::Csv,
+            incremental: true,
+            ..Default::default()
+        };
+        let first = export(&db, dir.path(), &options).unwrap();
+        assert!(first.reparsed_sessions.is_empty());
+
+        // Rebuilt under new ids, as by a reparse
+        db.delete_ingested_data(&["s1".to_string()], &[]).unwrap();
+        store_session(&db);
+        db.insert_messages(&[message(1, "first")]).unwrap();
+
+        let second = export(&db, dir.path(), &options).unwrap();
+        assert_eq!(second.rows["messages"], 1);
+        assert_eq!(second.reparsed_sessions, vec!["s1".to_string()]);
+        let manifest = Manifest::load(dir.path()).unwrap().unwrap();
+        assert_eq!(manifest.parts[1].reparsed_sessions, vec!["s1".to_string()]);
+
+        // Listed once
+        let third = export(&db, dir.path(), &options).unwrap();
+        assert!(third.reparsed_sessions.is_empty());
+    }
+
+    #[test]
+    fn test_export_requires_matching_options() {
+        let db = setup();
+        let dir = TempDir::new().unwrap();
+        export(&db, dir.path(), &ExportOptions::default()).unwrap();
+
+        let err = export(&db, dir.path(), &ExportOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("already holds an export"));
+
+        let options = ExportOptions {
+            content: false,
+            incremental: true,
+            ..Default::default()
+        };
+        let err = export(&db, dir.path(), &options).unwrap_err();
+        assert!(err.to_string().contains("message content"), "{err}");
+    }
+
+    #[test]
+    fn test_parquet_export_without_content() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let db = setup();
+        db.insert_messages(&[message(1, "secret")]).unwrap();
+        let dir = TempDir::new().unwrap();
+        let options = ExportOptions {
+            content: false,
+            ..Default::default()
+        };
+        export(&db, dir.path(), &options).unwrap();
+
+        let file = fs::File::open(dir.path().join("messages/part-00001.parquet")).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 1);
+        assert_eq!(
+            metadata.schema_descr().num_columns(),
+            tables::MESSAGES.columns.len()
+        );
+        let version = metadata
+            .key_value_metadata()
+            .and_then(|kv| kv.iter().find(|kv| kv.key == "aiobscura.schema_version"))
+            .and_then(|kv| kv.value.clone());
+        assert_eq!(version.as_deref(), Some("1"));
+
+        let row = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        let content = row
+            .get_column_iter()
+            .find(|(name, _)| name.as_str() == "content")
+            .map(|(_, field)| field.clone())
+            .unwrap();
+        assert_eq!(content, parquet::record::Field::Null);
+    }
+}
@@ -0,0 +1,301 @@
+//! Exported tables and their columns
+//!
+//! This is the export's public schema. Changing a column here changes what
+//! notebooks read: bump [`SCHEMA_VERSION`](super::SCHEMA_VERSION) and update
+//! `docs/export.md` along with it.
+
+use crate::types::{Assessment, Message, PluginMetric, Project, Session, Thread};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use ColumnType::{Int64, Json, String as Text, Timestamp};
+
+/// Type of an exported column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    /// UTF-8 text
+    String,
+    /// JSON document, stored as text
+    Json,
+    /// 64-bit signed integer
+    Int64,
+    /// Instant in UTC, microsecond precision
+    Timestamp,
+}
+
+/// One exported column. Every column is nullable.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Column {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+}
+
+/// One exported table.
+#[derive(Debug, Clone, Copy)]
+pub struct Table {
+    pub name: &'static str,
+    pub columns: &'static [Column],
+}
+
+/// A value in an exported row.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cell {
+    Null,
+    String(String),
+    Int64(i64),
+    Timestamp(DateTime<Utc>),
+}
+
+impl From<Option<String>> for Cell {
+    fn from(value: Option<String>) -> Self {
+        value.map_or(Cell::Null, Cell::String)
+    }
+}
+
+impl From<Option<i64>> for Cell {
+    fn from(value: Option<i64>) -> Self {
+        value.map_or(Cell::Null, Cell::Int64)
+    }
+}
+
+impl From<Option<i32>> for Cell {
+    fn from(value: Option<i32>) -> Self {
+        value.map_or(Cell::Null, |v| Cell::Int64(i64::from(v)))
+    }
+}
+
+impl From<Option<DateTime<Utc>>> for Cell {
+    fn from(value: Option<DateTime<Utc>>) -> Self {
+        value.map_or(Cell::Null, Cell::Timestamp)
+    }
+}
+
+const fn column(name: &'static str, column_type: ColumnType) -> Column {
+    Column { name, column_type }
+}
+
+pub const PROJECTS: Table = Table {
+    name: "projects",
+    columns: &[
+        column("id", Text),
+        column("path", Text),
+        column("name", Text),
+        column("created_at", Timestamp),
+        column("last_activity_at", Timestamp),
+        column("metadata", Json),
+    ],
+};
+
+pub const SESSIONS: Table = Table {
+    name: "sessions",
+    columns: &[
+        column("id", Text),
+        column("assistant", Text),
+        column("backing_model_id", Text),
+        column("project_id", Text),
+        column("started_at", Timestamp),
+        column("last_activity_at", Timestamp),
+        column("status", Text),
+        column("metadata", Json),
+    ],
+};
+
+pub const THREADS: Table = Table {
+    name: "threads",
+    columns: &[
+        column("id", Text),
+        column("session_id", Text),
+        column("thread_type", Text),
+        column("parent_thread_id", Text),
+        column("spawned_by_message_id", Int64),
+        column("started_at", Timestamp),
+        column("ended_at", Timestamp),
+        column("last_activity_at", Timestamp),
+        column("metadata", Json),
+    ],
+};
+
+pub const MESSAGES: Table = Table {
+    name: "messages",
+    columns: &[
+        column("id", Int64),
+        column("session_id", Text),
+        column("thread_id", Text),
+        column("seq", Int64),
+        column("emitted_at", Timestamp),
+        column("observed_at", Timestamp),
+        column("author_role", Text),
+        column("author_name", Text),
+        column("message_type", Text),
+        column("content", Text),
+        column("content_type", Text),
+        column("tool_name", Text),
+        column("tool_call_id", Text),
+        column("tool_input", Json),
+        column("tool_result", Text),
+        column("tokens_in", Int64),
+        column("tokens_out", Int64),
+        column("tokens_cache_read", Int64),
+        column("tokens_cache_write", Int64),
+        column("tokens_reasoning", Int64),
+        column("duration_ms", Int64),
+        column("record_id", Text),
+        column("parent_record_id", Text),
+    ],
+};
+
+pub const PLUGIN_METRICS: Table = Table {
+    name: "plugin_metrics",
+    columns: &[
+        column("id", Int64),
+        column("plugin_name", Text),
+        column("entity_type", Text),
+        column("entity_id", Text),
+        column("metric_name", Text),
+        column("metric_value", Json),
+        column("computed_at", Timestamp),
+    ],
+};
+
+pub const ASSESSMENTS: Table = Table {
+    name: "assessments",
+    columns: &[
+        column("id", Int64),
+        column("session_id", Text),
+        column("assessor", Text),
+        column("model", Text),
+        column("assessed_at", Timestamp),
+        column("scores", Json),
+        column("raw_response", Text),
+        column("prompt_hash", Text),
+    ],
+};
+
+/// Every exported table, in export order.
+pub const TABLES: &[Table] = &[
+    PROJECTS,
+    SESSIONS,
+    THREADS,
+    MESSAGES,
+    PLUGIN_METRICS,
+    ASSESSMENTS,
+];
+
+fn text(value: &str) -> Cell {
+    Cell::String(value.to_string())
+}
+
+fn json(value: &serde_json::Value) -> Cell {
+    Cell::String(value.to_string())
+}
+
+pub(crate) fn project_row(project: &Project) -> Vec<Cell> {
+    vec![
+        text(&project.id),
+        text(&project.path.to_string_lossy()),
+        project.name.clone().into(),
+        Cell::Timestamp(project.created_at),
+        project.last_activity_at.into(),
+        json(&project.metadata),
+    ]
+}
+
+pub(crate) fn session_row(session: &Session) -> Vec<Cell> {
+    vec![
+        text(&session.id),
+        text(session.assistant.as_str()),
+        session.backing_model_id.clone().into(),
+        session.project_id.clone().into(),
+        Cell::Timestamp(session.started_at),
+        session.last_activity_at.into(),
+        text(session.status.as_str()),
+        json(&session.metadata),
+    ]
+}
+
+pub(crate) fn thread_row(thread: &Thread) -> Vec<Cell> {
+    vec![
+        text(&thread.id),
+        text(&thread.session_id),
+        text(thread.thread_type.as_str()),
+        thread.parent_thread_id.clone().into(),
+        thread.spawned_by_message_id.into(),
+        Cell::Timestamp(thread.started_at),
+        thread.ended_at.into(),
+        thread.last_activity_at.into(),
+        json(&thread.metadata),
+    ]
+}
+
+/// Without `content`, message text, tool inputs and tool results are null.
+pub(crate) fn message_row(message: &Message, content: bool) -> Vec<Cell> {
+    let (body, tool_input, tool_result) = if content {
+        (
+            message.content.clone().into(),
+            message.tool_input.as_ref().map_or(Cell::Null, json),
+            message.tool_result.clone().into(),
+        )
+    } else {
+        (Cell::Null, Cell::Null, Cell::Null)
+    };
+    vec![
+        Cell::Int64(message.id),
+        text(&message.session_id),
+        text(&message.thread_id),
+        Cell::Int64(i64::from(message.seq)),
+        Cell::Timestamp(message.emitted_at),
+        Cell::Timestamp(message.observed_at),
+        text(message.author_role.as_str()),
+        message.author_name.clone().into(),
+        text(message.message_type.as_str()),
+        body,
+        message
+            .content_type
+            .as_ref()
+            .map(|content_type| content_type.to_string())
+            .into(),
+        message.tool_name.clone().into(),
+        message.tool_call_id.clone().into(),
+        tool_input,
+        tool_result,
+        message.tokens_in.into(),
+        message.tokens_out.into(),
+        message.tokens_cache_read.into(),
+        message.tokens_cache_write.into(),
+        message.tokens_reasoning.into(),
+        message.duration_ms.into(),
+        message.record_id.clone().into(),
+        message.parent_record_id.clone().into(),
+    ]
+}
+
+pub(crate) fn plugin_metric_row(metric: &PluginMetric) -> Vec<Cell> {
+    vec![
+        Cell::Int64(metric.id),
+        text(&metric.plugin_name),
+        text(&metric.entity_type),
+        metric.entity_id.clone().into(),
+        text(&metric.metric_name),
+        json(&metric.metric_value),
+        Cell::Timestamp(metric.computed_at),
+    ]
+}
+
+/// Without `content`, the assessor's raw response is null.
+pub(crate) fn assessment_row(assessment: &Assessment, content: bool) -> Vec<Cell> {
+    vec![
+        Cell::Int64(assessment.id),
+        text(&assessment.session_id),
+        text(&assessment.assessor),
+        assessment.model.clone().into(),
+        Cell::Timestamp(assessment.assessed_at),
+        json(&assessment.scores),
+        if content {
+            assessment.raw_response.clone().into()
+        } else {
+            Cell::Null
+        },
+        assessment.prompt_hash.clone().into(),
+    ]
+}
@@ -0,0 +1,170 @@
+//! Parquet and CSV files for one exported table
+
+use super::tables::{Cell, ColumnType, Table};
+use super::{ExportFormat, SCHEMA_VERSION};
+use crate::error::{Error, Result};
+use arrow_array::builder::{Int64Builder, StringBuilder, TimestampMicrosecondBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::SecondsFormat;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Parquet metadata key carrying the export schema version.
+const SCHEMA_VERSION_KEY: &str = "aiobscura.schema_version";
+
+/// Writes the rows of one table to a file.
+pub(crate) enum TableWriter {
+    Parquet {
+        writer: ArrowWriter<File>,
+        schema: SchemaRef,
+        table: Table,
+    },
+    Csv(csv::Writer<File>),
+}
+
+impl TableWriter {
+    pub(crate) fn create(path: &Path, format: ExportFormat, table: Table) -> Result<Self> {
+        let file = File::create(path)?;
+        match format {
+            ExportFormat::Parquet => {
+                let schema = arrow_schema(table);
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::ZSTD(ZstdLevel::default()))
+                    .set_key_value_metadata(Some(vec![KeyValue::new(
+                        SCHEMA_VERSION_KEY.to_string(),
+                        SCHEMA_VERSION.to_string(),
+                    )]))
+                    .build();
+                let writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))
+                    .map_err(parquet_error)?;
+                Ok(Self::Parquet {
+                    writer,
+                    schema,
+                    table,
+                })
+            }
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                writer
+                    .write_record(table.columns.iter().map(|column| column.name))
+                    .map_err(csv_error)?;
+                Ok(Self::Csv(writer))
+            }
+        }
+    }
+
+    /// Append rows, each with one cell per column.
+    pub(crate) fn write(&mut self, rows: &[Vec<Cell>]) -> Result<()> {
+        match self {
+            Self::Parquet {
+                writer,
+                schema,
+                table,
+            } => {
+                let columns = table
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, column)| arrow_column(column.column_type, rows, idx))
+                    .collect();
+                let batch = RecordBatch::try_new(schema.clone(), columns)
+                    .map_err(|e| Error::Export(e.to_string()))?;
+                writer.write(&batch).map_err(parquet_error)
+            }
+            Self::Csv(writer) => {
+                for row in rows {
+                    writer
+                        .write_record(row.iter().map(csv_field))
+                        .map_err(csv_error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Flush and close the file.
+    pub(crate) fn finish(self) -> Result<()> {
+        match self {
+            Self::Parquet { writer, .. } => writer.close().map(|_| ()).map_err(parquet_error),
+            Self::Csv(mut writer) => Ok(writer.flush()?),
+        }
+    }
+}
+
+fn arrow_schema(table: Table) -> SchemaRef {
+    let fields: Vec<Field> = table
+        .columns
+        .iter()
+        .map(|column| {
+            let data_type = match column.column_type {
+                ColumnType::String | ColumnType::Json => DataType::Utf8,
+                ColumnType::Int64 => DataType::Int64,
+                ColumnType::Timestamp => {
+                    DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+                }
+            };
+            Field::new(column.name, data_type, true)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+fn arrow_column(column_type: ColumnType, rows: &[Vec<Cell>], idx: usize) -> ArrayRef {
+    let cells = rows.iter().map(|row| &row[idx]);
+    match column_type {
+        ColumnType::String | ColumnType::Json => {
+            let mut builder = StringBuilder::new();
+            for cell in cells {
+                match cell {
+                    Cell::String(value) => builder.append_value(value),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnType::Int64 => {
+            let mut builder = Int64Builder::new();
+            for cell in cells {
+                match cell {
+                    Cell::Int64(value) => builder.append_value(*value),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnType::Timestamp => {
+            let mut builder = TimestampMicrosecondBuilder::new().with_timezone("UTC");
+            for cell in cells {
+                match cell {
+                    Cell::Timestamp(value) => builder.append_value(value.timestamp_micros()),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+    }
+}
+
+/// CSV text of a cell: nulls are empty, timestamps RFC 3339 in UTC.
+fn csv_field(cell: &Cell) -> String {
+    match cell {
+        Cell::Null => String::new(),
+        Cell::String(value) => value.clone(),
+        Cell::Int64(value) => value.to_string(),
+        Cell::Timestamp(value) => value.to_rfc3339_opts(SecondsFormat::Micros, true),
+    }
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> Error {
+    Error::Export(format!("failed to write Parquet: {e}"))
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    Error::Export(format!("failed to write CSV: {e}"))
+}
@@ -0,0 +1,144 @@
+//! Unix socket listener for hook events
+
+use super::{spool, Envelope};
+use crate::db::Database;
+use crate::error::{Error, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How long a connection may take to send its event. Hooks block the
+/// assistant, so neither side waits long.
+const IO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A running hook socket listener.
+///
+/// Events are stored on a background thread as they arrive. Dropping the
+/// listener stops it and removes the socket file.
+pub struct HookListener {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HookListener {
+    /// Listen on the Unix socket at `path`.
+    ///
+    /// A socket file left behind by a process that exited without cleaning
+    /// up is replaced; one another process is still listening on is an error.
+    /// Events that can't be stored are appended to the spool file at
+    /// `spool_path`, so the next [`drain_spool`](super::drain_spool) retries
+    /// them.
+    pub fn start(path: &Path, spool_path: &Path, db: Database) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(Error::Hook(format!(
+                    "another process is listening on {}",
+                    path.display()
+                )));
+            }
+            std::fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let spool_path = spool_path.to_path_buf();
+        let handle = std::thread::Builder::new()
+            .name("hook-listener".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => handle_connection(&db, &spool_path, stream),
+                        Err(e) => tracing::warn!(error = %e, "Hook socket accept failed"),
+                    }
+                    if thread_stop.load(Ordering::SeqCst) {
+                        // Store events from connections queued before the stop
+                        if listener.set_nonblocking(true).is_ok() {
+                            while let Ok((stream, _)) = listener.accept() {
+                                let _ = stream.set_nonblocking(false);
+                                handle_connection(&db, &spool_path, stream);
+                            }
+                        }
+                        break;
+                    }
+                }
+            })?;
+
+        tracing::info!(path = %path.display(), "Hook listener started");
+        Ok(Self {
+            path: path.to_path_buf(),
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// Path of the socket being listened on.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for HookListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop with an empty connection so it sees the flag
+        let _ = UnixStream::connect(&self.path);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn handle_connection(db: &Database, spool_path: &Path, stream: UnixStream) {
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let mut events = Vec::new();
+    // Lines of the readable events, spooled if storing them fails
+    let mut lines = String::new();
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::debug!(error = %e, "Hook connection read failed");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Envelope>(&line)
+            .map_err(Error::from)
+            .and_then(Envelope::into_event)
+        {
+            Ok(event) => {
+                events.push(event);
+                lines.push_str(&line);
+                lines.push('\n');
+            }
+            Err(e) => tracing::warn!(error = %e, "Dropping unreadable hook event"),
+        }
+    }
+
+    if let Err(e) = db.insert_hook_events(&events) {
+        tracing::warn!(error = %e, "Failed to store hook events, spooling them");
+        if let Err(e) = spool(spool_path, lines.as_bytes()) {
+            tracing::warn!(error = %e, "Failed to spool hook events");
+        }
+    }
+}
+
+/// Send envelope lines to a listening socket.
+pub(super) fn send(path: &Path, lines: &[u8]) -> std::io::Result<()> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    stream.write_all(lines)?;
+    stream.shutdown(std::net::Shutdown::Write)
+}
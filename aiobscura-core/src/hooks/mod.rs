@@ -0,0 +1,272 @@
+//! Hook-based push ingestion
+//!
+//! Session logs are polled, so they lag, and they never record some moments
+//! at all: a permission prompt waiting on the user, a notification, the
+//! agent stopping. Claude Code hooks see those as they happen. Configured to
+//! run `aiobscura hook`, each hook's JSON input is pushed to aiobscura and
+//! stored in `hook_events`.
+//!
+//! ## Delivery
+//!
+//! `aiobscura hook` ([`forward`]) writes the event to the Unix socket at
+//! [`Config::hook_socket_path`](crate::Config::hook_socket_path), where the
+//! process that owns ingest (`aiobscura-sync --watch` or the TUI) runs a
+//! [`HookListener`]. When nothing is listening, the event is appended to the
+//! spool file at [`Config::hook_spool_path`](crate::Config::hook_spool_path)
+//! instead, and the next sync drains it ([`drain_spool`]). The listener
+//! spools the events it fails to store the same way.
+//!
+//! Both carry one JSON envelope per line:
+//!
+//! ```json
+//! {"received_at": "2025-12-06T10:00:00Z", "payload": {"session_id": "...", "hook_event_name": "Notification", ...}}
+//! ```
+
+#[cfg(unix)]
+mod listener;
+
+#[cfg(unix)]
+pub use listener::HookListener;
+
+use crate::db::Database;
+use crate::error::{Error, Result};
+use crate::types::HookEvent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// One hook event on the socket or in the spool file.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    received_at: DateTime<Utc>,
+    payload: Value,
+}
+
+impl Envelope {
+    fn into_event(self) -> Result<HookEvent> {
+        event_from_payload(self.payload, self.received_at)
+    }
+}
+
+/// Where [`forward`] delivered a hook event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Handed to a running listener
+    Socket,
+    /// Appended to the spool file for the next sync
+    Spooled,
+}
+
+/// Forward a hook's JSON input to the running listener, or spool it when
+/// nothing is listening.
+///
+/// Input that isn't a hook event (no `session_id` or `hook_event_name`) is
+/// rejected, so the spool only ever holds events that can be stored.
+pub fn forward(input: &[u8], socket_path: &Path, spool_path: &Path) -> Result<Delivery> {
+    let payload: Value = serde_json::from_slice(input)?;
+    let envelope = Envelope {
+        received_at: Utc::now(),
+        payload,
+    };
+    event_from_payload(envelope.payload.clone(), envelope.received_at)?;
+
+    let mut line = serde_json::to_string(&envelope)?;
+    line.push('\n');
+
+    #[cfg(unix)]
+    if listener::send(socket_path, line.as_bytes()).is_ok() {
+        return Ok(Delivery::Socket);
+    }
+    #[cfg(not(unix))]
+    let _ = socket_path;
+
+    spool(spool_path, line.as_bytes())?;
+    Ok(Delivery::Spooled)
+}
+
+/// Append envelope lines to the spool file.
+fn spool(spool_path: &Path, lines: &[u8]) -> Result<()> {
+    if let Some(parent) = spool_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // A single append-mode write, so concurrent hooks don't interleave lines
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(spool_path)?
+        .write_all(lines)?;
+    Ok(())
+}
+
+/// Store the events waiting in the spool file, returning how many were
+/// stored.
+///
+/// The spool is renamed before it is read, so hooks that fire meanwhile
+/// start a new one. A renamed spool left behind by an interrupted drain is
+/// stored first. Unreadable lines are logged and dropped.
+pub fn drain_spool(db: &Database, spool_path: &Path) -> Result<usize> {
+    let draining = draining_path(spool_path);
+    if !draining.exists() {
+        match fs::rename(spool_path, &draining) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let content = fs::read_to_string(&draining)?;
+    let events: Vec<HookEvent> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str::<Envelope>(line)
+                .map_err(Error::from)
+                .and_then(Envelope::into_event)
+                .map_err(|e| tracing::warn!(error = %e, "Dropping unreadable spooled hook event"))
+                .ok()
+        })
+        .collect();
+
+    db.insert_hook_events(&events)?;
+    fs::remove_file(&draining)?;
+    Ok(events.len())
+}
+
+fn draining_path(spool_path: &Path) -> PathBuf {
+    let mut name = spool_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".draining");
+    spool_path.with_file_name(name)
+}
+
+/// Build a hook event from a Claude Code hook's JSON input.
+pub fn event_from_payload(payload: Value, received_at: DateTime<Utc>) -> Result<HookEvent> {
+    let field = |key: &str| {
+        payload
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let session_id =
+        field("session_id").ok_or_else(|| Error::Hook("hook input has no session_id".into()))?;
+    let event_name = field("hook_event_name")
+        .ok_or_else(|| Error::Hook("hook input has no hook_event_name".into()))?;
+
+    Ok(HookEvent {
+        id: 0,
+        session_id,
+        event_name,
+        tool_name: field("tool_name"),
+        tool_use_id: field("tool_use_id"),
+        message: field("message"),
+        received_at,
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_event_from_payload() {
+        let event = event_from_payload(
+            serde_json::json!({
+                "session_id": "abc",
+                "transcript_path": "/tmp/abc.jsonl",
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_use_id": "toolu_01",
+                "tool_input": {"command": "ls"}
+            }),
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(event.session_id, "abc");
+        assert_eq!(event.event_name, "PreToolUse");
+        assert_eq!(event.tool_name.as_deref(), Some("Bash"));
+        assert_eq!(event.tool_use_id.as_deref(), Some("toolu_01"));
+        assert_eq!(event.message, None);
+        assert_eq!(event.payload["tool_input"]["command"], "ls");
+
+        assert!(
+            event_from_payload(serde_json::json!({"hook_event_name": "Stop"}), Utc::now()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_forward_spools_and_drain_stores() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket = temp_dir.path().join("hook.sock");
+        let spool = temp_dir.path().join("state/hooks.jsonl");
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        let input = br#"{"session_id":"abc","hook_event_name":"Notification","message":"Claude needs your permission to use Bash"}"#;
+        assert_eq!(forward(input, &socket, &spool).unwrap(), Delivery::Spooled);
+        assert_eq!(forward(input, &socket, &spool).unwrap(), Delivery::Spooled);
+        assert!(forward(b"{}", &socket, &spool).is_err());
+
+        assert_eq!(drain_spool(&db, &spool).unwrap(), 2);
+        assert!(!spool.exists());
+        assert_eq!(drain_spool(&db, &spool).unwrap(), 0);
+
+        let events = db.get_session_hook_events("abc").unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].message.as_deref(),
+            Some("Claude needs your permission to use Bash")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_forward_delivers_to_listener() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket = temp_dir.path().join("hook.sock");
+        let spool = temp_dir.path().join("hooks.jsonl");
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::open(&db_path).unwrap();
+        db.migrate().unwrap();
+
+        let listener =
+            HookListener::start(&socket, &spool, Database::open(&db_path).unwrap()).unwrap();
+        assert!(HookListener::start(&socket, &spool, Database::open(&db_path).unwrap()).is_err());
+
+        let input = br#"{"session_id":"abc","hook_event_name":"Stop"}"#;
+        assert_eq!(forward(input, &socket, &spool).unwrap(), Delivery::Socket);
+        drop(listener);
+
+        assert!(!socket.exists());
+        assert!(!spool.exists());
+        let events = db.get_session_hook_events("abc").unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_name, "Stop");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_listener_spools_events_it_cannot_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket = temp_dir.path().join("hook.sock");
+        let spool = temp_dir.path().join("hooks.jsonl");
+
+        // Never migrated, so inserts fail
+        let broken = Database::open(&temp_dir.path().join("broken.db")).unwrap();
+        let listener = HookListener::start(&socket, &spool, broken).unwrap();
+        let input = br#"{"session_id":"abc","hook_event_name":"Stop"}"#;
+        assert_eq!(forward(input, &socket, &spool).unwrap(), Delivery::Socket);
+        drop(listener);
+
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        assert_eq!(drain_spool(&db, &spool).unwrap(), 1);
+        let events = db.get_session_hook_events("abc").unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_name, "Stop");
+    }
+}
@@ -0,0 +1,250 @@
+//! Offline import of another machine's assistant logs
+//!
+//! A snapshot is a directory, or a `.tar.gz`/`.tgz`/`.tar`/`.zip` archive
+//! of one, holding assistant data roots such as a teammate's `~/.claude`.
+//! Archives are unpacked below [`Config::imports_dir`](crate::config::Config::imports_dir)
+//! in a directory named after the import's label, so importing a newer
+//! snapshot under the same label resumes from the earlier checkpoints and
+//! never touches the checkpoints of the local roots.
+//!
+//! The built-in parsers are pointed at the snapshot with `with_root`, and
+//! each session they read is labeled with the root [`IMPORT_ROOT_LABEL`] and
+//! the import's label as its host. Imports are recorded with
+//! [`Database::record_import`], so [`recorded_import_parsers`] can read the
+//! snapshots again when they are reparsed.
+
+use crate::db::Database;
+use crate::error::{Error, Result};
+use crate::ingest::parsers::{self, RootedParser};
+use crate::ingest::{AssistantParser, SourceRoot};
+use crate::types::Assistant;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Root label recorded on imported sessions.
+pub const IMPORT_ROOT_LABEL: &str = "import";
+
+/// Assistants searched for in a snapshot. Aider comes last: its history
+/// files can sit anywhere, so other assistants' roots are claimed first.
+const IMPORT_ASSISTANTS: [Assistant; 5] = [
+    Assistant::ClaudeCode,
+    Assistant::Codex,
+    Assistant::Cursor,
+    Assistant::Gemini,
+    Assistant::Aider,
+];
+
+/// Directory to read a snapshot from.
+///
+/// A directory is read in place. An archive is unpacked into
+/// `imports_dir/<label>` first.
+pub fn prepare_import(source: &Path, imports_dir: &Path, label: &str) -> Result<PathBuf> {
+    if source.is_dir() {
+        return Ok(source.to_path_buf());
+    }
+    if !source.is_file() {
+        return Err(Error::Import(format!(
+            "{} is neither a directory nor an archive",
+            source.display()
+        )));
+    }
+
+    let dest = imports_dir.join(label_dir(label));
+    std::fs::create_dir_all(&dest)?;
+    unpack(source, &dest)?;
+    Ok(dest)
+}
+
+/// Parsers for every assistant with logs in the snapshot at `root`.
+///
+/// An assistant's data root may be `root` itself or one of its immediate
+/// subdirectories (e.g. `.claude/` in an archive of a home directory).
+pub fn import_parsers(root: &Path, label: &str) -> Result<Vec<Box<dyn AssistantParser>>> {
+    let mut candidates = vec![root.to_path_buf()];
+    let mut subdirs: Vec<PathBuf> = std::fs::read_dir(root)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+    candidates.extend(subdirs);
+
+    let mut found: Vec<Box<dyn AssistantParser>> = Vec::new();
+    for assistant in IMPORT_ASSISTANTS {
+        for candidate in &candidates {
+            // Another assistant's root is not this one's data
+            if found.iter().any(|parser| {
+                parser
+                    .root_path()
+                    .is_some_and(|claimed| claimed != root && candidate.starts_with(&claimed))
+            }) {
+                continue;
+            }
+            let Some(parser) = parsers::parser_with_root(assistant, candidate.clone()) else {
+                continue;
+            };
+            if parser.discover_files()?.is_empty() {
+                continue;
+            }
+            found.push(Box::new(RootedParser::new(
+                parser,
+                SourceRoot {
+                    label: IMPORT_ROOT_LABEL.to_string(),
+                    host: label.to_string(),
+                },
+            )));
+            // One root per assistant; a root's subdirectories are its own
+            break;
+        }
+    }
+    Ok(found)
+}
+
+/// Parsers for every recorded import whose snapshot is still on disk.
+pub fn recorded_import_parsers(db: &Database) -> Result<Vec<Box<dyn AssistantParser>>> {
+    let mut found = Vec::new();
+    for (root, label) in db.list_imports()? {
+        if root.is_dir() {
+            found.extend(import_parsers(&root, &label)?);
+        }
+    }
+    Ok(found)
+}
+
+fn unpack(archive: &Path, dest: &Path) -> Result<()> {
+    let name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let file = File::open(archive)?;
+
+    if name.ends_with(".zip") {
+        let mut zip = zip::ZipArchive::new(file).map_err(import_error(archive))?;
+        zip.extract(dest).map_err(import_error(archive))?;
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        tar::Archive::new(flate2::read::GzDecoder::new(file)).unpack(dest)?;
+    } else if name.ends_with(".tar") {
+        tar::Archive::new(file).unpack(dest)?;
+    } else {
+        return Err(Error::Import(format!(
+            "unsupported archive {}: expected .tar.gz, .tgz, .tar or .zip",
+            archive.display()
+        )));
+    }
+    Ok(())
+}
+
+fn import_error(archive: &Path) -> impl Fn(zip::result::ZipError) -> Error + '_ {
+    move |e| Error::Import(format!("failed to unpack {}: {}", archive.display(), e))
+}
+
+/// Directory name for a label, safe on any filesystem.
+fn label_dir(label: &str) -> String {
+    let name: String = label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.trim_matches('.') {
+        "" => "_".to_string(),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const SESSION_FILE: &str = ".claude/projects/-Users-alice-app/alice-session.jsonl";
+    const SESSION_LINE: &str = r#"{"uuid":"u1","sessionId":"alice-session","type":"user","timestamp":"2025-12-06T10:00:00.000Z","cwd":"/Users/alice/app","message":{"role":"user","content":"hi"}}"#;
+
+    fn write_snapshot(dir: &Path) {
+        let path = dir.join(SESSION_FILE);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, format!("{}\n", SESSION_LINE)).unwrap();
+    }
+
+    fn assert_finds_claude(root: &Path) {
+        let parsers = import_parsers(root, "alice").unwrap();
+        assert_eq!(parsers.len(), 1);
+        assert_eq!(parsers[0].assistant(), Assistant::ClaudeCode);
+        assert_eq!(parsers[0].root_path(), Some(root.join(".claude")));
+        assert_eq!(
+            parsers[0].source_root(),
+            Some(&SourceRoot {
+                label: IMPORT_ROOT_LABEL.to_string(),
+                host: "alice".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_import_directory_in_place() {
+        let temp = TempDir::new().unwrap();
+        let snapshot = temp.path().join("snapshot");
+        write_snapshot(&snapshot);
+
+        let root = prepare_import(&snapshot, &temp.path().join("imports"), "alice").unwrap();
+        assert_eq!(root, snapshot);
+        assert_finds_claude(&root);
+    }
+
+    #[test]
+    fn test_import_tar_gz() {
+        let temp = TempDir::new().unwrap();
+        let snapshot = temp.path().join("snapshot");
+        write_snapshot(&snapshot);
+
+        let archive = temp.path().join("alice.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&archive).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        builder.append_dir_all(".", &snapshot).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let imports = temp.path().join("imports");
+        let root = prepare_import(&archive, &imports, "alice@laptop").unwrap();
+        assert_eq!(root, imports.join("alice_laptop"));
+        assert_finds_claude(&root);
+    }
+
+    #[test]
+    fn test_import_zip() {
+        let temp = TempDir::new().unwrap();
+        let archive = temp.path().join("alice.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file(SESSION_FILE, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writeln!(zip, "{}", SESSION_LINE).unwrap();
+        zip.finish().unwrap();
+
+        let root = prepare_import(&archive, &temp.path().join("imports"), "alice").unwrap();
+        assert_finds_claude(&root);
+    }
+
+    #[test]
+    fn test_import_rejects_unknown_archive() {
+        let temp = TempDir::new().unwrap();
+        let archive = temp.path().join("alice.rar");
+        std::fs::write(&archive, b"not an archive").unwrap();
+        assert!(matches!(
+            prepare_import(&archive, temp.path(), "alice"),
+            Err(Error::Import(_))
+        ));
+    }
+
+    #[test]
+    fn test_label_dir() {
+        assert_eq!(label_dir("alice"), "alice");
+        assert_eq!(label_dir("alice/../bob"), "alice_.._bob");
+        assert_eq!(label_dir(".."), "_");
+    }
+}
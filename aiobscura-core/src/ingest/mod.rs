@@ -34,18 +34,27 @@
 //! println!("Synced {} messages from {} files", result.messages_inserted, result.files_processed);
 //! ```
 
+pub mod import;
 mod parser;
 pub mod parsers;
+pub(crate) mod project;
 
-pub use parser::{AssistantParser, ParseContext, ParseResult, SourcePattern};
+pub use parser::{
+    AssistantParser, FailedRecord, ParseContext, ParseResult, SourcePattern, SourceRoot, WatchPath,
+};
+pub use project::{ProjectResolver, ResolvedProject};
 
 use crate::db::Database;
 use crate::error::Result;
-use crate::types::{Checkpoint, Message, MessageType, Project, SourceFile};
-use chrono::Utc;
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use crate::types::{Assistant, Checkpoint, Message, MessageType, Project, SourceFile};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, OnceLock};
+
+/// Maximum number of parsed files written per transaction.
+const WRITE_BATCH_SIZE: usize = 64;
 
 /// Result of a full sync operation across all assistants.
 #[derive(Debug, Default)]
@@ -62,6 +71,10 @@ pub struct SyncResult {
     pub messages_inserted: usize,
     /// Number of threads created
     pub threads_created: usize,
+    /// Number of records that could not be parsed (stored in `parse_failures`)
+    pub records_failed: usize,
+    /// Number of spooled hook events stored
+    pub hook_events: usize,
     /// Errors encountered (file path → error message)
     pub errors: Vec<(PathBuf, String)>,
     /// Warnings from parsing
@@ -87,6 +100,8 @@ pub struct FileSyncResult {
     pub is_new_session: bool,
     /// Warnings from parsing
     pub warnings: Vec<String>,
+    /// Number of records that could not be parsed
+    pub failed_records: usize,
     /// Reason the file was skipped (if skipped)
     pub skip_reason: Option<SkipReason>,
     /// Summaries of messages parsed (for verbose output)
@@ -116,6 +131,22 @@ pub enum SkipReason {
     NoNewContent,
 }
 
+/// Selects previously ingested source files for
+/// [`IngestCoordinator::reparse`]. Unset fields match every file.
+#[derive(Debug, Clone, Default)]
+pub struct ReparseFilter {
+    /// Only files from this assistant
+    pub assistant: Option<Assistant>,
+    /// Only files of this custom agent or parser plugin
+    pub agent: Option<String>,
+    /// Only files modified at or after this time (as of their last sync)
+    pub modified_since: Option<DateTime<Utc>>,
+    /// Only files ingested by an older version of their parser
+    pub outdated_only: bool,
+    /// Only files with records in `parse_failures`, to retry them
+    pub failed_only: bool,
+}
+
 /// Coordinates ingestion across all registered parsers.
 ///
 /// The coordinator is responsible for:
@@ -126,20 +157,81 @@ pub enum SkipReason {
 pub struct IngestCoordinator {
     db: Database,
     parsers: Vec<Box<dyn AssistantParser>>,
+    parallelism: usize,
+    hook_spool: Option<PathBuf>,
+    projects: ProjectResolver,
+    /// Parsers for imported snapshots, loaded when a file no other parser
+    /// reads is looked up (e.g. by a reparse)
+    imports: OnceLock<Vec<Box<dyn AssistantParser>>>,
+    /// Whether projects stored before project resolution have been
+    /// re-resolved (see [`Self::resolve_stored_projects`])
+    projects_resolved: AtomicBool,
+}
+
+/// A parsed file waiting to be written to the database.
+struct ParsedFile<'a> {
+    parser: &'a dyn AssistantParser,
+    existing: Option<SourceFile>,
+    checkpoint: Checkpoint,
+    file_size: u64,
+    modified_at: DateTime<Utc>,
+    parse_result: ParseResult,
 }
 
 impl IngestCoordinator {
     /// Create a new coordinator with the default parsers.
     pub fn new(db: Database) -> Self {
+        Self::with_parsers(db, parsers::create_all_parsers())
+    }
+
+    /// Create a coordinator with the default parsers plus the custom agents
+    /// and parser plugins defined in `[[agents.custom]]` and `[[agents.plugin]]`.
+    ///
+    /// Full syncs also drain the hook spool file at
+    /// [`Config::hook_spool_path`](crate::config::Config::hook_spool_path), and
+    /// projects are resolved with the `[projects]` aliases and merges.
+    pub fn with_config(db: Database, config: &crate::config::Config) -> Self {
+        Self::with_parsers(db, parsers::create_parsers(config))
+            .with_parallelism(config.ingest.parallelism)
+            .with_hook_spool(crate::config::Config::hook_spool_path())
+            .with_project_resolver(ProjectResolver::from_config(&config.projects))
+    }
+
+    /// Create a coordinator with custom parsers.
+    pub fn with_parsers(db: Database, parsers: Vec<Box<dyn AssistantParser>>) -> Self {
         Self {
             db,
-            parsers: parsers::create_all_parsers(),
+            parsers,
+            parallelism: default_parallelism(),
+            hook_spool: None,
+            projects: ProjectResolver::default(),
+            imports: OnceLock::new(),
+            projects_resolved: AtomicBool::new(false),
         }
     }
 
-    /// Create a coordinator with custom parsers.
-    pub fn with_parsers(db: Database, parsers: Vec<Box<dyn AssistantParser>>) -> Self {
-        Self { db, parsers }
+    /// Drain hook events spooled by `aiobscura hook` during full syncs.
+    pub fn with_hook_spool(mut self, path: PathBuf) -> Self {
+        self.hook_spool = Some(path);
+        self
+    }
+
+    /// Use a project resolver other than the default (git, no overrides).
+    pub fn with_project_resolver(mut self, projects: ProjectResolver) -> Self {
+        self.projects = projects;
+        self
+    }
+
+    /// Set how many files are parsed concurrently during sync (0 = one per CPU).
+    ///
+    /// Database writes always happen on the calling thread.
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = if parallelism == 0 {
+            default_parallelism()
+        } else {
+            parallelism
+        };
+        self
     }
 
     /// Get read/write database handle used by this coordinator.
@@ -148,6 +240,9 @@ impl IngestCoordinator {
     }
 
     /// Register an additional parser.
+    ///
+    /// Out-of-process parsers can be registered by wrapping them in a
+    /// [`SubprocessParser`](parsers::SubprocessParser).
     pub fn register_parser(&mut self, parser: Box<dyn AssistantParser>) {
         self.parsers.push(parser);
     }
@@ -196,6 +291,58 @@ impl IngestCoordinator {
         Ok(all_files)
     }
 
+    /// Directories to watch for source file changes, across installed assistants.
+    ///
+    /// Paths already covered by a recursive watch on an ancestor are dropped.
+    pub fn watch_paths(&self) -> Vec<WatchPath> {
+        let mut paths: Vec<WatchPath> = self
+            .installed_assistants()
+            .into_iter()
+            .flat_map(|parser| parser.watch_paths())
+            .collect();
+        // Sort ancestors first, recursive before non-recursive for equal paths
+        paths.sort_by(|a, b| a.path.cmp(&b.path).then(b.recursive.cmp(&a.recursive)));
+
+        let mut result: Vec<WatchPath> = Vec::new();
+        for watch in paths {
+            let covered = result.iter().any(|existing| {
+                existing.path == watch.path
+                    || (existing.recursive && watch.path.starts_with(&existing.path))
+            });
+            if !covered {
+                result.push(watch);
+            }
+        }
+        result
+    }
+
+    /// Map a changed path to the source file that should be synced.
+    ///
+    /// Returns `None` if no parser's patterns match the path. SQLite sidecar
+    /// files (`-wal`, `-shm`, `-journal`) map to their database, since writes
+    /// to e.g. Cursor's `state.vscdb` often land in the write-ahead log first.
+    pub fn source_file_for(&self, path: &Path) -> Option<PathBuf> {
+        let path = sqlite_database_for(path).unwrap_or_else(|| path.to_path_buf());
+        let parser = self.parser_for_file(&path)?;
+        let root = parser.root_path()?;
+        parser
+            .source_patterns()
+            .iter()
+            .any(|p| {
+                glob::Pattern::new(&root.join(&p.pattern).to_string_lossy())
+                    .map(|pattern| pattern.matches_path(&path))
+                    .unwrap_or(false)
+            })
+            .then_some(path)
+    }
+
+    /// Sync a set of files, e.g. the ones reported changed by a file watcher.
+    ///
+    /// Files that fail to sync are recorded in [`SyncResult::errors`].
+    pub fn sync_files(&self, paths: &[PathBuf]) -> Result<SyncResult> {
+        self.sync_paths(paths.to_vec(), false, |_, _, _| {})
+    }
+
     /// Sync all discovered files (full sync).
     ///
     /// This discovers all source files and syncs each one, respecting
@@ -213,7 +360,7 @@ impl IngestCoordinator {
     /// Sync all discovered files with progress callback.
     ///
     /// The callback receives `(current_file_index, total_files, file_path)` before
-    /// each file is processed. This allows callers to display progress indicators.
+    /// each file is written. This allows callers to display progress indicators.
     ///
     /// ## Example
     ///
@@ -222,31 +369,277 @@ impl IngestCoordinator {
     ///     println!("Processing {}/{}: {}", current + 1, total, path.display());
     /// })?;
     /// ```
-    pub fn sync_all_with_progress<F>(&self, mut on_progress: F) -> Result<SyncResult>
+    pub fn sync_all_with_progress<F>(&self, on_progress: F) -> Result<SyncResult>
     where
         F: FnMut(usize, usize, &Path),
     {
         let files = self.discover_files()?;
-        let total = files.len();
-        let mut result = SyncResult::default();
-
-        // Process all files - order doesn't matter since spawn info is DB-backed
-        for (i, file) in files.iter().enumerate() {
-            on_progress(i, total, &file.path);
+        let mut result = self.sync_paths(
+            files.into_iter().map(|f| f.path).collect(),
+            false,
+            on_progress,
+        )?;
+
+        if let Some(spool) = &self.hook_spool {
+            match crate::hooks::drain_spool(&self.db, spool) {
+                Ok(count) => result.hook_events = count,
+                Err(e) => result.errors.push((spool.clone(), e.to_string())),
+            }
+        }
+        Ok(result)
+    }
 
-            match self.sync_file_internal(&file.path) {
-                Ok(file_result) => {
-                    Self::update_result(&mut result, file_result);
+    /// Parse files on up to `parallelism` worker threads and write the results
+    /// on the calling thread, in batched transactions.
+    ///
+    /// Main session files are written before agent files, so the spawn info
+    /// an agent thread is linked through is already in `agent_spawns` no
+    /// matter which file finishes parsing first.
+    ///
+    /// With `from_scratch`, files are parsed from the start instead of from
+    /// their checkpoints.
+    fn sync_paths<F>(
+        &self,
+        mut paths: Vec<PathBuf>,
+        from_scratch: bool,
+        mut on_progress: F,
+    ) -> Result<SyncResult>
+    where
+        F: FnMut(usize, usize, &Path),
+    {
+        self.resolve_stored_projects()?;
+
+        // Stable sort: discovery order is kept within each group
+        paths.sort_by_key(|path| is_agent_file(path));
+        let total = paths.len();
+        let main_files = paths.iter().filter(|path| !is_agent_file(path)).count();
+        let workers = self.parallelism.clamp(1, total.max(1));
+
+        let next = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::sync_channel(workers * 2);
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                let tx = tx.clone();
+                let (next, paths) = (&next, &paths);
+                scope.spawn(move || {
+                    while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        if tx
+                            .send((path, self.parse_file(path, from_scratch)))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+
+            let mut result = SyncResult::default();
+            let mut written = 0;
+            let mut main_written = 0;
+            let mut held_agents = Vec::new();
+
+            while let Ok(first) = rx.recv() {
+                let mut batch: Vec<_> = std::iter::once(first)
+                    .chain(rx.try_iter().take(WRITE_BATCH_SIZE - 1))
+                    .collect();
+
+                // Hold agent files back until every main session file is written
+                if main_written < main_files {
+                    let (agents, mains): (Vec<_>, Vec<_>) =
+                        batch.into_iter().partition(|(path, _)| is_agent_file(path));
+                    main_written += mains.len();
+                    held_agents.extend(agents);
+                    batch = mains;
                 }
-                Err(e) => {
-                    result.errors.push((file.path.clone(), e.to_string()));
+                if main_written == main_files {
+                    batch.append(&mut held_agents);
                 }
+
+                self.db.savepoint(|| {
+                    for (path, parsed) in batch {
+                        on_progress(written, total, path);
+                        written += 1;
+
+                        match parsed.and_then(|parsed| {
+                            self.db.savepoint(|| self.store_parsed(path, parsed))
+                        }) {
+                            Ok(file_result) => Self::update_result(&mut result, file_result),
+                            Err(e) => result.errors.push((path.clone(), e.to_string())),
+                        }
+                    }
+                    Ok(())
+                })?;
             }
-        }
 
+            Ok(result)
+        })
+    }
+
+    /// Source files that [`Self::reparse`] would re-ingest.
+    ///
+    /// Includes every other file that shares a session with a selected file,
+    /// since a session is deleted and rebuilt as a whole.
+    pub fn reparse_files(&self, filter: &ReparseFilter) -> Result<Vec<PathBuf>> {
+        let (paths, _) = self.reparse_targets(filter)?;
+        Ok(paths.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Re-ingest previously synced files from scratch.
+    ///
+    /// Deletes the sessions that came from the selected files, with their
+    /// threads, messages and analytics, resets the files' checkpoints and
+    /// syncs them again, all in one transaction. Use this when a parser has
+    /// learned to extract more from records it already ingested.
+    ///
+    /// If any file fails to parse, the transaction is rolled back and every
+    /// session keeps its previously ingested data.
+    pub fn reparse<F>(&self, filter: &ReparseFilter, on_progress: F) -> Result<SyncResult>
+    where
+        F: FnMut(usize, usize, &Path),
+    {
+        let (paths, session_ids) = self.reparse_targets(filter)?;
+        tracing::info!(
+            files = paths.len(),
+            sessions = session_ids.len(),
+            "Reparsing source files"
+        );
+
+        let result = self.db.savepoint(|| {
+            self.db.delete_ingested_data(&session_ids, &paths)?;
+            // The workers can't see the checkpoints deleted in this savepoint
+            let result =
+                self.sync_paths(paths.iter().map(PathBuf::from).collect(), true, on_progress)?;
+            // Committing would lose the deleted sessions of the failed files
+            if !result.errors.is_empty() {
+                let failed: Vec<String> = result
+                    .errors
+                    .iter()
+                    .map(|(path, error)| format!("{}: {error}", path.display()))
+                    .collect();
+                return Err(crate::error::Error::Reparse(format!(
+                    "{} file(s) failed, nothing was changed: {}",
+                    failed.len(),
+                    failed.join("; ")
+                )));
+            }
+            Ok(result)
+        })?;
+
+        // Images only the deleted messages referenced
+        if let Err(e) = self.db.remove_unreferenced_blobs() {
+            tracing::warn!(error = %e, "Failed to remove unreferenced blobs");
+        }
         Ok(result)
     }
 
+    /// Select source files matching `filter`, expanded to whole sessions.
+    ///
+    /// Returns the file paths and the IDs of the sessions built from them.
+    /// Groups that include a file no longer on disk or read by no parser are
+    /// skipped, since their data could not be rebuilt, as are groups with a
+    /// file whose raw data was pruned. Files of imported snapshots are read
+    /// by the parsers [`Database::record_import`] lets the coordinator
+    /// rebuild.
+    fn reparse_targets(&self, filter: &ReparseFilter) -> Result<(Vec<String>, Vec<String>)> {
+        let failed: HashSet<String> = if filter.failed_only {
+            self.db.list_parse_failure_files()?.into_iter().collect()
+        } else {
+            Default::default()
+        };
+        let selected: Vec<String> = self
+            .db
+            .list_source_files()?
+            .into_iter()
+            .filter(|file| {
+                filter.assistant.is_none_or(|a| file.assistant == a)
+                    && filter
+                        .agent
+                        .as_ref()
+                        .is_none_or(|agent| file.agent.as_ref() == Some(agent))
+                    && filter
+                        .modified_since
+                        .is_none_or(|since| file.modified_at >= since)
+                    && (!filter.outdated_only
+                        || self
+                            .parser_for_file(&file.path)
+                            .is_some_and(|p| p.version() > file.parser_version))
+                    && (!filter.failed_only || failed.contains(&*file.path.to_string_lossy()))
+            })
+            .map(|file| file.path.to_string_lossy().to_string())
+            .collect();
+
+        let pruned: HashSet<String> = self.db.list_pruned_source_files()?.into_iter().collect();
+
+        let mut sessions_by_file: HashMap<String, Vec<String>> = HashMap::new();
+        let mut files_by_session: HashMap<String, Vec<String>> = HashMap::new();
+        for (session_id, path) in self.db.list_session_source_links()? {
+            sessions_by_file
+                .entry(path.clone())
+                .or_default()
+                .push(session_id.clone());
+            files_by_session.entry(session_id).or_default().push(path);
+        }
+
+        let mut files = BTreeSet::new();
+        let mut sessions = BTreeSet::new();
+        for start in selected {
+            if files.contains(&start) {
+                continue;
+            }
+
+            // Collect the group of files and sessions connected to `start`
+            let mut group_files = BTreeSet::from([start.clone()]);
+            let mut group_sessions = BTreeSet::new();
+            let mut queue = vec![start];
+            while let Some(path) = queue.pop() {
+                for session_id in sessions_by_file.get(&path).into_iter().flatten() {
+                    if group_sessions.insert(session_id.clone()) {
+                        for other in &files_by_session[session_id] {
+                            if group_files.insert(other.clone()) {
+                                queue.push(other.clone());
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(missing) = group_files.iter().find(|p| !Path::new(p).exists()) {
+                tracing::warn!(
+                    path = %missing,
+                    sessions = group_sessions.len(),
+                    "Source file no longer exists, not reparsing its sessions"
+                );
+                continue;
+            }
+            if let Some(unread) = group_files
+                .iter()
+                .find(|p| self.parser_for_file(Path::new(p)).is_none())
+            {
+                tracing::warn!(
+                    path = %unread,
+                    sessions = group_sessions.len(),
+                    "No parser reads source file, not reparsing its sessions"
+                );
+                continue;
+            }
+            // Reparsing would restore raw data the retention policy dropped
+            if let Some(pruned) = group_files.iter().find(|p| pruned.contains(*p)) {
+                tracing::warn!(
+                    path = %pruned,
+                    sessions = group_sessions.len(),
+                    "Source file was pruned, not reparsing its sessions"
+                );
+                continue;
+            }
+            files.extend(group_files);
+            sessions.extend(group_sessions);
+        }
+
+        Ok((files.into_iter().collect(), sessions.into_iter().collect()))
+    }
+
     /// Update result counters from a file sync result.
     fn update_result(result: &mut SyncResult, file_result: FileSyncResult) {
         if file_result.new_messages > 0 {
@@ -279,6 +672,7 @@ impl IngestCoordinator {
                 "File skipped"
             );
         }
+        result.records_failed += file_result.failed_records;
         result.warnings.extend(file_result.warnings.iter().cloned());
         // Store per-file result for verbose output
         result.file_results.push(file_result);
@@ -298,6 +692,13 @@ impl IngestCoordinator {
     /// - Main sessions: persist spawn map to DB after parsing
     /// - Agent files: look up spawn info from DB to link threads
     fn sync_file_internal(&self, path: &Path) -> Result<FileSyncResult> {
+        self.resolve_stored_projects()?;
+        let parsed = self.parse_file(path, false)?;
+        self.db.savepoint(|| self.store_parsed(path, parsed))
+    }
+
+    /// Parse new content from a file, without writing to the database.
+    fn parse_file(&self, path: &Path, from_scratch: bool) -> Result<ParsedFile<'_>> {
         // Find the parser for this file
         let parser = self
             .parser_for_file(path)
@@ -307,7 +708,11 @@ impl IngestCoordinator {
             })?;
 
         // Get existing source file record (for checkpoint)
-        let existing = self.db.get_source_file(&path.to_string_lossy())?;
+        let existing = if from_scratch {
+            None
+        } else {
+            self.db.get_source_file(&path.to_string_lossy())?
+        };
         let checkpoint = existing
             .as_ref()
             .map(|s| s.checkpoint.clone())
@@ -331,12 +736,35 @@ impl IngestCoordinator {
         };
 
         // Parse the file
-        let mut parse_result = parser.parse(&ctx)?;
+        let parse_result = parser.parse(&ctx)?;
+
+        Ok(ParsedFile {
+            parser,
+            existing,
+            checkpoint,
+            file_size,
+            modified_at,
+            parse_result,
+        })
+    }
+
+    /// Write a parsed file to the database.
+    fn store_parsed(&self, path: &Path, parsed: ParsedFile<'_>) -> Result<FileSyncResult> {
+        let ParsedFile {
+            parser,
+            existing,
+            checkpoint,
+            file_size,
+            modified_at,
+            mut parse_result,
+        } = parsed;
 
         // Check if there's anything new
         if parse_result.messages.is_empty()
             && parse_result.session.is_none()
+            && parse_result.extra_sessions.is_empty()
             && parse_result.threads.is_empty()
+            && parse_result.failed_records.is_empty()
         {
             // Determine why the file was skipped
             let skip_reason = if file_size == 0 {
@@ -362,6 +790,7 @@ impl IngestCoordinator {
                 new_checkpoint: parse_result.new_checkpoint,
                 is_new_session: false,
                 warnings: parse_result.warnings,
+                failed_records: 0,
                 skip_reason,
                 message_summaries: Vec::new(),
             });
@@ -373,8 +802,9 @@ impl IngestCoordinator {
             file_type: existing
                 .as_ref()
                 .map(|s| s.file_type)
-                .unwrap_or(crate::types::FileType::Jsonl),
+                .unwrap_or_else(|| file_type_for(parser, path)),
             assistant: parser.assistant(),
+            agent: parser.agent().map(String::from),
             created_at: existing
                 .as_ref()
                 .map(|s| s.created_at)
@@ -383,22 +813,83 @@ impl IngestCoordinator {
             size_bytes: file_size,
             last_parsed_at: Some(Utc::now()),
             checkpoint: parse_result.new_checkpoint.clone(),
+            // Incremental parses extend what an earlier version ingested
+            parser_version: match &existing {
+                Some(existing) if !matches!(checkpoint, Checkpoint::None) => {
+                    existing.parser_version
+                }
+                _ => parser.version(),
+            },
         };
         self.db.upsert_source_file(&source_file)?;
 
-        // Store project (before session, since session references it)
+        // Quarantine records the parser could not interpret, numbering their
+        // lines from the start of the file rather than the checkpoint
+        if !parse_result.failed_records.is_empty() {
+            if let Checkpoint::ByteOffset { offset } = &checkpoint {
+                if *offset > 0 {
+                    let skipped = lines_before(path, *offset)?;
+                    for record in &mut parse_result.failed_records {
+                        record.line = record.line.map(|line| line + skipped);
+                    }
+                }
+            }
+            self.db.insert_parse_failures(
+                &path.to_string_lossy(),
+                parser.assistant(),
+                parser.version(),
+                &parse_result.failed_records,
+            )?;
+        }
+
+        // Store the project under its resolved identity (before sessions, since
+        // they reference it) and re-point the parser's project id
+        let mut project_ids = HashMap::new();
         if let Some(project) = &parse_result.project {
-            self.db.upsert_project(project)?;
+            let id =
+                self.store_project(&project.path, project.created_at, project.last_activity_at)?;
+            project_ids.insert(project.id.clone(), id);
+        }
+        for session in parse_result
+            .extra_sessions
+            .iter_mut()
+            .chain(parse_result.session.iter_mut())
+        {
+            if let Some(id) = session
+                .project_id
+                .as_ref()
+                .and_then(|id| project_ids.get(id))
+            {
+                session.project_id = Some(id.clone());
+            }
+            // Record which root (and host) the session was read from
+            if let Some(source) = parser.source_root() {
+                if !session.metadata.is_object() {
+                    session.metadata = serde_json::json!({});
+                }
+                session.metadata["source_root"] = source.label.clone().into();
+                session.metadata["source_host"] = source.host.clone().into();
+            }
         }
 
-        // Store backing model (before session, since session references it)
-        if let Some(ref session) = parse_result.session {
+        // Store backing models (before sessions, since sessions reference them)
+        for session in parse_result
+            .extra_sessions
+            .iter()
+            .chain(parse_result.session.iter())
+        {
             if let Some(ref model_id) = session.backing_model_id {
                 let backing_model = crate::types::BackingModel::from_id(model_id);
                 self.db.upsert_backing_model(&backing_model)?;
             }
         }
 
+        // Store additional sessions from multi-session sources
+        for session in &mut parse_result.extra_sessions {
+            self.hydrate_session_project(session)?;
+            self.db.upsert_session(session)?;
+        }
+
         // Store session
         let session_id = parse_result.session.as_ref().map(|s| s.id.clone());
         let is_new_session = existing.is_none() && parse_result.session.is_some();
@@ -514,7 +1005,11 @@ impl IngestCoordinator {
             let mut thread_last_activity: HashMap<String, chrono::DateTime<chrono::Utc>> =
                 HashMap::new();
             for msg in &parse_result.messages {
-                if msg.message_type == MessageType::Context {
+                // Summaries carry no timestamp of their own
+                if matches!(
+                    msg.message_type,
+                    MessageType::Context | MessageType::Summary
+                ) {
                     continue;
                 }
                 thread_last_activity
@@ -533,6 +1028,15 @@ impl IngestCoordinator {
             }
         }
 
+        // Link continued sessions once their records are stored
+        if let Some(ref sid) = session_id {
+            self.db.link_continued_sessions(
+                sid,
+                &parse_result.messages,
+                matches!(checkpoint, Checkpoint::None),
+            )?;
+        }
+
         // Store plans and link to session
         if let Some(ref sid) = session_id {
             for plan in &parse_result.plans {
@@ -557,6 +1061,7 @@ impl IngestCoordinator {
             new_checkpoint: parse_result.new_checkpoint,
             is_new_session,
             warnings: parse_result.warnings,
+            failed_records: parse_result.failed_records.len(),
             skip_reason: None,
             message_summaries,
         })
@@ -573,41 +1078,132 @@ impl IngestCoordinator {
 
         if session.project_id.is_none() {
             if let Some(cwd) = session.metadata.get("cwd").and_then(|v| v.as_str()) {
-                if let Some(project) = self.db.get_project_by_path(Path::new(cwd))? {
-                    session.project_id = Some(project.id);
-                } else {
-                    let project_id = Self::generate_project_id(cwd);
-                    let project_name = Self::extract_dir_name(cwd);
-                    let project = Project {
-                        id: project_id.clone(),
-                        path: PathBuf::from(cwd),
-                        name: Some(project_name),
-                        created_at: session.started_at,
-                        last_activity_at: session.last_activity_at,
-                        metadata: serde_json::json!({}),
-                    };
-                    self.db.upsert_project(&project)?;
-                    session.project_id = Some(project_id);
-                }
+                let project_id = self.store_project(
+                    Path::new(cwd),
+                    session.started_at,
+                    session.last_activity_at,
+                )?;
+                session.project_id = Some(project_id);
             }
         }
 
         Ok(())
     }
 
-    fn generate_project_id(path: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(path.as_bytes());
-        let hash = hasher.finalize();
-        format!("{:x}", hash)[..16].to_string()
+    /// Re-resolve projects stored before project resolution, which were
+    /// keyed by the hash of the raw directory, and merge the ones that now
+    /// resolve to the same project. Runs before the first sync; the database
+    /// records that it is done.
+    ///
+    /// A deleted linked worktree no longer resolves through git, so it is
+    /// matched against the worktrees its repository still records, for
+    /// repositories that are themselves stored projects.
+    pub fn resolve_stored_projects(&self) -> Result<()> {
+        if self.projects_resolved.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        let stored = self.db.list_unresolved_projects()?;
+        let mut resolved: Vec<(Project, ResolvedProject)> = stored
+            .into_iter()
+            .map(|project| {
+                let resolved = self.projects.resolve(&project.path);
+                (project, resolved)
+            })
+            .collect();
+
+        let worktrees: Vec<(PathBuf, ResolvedProject)> = resolved
+            .iter()
+            .filter(|(_, resolved)| resolved.is_git)
+            .flat_map(|(_, resolved)| {
+                project::linked_worktrees(&resolved.path)
+                    .into_iter()
+                    .map(|worktree| (worktree, resolved.clone()))
+            })
+            .collect();
+        for (project, resolved) in &mut resolved {
+            if resolved.is_git || project.path.exists() {
+                continue;
+            }
+            if let Some((_, repo)) = worktrees
+                .iter()
+                .find(|(worktree, _)| project.path.starts_with(worktree))
+            {
+                *resolved = repo.clone();
+            }
+        }
+
+        // Group by resolved id, keeping the stored order within a group
+        let mut groups: Vec<(ResolvedProject, Vec<Project>)> = Vec::new();
+        for (project, resolved) in resolved {
+            match groups.iter_mut().find(|(r, _)| r.id == resolved.id) {
+                Some((_, projects)) => projects.push(project),
+                None => groups.push((resolved, vec![project])),
+            }
+        }
+
+        for (resolved, mut projects) in groups {
+            let target = match self.db.get_project(&resolved.id)? {
+                Some(existing) => existing.id,
+                None => {
+                    // Prefer the row already at the project root, whose path
+                    // the resolved project takes over
+                    let idx = projects
+                        .iter()
+                        .position(|p| p.path == resolved.path)
+                        .unwrap_or(0);
+                    let first = projects.remove(idx);
+                    self.db.rekey_project(
+                        &first.id,
+                        &Project {
+                            id: resolved.id.clone(),
+                            path: resolved.path.clone(),
+                            name: Some(resolved.name.clone()),
+                            created_at: first.created_at,
+                            last_activity_at: first.last_activity_at,
+                            metadata: resolved.metadata(),
+                        },
+                    )?;
+                    resolved.id.clone()
+                }
+            };
+            for project in projects.iter().filter(|p| p.id != target) {
+                let moved = self.db.merge_projects(&project.id, &target)?;
+                tracing::info!(from = %project.id, into = %target, moved, "Merged stored project");
+            }
+        }
+
+        self.db.mark_projects_resolved()?;
+        self.projects_resolved.store(true, Ordering::Release);
+        Ok(())
     }
 
-    fn extract_dir_name(path: &str) -> String {
-        Path::new(path)
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string()
+    /// Resolve the project `dir` belongs to and store it, returning its id.
+    ///
+    /// An existing project keeps its path and name; only its last activity
+    /// moves forward.
+    fn store_project(
+        &self,
+        dir: &Path,
+        created_at: DateTime<Utc>,
+        last_activity_at: Option<DateTime<Utc>>,
+    ) -> Result<String> {
+        let resolved = self.projects.resolve(dir);
+        let project = match self.db.get_project(&resolved.id)? {
+            Some(mut existing) => {
+                existing.last_activity_at = existing.last_activity_at.max(last_activity_at);
+                existing
+            }
+            None => Project {
+                id: resolved.id.clone(),
+                path: resolved.path.clone(),
+                name: Some(resolved.name.clone()),
+                created_at,
+                last_activity_at,
+                metadata: resolved.metadata(),
+            },
+        };
+        self.db.upsert_project(&project)?;
+        Ok(project.id)
     }
 
     /// Update thread spawn info after parsing an agent file.
@@ -625,18 +1221,96 @@ impl IngestCoordinator {
     }
 
     /// Find the parser that handles a given file.
+    ///
+    /// When several roots contain the file (e.g. Aider's `$HOME` and a custom
+    /// agent below it), the most specific root wins.
     fn parser_for_file(&self, path: &Path) -> Option<&dyn AssistantParser> {
-        for parser in &self.parsers {
-            if let Some(root) = parser.root_path() {
-                if path.starts_with(&root) {
-                    return Some(parser.as_ref());
-                }
+        deepest_parser(&self.parsers, path).or_else(|| {
+            let imports = self.imports.get_or_init(|| {
+                import::recorded_import_parsers(&self.db).unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "Failed to load imported snapshots");
+                    Vec::new()
+                })
+            });
+            deepest_parser(imports, path)
+        })
+    }
+}
+
+/// The parser among `parsers` with the deepest root containing `path`.
+///
+/// Among parsers sharing that root (e.g. two custom agents), one with a
+/// source pattern matching `path` wins.
+fn deepest_parser<'a>(
+    parsers: &'a [Box<dyn AssistantParser>],
+    path: &Path,
+) -> Option<&'a dyn AssistantParser> {
+    parsers
+        .iter()
+        .filter_map(|parser| {
+            let root = parser.root_path()?;
+            if !path.starts_with(&root) {
+                return None;
             }
+            let matches = parser.source_patterns().iter().any(|pattern| {
+                glob::Pattern::new(&root.join(&pattern.pattern).to_string_lossy())
+                    .is_ok_and(|glob| glob.matches_path(path))
+            });
+            Some(((root.components().count(), matches), parser.as_ref()))
+        })
+        .max_by_key(|(rank, _)| *rank)
+        .map(|(_, parser)| parser)
+}
+
+/// Default number of files parsed concurrently: one per CPU.
+fn default_parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Number of lines in the first `offset` bytes of the file at `path`.
+fn lines_before(path: &Path, offset: u64) -> Result<i32> {
+    use std::io::Read;
+
+    let mut prefix = std::fs::File::open(path)?.take(offset);
+    let mut buf = [0u8; 64 * 1024];
+    let mut lines = 0;
+    loop {
+        let n = prefix.read(&mut buf)?;
+        if n == 0 {
+            return Ok(lines);
         }
-        None
+        lines += buf[..n].iter().filter(|&&b| b == b'\n').count() as i32;
     }
 }
 
+/// Determine the file type of a newly discovered file from the parser's patterns.
+fn file_type_for(parser: &dyn AssistantParser, path: &Path) -> crate::types::FileType {
+    let Some(root) = parser.root_path() else {
+        return crate::types::FileType::Jsonl;
+    };
+    parser
+        .source_patterns()
+        .into_iter()
+        .find(|p| {
+            glob::Pattern::new(&root.join(&p.pattern).to_string_lossy())
+                .map(|pattern| pattern.matches_path(path))
+                .unwrap_or(false)
+        })
+        .map(|p| p.file_type)
+        .unwrap_or(crate::types::FileType::Jsonl)
+}
+
+/// Map an SQLite sidecar file (`-wal`, `-shm`, `-journal`) to its database.
+fn sqlite_database_for(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    ["-wal", "-shm", "-journal"]
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .map(|base| path.with_file_name(base))
+}
+
 /// Check if a file is an agent file (agent-*.jsonl pattern).
 fn is_agent_file(path: &Path) -> bool {
     path.file_name()
@@ -731,4 +1405,178 @@ mod tests {
             Some("".to_string())
         );
     }
+
+    #[test]
+    fn test_sqlite_database_for() {
+        assert_eq!(
+            sqlite_database_for(Path::new("/ws/state.vscdb-wal")),
+            Some(PathBuf::from("/ws/state.vscdb"))
+        );
+        assert_eq!(
+            sqlite_database_for(Path::new("/ws/state.vscdb-journal")),
+            Some(PathBuf::from("/ws/state.vscdb"))
+        );
+        assert_eq!(sqlite_database_for(Path::new("/ws/state.vscdb")), None);
+    }
+
+    #[test]
+    fn test_watch_paths_and_source_file_for() {
+        let home = tempfile::tempdir().unwrap();
+        let codex_root = home.path().join(".codex");
+        let cursor_root = home.path().join("Cursor/User");
+        std::fs::create_dir_all(&codex_root).unwrap();
+        std::fs::create_dir_all(&cursor_root).unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        let coordinator = IngestCoordinator::with_parsers(
+            db,
+            vec![
+                Box::new(parsers::AiderParser::with_root(home.path().to_path_buf())),
+                Box::new(parsers::CodexParser::with_root(codex_root.clone())),
+                Box::new(parsers::CursorParser::with_root(cursor_root.clone())),
+            ],
+        );
+
+        let watched = coordinator.watch_paths();
+        assert!(watched.contains(&WatchPath {
+            path: home.path().to_path_buf(),
+            recursive: false,
+        }));
+        assert!(watched.contains(&WatchPath {
+            path: codex_root.join("sessions"),
+            recursive: true,
+        }));
+        assert!(watched.contains(&WatchPath {
+            path: cursor_root.join("workspaceStorage"),
+            recursive: true,
+        }));
+
+        let rollout = codex_root.join("sessions/2025/01/02/rollout-abc.jsonl");
+        assert_eq!(coordinator.source_file_for(&rollout), Some(rollout));
+        assert_eq!(
+            coordinator.source_file_for(&codex_root.join("sessions/2025/01/02/notes.txt")),
+            None
+        );
+        assert_eq!(
+            coordinator.source_file_for(&cursor_root.join("globalStorage/state.vscdb-wal")),
+            Some(cursor_root.join("globalStorage/state.vscdb"))
+        );
+        assert_eq!(
+            coordinator.source_file_for(&home.path().join("notes.md")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_stored_projects_merges_path_keyed_projects() {
+        use crate::types::{FileType, Session, SessionStatus};
+        use std::process::Command;
+
+        let git = |dir: &Path, args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .status()
+                .expect("git should run");
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        git(&repo, &["init", "-q"]);
+        std::fs::write(repo.join("src/lib.rs"), "").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "init"]);
+        let worktree = temp.path().join("repo-feature");
+        let worktree_arg = worktree.to_str().unwrap();
+        git(
+            &repo,
+            &["worktree", "add", "-q", "-b", "feature", worktree_arg],
+        );
+        let elsewhere = temp.path().join("notes");
+        std::fs::create_dir_all(&elsewhere).unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.upsert_source_file(&SourceFile {
+            path: PathBuf::from("/logs/session.jsonl"),
+            file_type: FileType::Jsonl,
+            assistant: Assistant::ClaudeCode,
+            agent: None,
+            created_at: Utc::now(),
+            modified_at: Utc::now(),
+            size_bytes: 0,
+            last_parsed_at: None,
+            checkpoint: Checkpoint::None,
+            parser_version: 1,
+        })
+        .unwrap();
+
+        // Rows as they were stored before resolution: keyed by the raw cwd
+        let dirs = [repo.join("src"), worktree.join("src"), elsewhere.clone()];
+        for dir in &dirs {
+            let id = project::project_id(&dir.to_string_lossy());
+            db.upsert_project(&Project {
+                id: id.clone(),
+                path: dir.clone(),
+                name: None,
+                created_at: Utc::now(),
+                last_activity_at: None,
+                metadata: serde_json::json!({}),
+            })
+            .unwrap();
+            db.upsert_session(&Session {
+                id: format!("session-{}", id),
+                assistant: Assistant::ClaudeCode,
+                backing_model_id: None,
+                project_id: Some(id),
+                started_at: Utc::now(),
+                last_activity_at: None,
+                status: SessionStatus::Inactive,
+                source_file_path: "/logs/session.jsonl".to_string(),
+                metadata: serde_json::json!({}),
+            })
+            .unwrap();
+        }
+        db.connection()
+            .unwrap()
+            .execute("UPDATE projects SET resolved = 0", [])
+            .unwrap();
+
+        // The worktree is deleted but not pruned
+        std::fs::remove_dir_all(&worktree).unwrap();
+
+        let coordinator = IngestCoordinator::with_parsers(db, Vec::new());
+        coordinator.resolve_stored_projects().unwrap();
+        let db = coordinator.db();
+
+        let main = ProjectResolver::default().resolve(&repo);
+        let project = db.get_project(&main.id).unwrap().unwrap();
+        assert_eq!(project.path, main.path);
+        assert_eq!(project.name.as_deref(), Some("repo"));
+        let elsewhere_id = project::project_id(&elsewhere.to_string_lossy());
+        assert!(db.get_project(&elsewhere_id).unwrap().is_some());
+        assert!(db.list_unresolved_projects().unwrap().is_empty());
+
+        let count = |project_id: &str| -> i64 {
+            db.connection()
+                .unwrap()
+                .query_row(
+                    "SELECT COUNT(*) FROM sessions WHERE project_id = ?",
+                    [project_id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(count(&main.id), 2);
+        assert_eq!(count(&elsewhere_id), 1);
+        let projects: i64 = db
+            .connection()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM projects", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(projects, 2);
+    }
 }
@@ -14,6 +14,7 @@ use crate::error::Result;
 use crate::types::{
     Assistant, Checkpoint, FileType, Message, Plan, Project, Session, SourceFile, Thread,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -30,16 +31,61 @@ pub struct SourcePattern {
     pub description: String,
 }
 
+/// A directory to watch for changes to an assistant's source files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchPath {
+    /// Directory to watch
+    pub path: PathBuf,
+    /// Whether changes in subdirectories should be reported too
+    pub recursive: bool,
+}
+
+/// Where a parser's data root lives, recorded on every session read from it.
+///
+/// Lets one assistant be read from several roots (profiles, containers,
+/// remote mounts) and its sessions told apart afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRoot {
+    /// Label of the root (e.g. "default", "work")
+    pub label: String,
+    /// Label of the machine or container the root belongs to
+    pub host: String,
+}
+
+/// A source record the parser could not interpret.
+///
+/// Stored in the `parse_failures` table so it isn't lost once the checkpoint
+/// moves past it, and retried when the file is reparsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedRecord {
+    /// Byte offset of the record in the source file
+    pub offset: u64,
+    /// Line number, counted from where this parse started (the coordinator
+    /// stores it counted from the start of the file)
+    pub line: Option<i32>,
+    /// Raw record text
+    pub raw: String,
+    /// Why the record could not be parsed
+    pub error: String,
+}
+
 /// Result of parsing a source file.
 ///
 /// Contains all entities extracted from the file, plus the updated checkpoint
 /// for incremental parsing.
-#[derive(Debug, Default)]
+///
+/// Serializable so that out-of-process parsers can return it as JSON
+/// (see [`SubprocessParser`](super::parsers::SubprocessParser)).
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ParseResult {
     /// Project inferred from cwd (auto-created)
     pub project: Option<Project>,
     /// Session to upsert (may be new or updated)
     pub session: Option<Session>,
+    /// Additional sessions for sources that hold several sessions per file
+    /// (e.g., Aider chat history). Stored the same way as [`Self::session`].
+    pub extra_sessions: Vec<Session>,
     /// Threads to insert (typically just main thread on first parse)
     pub threads: Vec<Thread>,
     /// Messages to insert
@@ -50,6 +96,8 @@ pub struct ParseResult {
     pub new_checkpoint: Checkpoint,
     /// Warnings encountered during parsing (non-fatal)
     pub warnings: Vec<String>,
+    /// Records that could not be parsed (also reported in [`Self::warnings`])
+    pub failed_records: Vec<FailedRecord>,
     /// Map of agentId -> spawning message seq (for linking agent threads)
     ///
     /// Populated when parsing main session files that contain Task tool results.
@@ -58,7 +106,23 @@ pub struct ParseResult {
     pub agent_spawn_map: HashMap<String, i64>,
 }
 
+impl ParseResult {
+    /// Report a line that could not be parsed, as a warning and as a
+    /// [`FailedRecord`] to quarantine.
+    pub fn record_failure(&mut self, line: i32, offset: u64, raw: &str, error: String) {
+        self.warnings
+            .push(format!("Line {} (offset {}): {}", line, offset, error));
+        self.failed_records.push(FailedRecord {
+            offset,
+            line: Some(line),
+            raw: raw.to_string(),
+            error,
+        });
+    }
+}
+
 /// Context passed to parser with file metadata and checkpoint info.
+#[derive(Serialize)]
 pub struct ParseContext<'a> {
     /// Path to the source file
     pub path: &'a Path,
@@ -91,11 +155,35 @@ pub trait AssistantParser: Send + Sync {
     /// Which assistant this parser handles
     fn assistant(&self) -> Assistant;
 
+    /// Name of the custom agent or parser plugin this parser reads.
+    ///
+    /// Parsers configured in `config.toml` all handle [`Assistant::Custom`];
+    /// the name tells their source files and sessions apart.
+    fn agent(&self) -> Option<&str> {
+        None
+    }
+
+    /// Version of this parser's interpretation of its source files.
+    ///
+    /// Recorded on each ingested source file. Bump it when the parser starts
+    /// extracting something new from records it already handled, so that
+    /// `aiobscura-sync --reparse --outdated` can find files that need
+    /// re-ingesting.
+    fn version(&self) -> u32 {
+        1
+    }
+
     /// Root directory for this assistant's data (e.g., ~/.claude)
     ///
     /// Returns `None` if the path cannot be determined (e.g., $HOME not set).
     fn root_path(&self) -> Option<PathBuf>;
 
+    /// Label and host of [`Self::root_path`], if this parser was configured
+    /// with one. Recorded in the metadata of every session it parses.
+    fn source_root(&self) -> Option<&SourceRoot> {
+        None
+    }
+
     /// Check if this assistant is installed (root path exists)
     fn is_installed(&self) -> bool {
         self.root_path().map(|p| p.exists()).unwrap_or(false)
@@ -133,52 +221,140 @@ pub trait AssistantParser: Send + Sync {
     /// For most assistants, this is the file stem (e.g., UUID from `{uuid}.jsonl`).
     fn extract_session_id(&self, file_path: &Path) -> Option<String>;
 
+    /// Directories to watch for changes in watch mode.
+    ///
+    /// Default implementation watches the longest literal directory prefix of
+    /// each pattern in [`Self::source_patterns`], recursively when a wildcard
+    /// directory follows it. Override this when that would cover too much
+    /// (e.g. a pattern rooted at `$HOME`); sources outside the returned paths
+    /// are still picked up by periodic full syncs.
+    fn watch_paths(&self) -> Vec<WatchPath> {
+        let Some(root) = self.root_path() else {
+            return vec![];
+        };
+        self.source_patterns()
+            .iter()
+            .map(|pattern| watch_path_for_pattern(&root, &pattern.pattern))
+            .collect()
+    }
+
     /// Discover all source files matching this parser's patterns.
     ///
     /// Default implementation uses glob patterns from [`Self::source_patterns`].
     fn discover_files(&self) -> Result<Vec<SourceFile>> {
-        let root = match self.root_path() {
-            Some(r) => r,
-            None => return Ok(vec![]),
-        };
+        glob_source_files(self)
+    }
+}
 
-        let mut files = Vec::new();
-
-        for pattern in self.source_patterns() {
-            let full_pattern = root.join(&pattern.pattern);
-            let pattern_str = full_pattern.to_string_lossy();
-
-            let entries = glob::glob(&pattern_str).map_err(|e| crate::error::Error::Parse {
-                agent: self.assistant().to_string(),
-                message: format!("Invalid glob pattern: {}", e),
-            })?;
-
-            for entry in entries.flatten() {
-                let metadata = std::fs::metadata(&entry).ok();
-                let now = chrono::Utc::now();
-                let (size, modified, created) = metadata
-                    .map(|m| {
-                        (
-                            m.len(),
-                            m.modified().ok().map(chrono::DateTime::from).unwrap_or(now),
-                            m.created().ok().map(chrono::DateTime::from).unwrap_or(now),
-                        )
-                    })
-                    .unwrap_or((0, now, now));
-
-                files.push(SourceFile {
-                    path: entry,
-                    file_type: pattern.file_type,
-                    assistant: self.assistant(),
-                    created_at: created,
-                    modified_at: modified,
-                    size_bytes: size,
-                    last_parsed_at: None,
-                    checkpoint: Checkpoint::None,
-                });
-            }
+/// Source files matching the glob patterns of `parser`.
+pub(crate) fn glob_source_files<P: AssistantParser + ?Sized>(
+    parser: &P,
+) -> Result<Vec<SourceFile>> {
+    let root = match parser.root_path() {
+        Some(r) => r,
+        None => return Ok(vec![]),
+    };
+
+    let mut files = Vec::new();
+
+    for pattern in parser.source_patterns() {
+        let full_pattern = root.join(&pattern.pattern);
+        let pattern_str = full_pattern.to_string_lossy();
+
+        let entries = glob::glob(&pattern_str).map_err(|e| crate::error::Error::Parse {
+            agent: parser.assistant().to_string(),
+            message: format!("Invalid glob pattern: {}", e),
+        })?;
+
+        for entry in entries.flatten() {
+            files.push(source_file(parser, entry, pattern.file_type));
         }
+    }
+
+    Ok(files)
+}
+
+/// A not yet parsed source file of `parser` at `path`, with its current
+/// size and timestamps.
+pub(crate) fn source_file<P: AssistantParser + ?Sized>(
+    parser: &P,
+    path: PathBuf,
+    file_type: FileType,
+) -> SourceFile {
+    let metadata = std::fs::metadata(&path).ok();
+    let now = chrono::Utc::now();
+    let (size, modified, created) = metadata
+        .map(|m| {
+            (
+                m.len(),
+                m.modified().ok().map(chrono::DateTime::from).unwrap_or(now),
+                m.created().ok().map(chrono::DateTime::from).unwrap_or(now),
+            )
+        })
+        .unwrap_or((0, now, now));
+
+    SourceFile {
+        path,
+        file_type,
+        assistant: parser.assistant(),
+        agent: parser.agent().map(String::from),
+        created_at: created,
+        modified_at: modified,
+        size_bytes: size,
+        last_parsed_at: None,
+        checkpoint: Checkpoint::None,
+        parser_version: parser.version(),
+    }
+}
+
+/// Derive the directory to watch for a glob pattern relative to `root`.
+fn watch_path_for_pattern(root: &Path, pattern: &str) -> WatchPath {
+    let components: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let literal = components
+        .iter()
+        .take_while(|c| !c.contains(['*', '?', '[']))
+        .count();
+
+    // The last component names the file itself, so watch its directory.
+    let dir_len = literal.min(components.len().saturating_sub(1));
+    let path = components[..dir_len]
+        .iter()
+        .fold(root.to_path_buf(), |path, c| path.join(c));
+
+    WatchPath {
+        path,
+        recursive: components.len() - dir_len > 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_path_for_pattern() {
+        let root = Path::new("/home/user/.codex");
 
-        Ok(files)
+        assert_eq!(
+            watch_path_for_pattern(root, "sessions/*/*/*/rollout-*.jsonl"),
+            WatchPath {
+                path: root.join("sessions"),
+                recursive: true,
+            }
+        );
+        assert_eq!(
+            watch_path_for_pattern(root, "globalStorage/state.vscdb"),
+            WatchPath {
+                path: root.join("globalStorage"),
+                recursive: false,
+            }
+        );
+        assert_eq!(
+            watch_path_for_pattern(root, "session-*.json"),
+            WatchPath {
+                path: root.to_path_buf(),
+                recursive: false,
+            }
+        );
     }
 }
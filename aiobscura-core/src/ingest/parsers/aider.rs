@@ -0,0 +1,1207 @@
+//! Aider markdown chat history parser
+//!
+//! Parses the per-repository transcripts Aider writes to
+//! `<repo>/.aider.chat.history.md`. The sibling `.aider.input.history` file is
+//! read alongside it to recover per-prompt timestamps, since the markdown
+//! transcript only timestamps the start of each session.
+//!
+//! See [`docs/aider-log-format.md`](../../../../docs/aider-log-format.md)
+//! for the format specification.
+//!
+//! # Discovery
+//!
+//! Aider has no central log directory: history files live in whichever
+//! repository Aider was launched from. The parser root defaults to the home
+//! directory and searches up to [`MAX_SEARCH_DEPTH`] directories below it
+//! (e.g. `~/dev/org/repo/.aider.chat.history.md`). That search is slow, so
+//! the files it finds are reused for [`RESCAN_INTERVAL`] before searching
+//! again.
+//!
+//! # Sessions
+//!
+//! A single history file accumulates many sessions, each introduced by a
+//! `# aider chat started at <local time>` header. Every header starts a new
+//! [`Session`] whose ID is derived from the file path and the header's byte
+//! offset, so IDs are stable across re-syncs. The most recent session is
+//! returned in [`ParseResult::session`]; earlier ones in
+//! [`ParseResult::extra_sessions`].
+//!
+//! # Message Mapping
+//!
+//! | Markdown | Message |
+//! |----------|---------|
+//! | `#### text` | Human [`MessageType::Prompt`] |
+//! | `#### /run cmd`, `#### !cmd`, `#### /test cmd` | Human [`MessageType::ToolCall`] (`Bash`) |
+//! | `> ...` after a run command | Tool [`MessageType::ToolResult`] (`Bash`) |
+//! | Plain text | Assistant [`MessageType::Response`] |
+//! | `SEARCH/REPLACE` or unified diff block in a response | Assistant [`MessageType::ToolCall`] (`Edit`) |
+//! | `> Applied edit to ...` after edits | Tool [`MessageType::ToolResult`] (`Edit`) |
+//! | Other `> ...` output | System [`MessageType::Context`] |
+//!
+//! Tool names and inputs follow Claude Code's `Bash`/`Edit` shapes so that
+//! cross-assistant file and tool analytics work without special cases.
+//!
+//! # Error Handling
+//!
+//! - **Invalid UTF-8**: Decoded lossily; byte offsets still refer to the file.
+//! - **Unparseable timestamps**: The session falls back to the file's
+//!   modification time and a warning is recorded in [`ParseResult::warnings`].
+//! - **Missing input history**: Prompts inherit the session start time.
+//! - **File truncation detected**: When the checkpoint offset exceeds the current
+//!   file size, the parser resets to offset 0 and re-parses from the beginning.
+//!
+//! # Incremental Parsing
+//!
+//! The parser uses [`Checkpoint::ByteOffset`] pointing just past the last
+//! complete line. On resume it replays the session containing the checkpoint
+//! from its header (to recover the session ID and model) but only emits
+//! messages that start at or after the checkpoint. Lines appended to a block
+//! that started before the checkpoint are emitted as a continuation message.
+//!
+//! A message's seq is the line number it starts on (bumped past the previous
+//! message when two start on one line), so seqs grow through the file and do
+//! not depend on where parses happened to stop. A continuation gets the seq
+//! of its first line. Token usage reported after its response was emitted
+//! by an earlier parse is recorded on the `> Tokens:` message itself.
+
+use crate::error::{Error, Result};
+use crate::ingest::parser::{
+    self, AssistantParser, ParseContext, ParseResult, SourcePattern, WatchPath,
+};
+use crate::types::{
+    Assistant, AuthorRole, Checkpoint, FileType, Message, MessageType, Project, Session,
+    SessionStatus, SourceFile, Thread, ThreadType,
+};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// File name of Aider's markdown chat transcript.
+const CHAT_HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// File name of Aider's prompt input history (used for prompt timestamps).
+const INPUT_HISTORY_FILE: &str = ".aider.input.history";
+
+/// Header line that starts a new session in the chat history.
+const SESSION_HEADER: &str = "# aider chat started at ";
+
+/// How many directory levels below the root to search for history files.
+const MAX_SEARCH_DEPTH: usize = 3;
+
+/// How long the history files found below the root are reused before the
+/// root is searched again.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Parser for Aider markdown chat history files.
+pub struct AiderParser {
+    root: Option<PathBuf>,
+    /// History files found by the last search of the root, and when
+    discovered: Mutex<Option<(Instant, Vec<PathBuf>)>>,
+}
+
+impl AiderParser {
+    /// Create a new parser rooted at the home directory.
+    pub fn new() -> Self {
+        Self {
+            root: dirs::home_dir(),
+            discovered: Mutex::new(None),
+        }
+    }
+
+    /// Create a parser with a custom root path (for testing).
+    pub fn with_root(root: PathBuf) -> Self {
+        Self {
+            root: Some(root),
+            discovered: Mutex::new(None),
+        }
+    }
+}
+
+impl Default for AiderParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================
+// Markdown blocks
+// ============================================
+
+/// Kind of a contiguous run of lines in the chat history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    /// `#### ` prefixed user input
+    User,
+    /// `> ` prefixed tool output / status lines
+    Quote,
+    /// Unprefixed assistant response text
+    Assistant,
+}
+
+impl BlockKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BlockKind::User => "user",
+            BlockKind::Quote => "quote",
+            BlockKind::Assistant => "assistant",
+        }
+    }
+}
+
+/// A contiguous run of lines of the same kind.
+struct Block {
+    kind: BlockKind,
+    /// Byte offset of the first line
+    offset: u64,
+    /// 1-based line number of the first line
+    line: i32,
+    /// Line contents with the markdown prefix stripped
+    lines: Vec<String>,
+    /// Byte offset of each line
+    offsets: Vec<u64>,
+}
+
+impl Block {
+    fn text(&self) -> String {
+        Self::join(&self.lines)
+    }
+
+    fn join(lines: &[String]) -> String {
+        lines.join("\n").trim().to_string()
+    }
+}
+
+/// What the next `> ` block is expected to report on.
+enum PendingTool {
+    /// A shell command issued via `/run`, `/test` or `!`
+    Run { command: String },
+    /// Edit blocks proposed in the preceding response
+    Edits { files: Vec<String> },
+}
+
+/// Per-session parse state.
+struct SessionState {
+    id: String,
+    thread_id: String,
+    started_at: DateTime<Utc>,
+    current_time: DateTime<Utc>,
+    last_activity: DateTime<Utc>,
+    seq: i32,
+    /// Whether any part of this session lies at or after the resume offset
+    emitted: bool,
+    model: Option<String>,
+    edit_format: Option<String>,
+    aider_version: Option<String>,
+    command_line: Option<String>,
+    pending_tool: Option<PendingTool>,
+    /// Index into `ParseResult::messages` of the last assistant response
+    last_response_idx: Option<usize>,
+}
+
+/// A prompt recorded in `.aider.input.history`.
+struct InputEntry {
+    timestamp: DateTime<Utc>,
+    text: String,
+}
+
+/// An edit proposed inside an assistant response.
+struct EditBlock {
+    file_path: String,
+    line_idx: usize,
+    input: serde_json::Value,
+    format: &'static str,
+}
+
+// ============================================
+// Helper functions
+// ============================================
+
+/// Parse an Aider local timestamp (`2024-05-01 10:23:45[.123456]`).
+fn parse_local_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parse an abbreviated token count such as `150`, `2.1k` or `1.2M`.
+fn parse_token_count(s: &str) -> Option<i32> {
+    let s = s.trim();
+    let (number, multiplier) = if let Some(n) = s.strip_suffix(['k', 'K']) {
+        (n, 1_000.0)
+    } else if let Some(n) = s.strip_suffix(['m', 'M']) {
+        (n, 1_000_000.0)
+    } else {
+        (s, 1.0)
+    };
+    let value: f64 = number.replace(',', "").parse().ok()?;
+    Some((value * multiplier).round() as i32)
+}
+
+/// Extract `(sent, received)` from a `Tokens: 2.1k sent, 150 received.` line.
+fn parse_token_line(line: &str) -> Option<(Option<i32>, Option<i32>)> {
+    let rest = line.trim().strip_prefix("Tokens:")?;
+    let rest = rest.split("Cost:").next().unwrap_or(rest);
+
+    let mut sent = None;
+    let mut received = None;
+    for part in rest.split(',') {
+        let mut words = part.trim().trim_end_matches('.').split_whitespace();
+        let (Some(count), Some(label)) = (words.next(), words.next()) else {
+            continue;
+        };
+        match label {
+            "sent" => sent = parse_token_count(count),
+            "received" => received = parse_token_count(count),
+            _ => {}
+        }
+    }
+
+    if sent.is_none() && received.is_none() {
+        None
+    } else {
+        Some((sent, received))
+    }
+}
+
+/// Split a user command into `(command, argument)`, e.g. `("/run", "pytest")`.
+fn split_command(text: &str) -> Option<(&str, &str)> {
+    if let Some(cmd) = text.strip_prefix('!') {
+        return Some(("!", cmd.trim()));
+    }
+    if !text.starts_with('/') {
+        return None;
+    }
+    let (cmd, arg) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    Some((cmd, arg.trim()))
+}
+
+/// Clean a filename line preceding an edit block (strips markdown decoration).
+fn clean_file_name(line: &str) -> String {
+    line.trim()
+        .trim_matches(|c| c == '`' || c == '*' || c == ':')
+        .trim()
+        .to_string()
+}
+
+/// Find SEARCH/REPLACE and unified diff edit blocks in a response.
+fn extract_edit_blocks(lines: &[String]) -> Vec<EditBlock> {
+    let mut edits = Vec::new();
+    let mut last_candidate: Option<String> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+
+        if line.trim_start().starts_with("<<<<<<< SEARCH") {
+            let start = i;
+            let mut old = Vec::new();
+            let mut new = Vec::new();
+            let mut in_replace = false;
+            i += 1;
+            while i < lines.len() {
+                let l = lines[i].trim_end();
+                if l.trim_start().starts_with(">>>>>>> REPLACE") {
+                    break;
+                }
+                if !in_replace && l.trim() == "=======" {
+                    in_replace = true;
+                } else if in_replace {
+                    new.push(l);
+                } else {
+                    old.push(l);
+                }
+                i += 1;
+            }
+            let file_path = last_candidate.clone().unwrap_or_default();
+            edits.push(EditBlock {
+                input: serde_json::json!({
+                    "file_path": file_path,
+                    "old_string": old.join("\n"),
+                    "new_string": new.join("\n"),
+                }),
+                file_path,
+                line_idx: start,
+                format: "search_replace",
+            });
+        } else if line.trim_start().starts_with("```diff") {
+            let start = i;
+            let mut diff = Vec::new();
+            let mut file_path = None;
+            i += 1;
+            while i < lines.len() {
+                let l = lines[i].trim_end();
+                if l.trim_start().starts_with("```") {
+                    break;
+                }
+                if let Some(path) = l.strip_prefix("+++ ") {
+                    let path = path.trim();
+                    file_path = Some(path.strip_prefix("b/").unwrap_or(path).to_string());
+                }
+                diff.push(l);
+                i += 1;
+            }
+            if let Some(file_path) = file_path {
+                edits.push(EditBlock {
+                    input: serde_json::json!({
+                        "file_path": file_path,
+                        "diff": diff.join("\n"),
+                    }),
+                    file_path,
+                    line_idx: start,
+                    format: "udiff",
+                });
+            }
+        } else if !line.trim().is_empty() && !line.trim_start().starts_with("```") {
+            last_candidate = Some(clean_file_name(line));
+        }
+
+        i += 1;
+    }
+
+    edits
+}
+
+/// Read `.aider.input.history` next to the chat history, if present.
+fn load_input_history(chat_path: &Path) -> Vec<InputEntry> {
+    let Some(dir) = chat_path.parent() else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read(dir.join(INPUT_HISTORY_FILE)) else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<InputEntry> = Vec::new();
+    for line in String::from_utf8_lossy(&content).lines() {
+        if let Some(ts) = line.strip_prefix("# ") {
+            if let Some(timestamp) = parse_local_timestamp(ts) {
+                entries.push(InputEntry {
+                    timestamp,
+                    text: String::new(),
+                });
+            }
+        } else if let Some(text) = line.strip_prefix('+') {
+            if let Some(entry) = entries.last_mut() {
+                if !entry.text.is_empty() {
+                    entry.text.push('\n');
+                }
+                entry.text.push_str(text);
+            }
+        }
+    }
+    entries
+}
+
+/// Map an Aider model name to a backing model ID (`provider:model`).
+fn backing_model_id(model: &str) -> String {
+    if let Some((provider, name)) = model.split_once('/') {
+        return format!("{}:{}", provider, name);
+    }
+    let provider = if model.starts_with("claude") {
+        "anthropic"
+    } else if model.starts_with("gpt")
+        || model.starts_with("o1")
+        || model.starts_with("o3")
+        || model.starts_with("o4")
+    {
+        "openai"
+    } else if model.starts_with("gemini") {
+        "google"
+    } else if model.starts_with("deepseek") {
+        "deepseek"
+    } else {
+        "unknown"
+    };
+    format!("{}:{}", provider, model)
+}
+
+impl AssistantParser for AiderParser {
+    fn assistant(&self) -> Assistant {
+        Assistant::Aider
+    }
+
+    fn root_path(&self) -> Option<PathBuf> {
+        self.root.clone()
+    }
+
+    fn source_patterns(&self) -> Vec<SourcePattern> {
+        (0..=MAX_SEARCH_DEPTH)
+            .map(|depth| SourcePattern {
+                pattern: format!("{}{}", "*/".repeat(depth), CHAT_HISTORY_FILE),
+                file_type: FileType::Markdown,
+                description: "Aider chat history".to_string(),
+            })
+            .collect()
+    }
+
+    fn watch_paths(&self) -> Vec<WatchPath> {
+        // A recursive watch on $HOME would register every directory below it,
+        // so only the root itself is watched. Deeper history files are picked
+        // up by periodic full syncs, once they are discovered.
+        self.root
+            .iter()
+            .map(|root| WatchPath {
+                path: root.clone(),
+                recursive: false,
+            })
+            .collect()
+    }
+
+    fn discover_files(&self) -> Result<Vec<SourceFile>> {
+        let cached = self.discovered.lock().ok().and_then(|discovered| {
+            discovered
+                .as_ref()
+                .filter(|(at, _)| at.elapsed() < RESCAN_INTERVAL)
+                .map(|(_, paths)| paths.clone())
+        });
+        if let Some(paths) = cached {
+            return Ok(paths
+                .into_iter()
+                .filter(|path| path.is_file())
+                .map(|path| parser::source_file(self, path, FileType::Markdown))
+                .collect());
+        }
+
+        let files = parser::glob_source_files(self)?;
+        if let Ok(mut discovered) = self.discovered.lock() {
+            let paths = files.iter().map(|file| file.path.clone()).collect();
+            *discovered = Some((Instant::now(), paths));
+        }
+        Ok(files)
+    }
+
+    fn parse(&self, ctx: &ParseContext) -> Result<ParseResult> {
+        let mut result = ParseResult::default();
+
+        let bytes = std::fs::read(ctx.path).map_err(|e| {
+            Error::Io(std::io::Error::new(
+                e.kind(),
+                format!("Failed to open {}: {}", ctx.path.display(), e),
+            ))
+        })?;
+        let file_size = bytes.len() as u64;
+
+        // Determine start offset from checkpoint
+        let start_offset = match ctx.checkpoint {
+            Checkpoint::ByteOffset { offset } => {
+                if *offset > file_size {
+                    result.warnings.push(format!(
+                        "File truncated: checkpoint {} > file size {}, starting from beginning",
+                        offset, file_size
+                    ));
+                    0
+                } else {
+                    *offset
+                }
+            }
+            _ => 0,
+        };
+
+        // Only consume complete lines; a partially written line is picked up next sync
+        let complete_len = bytes
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map(|i| i as u64 + 1)
+            .unwrap_or(0);
+
+        if start_offset >= complete_len {
+            result.new_checkpoint = Checkpoint::ByteOffset {
+                offset: start_offset,
+            };
+            return Ok(result);
+        }
+
+        // Split into (offset, line) pairs
+        let mut lines: Vec<(u64, String)> = Vec::new();
+        let mut offset = 0u64;
+        for raw in bytes[..complete_len as usize].split(|&b| b == b'\n') {
+            if offset >= complete_len {
+                break;
+            }
+            let line = String::from_utf8_lossy(raw);
+            lines.push((offset, line.trim_end_matches('\r').to_string()));
+            offset += raw.len() as u64 + 1;
+        }
+
+        // Replay from the header of the session containing the checkpoint
+        let replay_from = lines
+            .iter()
+            .position(|(o, _)| *o >= start_offset)
+            .unwrap_or(lines.len());
+        let replay_from = lines[..replay_from]
+            .iter()
+            .rposition(|(_, l)| l.starts_with(SESSION_HEADER))
+            .unwrap_or(0);
+
+        let input_history = load_input_history(ctx.path);
+        let mut input_idx = 0usize;
+
+        let repo_dir = ctx.path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let repo_dir_str = repo_dir.to_string_lossy().to_string();
+        let source_path = ctx.path.to_string_lossy().to_string();
+        let observed_at = Utc::now();
+
+        let mut sessions: Vec<SessionState> = Vec::new();
+        let mut block: Option<Block> = None;
+        let mut in_fence = false;
+
+        for (idx, (line_offset, line)) in lines.iter().enumerate().skip(replay_from) {
+            let line_number = idx as i32 + 1;
+
+            if let Some(ts) = line.strip_prefix(SESSION_HEADER) {
+                in_fence = false;
+                if let Some(b) = block.take() {
+                    if let Some(state) = sessions.last_mut() {
+                        self.handle_block(
+                            state,
+                            b,
+                            start_offset,
+                            &repo_dir,
+                            &source_path,
+                            observed_at,
+                            &input_history,
+                            &mut input_idx,
+                            &mut result,
+                        );
+                    }
+                }
+
+                let started_at = parse_local_timestamp(ts).unwrap_or_else(|| {
+                    result.warnings.push(format!(
+                        "Line {} (offset {}): invalid session timestamp: {}",
+                        line_number, line_offset, ts
+                    ));
+                    ctx.modified_at
+                });
+                sessions.push(Self::new_session(
+                    &source_path,
+                    *line_offset,
+                    started_at,
+                    *line_offset >= start_offset,
+                ));
+                continue;
+            }
+
+            // Inside a fenced code block every line belongs to the response
+            // (SEARCH/REPLACE markers such as `>>>>>>> REPLACE` look like quotes)
+            let (kind, content) = if in_fence {
+                (Some(BlockKind::Assistant), line.as_str())
+            } else if let Some(rest) = line.strip_prefix("####") {
+                (
+                    Some(BlockKind::User),
+                    rest.strip_prefix(' ').unwrap_or(rest),
+                )
+            } else if let Some(rest) = line.strip_prefix('>') {
+                (
+                    Some(BlockKind::Quote),
+                    rest.strip_prefix(' ').unwrap_or(rest).trim_end(),
+                )
+            } else if line.trim().is_empty() {
+                (None, "")
+            } else {
+                (Some(BlockKind::Assistant), line.as_str())
+            };
+
+            if kind == Some(BlockKind::Assistant) && line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+            }
+
+            // Blank lines never end a block; they only separate paragraphs
+            let Some(kind) = kind else {
+                if let Some(b) = block.as_mut() {
+                    if b.kind == BlockKind::Assistant {
+                        b.lines.push(String::new());
+                        b.offsets.push(*line_offset);
+                    }
+                }
+                continue;
+            };
+
+            if block.as_ref().is_some_and(|b| b.kind != kind) {
+                if let Some(b) = block.take() {
+                    if let Some(state) = sessions.last_mut() {
+                        self.handle_block(
+                            state,
+                            b,
+                            start_offset,
+                            &repo_dir,
+                            &source_path,
+                            observed_at,
+                            &input_history,
+                            &mut input_idx,
+                            &mut result,
+                        );
+                    }
+                }
+            }
+
+            // Content before the first header (very old files) gets an implicit session
+            if sessions.is_empty() {
+                sessions.push(Self::new_session(
+                    &source_path,
+                    0,
+                    ctx.modified_at,
+                    start_offset == 0,
+                ));
+            }
+
+            match block.as_mut() {
+                Some(b) => {
+                    b.lines.push(content.to_string());
+                    b.offsets.push(*line_offset);
+                }
+                None => {
+                    block = Some(Block {
+                        kind,
+                        offset: *line_offset,
+                        line: line_number,
+                        lines: vec![content.to_string()],
+                        offsets: vec![*line_offset],
+                    })
+                }
+            }
+        }
+
+        if let Some(b) = block.take() {
+            if let Some(state) = sessions.last_mut() {
+                self.handle_block(
+                    state,
+                    b,
+                    start_offset,
+                    &repo_dir,
+                    &source_path,
+                    observed_at,
+                    &input_history,
+                    &mut input_idx,
+                    &mut result,
+                );
+            }
+        }
+
+        // Emit sessions and threads touched by this parse
+        let mut emitted: Vec<Session> = Vec::new();
+        for state in sessions.into_iter().filter(|s| s.emitted) {
+            result.threads.push(Thread {
+                id: state.thread_id.clone(),
+                session_id: state.id.clone(),
+                thread_type: ThreadType::Main,
+                parent_thread_id: None,
+                spawned_by_message_id: None,
+                started_at: state.started_at,
+                ended_at: None,
+                last_activity_at: Some(state.last_activity),
+                metadata: serde_json::json!({}),
+            });
+
+            emitted.push(Session {
+                id: state.id,
+                assistant: Assistant::Aider,
+                backing_model_id: state.model.as_deref().map(backing_model_id),
+                project_id: Some(Self::generate_project_id(&repo_dir_str)),
+                started_at: state.started_at,
+                last_activity_at: Some(state.last_activity),
+                status: SessionStatus::from_last_activity(Some(state.last_activity)),
+                source_file_path: source_path.clone(),
+                metadata: serde_json::json!({
+                    "cwd": repo_dir_str,
+                    "model": state.model,
+                    "edit_format": state.edit_format,
+                    "aider_version": state.aider_version,
+                    "command_line": state.command_line,
+                }),
+            });
+        }
+
+        if let (Some(first), Some(last)) = (emitted.first(), emitted.last()) {
+            result.project = Some(Project {
+                id: Self::generate_project_id(&repo_dir_str),
+                path: repo_dir.clone(),
+                name: Some(Self::extract_dir_name(&repo_dir_str)),
+                created_at: first.started_at,
+                last_activity_at: last.last_activity_at,
+                metadata: serde_json::json!({}),
+            });
+        }
+
+        result.session = emitted.pop();
+        result.extra_sessions = emitted;
+        result.new_checkpoint = Checkpoint::ByteOffset {
+            offset: complete_len,
+        };
+
+        Ok(result)
+    }
+
+    fn extract_project_path(&self, file_path: &Path) -> Option<PathBuf> {
+        // History files live at the repository root
+        file_path.parent().map(Path::to_path_buf)
+    }
+
+    fn extract_session_id(&self, file_path: &Path) -> Option<String> {
+        // A history file holds many sessions; IDs come from session headers
+        let _ = file_path;
+        None
+    }
+}
+
+impl AiderParser {
+    fn new_session(
+        source_path: &str,
+        header_offset: u64,
+        started_at: DateTime<Utc>,
+        emitted: bool,
+    ) -> SessionState {
+        let id = Self::generate_session_id(source_path, header_offset);
+        SessionState {
+            thread_id: format!("{}-main", id),
+            id,
+            started_at,
+            current_time: started_at,
+            last_activity: started_at,
+            seq: 0,
+            emitted,
+            model: None,
+            edit_format: None,
+            aider_version: None,
+            command_line: None,
+            pending_tool: None,
+            last_response_idx: None,
+        }
+    }
+
+    /// Convert a block into messages, emitting only those at or after `start_offset`.
+    ///
+    /// A block that straddles `start_offset` was emitted in part by the
+    /// previous parse; only its lines from `start_offset` on are emitted.
+    #[allow(clippy::too_many_arguments)]
+    fn handle_block(
+        &self,
+        state: &mut SessionState,
+        block: Block,
+        start_offset: u64,
+        repo_dir: &Path,
+        source_path: &str,
+        observed_at: DateTime<Utc>,
+        input_history: &[InputEntry],
+        input_idx: &mut usize,
+        result: &mut ParseResult,
+    ) {
+        let emit = block.offset >= start_offset;
+        if emit {
+            state.emitted = true;
+        }
+        let text = block.text();
+        let raw_data = serde_json::json!({
+            "kind": block.kind.as_str(),
+            "text": block.lines.join("\n"),
+        });
+
+        // A message's seq is the line it starts on (see the module docs)
+        let base = |state: &mut SessionState, line: i32| {
+            state.seq = line.max(state.seq + 1);
+            Message {
+                id: 0,
+                session_id: state.id.clone(),
+                thread_id: state.thread_id.clone(),
+                seq: state.seq,
+                emitted_at: state.current_time,
+                observed_at,
+                author_role: AuthorRole::System,
+                author_name: None,
+                message_type: MessageType::Context,
+                content: None,
+                content_type: None,
+                tool_name: None,
+                tool_input: None,
+                tool_result: None,
+                tool_call_id: None,
+                tokens_in: None,
+                tokens_out: None,
+                tokens_cache_read: None,
+                tokens_cache_write: None,
+                tokens_reasoning: None,
+                duration_ms: None,
+                source_file_path: source_path.to_string(),
+                source_offset: block.offset as i64,
+                source_line: Some(line),
+                record_id: None,
+                parent_record_id: None,
+                raw_data: raw_data.clone(),
+                metadata: serde_json::json!({}),
+            }
+        };
+
+        let mut messages: Vec<Message> = Vec::new();
+
+        match block.kind {
+            BlockKind::User => {
+                // Recover the prompt time from the input history
+                if let Some(found) = input_history[*input_idx..]
+                    .iter()
+                    .position(|e| e.timestamp >= state.started_at && e.text.trim() == text.as_str())
+                {
+                    *input_idx += found + 1;
+                    state.current_time = input_history[*input_idx - 1].timestamp;
+                }
+                state.pending_tool = None;
+
+                let mut msg = base(state, block.line);
+                msg.emitted_at = state.current_time;
+                msg.author_role = AuthorRole::Human;
+                msg.content = Some(text.clone());
+
+                match split_command(&text) {
+                    Some((cmd @ ("/run" | "/test" | "!"), command)) => {
+                        msg.message_type = MessageType::ToolCall;
+                        msg.tool_name = Some("Bash".to_string());
+                        msg.tool_input = Some(serde_json::json!({ "command": command }));
+                        msg.metadata = serde_json::json!({ "aider_command": cmd });
+                        state.pending_tool = Some(PendingTool::Run {
+                            command: command.to_string(),
+                        });
+                    }
+                    Some(("/ask" | "/code" | "/architect", _)) | None => {
+                        msg.message_type = MessageType::Prompt;
+                    }
+                    Some((cmd, _)) => {
+                        msg.message_type = MessageType::Context;
+                        msg.author_name = Some(cmd.to_string());
+                        msg.metadata = serde_json::json!({ "aider_command": cmd });
+                    }
+                }
+                messages.push(msg);
+            }
+
+            BlockKind::Assistant => {
+                let mut msg = base(state, block.line);
+                msg.author_role = AuthorRole::Assistant;
+                msg.message_type = MessageType::Response;
+                msg.content = Some(text.clone());
+                messages.push(msg);
+
+                let edits = extract_edit_blocks(&block.lines);
+                let mut files = Vec::new();
+                for edit in edits {
+                    let absolute = repo_dir.join(&edit.file_path);
+                    let mut input = edit.input;
+                    input["file_path"] =
+                        serde_json::Value::String(absolute.to_string_lossy().to_string());
+
+                    let mut call = base(state, block.line + edit.line_idx as i32);
+                    call.author_role = AuthorRole::Assistant;
+                    call.message_type = MessageType::ToolCall;
+                    call.tool_name = Some("Edit".to_string());
+                    call.tool_input = Some(input);
+                    call.metadata = serde_json::json!({ "edit_format": edit.format });
+                    messages.push(call);
+                    files.push(edit.file_path);
+                }
+                state.pending_tool = if files.is_empty() {
+                    None
+                } else {
+                    Some(PendingTool::Edits { files })
+                };
+            }
+
+            BlockKind::Quote => {
+                // Session banner: pick up model and version details
+                if state.seq == 0 {
+                    for line in &block.lines {
+                        if let Some(model) = line
+                            .strip_prefix("Main model: ")
+                            .or_else(|| line.strip_prefix("Model: "))
+                        {
+                            let mut parts = model.split(" with ");
+                            state.model = parts.next().map(|m| m.trim().to_string());
+                            state.edit_format = parts.next().map(|f| {
+                                let f = f.split(',').next().unwrap_or(f).trim();
+                                f.trim_end_matches(" edit format").to_string()
+                            });
+                        } else if let Some(version) = line.strip_prefix("Aider v") {
+                            state.aider_version = Some(version.trim().to_string());
+                        } else if state.command_line.is_none() && line.contains("aider") {
+                            state.command_line = Some(line.trim().to_string());
+                        }
+                    }
+                }
+
+                let mut msg = base(state, block.line);
+
+                // Token usage belongs to the preceding response. A response
+                // emitted by an earlier parse can't be updated, so the usage
+                // stays on this message instead.
+                if let Some((sent, received)) =
+                    block.lines.iter().rev().find_map(|l| parse_token_line(l))
+                {
+                    match state
+                        .last_response_idx
+                        .and_then(|i| result.messages.get_mut(i))
+                    {
+                        Some(resp) => {
+                            resp.tokens_in = sent;
+                            resp.tokens_out = received;
+                        }
+                        None => {
+                            msg.tokens_in = sent;
+                            msg.tokens_out = received;
+                        }
+                    }
+                }
+
+                match state.pending_tool.take() {
+                    Some(PendingTool::Run { command }) => {
+                        msg.author_role = AuthorRole::Tool;
+                        msg.message_type = MessageType::ToolResult;
+                        msg.tool_name = Some("Bash".to_string());
+                        msg.tool_result = Some(text.clone());
+                        msg.metadata = serde_json::json!({ "command": command });
+                    }
+                    Some(PendingTool::Edits { files })
+                        if block.lines.iter().any(|l| l.starts_with("Applied edit to")) =>
+                    {
+                        msg.author_role = AuthorRole::Tool;
+                        msg.message_type = MessageType::ToolResult;
+                        msg.tool_name = Some("Edit".to_string());
+                        msg.tool_result = Some(text.clone());
+                        msg.metadata = serde_json::json!({ "files": files });
+                    }
+                    _ => {
+                        msg.author_name = Some("aider".to_string());
+                        msg.content = Some(text.clone());
+                    }
+                }
+                messages.push(msg);
+            }
+        }
+
+        state.last_activity = state.last_activity.max(state.current_time);
+
+        let messages = if emit {
+            messages
+        } else {
+            match block.offsets.iter().position(|o| *o >= start_offset) {
+                Some(split) => Self::continuation(&block, split, messages),
+                None => Vec::new(),
+            }
+        };
+        if let Some(last) = messages.last() {
+            state.emitted = true;
+            state.seq = state.seq.max(last.seq);
+        }
+
+        if emit || !messages.is_empty() {
+            for msg in messages {
+                if msg.message_type == MessageType::Response {
+                    state.last_response_idx = Some(result.messages.len());
+                }
+                result.messages.push(msg);
+            }
+        } else if block.kind == BlockKind::Assistant {
+            state.last_response_idx = None;
+        }
+    }
+
+    /// Messages for the lines of `block` from index `split` on, whose
+    /// earlier lines were emitted by a previous parse.
+    ///
+    /// The block's text continues in a message with the seq of the line
+    /// it starts on. Edits are emitted if they were not complete before
+    /// `split`, and token usage if it was not reported before `split`.
+    fn continuation(block: &Block, split: usize, mut messages: Vec<Message>) -> Vec<Message> {
+        if messages.is_empty() {
+            return messages;
+        }
+        let edits = messages.split_off(1);
+        let mut continued = messages.remove(0);
+        let mut result: Vec<Message> = Vec::new();
+
+        let text = Block::join(&block.lines[split..]);
+        if !text.is_empty() {
+            let line = block.line + split as i32;
+            continued.seq = line;
+            continued.source_offset = block.offsets[split] as i64;
+            continued.source_line = Some(line);
+            continued.raw_data["text"] = serde_json::Value::String(block.lines[split..].join("\n"));
+            if continued.tool_result.is_some() {
+                continued.tool_result = Some(text);
+            } else {
+                continued.content = Some(text);
+            }
+            if block.lines[..split]
+                .iter()
+                .any(|l| parse_token_line(l).is_some())
+            {
+                continued.tokens_in = None;
+                continued.tokens_out = None;
+            }
+            result.push(continued);
+        }
+
+        let seen = extract_edit_blocks(&block.lines[..split]).len();
+        for mut edit in edits.into_iter().skip(seen) {
+            // An edit starting on the continued line follows it
+            if let Some(prev) = result.last() {
+                edit.seq = edit.seq.max(prev.seq + 1);
+            }
+            result.push(edit);
+        }
+        result
+    }
+
+    /// Generate a stable session ID from the file path and session header offset.
+    fn generate_session_id(source_path: &str, header_offset: u64) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(source_path.as_bytes());
+        hasher.update(b":");
+        hasher.update(header_offset.to_string().as_bytes());
+        let hash = hasher.finalize();
+        format!("aider-{}", &format!("{:x}", hash)[..16])
+    }
+
+    /// Generate a deterministic project ID from the path using SHA256.
+    fn generate_project_id(path: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(path.as_bytes());
+        let hash = hasher.finalize();
+        format!("{:x}", hash)[..16].to_string()
+    }
+
+    /// Extract the directory name from a path for use as project name.
+    fn extract_dir_name(path: &str) -> String {
+        Path::new(path)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_patterns() {
+        let parser = AiderParser::new();
+        let patterns = parser.source_patterns();
+
+        assert_eq!(patterns.len(), MAX_SEARCH_DEPTH + 1);
+        assert_eq!(patterns[0].pattern, ".aider.chat.history.md");
+        assert_eq!(patterns[2].pattern, "*/*/.aider.chat.history.md");
+        assert!(patterns.iter().all(|p| p.file_type == FileType::Markdown));
+    }
+
+    #[test]
+    fn test_watch_paths_do_not_recurse_into_root() {
+        let parser = AiderParser::with_root(PathBuf::from("/home/user"));
+        assert_eq!(
+            parser.watch_paths(),
+            vec![WatchPath {
+                path: PathBuf::from("/home/user"),
+                recursive: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_discovered_files_are_reused_until_rescan() {
+        let temp = tempfile::tempdir().unwrap();
+        let first = temp.path().join("a").join(CHAT_HISTORY_FILE);
+        std::fs::create_dir_all(first.parent().unwrap()).unwrap();
+        std::fs::write(&first, "").unwrap();
+
+        let parser = AiderParser::with_root(temp.path().to_path_buf());
+        assert_eq!(parser.discover_files().unwrap().len(), 1);
+
+        // Found by the next search of the root, not before
+        let second = temp.path().join("b/c").join(CHAT_HISTORY_FILE);
+        std::fs::create_dir_all(second.parent().unwrap()).unwrap();
+        std::fs::write(&second, "#### hi\n").unwrap();
+        std::fs::remove_file(&first).unwrap();
+        assert!(parser.discover_files().unwrap().is_empty());
+
+        *parser.discovered.lock().unwrap() = None;
+        let files = parser.discover_files().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, second);
+        assert_eq!(files[0].size_bytes, 8);
+    }
+
+    #[test]
+    fn test_assistant_type() {
+        let parser = AiderParser::new();
+        assert_eq!(parser.assistant(), Assistant::Aider);
+    }
+
+    #[test]
+    fn test_with_root() {
+        let custom_root = PathBuf::from("/custom/path");
+        let parser = AiderParser::with_root(custom_root.clone());
+        assert_eq!(parser.root_path(), Some(custom_root));
+    }
+
+    #[test]
+    fn test_parse_token_line() {
+        assert_eq!(
+            parse_token_line("Tokens: 2.1k sent, 150 received. Cost: $0.01 message."),
+            Some((Some(2100), Some(150)))
+        );
+        assert_eq!(
+            parse_token_line("Tokens: 12k sent, 3.4k cache hit, 1.2k received."),
+            Some((Some(12000), Some(1200)))
+        );
+        assert_eq!(parse_token_line("Applied edit to main.py"), None);
+    }
+
+    #[test]
+    fn test_extract_edit_blocks() {
+        let lines: Vec<String> = [
+            "Here is the change:",
+            "",
+            "src/main.py",
+            "```python",
+            "<<<<<<< SEARCH",
+            "print('a')",
+            "=======",
+            "print('b')",
+            ">>>>>>> REPLACE",
+            "```",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let edits = extract_edit_blocks(&lines);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].file_path, "src/main.py");
+        assert_eq!(edits[0].line_idx, 4);
+        assert_eq!(edits[0].input["old_string"], "print('a')");
+        assert_eq!(edits[0].input["new_string"], "print('b')");
+    }
+
+    #[test]
+    fn test_backing_model_id() {
+        assert_eq!(backing_model_id("gpt-4o"), "openai:gpt-4o");
+        assert_eq!(
+            backing_model_id("claude-3-5-sonnet-20241022"),
+            "anthropic:claude-3-5-sonnet-20241022"
+        );
+        assert_eq!(
+            backing_model_id("openrouter/anthropic/claude-3.5-sonnet"),
+            "openrouter:anthropic/claude-3.5-sonnet"
+        );
+    }
+
+    #[test]
+    fn test_session_id_is_stable() {
+        let a = AiderParser::generate_session_id("/repo/.aider.chat.history.md", 0);
+        let b = AiderParser::generate_session_id("/repo/.aider.chat.history.md", 0);
+        let c = AiderParser::generate_session_id("/repo/.aider.chat.history.md", 120);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.starts_with("aider-"));
+    }
+}
@@ -87,6 +87,8 @@ struct RawRecord {
     // Common fields
     uuid: Option<String>,
     parent_uuid: Option<String>,
+    // Set on compact boundaries, whose parentUuid is null
+    logical_parent_uuid: Option<String>,
     session_id: Option<String>,
     #[serde(rename = "type")]
     record_type: Option<String>,
@@ -108,6 +110,17 @@ struct RawRecord {
 
     // Tool result (for user messages)
     tool_use_result: Option<serde_json::Value>,
+
+    // Context compaction
+    is_compact_summary: Option<bool>,
+    compact_metadata: Option<serde_json::Value>,
+    // Text of system records; left untyped since only some carry a string
+    content: Option<serde_json::Value>,
+    subtype: Option<String>,
+
+    // `summary` records: a title for the conversation ending at leafUuid
+    summary: Option<String>,
+    leaf_uuid: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -160,8 +173,9 @@ enum ContentBlock {
 
 /// Source information for an image content block.
 ///
-/// The `data` field is intentionally omitted as it contains the full
-/// base64-encoded image which would be too large to store.
+/// The `data` field is intentionally omitted: the base64 payload is moved
+/// from `raw_data` to the blob store when the message is stored, using the
+/// `image_pointer` recorded in the message metadata.
 #[derive(Debug, Deserialize)]
 struct ImageSource {
     /// Source type, typically "base64" (not currently used but kept for completeness)
@@ -170,7 +184,6 @@ struct ImageSource {
     source_type: String,
     /// Media type, e.g., "image/png", "image/jpeg"
     media_type: String,
-    // data field intentionally omitted - too large to store
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -187,6 +200,13 @@ impl AssistantParser for ClaudeCodeParser {
         Assistant::ClaudeCode
     }
 
+    /// Version 2 keeps record uuids and parent links, and models `summary`
+    /// records and context compaction. Version 3 records the model of each
+    /// message with token usage.
+    fn version(&self) -> u32 {
+        3
+    }
+
     fn root_path(&self) -> Option<PathBuf> {
         self.root.clone()
     }
@@ -287,13 +307,15 @@ impl AssistantParser for ClaudeCodeParser {
 
             let line = match line_result {
                 Ok(l) => l,
-                Err(e) => {
+                // Invalid UTF-8 spoils one line; other errors would repeat forever
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
                     result.warnings.push(format!(
                         "Line {} (offset {}): read error: {}",
                         line_number, current_offset, e
                     ));
                     continue;
                 }
+                Err(e) => return Err(e.into()),
             };
 
             let line_bytes = line.len() as u64 + 1; // +1 for newline
@@ -309,10 +331,12 @@ impl AssistantParser for ClaudeCodeParser {
             let raw_json: serde_json::Value = match serde_json::from_str(&line) {
                 Ok(v) => v,
                 Err(e) => {
-                    result.warnings.push(format!(
-                        "Line {} (offset {}): JSON parse error: {}",
-                        line_number, record_offset, e
-                    ));
+                    result.record_failure(
+                        line_number,
+                        record_offset,
+                        &line,
+                        format!("JSON parse error: {}", e),
+                    );
                     continue;
                 }
             };
@@ -339,10 +363,12 @@ impl AssistantParser for ClaudeCodeParser {
             let record: RawRecord = match serde_json::from_value(raw_json.clone()) {
                 Ok(r) => r,
                 Err(e) => {
-                    result.warnings.push(format!(
-                        "Line {} (offset {}): deserialization error: {}",
-                        line_number, record_offset, e
-                    ));
+                    result.record_failure(
+                        line_number,
+                        record_offset,
+                        &line,
+                        format!("deserialization error: {}", e),
+                    );
                     continue;
                 }
             };
@@ -418,7 +444,7 @@ impl AssistantParser for ClaudeCodeParser {
             let pre_message_seq = seq;
 
             // Convert to Message(s)
-            let messages = self.record_to_messages(
+            let mut messages = self.record_to_messages(
                 &record,
                 &raw_json,
                 session_id.as_ref().unwrap_or(&String::new()),
@@ -432,6 +458,13 @@ impl AssistantParser for ClaudeCodeParser {
                 Some(line_number),
             );
 
+            // Price usage by the model that produced it; sessions can switch models
+            if let Some(model) = record.message.as_ref().and_then(|m| m.model.as_deref()) {
+                for msg in messages.iter_mut().filter(|m| m.tokens_in.is_some()) {
+                    msg.metadata["model"] = serde_json::json!(format!("anthropic:{}", model));
+                }
+            }
+
             // Record uuid -> seq mapping for the first message created from this record
             // (used for agent spawn linkage - the spawning message is the tool_use)
             if let Some(uuid) = &record.uuid {
@@ -462,7 +495,10 @@ impl AssistantParser for ClaudeCodeParser {
             // Update thread's last activity for non-context messages
             if let Some(ref tid) = thread_id {
                 for msg in &messages {
-                    if msg.message_type != MessageType::Context {
+                    if !matches!(
+                        msg.message_type,
+                        MessageType::Context | MessageType::Summary
+                    ) {
                         thread_last_activity.insert(tid.clone(), emitted_at);
                         break; // Only need to update once per record
                     }
@@ -668,16 +704,25 @@ impl ClaudeCodeParser {
         };
         let mut messages = Vec::new();
         let record_type = record.record_type.as_deref().unwrap_or("unknown");
+        // Compact boundaries continue the conversation from logicalParentUuid;
+        // summary records attach to the leaf they describe
+        let parent_record_id = record
+            .parent_uuid
+            .as_ref()
+            .or(record.logical_parent_uuid.as_ref())
+            .or(record.leaf_uuid.as_ref());
 
         match record_type {
             "assistant" => {
                 if let Some(ref msg) = record.message {
                     // Extract usage
-                    let (tokens_in, tokens_out) = msg
-                        .usage
-                        .as_ref()
-                        .map(|u| (u.input_tokens, u.output_tokens))
-                        .unwrap_or((None, None));
+                    let usage = msg.usage.as_ref();
+                    let tokens_in = usage.and_then(|u| u.input_tokens);
+                    let tokens_out = usage.and_then(|u| u.output_tokens);
+                    let tokens_cache_read = usage.and_then(|u| u.cache_read_input_tokens);
+                    let tokens_cache_write = usage.and_then(|u| u.cache_creation_input_tokens);
+                    // Claude doesn't report thinking tokens separately
+                    let tokens_reasoning = None;
 
                     // Process content
                     if let Some(ref content) = msg.content {
@@ -700,19 +745,25 @@ impl ClaudeCodeParser {
                                         tool_name: None,
                                         tool_input: None,
                                         tool_result: None,
+                                        tool_call_id: None,
                                         tokens_in,
                                         tokens_out,
+                                        tokens_cache_read,
+                                        tokens_cache_write,
+                                        tokens_reasoning,
                                         duration_ms: None,
                                         source_file_path: source_path.to_string(),
                                         source_offset,
                                         source_line,
+                                        record_id: record.uuid.clone(),
+                                        parent_record_id: parent_record_id.cloned(),
                                         raw_data: raw_json.clone(),
                                         metadata: serde_json::json!({}),
                                     });
                                 }
                             }
                             RawContent::Blocks(blocks) => {
-                                for block in blocks {
+                                for (block_index, block) in blocks.iter().enumerate() {
                                     match block {
                                         ContentBlock::Text { text } => {
                                             if !text.is_empty() {
@@ -732,12 +783,18 @@ impl ClaudeCodeParser {
                                                     tool_name: None,
                                                     tool_input: None,
                                                     tool_result: None,
+                                                    tool_call_id: None,
                                                     tokens_in,
                                                     tokens_out,
+                                                    tokens_cache_read,
+                                                    tokens_cache_write,
+                                                    tokens_reasoning,
                                                     duration_ms: None,
                                                     source_file_path: source_path.to_string(),
                                                     source_offset,
                                                     source_line,
+                                                    record_id: record.uuid.clone(),
+                                                    parent_record_id: parent_record_id.cloned(),
                                                     raw_data: raw_json.clone(),
                                                     metadata: serde_json::json!({}),
                                                 });
@@ -760,12 +817,18 @@ impl ClaudeCodeParser {
                                                 tool_name: Some(name.clone()),
                                                 tool_input: Some(input.clone()),
                                                 tool_result: None,
+                                                tool_call_id: Some(id.clone()),
                                                 tokens_in,
                                                 tokens_out,
+                                                tokens_cache_read,
+                                                tokens_cache_write,
+                                                tokens_reasoning,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
                                                 metadata: serde_json::json!({
                                                     "tool_use_id": id,
@@ -797,14 +860,22 @@ impl ClaudeCodeParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: None,
+                                                tool_call_id: None,
                                                 tokens_in,
                                                 tokens_out,
+                                                tokens_cache_read,
+                                                tokens_cache_write,
+                                                tokens_reasoning,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
-                                                metadata: serde_json::json!({}),
+                                                metadata: serde_json::json!({
+                                                    "image_pointer": format!("/message/content/{}/source", block_index),
+                                                }),
                                             });
                                         }
                                         ContentBlock::ToolResult { .. } => {
@@ -831,12 +902,18 @@ impl ClaudeCodeParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: None,
+                                                tool_call_id: None,
                                                 tokens_in,
                                                 tokens_out,
+                                                tokens_cache_read,
+                                                tokens_cache_write,
+                                                tokens_reasoning,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
                                                 metadata: serde_json::json!({}),
                                             });
@@ -863,12 +940,18 @@ impl ClaudeCodeParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: None,
+                                                tool_call_id: None,
                                                 tokens_in,
                                                 tokens_out,
+                                                tokens_cache_read,
+                                                tokens_cache_write,
+                                                tokens_reasoning,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
                                                 metadata: serde_json::json!({}),
                                             });
@@ -902,19 +985,25 @@ impl ClaudeCodeParser {
                                         tool_name: None,
                                         tool_input: None,
                                         tool_result: None,
+                                        tool_call_id: None,
                                         tokens_in: None,
                                         tokens_out: None,
+                                        tokens_cache_read: None,
+                                        tokens_cache_write: None,
+                                        tokens_reasoning: None,
                                         duration_ms: None,
                                         source_file_path: source_path.to_string(),
                                         source_offset,
                                         source_line,
+                                        record_id: record.uuid.clone(),
+                                        parent_record_id: parent_record_id.cloned(),
                                         raw_data: raw_json.clone(),
                                         metadata: serde_json::json!({}),
                                     });
                                 }
                             }
                             RawContent::Blocks(blocks) => {
-                                for block in blocks {
+                                for (block_index, block) in blocks.iter().enumerate() {
                                     match block {
                                         ContentBlock::Text { text } => {
                                             if !text.is_empty() {
@@ -934,12 +1023,18 @@ impl ClaudeCodeParser {
                                                     tool_name: None,
                                                     tool_input: None,
                                                     tool_result: None,
+                                                    tool_call_id: None,
                                                     tokens_in: None,
                                                     tokens_out: None,
+                                                    tokens_cache_read: None,
+                                                    tokens_cache_write: None,
+                                                    tokens_reasoning: None,
                                                     duration_ms: None,
                                                     source_file_path: source_path.to_string(),
                                                     source_offset,
                                                     source_line,
+                                                    record_id: record.uuid.clone(),
+                                                    parent_record_id: parent_record_id.cloned(),
                                                     raw_data: raw_json.clone(),
                                                     metadata: serde_json::json!({}),
                                                 });
@@ -974,12 +1069,18 @@ impl ClaudeCodeParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: Some(result_str),
+                                                tool_call_id: Some(tool_use_id.clone()),
                                                 tokens_in: None,
                                                 tokens_out: None,
+                                                tokens_cache_read: None,
+                                                tokens_cache_write: None,
+                                                tokens_reasoning: None,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
                                                 metadata: serde_json::json!({
                                                     "tool_use_id": tool_use_id,
@@ -1010,14 +1111,22 @@ impl ClaudeCodeParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: None,
+                                                tool_call_id: None,
                                                 tokens_in: None,
                                                 tokens_out: None,
+                                                tokens_cache_read: None,
+                                                tokens_cache_write: None,
+                                                tokens_reasoning: None,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
-                                                metadata: serde_json::json!({}),
+                                                metadata: serde_json::json!({
+                                                    "image_pointer": format!("/message/content/{}/source", block_index),
+                                                }),
                                             });
                                         }
                                         ContentBlock::ToolUse { .. } => {
@@ -1044,12 +1153,18 @@ impl ClaudeCodeParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: None,
+                                                tool_call_id: None,
                                                 tokens_in: None,
                                                 tokens_out: None,
+                                                tokens_cache_read: None,
+                                                tokens_cache_write: None,
+                                                tokens_reasoning: None,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
                                                 metadata: serde_json::json!({}),
                                             });
@@ -1076,12 +1191,18 @@ impl ClaudeCodeParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: None,
+                                                tool_call_id: None,
                                                 tokens_in: None,
                                                 tokens_out: None,
+                                                tokens_cache_read: None,
+                                                tokens_cache_write: None,
+                                                tokens_reasoning: None,
                                                 duration_ms: None,
                                                 source_file_path: source_path.to_string(),
                                                 source_offset,
                                                 source_line,
+                                                record_id: record.uuid.clone(),
+                                                parent_record_id: parent_record_id.cloned(),
                                                 raw_data: raw_json.clone(),
                                                 metadata: serde_json::json!({}),
                                             });
@@ -1093,6 +1214,82 @@ impl ClaudeCodeParser {
                     }
                 }
             }
+            "summary" => {
+                *seq += 1;
+                messages.push(Message {
+                    id: 0,
+                    session_id: session_id.to_string(),
+                    thread_id: thread_id.to_string(),
+                    seq: *seq,
+                    emitted_at,
+                    observed_at,
+                    author_role: AuthorRole::System,
+                    author_name: None,
+                    message_type: MessageType::Summary,
+                    content: record.summary.clone(),
+                    content_type: Some(ContentType::Text),
+                    tool_name: None,
+                    tool_input: None,
+                    tool_result: None,
+                    tool_call_id: None,
+                    tokens_in: None,
+                    tokens_out: None,
+                    tokens_cache_read: None,
+                    tokens_cache_write: None,
+                    tokens_reasoning: None,
+                    duration_ms: None,
+                    source_file_path: source_path.to_string(),
+                    source_offset,
+                    source_line,
+                    record_id: record.uuid.clone(),
+                    parent_record_id: parent_record_id.cloned(),
+                    raw_data: raw_json.clone(),
+                    metadata: serde_json::json!({}),
+                });
+            }
+            "system" if record.subtype.as_deref() == Some("compact_boundary") => {
+                // Marks where the context was compacted; the summary that
+                // replaces the earlier conversation follows as a user record
+                let compact = record.compact_metadata.as_ref();
+                *seq += 1;
+                messages.push(Message {
+                    id: 0,
+                    session_id: session_id.to_string(),
+                    thread_id: thread_id.to_string(),
+                    seq: *seq,
+                    emitted_at,
+                    observed_at,
+                    author_role: AuthorRole::System,
+                    author_name: Some("compact_boundary".to_string()),
+                    message_type: MessageType::Context,
+                    content: record
+                        .content
+                        .as_ref()
+                        .and_then(|c| c.as_str())
+                        .map(String::from),
+                    content_type: Some(ContentType::Text),
+                    tool_name: None,
+                    tool_input: None,
+                    tool_result: None,
+                    tool_call_id: None,
+                    tokens_in: None,
+                    tokens_out: None,
+                    tokens_cache_read: None,
+                    tokens_cache_write: None,
+                    tokens_reasoning: None,
+                    duration_ms: None,
+                    source_file_path: source_path.to_string(),
+                    source_offset,
+                    source_line,
+                    record_id: record.uuid.clone(),
+                    parent_record_id: parent_record_id.cloned(),
+                    raw_data: raw_json.clone(),
+                    metadata: serde_json::json!({
+                        "trigger": compact.and_then(|m| m.get("trigger")),
+                        "pre_tokens": compact.and_then(|m| m.get("preTokens")),
+                    }),
+                });
+            }
             _ => {
                 // Unknown record type - still capture it
                 *seq += 1;
@@ -1111,18 +1308,35 @@ impl ClaudeCodeParser {
                     tool_name: None,
                     tool_input: None,
                     tool_result: None,
+                    tool_call_id: None,
                     tokens_in: None,
                     tokens_out: None,
+                    tokens_cache_read: None,
+                    tokens_cache_write: None,
+                    tokens_reasoning: None,
                     duration_ms: None,
                     source_file_path: source_path.to_string(),
                     source_offset,
                     source_line,
+                    record_id: record.uuid.clone(),
+                    parent_record_id: parent_record_id.cloned(),
                     raw_data: raw_json.clone(),
                     metadata: serde_json::json!({}),
                 });
             }
         }
 
+        // The continuation prompt written after a compaction carries the
+        // summary of everything before it
+        if record.is_compact_summary == Some(true) {
+            for msg in &mut messages {
+                if msg.message_type == MessageType::Prompt {
+                    msg.message_type = MessageType::Compaction;
+                    msg.author_role = AuthorRole::System;
+                }
+            }
+        }
+
         messages
     }
 }
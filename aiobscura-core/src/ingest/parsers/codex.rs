@@ -90,6 +90,23 @@ fn is_system_injected_context(text: &str) -> bool {
         || trimmed.starts_with("# AGENTS.md instructions for")
 }
 
+/// Detect tool outputs that report a failed call.
+///
+/// Shell outputs start with `Exit code: N`; structured outputs are JSON with
+/// `metadata.exit_code`.
+fn output_is_error(output: &str) -> bool {
+    let exit_code = match output.strip_prefix("Exit code: ") {
+        Some(rest) => rest
+            .lines()
+            .next()
+            .and_then(|c| c.trim().parse::<i64>().ok()),
+        None => serde_json::from_str::<serde_json::Value>(output)
+            .ok()
+            .and_then(|v| v.get("metadata")?.get("exit_code")?.as_i64()),
+    };
+    exit_code.is_some_and(|code| code != 0)
+}
+
 // ============================================
 // Raw JSONL record types (serde deserialization)
 // ============================================
@@ -157,6 +174,20 @@ struct TokenUsage {
     total_tokens: Option<i32>,
 }
 
+impl TokenUsage {
+    /// Split `input_tokens` into (uncached, cached).
+    ///
+    /// Codex counts cached tokens within `input_tokens`; aiobscura stores
+    /// them separately, as Claude reports them.
+    fn split_input(&self) -> (Option<i32>, Option<i32>) {
+        let cached = self.cached_input_tokens;
+        let uncached = self
+            .input_tokens
+            .map(|input| input.saturating_sub(cached.unwrap_or(0)).max(0));
+        (uncached, cached)
+    }
+}
+
 /// Response item payload subtypes.
 #[derive(Debug, Deserialize, Default)]
 #[serde(default)]
@@ -203,6 +234,14 @@ impl AssistantParser for CodexParser {
         Assistant::Codex
     }
 
+    /// Version 2 reads cached-input and reasoning token counts from
+    /// `token_count` events. Version 3 links tool calls to their outputs and
+    /// records failed calls as errors. Version 4 records the model of each
+    /// response with token usage.
+    fn version(&self) -> u32 {
+        4
+    }
+
     fn root_path(&self) -> Option<PathBuf> {
         self.root.clone()
     }
@@ -264,6 +303,8 @@ impl AssistantParser for CodexParser {
         let mut session_id: Option<String> = self.extract_session_id(ctx.path);
         let mut thread_id: Option<String> = session_id.as_ref().map(|sid| format!("{}-main", sid));
         let mut model_id: Option<String> = None;
+        // Model of the current turn; sessions can switch models between turns
+        let mut turn_model: Option<String> = None;
         let mut cwd: Option<String> = None;
         let mut git_info: Option<GitInfo> = None;
         let mut last_token_usage: Option<TokenUsage> = None;
@@ -295,13 +336,15 @@ impl AssistantParser for CodexParser {
 
             let line = match line_result {
                 Ok(l) => l,
-                Err(e) => {
+                // Invalid UTF-8 spoils one line; other errors would repeat forever
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
                     result.warnings.push(format!(
                         "Line {} (offset {}): read error: {}",
                         line_number, current_offset, e
                     ));
                     continue;
                 }
+                Err(e) => return Err(e.into()),
             };
 
             let line_bytes = line.len() as u64 + 1; // +1 for newline
@@ -317,10 +360,12 @@ impl AssistantParser for CodexParser {
             let raw_json: serde_json::Value = match serde_json::from_str(&line) {
                 Ok(v) => v,
                 Err(e) => {
-                    result.warnings.push(format!(
-                        "Line {} (offset {}): JSON parse error: {}",
-                        line_number, record_offset, e
-                    ));
+                    result.record_failure(
+                        line_number,
+                        record_offset,
+                        &line,
+                        format!("JSON parse error: {}", e),
+                    );
                     continue;
                 }
             };
@@ -329,10 +374,12 @@ impl AssistantParser for CodexParser {
             let event: RawEvent = match serde_json::from_value(raw_json.clone()) {
                 Ok(e) => e,
                 Err(e) => {
-                    result.warnings.push(format!(
-                        "Line {} (offset {}): deserialization error: {}",
-                        line_number, record_offset, e
-                    ));
+                    result.record_failure(
+                        line_number,
+                        record_offset,
+                        &line,
+                        format!("deserialization error: {}", e),
+                    );
                     continue;
                 }
             };
@@ -434,12 +481,18 @@ impl AssistantParser for CodexParser {
                                 tool_name: None,
                                 tool_input: None,
                                 tool_result: None,
+                                tool_call_id: None,
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({}),
                             });
@@ -497,15 +550,23 @@ impl AssistantParser for CodexParser {
                                             seq += 1;
 
                                             // Apply token counts from last token_count event
-                                            let (tokens_in, tokens_out) =
-                                                if author_role == AuthorRole::Assistant {
-                                                    last_token_usage
-                                                        .as_ref()
-                                                        .map(|u| (u.input_tokens, u.output_tokens))
-                                                        .unwrap_or((None, None))
-                                                } else {
-                                                    (None, None)
-                                                };
+                                            let usage = last_token_usage
+                                                .as_ref()
+                                                .filter(|_| author_role == AuthorRole::Assistant);
+                                            let (tokens_in, tokens_cache_read) = usage
+                                                .map(TokenUsage::split_input)
+                                                .unwrap_or_default();
+                                            let tokens_out = usage.and_then(|u| u.output_tokens);
+                                            let tokens_reasoning =
+                                                usage.and_then(|u| u.reasoning_output_tokens);
+                                            // Codex doesn't report cache writes
+                                            let tokens_cache_write = None;
+                                            let metadata = match (usage, &turn_model) {
+                                                (Some(_), Some(model)) => serde_json::json!({
+                                                    "model": format!("openai:{}", model),
+                                                }),
+                                                _ => serde_json::json!({}),
+                                            };
 
                                             result.messages.push(Message {
                                                 id: 0,
@@ -522,14 +583,20 @@ impl AssistantParser for CodexParser {
                                                 tool_name: None,
                                                 tool_input: None,
                                                 tool_result: None,
+                                                tool_call_id: None,
                                                 tokens_in,
                                                 tokens_out,
+                                                tokens_cache_read,
+                                                tokens_cache_write,
+                                                tokens_reasoning,
                                                 duration_ms: None,
                                                 source_file_path: source_path.clone(),
                                                 source_offset: record_offset as i64,
                                                 source_line: Some(line_number),
+                                                record_id: None,
+                                                parent_record_id: None,
                                                 raw_data: raw_json.clone(),
-                                                metadata: serde_json::json!({}),
+                                                metadata,
                                             });
                                         }
                                     }
@@ -566,12 +633,18 @@ impl AssistantParser for CodexParser {
                                 tool_name: payload.name.clone(),
                                 tool_input,
                                 tool_result: None,
+                                tool_call_id: payload.call_id.clone(),
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({
                                     "call_id": payload.call_id,
@@ -591,18 +664,32 @@ impl AssistantParser for CodexParser {
                                 observed_at,
                                 author_role: AuthorRole::Tool,
                                 author_name: None,
-                                message_type: MessageType::ToolResult,
+                                message_type: if payload
+                                    .output
+                                    .as_deref()
+                                    .is_some_and(output_is_error)
+                                {
+                                    MessageType::Error
+                                } else {
+                                    MessageType::ToolResult
+                                },
                                 content: None,
                                 content_type: None,
                                 tool_name: None,
                                 tool_input: None,
                                 tool_result: payload.output.clone(),
+                                tool_call_id: payload.call_id.clone(),
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({
                                     "call_id": payload.call_id,
@@ -648,12 +735,18 @@ impl AssistantParser for CodexParser {
                                 tool_name: None,
                                 tool_input: None,
                                 tool_result: None,
+                                tool_call_id: None,
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({
                                     "reasoning": true,
@@ -690,12 +783,18 @@ impl AssistantParser for CodexParser {
                                 tool_name: None,
                                 tool_input: None,
                                 tool_result: None,
+                                tool_call_id: None,
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({
                                     "git_snapshot": payload.ghost_commit,
@@ -729,12 +828,18 @@ impl AssistantParser for CodexParser {
                                     "input": event.payload.get("input"),
                                 })),
                                 tool_result: None,
+                                tool_call_id: payload.call_id.clone(),
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({
                                     "call_id": payload.call_id,
@@ -756,18 +861,32 @@ impl AssistantParser for CodexParser {
                                 observed_at,
                                 author_role: AuthorRole::Tool,
                                 author_name: None,
-                                message_type: MessageType::ToolResult,
+                                message_type: if payload
+                                    .output
+                                    .as_deref()
+                                    .is_some_and(output_is_error)
+                                {
+                                    MessageType::Error
+                                } else {
+                                    MessageType::ToolResult
+                                },
                                 content: None,
                                 content_type: None,
                                 tool_name: None,
                                 tool_input: None,
                                 tool_result: payload.output.clone(),
+                                tool_call_id: payload.call_id.clone(),
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({
                                     "call_id": payload.call_id,
@@ -794,12 +913,18 @@ impl AssistantParser for CodexParser {
                                 tool_name: None,
                                 tool_input: None,
                                 tool_result: None,
+                                tool_call_id: None,
                                 tokens_in: None,
                                 tokens_out: None,
+                                tokens_cache_read: None,
+                                tokens_cache_write: None,
+                                tokens_reasoning: None,
                                 duration_ms: None,
                                 source_file_path: source_path.clone(),
                                 source_offset: record_offset as i64,
                                 source_line: Some(line_number),
+                                record_id: None,
+                                parent_record_id: None,
                                 raw_data: raw_json.clone(),
                                 metadata: serde_json::json!({}),
                             });
@@ -815,6 +940,9 @@ impl AssistantParser for CodexParser {
                     if model_id.is_none() {
                         model_id = payload.model.clone();
                     }
+                    if payload.model.is_some() {
+                        turn_model = payload.model.clone();
+                    }
 
                     // Update cwd if changed
                     if let Some(new_cwd) = payload.cwd {
@@ -840,12 +968,18 @@ impl AssistantParser for CodexParser {
                         tool_name: None,
                         tool_input: None,
                         tool_result: None,
+                        tool_call_id: None,
                         tokens_in: None,
                         tokens_out: None,
+                        tokens_cache_read: None,
+                        tokens_cache_write: None,
+                        tokens_reasoning: None,
                         duration_ms: None,
                         source_file_path: source_path.clone(),
                         source_offset: record_offset as i64,
                         source_line: Some(line_number),
+                        record_id: None,
+                        parent_record_id: None,
                         raw_data: raw_json.clone(),
                         metadata: serde_json::json!({}),
                     });
@@ -1030,6 +1164,20 @@ mod tests {
         assert_eq!(parser.root_path(), Some(custom_root));
     }
 
+    #[test]
+    fn test_output_is_error() {
+        assert!(!output_is_error("Exit code: 0\nOutput:\nok"));
+        assert!(output_is_error("Exit code: 1\nWall time: 0.1 seconds"));
+        assert!(output_is_error(
+            r#"{"output":"patch failed","metadata":{"exit_code":1}}"#
+        ));
+        assert!(!output_is_error(
+            r#"{"output":"Success","metadata":{"exit_code":0}}"#
+        ));
+        // Plain output, e.g. file contents that happen to be JSON
+        assert!(!output_is_error(r#"{"name": "myproject"}"#));
+    }
+
     #[test]
     fn test_generate_project_id() {
         let id1 = CodexParser::generate_project_id("/Users/test/dev/project");
@@ -0,0 +1,910 @@
+//! Cursor `state.vscdb` parser
+//!
+//! Parses AI conversations from Cursor's SQLite state databases:
+//!
+//! - `<config>/Cursor/User/globalStorage/state.vscdb` — Composer/Agent
+//!   conversations in the `cursorDiskKV` table (`composerData:{id}` and
+//!   `bubbleId:{composerId}:{bubbleId}` keys)
+//! - `<config>/Cursor/User/workspaceStorage/{hash}/state.vscdb` — legacy chat
+//!   panel tabs in the `ItemTable` table
+//!   (`workbench.panel.aichat.view.aichat.chatdata` key)
+//!
+//! `<config>` is the platform config directory (`~/Library/Application Support`
+//! on macOS, `~/.config` on Linux, `%APPDATA%` on Windows).
+//!
+//! See [`docs/cursor-log-format.md`](../../../../docs/cursor-log-format.md)
+//! for the storage layout.
+//!
+//! # Error Handling
+//!
+//! Databases are always opened read-only; Cursor may be writing to them.
+//!
+//! - **Malformed JSON values**: Logged as warning, row skipped, parsing continues.
+//! - **Missing bubbles**: A conversation stops at the first bubble that has not
+//!   been written yet and is picked up again on the next sync.
+//! - **Unfinished bubbles**: Likewise for a bubble whose tool call has no
+//!   result yet, or whose text may still be streaming.
+//! - **Missing tables**: Treated as an empty database.
+//! - **Database rewritten** (rowids went backwards, e.g. after `VACUUM`): the
+//!   rowid cursor is reset but per-conversation progress is kept, so nothing is
+//!   emitted twice.
+//!
+//! # Incremental Parsing
+//!
+//! The parser uses [`Checkpoint::DatabaseCursor`] with `cursor_column = "rowid"`.
+//! Cursor's key/value tables use `ON CONFLICT REPLACE`, so every update gives
+//! the row a new, higher rowid; only conversations with a row above the
+//! cursor (their `composerData` or one of their bubbles) are re-read. The
+//! `cursor_value` is a JSON document holding the max rowid seen and, per
+//! conversation, how many bubbles and message sequence numbers were emitted.
+
+use crate::error::{Error, Result};
+use crate::ingest::parser::{AssistantParser, ParseContext, ParseResult, SourcePattern};
+use crate::types::{
+    Assistant, AuthorRole, Checkpoint, FileType, Message, MessageType, Session, SessionStatus,
+    Thread, ThreadType,
+};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Table holding Composer conversations in the global database.
+const GLOBAL_TABLE: &str = "cursorDiskKV";
+
+/// Table holding legacy chat tabs in workspace databases.
+const WORKSPACE_TABLE: &str = "ItemTable";
+
+/// ItemTable key of the legacy chat panel data.
+const CHAT_DATA_KEY: &str = "workbench.panel.aichat.view.aichat.chatdata";
+
+/// ItemTable key listing the Composer conversations of a workspace.
+const COMPOSER_LIST_KEY: &str = "composer.composerData";
+
+/// Parser for Cursor's SQLite state databases.
+pub struct CursorParser {
+    root: Option<PathBuf>,
+}
+
+impl CursorParser {
+    /// Create a new parser with the default root path (`<config>/Cursor/User`).
+    pub fn new() -> Self {
+        Self {
+            root: dirs::config_dir().map(|c| c.join("Cursor").join("User")),
+        }
+    }
+
+    /// Create a parser with a custom root path (for testing).
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root: Some(root) }
+    }
+}
+
+impl Default for CursorParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================
+// Checkpoint state
+// ============================================
+
+/// Decoded `cursor_value` of the [`Checkpoint::DatabaseCursor`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct CursorState {
+    /// Highest rowid processed
+    rowid: i64,
+    /// Progress per conversation ID
+    conversations: HashMap<String, ConversationProgress>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct ConversationProgress {
+    /// Number of bubbles already emitted
+    bubbles: usize,
+    /// Last message seq used
+    seq: i32,
+}
+
+// ============================================
+// Raw value types (serde deserialization)
+// ============================================
+
+/// `composerData:{id}` value in `cursorDiskKV`.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawComposer {
+    composer_id: Option<String>,
+    name: Option<String>,
+    created_at: Option<i64>,
+    last_updated_at: Option<i64>,
+    /// Older versions store bubbles inline
+    conversation: Option<Vec<serde_json::Value>>,
+    /// Newer versions store bubbles under separate keys
+    full_conversation_headers_only: Option<Vec<RawBubbleHeader>>,
+    model_config: Option<RawModelConfig>,
+    /// "generating" while a response is being written
+    status: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawBubbleHeader {
+    bubble_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawModelConfig {
+    model_name: Option<String>,
+}
+
+/// A single chat bubble (user or AI turn).
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawBubble {
+    /// 1 = user, 2 = AI (Composer); "user" / "ai" (legacy chat)
+    #[serde(rename = "type")]
+    bubble_type: serde_json::Value,
+    text: Option<String>,
+    created_at: serde_json::Value,
+    timing_info: Option<RawTimingInfo>,
+    token_count: Option<RawTokenCount>,
+    tool_former_data: Option<RawToolFormerData>,
+    model_info: Option<RawModelInfo>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawTimingInfo {
+    client_start_time: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawTokenCount {
+    input_tokens: Option<i32>,
+    output_tokens: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawToolFormerData {
+    name: Option<String>,
+    raw_args: Option<String>,
+    result: Option<String>,
+    status: Option<String>,
+    tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawModelInfo {
+    model_name: Option<String>,
+}
+
+/// Legacy chat panel data in workspace `ItemTable`.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct RawChatData {
+    tabs: Vec<RawChatTab>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawChatTab {
+    tab_id: Option<String>,
+    chat_title: Option<String>,
+    last_send_time: Option<i64>,
+    bubbles: Vec<serde_json::Value>,
+}
+
+/// `composer.composerData` value in workspace `ItemTable`.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawComposerList {
+    all_composers: Vec<RawComposerHeader>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct RawComposerHeader {
+    composer_id: Option<String>,
+}
+
+/// `workspace.json` next to a workspace database.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct RawWorkspace {
+    folder: Option<String>,
+}
+
+/// A conversation extracted from either table, ready to be mapped.
+struct Conversation {
+    id: String,
+    title: Option<String>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    model: Option<String>,
+    cwd: Option<String>,
+    kind: &'static str,
+    /// rowid of the row the conversation was read from
+    rowid: i64,
+    /// Whether a response is still being written
+    generating: bool,
+    bubbles: Vec<serde_json::Value>,
+}
+
+// ============================================
+// Helper functions
+// ============================================
+
+/// Convert a millisecond epoch timestamp.
+fn from_millis(ms: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(ms)
+}
+
+/// Read a TEXT or BLOB column as UTF-8.
+fn value_text(value: rusqlite::types::ValueRef<'_>) -> Option<String> {
+    match value {
+        rusqlite::types::ValueRef::Text(t) | rusqlite::types::ValueRef::Blob(t) => {
+            Some(String::from_utf8_lossy(t).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Decode a `file://` URI from `workspace.json` into a filesystem path.
+fn file_uri_to_path(uri: &str) -> Option<String> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                decoded.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    Some(String::from_utf8_lossy(&decoded).to_string())
+}
+
+/// Read the workspace folder for a workspace database, if recorded.
+fn workspace_folder(db_path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(db_path.parent()?.join("workspace.json")).ok()?;
+    let workspace: RawWorkspace = serde_json::from_str(&content).ok()?;
+    file_uri_to_path(&workspace.folder?)
+}
+
+/// Open a Cursor database without ever writing to it.
+fn open_read_only(path: &Path) -> Result<Connection> {
+    Ok(Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [table],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Map a Cursor model name to a backing model ID (`provider:model`).
+fn backing_model_id(model: &str) -> String {
+    let provider = if model.starts_with("claude") {
+        "anthropic"
+    } else if model.starts_with("gpt")
+        || model.starts_with("o1")
+        || model.starts_with("o3")
+        || model.starts_with("o4")
+    {
+        "openai"
+    } else if model.starts_with("gemini") {
+        "google"
+    } else {
+        "cursor"
+    };
+    format!("{}:{}", provider, model)
+}
+
+/// Whether a bubble was authored by the user (Composer `1`, legacy `"user"`).
+fn is_user_bubble(bubble_type: &serde_json::Value) -> bool {
+    bubble_type.as_i64() == Some(1) || bubble_type.as_str() == Some("user")
+}
+
+/// Whether a bubble is complete and can be emitted.
+///
+/// A tool call is complete once it has a result or a final status; its text
+/// is written before the call. Otherwise the last bubble's text may still be
+/// streaming while the conversation is generating, and an empty last AI
+/// bubble has not started yet.
+fn bubble_finished(bubble: &RawBubble, is_last: bool, generating: bool) -> bool {
+    if is_user_bubble(&bubble.bubble_type) {
+        return true;
+    }
+    if let Some(tool) = &bubble.tool_former_data {
+        return tool.result.is_some()
+            || matches!(
+                tool.status.as_deref(),
+                Some("completed" | "error" | "cancelled")
+            );
+    }
+    let empty = bubble.text.as_deref().is_none_or(|t| t.trim().is_empty());
+    !(is_last && (generating || empty))
+}
+
+/// Best-effort bubble timestamp.
+fn bubble_timestamp(bubble: &RawBubble) -> Option<DateTime<Utc>> {
+    match &bubble.created_at {
+        serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc)),
+        serde_json::Value::Number(n) => n.as_i64().and_then(from_millis),
+        _ => None,
+    }
+    .or_else(|| {
+        bubble
+            .timing_info
+            .as_ref()
+            .and_then(|t| t.client_start_time)
+            .and_then(|ms| from_millis(ms as i64))
+    })
+}
+
+impl AssistantParser for CursorParser {
+    fn assistant(&self) -> Assistant {
+        Assistant::Cursor
+    }
+
+    fn root_path(&self) -> Option<PathBuf> {
+        self.root.clone()
+    }
+
+    fn source_patterns(&self) -> Vec<SourcePattern> {
+        vec![
+            SourcePattern {
+                pattern: "globalStorage/state.vscdb".to_string(),
+                file_type: FileType::Sqlite,
+                description: "Cursor Composer conversations".to_string(),
+            },
+            SourcePattern {
+                pattern: "workspaceStorage/*/state.vscdb".to_string(),
+                file_type: FileType::Sqlite,
+                description: "Cursor workspace chat history".to_string(),
+            },
+        ]
+    }
+
+    fn parse(&self, ctx: &ParseContext) -> Result<ParseResult> {
+        let mut result = ParseResult::default();
+
+        let is_global = ctx
+            .path
+            .parent()
+            .and_then(|p| p.file_name())
+            .is_some_and(|n| n == "globalStorage");
+        let table = if is_global {
+            GLOBAL_TABLE
+        } else {
+            WORKSPACE_TABLE
+        };
+
+        // Restore progress from checkpoint
+        let mut state = match ctx.checkpoint {
+            Checkpoint::DatabaseCursor {
+                table: cp_table,
+                cursor_value,
+                ..
+            } if cp_table == table => match serde_json::from_str(cursor_value) {
+                Ok(state) => state,
+                Err(e) => {
+                    result.warnings.push(format!(
+                        "Invalid checkpoint cursor, starting from beginning: {}",
+                        e
+                    ));
+                    CursorState::default()
+                }
+            },
+            _ => CursorState::default(),
+        };
+
+        let conn = open_read_only(ctx.path).map_err(|e| Error::Parse {
+            agent: Assistant::Cursor.to_string(),
+            message: format!("Failed to open {}: {}", ctx.path.display(), e),
+        })?;
+
+        if !table_exists(&conn, table)? {
+            result.new_checkpoint = Self::checkpoint(table, &state)?;
+            return Ok(result);
+        }
+
+        let max_rowid: i64 = conn.query_row(
+            &format!("SELECT COALESCE(MAX(rowid), 0) FROM {}", table),
+            [],
+            |r| r.get(0),
+        )?;
+        if max_rowid < state.rowid {
+            result.warnings.push(format!(
+                "Database rewritten: max rowid {} < checkpoint {}, rescanning",
+                max_rowid, state.rowid
+            ));
+            state.rowid = 0;
+        }
+
+        let conversations = if is_global {
+            self.read_composers(&conn, &state, max_rowid, &mut result)?
+        } else {
+            Self::read_chat_tabs(&conn, ctx.path, &state, max_rowid, &mut result)?
+        };
+
+        let source_path = ctx.path.to_string_lossy().to_string();
+        let observed_at = Utc::now();
+        let mut sessions = Vec::new();
+
+        for conv in conversations {
+            let progress = state
+                .conversations
+                .get(&conv.id)
+                .copied()
+                .unwrap_or_default();
+            let (session, thread, progress) =
+                Self::map_conversation(&conv, progress, &source_path, observed_at, &mut result);
+            state.conversations.insert(conv.id.clone(), progress);
+            if let Some(session) = session {
+                sessions.push(session);
+            }
+            if let Some(thread) = thread {
+                result.threads.push(thread);
+            }
+        }
+
+        state.rowid = max_rowid;
+        result.session = sessions.pop();
+        result.extra_sessions = sessions;
+        result.new_checkpoint = Self::checkpoint(table, &state)?;
+
+        Ok(result)
+    }
+
+    fn extract_project_path(&self, file_path: &Path) -> Option<PathBuf> {
+        // Workspace databases record their folder in workspace.json
+        workspace_folder(file_path).map(PathBuf::from)
+    }
+
+    fn extract_session_id(&self, file_path: &Path) -> Option<String> {
+        // A database holds many conversations; IDs come from composer/tab IDs
+        let _ = file_path;
+        None
+    }
+}
+
+impl CursorParser {
+    fn checkpoint(table: &str, state: &CursorState) -> Result<Checkpoint> {
+        Ok(Checkpoint::DatabaseCursor {
+            table: table.to_string(),
+            cursor_column: "rowid".to_string(),
+            cursor_value: serde_json::to_string(state)?,
+        })
+    }
+
+    /// Read changed Composer conversations from the global `cursorDiskKV` table.
+    fn read_composers(
+        &self,
+        conn: &Connection,
+        state: &CursorState,
+        max_rowid: i64,
+        result: &mut ParseResult,
+    ) -> Result<Vec<Conversation>> {
+        // Conversations whose composerData or bubbles changed
+        let mut stmt = conn.prepare(
+            "SELECT rowid, key, value FROM cursorDiskKV
+             WHERE key LIKE 'composerData:%'
+               AND (rowid > ?1 AND rowid <= ?2
+                    OR substr(key, 14) IN (
+                        SELECT substr(key, 10, instr(substr(key, 10), ':') - 1)
+                        FROM cursorDiskKV
+                        WHERE rowid > ?1 AND rowid <= ?2 AND key LIKE 'bubbleId:%'))
+             ORDER BY rowid",
+        )?;
+        let rows = stmt
+            .query_map([state.rowid, max_rowid], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    value_text(row.get_ref(2)?),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let folders = self.composer_folders();
+        let mut bubble_stmt = conn.prepare("SELECT value FROM cursorDiskKV WHERE key = ?")?;
+        let mut conversations = Vec::new();
+
+        for (rowid, key, value) in rows {
+            let Some(value) = value else { continue };
+            let composer: RawComposer = match serde_json::from_str(&value) {
+                Ok(c) => c,
+                Err(e) => {
+                    result
+                        .warnings
+                        .push(format!("Row {} ({}): JSON parse error: {}", rowid, key, e));
+                    continue;
+                }
+            };
+            let id = composer
+                .composer_id
+                .clone()
+                .unwrap_or_else(|| key.trim_start_matches("composerData:").to_string());
+
+            let mut bubbles = Vec::new();
+            if let Some(inline) = composer.conversation {
+                bubbles = inline;
+            } else if let Some(headers) = composer.full_conversation_headers_only {
+                for header in headers {
+                    let Some(bubble_id) = header.bubble_id else {
+                        continue;
+                    };
+                    let bubble_key = format!("bubbleId:{}:{}", id, bubble_id);
+                    let bubble = bubble_stmt
+                        .query_row([&bubble_key], |r| Ok(value_text(r.get_ref(0)?)))
+                        .optional()?
+                        .flatten();
+                    // Stop at the first bubble not yet written; retried next sync
+                    let Some(bubble) = bubble else { break };
+                    match serde_json::from_str(&bubble) {
+                        Ok(v) => bubbles.push(v),
+                        Err(e) => {
+                            result.warnings.push(format!(
+                                "Row {} ({}): JSON parse error: {}",
+                                rowid, bubble_key, e
+                            ));
+                            break;
+                        }
+                    }
+                }
+            }
+
+            conversations.push(Conversation {
+                cwd: folders.get(&id).cloned(),
+                id,
+                title: composer.name,
+                created_at: composer.created_at.and_then(from_millis),
+                updated_at: composer.last_updated_at.and_then(from_millis),
+                model: composer.model_config.and_then(|m| m.model_name),
+                kind: "composer",
+                rowid,
+                generating: composer.status.as_deref() == Some("generating"),
+                bubbles,
+            });
+        }
+
+        Ok(conversations)
+    }
+
+    /// Read legacy chat panel tabs from a workspace `ItemTable`.
+    fn read_chat_tabs(
+        conn: &Connection,
+        path: &Path,
+        state: &CursorState,
+        max_rowid: i64,
+        result: &mut ParseResult,
+    ) -> Result<Vec<Conversation>> {
+        let row = conn
+            .query_row(
+                "SELECT rowid, value FROM ItemTable WHERE key = ?1 AND rowid > ?2 AND rowid <= ?3",
+                rusqlite::params![CHAT_DATA_KEY, state.rowid, max_rowid],
+                |r| Ok((r.get::<_, i64>(0)?, value_text(r.get_ref(1)?))),
+            )
+            .optional()?;
+
+        let Some((rowid, Some(value))) = row else {
+            return Ok(Vec::new());
+        };
+        let chat: RawChatData = match serde_json::from_str(&value) {
+            Ok(c) => c,
+            Err(e) => {
+                result.warnings.push(format!(
+                    "Row {} ({}): JSON parse error: {}",
+                    rowid, CHAT_DATA_KEY, e
+                ));
+                return Ok(Vec::new());
+            }
+        };
+
+        let cwd = workspace_folder(path);
+        Ok(chat
+            .tabs
+            .into_iter()
+            .filter_map(|tab| {
+                Some(Conversation {
+                    id: tab.tab_id?,
+                    title: tab.chat_title,
+                    created_at: None,
+                    updated_at: tab.last_send_time.and_then(from_millis),
+                    model: None,
+                    cwd: cwd.clone(),
+                    kind: "chat",
+                    rowid,
+                    generating: false,
+                    bubbles: tab.bubbles,
+                })
+            })
+            .collect())
+    }
+
+    /// Map composer IDs to workspace folders using the workspace databases.
+    fn composer_folders(&self) -> HashMap<String, String> {
+        let mut folders = HashMap::new();
+        let Some(root) = &self.root else {
+            return folders;
+        };
+        let pattern = root.join("workspaceStorage/*/state.vscdb");
+        let Ok(entries) = glob::glob(&pattern.to_string_lossy()) else {
+            return folders;
+        };
+
+        for db_path in entries.flatten() {
+            let Some(folder) = workspace_folder(&db_path) else {
+                continue;
+            };
+            let Ok(conn) = open_read_only(&db_path) else {
+                continue;
+            };
+            let value = conn
+                .query_row(
+                    "SELECT value FROM ItemTable WHERE key = ?",
+                    [COMPOSER_LIST_KEY],
+                    |r| Ok(value_text(r.get_ref(0)?)),
+                )
+                .ok()
+                .flatten();
+            let Some(list) = value.and_then(|v| serde_json::from_str::<RawComposerList>(&v).ok())
+            else {
+                continue;
+            };
+            for composer in list.all_composers {
+                if let Some(id) = composer.composer_id {
+                    folders.insert(id, folder.clone());
+                }
+            }
+        }
+
+        folders
+    }
+
+    /// Emit messages for bubbles past `progress`, returning the session and
+    /// thread to upsert (if anything new was found) and the updated progress.
+    fn map_conversation(
+        conv: &Conversation,
+        mut progress: ConversationProgress,
+        source_path: &str,
+        observed_at: DateTime<Utc>,
+        result: &mut ParseResult,
+    ) -> (Option<Session>, Option<Thread>, ConversationProgress) {
+        let is_new = progress.bubbles == 0 && progress.seq == 0;
+        let thread_id = format!("{}-main", conv.id);
+        let mut model = conv.model.clone();
+        let mut last_timestamp = conv.created_at.or(conv.updated_at).unwrap_or(observed_at);
+        let mut first_timestamp = conv.created_at;
+        let emitted_before = result.messages.len();
+
+        for (idx, raw) in conv.bubbles.iter().enumerate() {
+            let bubble: RawBubble = serde_json::from_value(raw.clone()).unwrap_or_default();
+            if let Some(ts) = bubble_timestamp(&bubble) {
+                last_timestamp = ts;
+                first_timestamp.get_or_insert(ts);
+            }
+            if model.is_none() {
+                model = bubble
+                    .model_info
+                    .as_ref()
+                    .and_then(|m| m.model_name.clone());
+            }
+            if idx < progress.bubbles {
+                continue;
+            }
+
+            // Stop before an unfinished bubble; it is read again next sync
+            if !bubble_finished(&bubble, idx + 1 == conv.bubbles.len(), conv.generating) {
+                break;
+            }
+
+            let text = bubble.text.clone().filter(|t| !t.trim().is_empty());
+            let is_user = is_user_bubble(&bubble.bubble_type);
+
+            let base = |seq: i32| Message {
+                id: 0,
+                session_id: conv.id.clone(),
+                thread_id: thread_id.clone(),
+                seq,
+                emitted_at: last_timestamp,
+                observed_at,
+                author_role: AuthorRole::Assistant,
+                author_name: None,
+                message_type: MessageType::Response,
+                content: None,
+                content_type: None,
+                tool_name: None,
+                tool_input: None,
+                tool_result: None,
+                tool_call_id: None,
+                tokens_in: None,
+                tokens_out: None,
+                tokens_cache_read: None,
+                tokens_cache_write: None,
+                tokens_reasoning: None,
+                duration_ms: None,
+                source_file_path: source_path.to_string(),
+                source_offset: conv.rowid,
+                source_line: None,
+                record_id: None,
+                parent_record_id: None,
+                raw_data: raw.clone(),
+                metadata: serde_json::json!({ "bubble_index": idx }),
+            };
+
+            if is_user {
+                progress.seq += 1;
+                let mut msg = base(progress.seq);
+                msg.author_role = AuthorRole::Human;
+                msg.message_type = MessageType::Prompt;
+                msg.content = text;
+                result.messages.push(msg);
+            } else {
+                let tokens = bubble.token_count.as_ref();
+                if text.is_some() {
+                    progress.seq += 1;
+                    let mut msg = base(progress.seq);
+                    msg.content = text;
+                    msg.tokens_in = tokens.and_then(|t| t.input_tokens).filter(|n| *n > 0);
+                    msg.tokens_out = tokens.and_then(|t| t.output_tokens).filter(|n| *n > 0);
+                    result.messages.push(msg);
+                }
+
+                if let Some(tool) = &bubble.tool_former_data {
+                    let input = tool.raw_args.as_ref().map(|args| {
+                        serde_json::from_str(args)
+                            .unwrap_or_else(|_| serde_json::Value::String(args.clone()))
+                    });
+                    let metadata = serde_json::json!({
+                        "bubble_index": idx,
+                        "call_id": tool.tool_call_id,
+                        "status": tool.status,
+                    });
+
+                    progress.seq += 1;
+                    let mut call = base(progress.seq);
+                    call.message_type = MessageType::ToolCall;
+                    call.tool_name = tool.name.clone();
+                    call.tool_input = input;
+                    call.tool_call_id = tool.tool_call_id.clone();
+                    call.metadata = metadata.clone();
+                    result.messages.push(call);
+
+                    if tool.result.is_some() {
+                        progress.seq += 1;
+                        let mut res = base(progress.seq);
+                        res.author_role = AuthorRole::Tool;
+                        res.message_type = if tool.status.as_deref() == Some("error") {
+                            MessageType::Error
+                        } else {
+                            MessageType::ToolResult
+                        };
+                        res.tool_name = tool.name.clone();
+                        res.tool_result = tool.result.clone();
+                        res.tool_call_id = tool.tool_call_id.clone();
+                        res.metadata = metadata;
+                        result.messages.push(res);
+                    }
+                }
+            }
+
+            progress.bubbles = idx + 1;
+        }
+
+        if !is_new && result.messages.len() == emitted_before {
+            return (None, None, progress);
+        }
+
+        let started_at = first_timestamp.unwrap_or(last_timestamp);
+        let last_activity = conv
+            .updated_at
+            .map_or(last_timestamp, |u| u.max(last_timestamp));
+
+        let session = Session {
+            id: conv.id.clone(),
+            assistant: Assistant::Cursor,
+            backing_model_id: model.as_deref().map(backing_model_id),
+            project_id: None,
+            started_at,
+            last_activity_at: Some(last_activity),
+            status: SessionStatus::from_last_activity(Some(last_activity)),
+            source_file_path: source_path.to_string(),
+            metadata: serde_json::json!({
+                "cwd": conv.cwd,
+                "title": conv.title,
+                "kind": conv.kind,
+                "model": model,
+            }),
+        };
+
+        let thread = Thread {
+            id: thread_id,
+            session_id: conv.id.clone(),
+            thread_type: ThreadType::Main,
+            parent_thread_id: None,
+            spawned_by_message_id: None,
+            started_at,
+            ended_at: None,
+            last_activity_at: Some(last_activity),
+            metadata: serde_json::json!({}),
+        };
+
+        (Some(session), Some(thread), progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_patterns() {
+        let parser = CursorParser::new();
+        let patterns = parser.source_patterns();
+
+        assert_eq!(patterns.len(), 2);
+        assert!(patterns.iter().all(|p| p.file_type == FileType::Sqlite));
+        assert!(patterns.iter().all(|p| p.pattern.ends_with("state.vscdb")));
+    }
+
+    #[test]
+    fn test_assistant_type() {
+        let parser = CursorParser::new();
+        assert_eq!(parser.assistant(), Assistant::Cursor);
+    }
+
+    #[test]
+    fn test_with_root() {
+        let custom_root = PathBuf::from("/custom/path");
+        let parser = CursorParser::with_root(custom_root.clone());
+        assert_eq!(parser.root_path(), Some(custom_root));
+    }
+
+    #[test]
+    fn test_file_uri_to_path() {
+        assert_eq!(
+            file_uri_to_path("file:///Users/test/my%20project"),
+            Some("/Users/test/my project".to_string())
+        );
+        assert_eq!(file_uri_to_path("vscode-remote://host/x"), None);
+    }
+
+    #[test]
+    fn test_is_user_bubble() {
+        assert!(is_user_bubble(&serde_json::json!(1)));
+        assert!(is_user_bubble(&serde_json::json!("user")));
+        assert!(!is_user_bubble(&serde_json::json!(2)));
+        assert!(!is_user_bubble(&serde_json::json!("ai")));
+    }
+}
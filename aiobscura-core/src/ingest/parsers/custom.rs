@@ -0,0 +1,500 @@
+//! Configuration-defined JSONL parser
+//!
+//! Parses JSONL logs of agents that have no built-in parser, using the field
+//! mappings from an `[[agents.custom]]` section of `config.toml`
+//! (see [`CustomAgentConfig`]).
+//!
+//! Each non-empty line is one record. A record becomes one message:
+//!
+//! - a tool result when `tool_result` resolves,
+//! - otherwise a tool call when `tool_name` resolves,
+//! - otherwise a prompt, response or context message depending on its role.
+//!
+//! Session IDs are prefixed with the agent name (`{name}:{id}`) so that IDs
+//! from different agents cannot collide. A file may hold several sessions
+//! when `session_id` is mapped; each distinct ID becomes its own session.
+//!
+//! # Error Handling
+//!
+//! - **Malformed JSON lines**: Logged as warning, line skipped, parsing continues.
+//! - **Unmapped or missing fields**: Left empty; a record without a timestamp
+//!   uses the previous record's timestamp.
+//! - **File truncation detected**: Parsing restarts from the beginning.
+//! - **Incomplete last line**: Left for the next sync.
+//!
+//! # Incremental Parsing
+//!
+//! Like the built-in JSONL parsers, the checkpoint is a
+//! [`Checkpoint::ByteOffset`] pointing past the last complete line.
+
+use crate::config::CustomAgentConfig;
+use crate::error::{Error, Result};
+use crate::ingest::parser::{AssistantParser, ParseContext, ParseResult, SourcePattern};
+use crate::types::{
+    Assistant, AuthorRole, Checkpoint, ContentType, FileType, Message, MessageType, Session,
+    SessionStatus, Thread, ThreadType,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Role mapping applied before the configured `roles`.
+const DEFAULT_ROLES: &[(&str, AuthorRole)] = &[
+    ("user", AuthorRole::Human),
+    ("human", AuthorRole::Human),
+    ("assistant", AuthorRole::Assistant),
+    ("tool", AuthorRole::Tool),
+    ("system", AuthorRole::System),
+];
+
+/// Parser for a JSONL agent log described in configuration.
+pub struct ConfigurableJsonlParser {
+    config: CustomAgentConfig,
+    root: PathBuf,
+    roles: HashMap<String, AuthorRole>,
+}
+
+impl ConfigurableJsonlParser {
+    /// Create a parser from an `[[agents.custom]]` section.
+    ///
+    /// Returns [`Error::Config`] if the section is invalid.
+    pub fn new(config: CustomAgentConfig) -> Result<Self> {
+        config.validate()?;
+
+        let mut roles: HashMap<String, AuthorRole> = DEFAULT_ROLES
+            .iter()
+            .map(|(raw, role)| (raw.to_string(), *role))
+            .collect();
+        for (raw, role) in &config.roles {
+            roles.insert(raw.clone(), role.parse().map_err(Error::Config)?);
+        }
+
+        Ok(Self {
+            root: config.expanded_root(),
+            config,
+            roles,
+        })
+    }
+
+    /// Name of the configured agent.
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Look up a mapped field in a record.
+    fn field<'a>(
+        &self,
+        record: &'a serde_json::Value,
+        pointer: &Option<String>,
+    ) -> Option<&'a serde_json::Value> {
+        let pointer = pointer.as_deref()?;
+        record.pointer(pointer).filter(|v| !v.is_null())
+    }
+
+    /// Look up a mapped field as text; non-string values are serialized.
+    fn text_field(&self, record: &serde_json::Value, pointer: &Option<String>) -> Option<String> {
+        match self.field(record, pointer)? {
+            serde_json::Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn int_field(&self, record: &serde_json::Value, pointer: &Option<String>) -> Option<i32> {
+        let value = self.field(record, pointer)?;
+        value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+            .and_then(|n| i32::try_from(n).ok())
+    }
+
+    fn namespaced_session_id(&self, raw: &str) -> String {
+        format!("{}:{}", self.config.name, raw)
+    }
+
+    fn backing_model_id(&self, model: &str) -> String {
+        if model.contains(':') {
+            return model.to_string();
+        }
+        let provider = self
+            .config
+            .model_provider
+            .as_deref()
+            .unwrap_or(&self.config.name);
+        format!("{}:{}", provider, model)
+    }
+}
+
+/// Parse an RFC 3339 string or an epoch timestamp (seconds or milliseconds).
+fn parse_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    match value {
+        serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc)),
+        serde_json::Value::Number(n) => {
+            let n = n.as_f64()?;
+            // Anything past year 2286 in seconds is taken as milliseconds
+            if n.abs() >= 1e10 {
+                DateTime::from_timestamp_millis(n as i64)
+            } else {
+                DateTime::from_timestamp_millis((n * 1000.0) as i64)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Per-session state accumulated while parsing.
+struct SessionState {
+    started_at: DateTime<Utc>,
+    last_activity_at: DateTime<Utc>,
+    model: Option<String>,
+    cwd: Option<String>,
+}
+
+impl AssistantParser for ConfigurableJsonlParser {
+    fn assistant(&self) -> Assistant {
+        Assistant::Custom
+    }
+
+    fn agent(&self) -> Option<&str> {
+        Some(&self.config.name)
+    }
+
+    fn root_path(&self) -> Option<PathBuf> {
+        Some(self.root.clone())
+    }
+
+    fn source_patterns(&self) -> Vec<SourcePattern> {
+        self.config
+            .sources
+            .iter()
+            .map(|source| SourcePattern {
+                pattern: source.pattern.clone(),
+                file_type: FileType::Jsonl,
+                description: source
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("{} logs", self.config.name)),
+            })
+            .collect()
+    }
+
+    fn parse(&self, ctx: &ParseContext) -> Result<ParseResult> {
+        let mut result = ParseResult::default();
+        let fields = &self.config.fields;
+
+        let file = File::open(ctx.path).map_err(|e| {
+            Error::Io(std::io::Error::new(
+                e.kind(),
+                format!("Failed to open {}: {}", ctx.path.display(), e),
+            ))
+        })?;
+
+        // Determine start offset from checkpoint
+        let start_offset = match ctx.checkpoint {
+            Checkpoint::ByteOffset { offset } => {
+                if *offset > ctx.file_size {
+                    result.warnings.push(format!(
+                        "File truncated: checkpoint {} > file size {}, starting from beginning",
+                        offset, ctx.file_size
+                    ));
+                    0
+                } else {
+                    *offset
+                }
+            }
+            _ => 0,
+        };
+
+        if start_offset >= ctx.file_size {
+            result.new_checkpoint = Checkpoint::ByteOffset {
+                offset: ctx.file_size,
+            };
+            return Ok(result);
+        }
+
+        let mut reader = BufReader::new(file);
+        if start_offset > 0 {
+            reader.seek(SeekFrom::Start(start_offset))?;
+        }
+
+        let default_session_id = self
+            .extract_session_id(ctx.path)
+            .unwrap_or_else(|| self.config.name.clone());
+        let source_path = ctx.path.to_string_lossy().to_string();
+        let observed_at = Utc::now();
+
+        let mut current_offset = start_offset;
+        let mut line_number = 0;
+        let mut seq = 0i32;
+        let mut last_timestamp = observed_at;
+        // Sessions in order of first appearance
+        let mut session_order: Vec<String> = Vec::new();
+        let mut sessions: HashMap<String, SessionState> = HashMap::new();
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let bytes_read = reader.read_line(&mut line)?;
+            if bytes_read == 0 {
+                break;
+            }
+            // Incomplete last line: leave it for the next sync
+            if !line.ends_with('\n') {
+                break;
+            }
+
+            line_number += 1;
+            let record_offset = current_offset;
+            current_offset += bytes_read as u64;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record: serde_json::Value = match serde_json::from_str(line.trim_end()) {
+                Ok(v) => v,
+                Err(e) => {
+                    result.warnings.push(format!(
+                        "Line {} (offset {}): JSON parse error: {}",
+                        line_number, record_offset, e
+                    ));
+                    continue;
+                }
+            };
+
+            let emitted_at = self
+                .field(&record, &fields.timestamp)
+                .and_then(parse_timestamp)
+                .unwrap_or(last_timestamp);
+            last_timestamp = emitted_at;
+
+            let session_id = self.namespaced_session_id(
+                &self
+                    .text_field(&record, &fields.session_id)
+                    .unwrap_or_else(|| default_session_id.clone()),
+            );
+            let state = sessions.entry(session_id.clone()).or_insert_with(|| {
+                session_order.push(session_id.clone());
+                SessionState {
+                    started_at: emitted_at,
+                    last_activity_at: emitted_at,
+                    model: None,
+                    cwd: None,
+                }
+            });
+            state.last_activity_at = state.last_activity_at.max(emitted_at);
+            if let Some(model) = self.text_field(&record, &fields.model) {
+                state.model = Some(model);
+            }
+            if let Some(cwd) = self.text_field(&record, &fields.cwd) {
+                state.cwd = Some(cwd);
+            }
+
+            let raw_role = self.text_field(&record, &fields.role);
+            let role = raw_role.as_deref().and_then(|r| self.roles.get(r)).copied();
+            let content = self.text_field(&record, &fields.content);
+            let tool_name = self.text_field(&record, &fields.tool_name);
+            let tool_result = self.text_field(&record, &fields.tool_result);
+            let tool_input = self.field(&record, &fields.tool_input).map(|v| match v {
+                serde_json::Value::String(s) => serde_json::from_str(s).unwrap_or(v.clone()),
+                other => other.clone(),
+            });
+
+            let (author_role, message_type) = if tool_result.is_some() {
+                (AuthorRole::Tool, MessageType::ToolResult)
+            } else if tool_name.is_some() {
+                (role.unwrap_or(AuthorRole::Assistant), MessageType::ToolCall)
+            } else {
+                match role {
+                    Some(r @ (AuthorRole::Human | AuthorRole::Caller)) => (r, MessageType::Prompt),
+                    Some(r @ (AuthorRole::Assistant | AuthorRole::Agent)) => {
+                        (r, MessageType::Response)
+                    }
+                    Some(AuthorRole::Tool) => (AuthorRole::Tool, MessageType::ToolResult),
+                    Some(AuthorRole::System) | None => (AuthorRole::System, MessageType::Context),
+                }
+            };
+
+            seq += 1;
+            result.messages.push(Message {
+                id: 0,
+                session_id: session_id.clone(),
+                thread_id: format!("{}-main", session_id),
+                seq,
+                emitted_at,
+                observed_at,
+                author_role,
+                author_name: if role.is_none() { raw_role } else { None },
+                message_type,
+                content_type: content.as_ref().map(|_| ContentType::Text),
+                content,
+                tool_name,
+                tool_input,
+                tool_result,
+                tool_call_id: None,
+                tokens_in: self.int_field(&record, &fields.tokens_in),
+                tokens_out: self.int_field(&record, &fields.tokens_out),
+                tokens_cache_read: None,
+                tokens_cache_write: None,
+                tokens_reasoning: None,
+                duration_ms: None,
+                source_file_path: source_path.clone(),
+                source_offset: record_offset as i64,
+                source_line: Some(line_number),
+                record_id: None,
+                parent_record_id: None,
+                raw_data: record,
+                metadata: serde_json::json!({}),
+            });
+        }
+
+        let mut built = Vec::new();
+        for sid in session_order {
+            let state = &sessions[&sid];
+
+            result.threads.push(Thread {
+                id: format!("{}-main", sid),
+                session_id: sid.clone(),
+                thread_type: ThreadType::Main,
+                parent_thread_id: None,
+                spawned_by_message_id: None,
+                started_at: state.started_at,
+                ended_at: None,
+                last_activity_at: Some(state.last_activity_at),
+                metadata: serde_json::json!({}),
+            });
+
+            built.push(Session {
+                id: sid.clone(),
+                assistant: Assistant::Custom,
+                backing_model_id: state.model.as_deref().map(|m| self.backing_model_id(m)),
+                // Resolved from the cwd in metadata when stored
+                project_id: None,
+                started_at: state.started_at,
+                last_activity_at: Some(state.last_activity_at),
+                status: SessionStatus::from_last_activity(Some(state.last_activity_at)),
+                source_file_path: source_path.clone(),
+                metadata: serde_json::json!({
+                    "agent": self.config.name,
+                    "cwd": state.cwd,
+                }),
+            });
+        }
+
+        result.session = built.pop();
+        result.extra_sessions = built;
+        result.new_checkpoint = Checkpoint::ByteOffset {
+            offset: current_offset,
+        };
+
+        Ok(result)
+    }
+
+    fn extract_project_path(&self, file_path: &Path) -> Option<PathBuf> {
+        // The project comes from the mapped `cwd` field, not the file path
+        let _ = file_path;
+        None
+    }
+
+    fn extract_session_id(&self, file_path: &Path) -> Option<String> {
+        file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CustomFieldMappings, CustomSourceConfig};
+
+    fn config() -> CustomAgentConfig {
+        CustomAgentConfig {
+            name: "buildbot".to_string(),
+            root: PathBuf::from("/custom/path"),
+            sources: vec![CustomSourceConfig {
+                pattern: "sessions/*.jsonl".to_string(),
+                description: None,
+            }],
+            fields: CustomFieldMappings {
+                role: Some("/role".to_string()),
+                ..Default::default()
+            },
+            roles: HashMap::from([("bot".to_string(), "assistant".to_string())]),
+            model_provider: None,
+        }
+    }
+
+    #[test]
+    fn test_source_patterns() {
+        let parser = ConfigurableJsonlParser::new(config()).unwrap();
+        let patterns = parser.source_patterns();
+
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].pattern, "sessions/*.jsonl");
+        assert_eq!(patterns[0].file_type, FileType::Jsonl);
+    }
+
+    #[test]
+    fn test_assistant_type() {
+        let parser = ConfigurableJsonlParser::new(config()).unwrap();
+        assert_eq!(parser.assistant(), Assistant::Custom);
+        assert_eq!(parser.root_path(), Some(PathBuf::from("/custom/path")));
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        let mut bad_pointer = config();
+        bad_pointer.fields.content = Some("message.text".to_string());
+        assert!(ConfigurableJsonlParser::new(bad_pointer).is_err());
+
+        let mut bad_role = config();
+        bad_role
+            .roles
+            .insert("robot".to_string(), "android".to_string());
+        assert!(ConfigurableJsonlParser::new(bad_role).is_err());
+    }
+
+    #[test]
+    fn test_role_mapping() {
+        let parser = ConfigurableJsonlParser::new(config()).unwrap();
+        assert_eq!(parser.roles.get("user"), Some(&AuthorRole::Human));
+        assert_eq!(parser.roles.get("bot"), Some(&AuthorRole::Assistant));
+    }
+
+    #[test]
+    fn test_backing_model_id() {
+        let mut cfg = config();
+        let parser = ConfigurableJsonlParser::new(cfg.clone()).unwrap();
+        assert_eq!(parser.backing_model_id("m1"), "buildbot:m1");
+        assert_eq!(parser.backing_model_id("openai:gpt-4o"), "openai:gpt-4o");
+
+        cfg.model_provider = Some("anthropic".to_string());
+        let parser = ConfigurableJsonlParser::new(cfg).unwrap();
+        assert_eq!(parser.backing_model_id("claude"), "anthropic:claude");
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let expected = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_timestamp(&serde_json::json!("2025-01-01T00:00:00Z")),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_timestamp(&serde_json::json!(1735689600)),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_timestamp(&serde_json::json!(1735689600000i64)),
+            Some(expected)
+        );
+        assert_eq!(parse_timestamp(&serde_json::json!(true)), None);
+    }
+}
@@ -9,17 +9,34 @@
 //! |-----------|--------|--------|
 //! | Claude Code | [`ClaudeCodeParser`] | ✅ Implemented |
 //! | Codex | [`CodexParser`] | ✅ Implemented |
-//! | Aider | - | 📋 Planned |
-//! | Cursor | - | 📋 Planned |
+//! | Aider | [`AiderParser`] | ✅ Implemented |
+//! | Cursor | [`CursorParser`] | ✅ Implemented |
+//! | Gemini CLI | [`GeminiParser`] | ✅ Implemented |
+//! | Custom JSONL agents | [`ConfigurableJsonlParser`] | ✅ Configured in `[[agents.custom]]` |
+//! | Parser plugins | [`SubprocessParser`] | ✅ Configured in `[[agents.plugin]]` |
 
+mod aider;
 mod claude;
 mod codex;
+mod cursor;
+mod custom;
+mod gemini;
+mod rooted;
+mod subprocess;
 
+pub use aider::AiderParser;
 pub use claude::ClaudeCodeParser;
 pub use codex::CodexParser;
+pub use cursor::CursorParser;
+pub use custom::ConfigurableJsonlParser;
+pub use gemini::GeminiParser;
+pub use rooted::RootedParser;
+pub use subprocess::{SubprocessParser, PLUGIN_PROTOCOL_VERSION};
 
-use super::AssistantParser;
+use super::{AssistantParser, SourceRoot};
+use crate::config::Config;
 use crate::types::Assistant;
+use std::path::PathBuf;
 
 /// Create all available parsers.
 ///
@@ -29,11 +46,75 @@ pub fn create_all_parsers() -> Vec<Box<dyn AssistantParser>> {
     vec![
         Box::new(ClaudeCodeParser::new()),
         Box::new(CodexParser::new()),
-        // Future: Box::new(AiderParser::new()),
-        // Future: Box::new(CursorParser::new()),
+        Box::new(CursorParser::new()),
+        Box::new(GeminiParser::new()),
+        // Aider is rooted at $HOME; files are routed to the parser with the
+        // most specific matching root, so it only gets what others don't claim.
+        Box::new(AiderParser::new()),
     ]
 }
 
+/// Create all available parsers plus the custom agents and parser plugins
+/// defined in `config`.
+///
+/// Built-in assistants are read from their default root (or `*_path`
+/// override), labeled `default`, and from each of their `[[agents.root]]`
+/// sections. Invalid `[[agents.custom]]`, `[[agents.plugin]]` and
+/// `[[agents.root]]` sections are logged and skipped.
+pub fn create_parsers(config: &Config) -> Vec<Box<dyn AssistantParser>> {
+    let host = local_host();
+    let mut parsers: Vec<Box<dyn AssistantParser>> = Vec::new();
+    for parser in create_all_parsers() {
+        let assistant = parser.assistant();
+        let parser = match config.agents.path_override(assistant) {
+            Some(root) => parser_with_root(assistant, root).unwrap_or(parser),
+            None => parser,
+        };
+        let source = SourceRoot {
+            label: "default".to_string(),
+            host: host.clone(),
+        };
+        parsers.push(Box::new(RootedParser::new(parser, source)));
+    }
+    for root in &config.agents.root {
+        match parser_with_root(root.assistant, root.expanded_path()) {
+            Some(parser) => {
+                let source = SourceRoot {
+                    label: root.label.clone(),
+                    host: root.host.clone().unwrap_or_else(|| host.clone()),
+                };
+                parsers.push(Box::new(RootedParser::new(parser, source)));
+            }
+            None => tracing::warn!(
+                assistant = %root.assistant,
+                label = %root.label,
+                "Skipping agent root: only built-in assistants take extra roots"
+            ),
+        }
+    }
+    for custom in &config.agents.custom {
+        match ConfigurableJsonlParser::new(custom.clone()) {
+            Ok(parser) => parsers.push(Box::new(parser)),
+            Err(e) => tracing::warn!(
+                agent = %custom.name,
+                error = %e,
+                "Skipping invalid custom agent"
+            ),
+        }
+    }
+    for plugin in &config.agents.plugin {
+        match SubprocessParser::new(plugin.clone()) {
+            Ok(parser) => parsers.push(Box::new(parser)),
+            Err(e) => tracing::warn!(
+                agent = %plugin.name,
+                error = %e,
+                "Skipping invalid parser plugin"
+            ),
+        }
+    }
+    parsers
+}
+
 /// Get a parser for a specific assistant.
 ///
 /// Returns `None` if no parser is implemented for the given assistant.
@@ -41,13 +122,66 @@ pub fn parser_for(assistant: Assistant) -> Option<Box<dyn AssistantParser>> {
     match assistant {
         Assistant::ClaudeCode => Some(Box::new(ClaudeCodeParser::new())),
         Assistant::Codex => Some(Box::new(CodexParser::new())),
-        _ => None, // Not yet implemented
+        Assistant::Aider => Some(Box::new(AiderParser::new())),
+        Assistant::Cursor => Some(Box::new(CursorParser::new())),
+        Assistant::Gemini => Some(Box::new(GeminiParser::new())),
+        // Custom parsers need their configuration; see parser_for_agent
+        Assistant::Custom => None,
     }
 }
 
+/// Get the parser of the custom agent or parser plugin named `name` in
+/// `config`.
+///
+/// Custom agents and plugins all handle [`Assistant::Custom`]; their name
+/// is recorded as [`AssistantParser::agent`]. Returns `None` if no valid
+/// `[[agents.custom]]` or `[[agents.plugin]]` section has that name.
+pub fn parser_for_agent(config: &Config, name: &str) -> Option<Box<dyn AssistantParser>> {
+    if let Some(custom) = config.agents.custom.iter().find(|c| c.name == name) {
+        let parser = ConfigurableJsonlParser::new(custom.clone()).ok()?;
+        return Some(Box::new(parser));
+    }
+    let plugin = config.agents.plugin.iter().find(|p| p.name == name)?;
+    let parser = SubprocessParser::new(plugin.clone()).ok()?;
+    Some(Box::new(parser))
+}
+
+/// Get a parser for a built-in assistant that reads from `root`.
+///
+/// Returns `None` for [`Assistant::Custom`], whose parsers are configured
+/// with their own root.
+pub fn parser_with_root(assistant: Assistant, root: PathBuf) -> Option<Box<dyn AssistantParser>> {
+    match assistant {
+        Assistant::ClaudeCode => Some(Box::new(ClaudeCodeParser::with_root(root))),
+        Assistant::Codex => Some(Box::new(CodexParser::with_root(root))),
+        Assistant::Aider => Some(Box::new(AiderParser::with_root(root))),
+        Assistant::Cursor => Some(Box::new(CursorParser::with_root(root))),
+        Assistant::Gemini => Some(Box::new(GeminiParser::with_root(root))),
+        Assistant::Custom => None,
+    }
+}
+
+/// Name of this machine, the default host label of every root.
+fn local_host() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .or_else(|| {
+            std::process::Command::new("hostname")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        })
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::FileType;
 
     #[test]
     fn test_create_all_parsers() {
@@ -73,8 +207,114 @@ mod tests {
     }
 
     #[test]
-    fn test_parser_for_unimplemented() {
+    fn test_parser_for_aider() {
         let parser = parser_for(Assistant::Aider);
-        assert!(parser.is_none());
+        assert!(parser.is_some());
+        assert_eq!(parser.unwrap().assistant(), Assistant::Aider);
+    }
+
+    #[test]
+    fn test_parser_for_cursor() {
+        let parser = parser_for(Assistant::Cursor);
+        assert!(parser.is_some());
+        assert_eq!(parser.unwrap().assistant(), Assistant::Cursor);
+    }
+
+    #[test]
+    fn test_parser_for_custom() {
+        assert!(parser_for(Assistant::Custom).is_none());
+    }
+
+    #[test]
+    fn test_create_parsers_with_custom_agent() {
+        let config: Config = toml::from_str(
+            r#"
+[[agents.custom]]
+name = "buildbot"
+root = "/tmp/buildbot"
+
+[[agents.custom.sources]]
+pattern = "*.jsonl"
+
+[[agents.custom]]
+name = "broken"
+root = "/tmp/broken"
+sources = []
+"#,
+        )
+        .unwrap();
+
+        let parsers = create_parsers(&config);
+        assert_eq!(parsers.len(), create_all_parsers().len() + 1);
+        assert_eq!(parsers.last().unwrap().assistant(), Assistant::Custom);
+    }
+
+    #[test]
+    fn test_create_parsers_with_plugin() {
+        let config: Config = toml::from_str(
+            r#"
+[[agents.plugin]]
+name = "shipit"
+command = "shipit-aiobscura"
+root = "/tmp/shipit"
+
+[[agents.plugin.sources]]
+pattern = "runs/*.json"
+file_type = "json"
+"#,
+        )
+        .unwrap();
+
+        let parsers = create_parsers(&config);
+        assert_eq!(parsers.len(), create_all_parsers().len() + 1);
+        let plugin = parsers.last().unwrap();
+        assert_eq!(plugin.assistant(), Assistant::Custom);
+        assert_eq!(plugin.source_patterns()[0].file_type, FileType::Json);
+    }
+
+    #[test]
+    fn test_create_parsers_with_roots() {
+        let config: Config = toml::from_str(
+            r#"
+[[agents.root]]
+assistant = "claude_code"
+path = "/profiles/work/.claude"
+label = "work"
+host = "laptop"
+
+[[agents.root]]
+assistant = "custom"
+path = "/tmp/custom"
+label = "ignored"
+"#,
+        )
+        .unwrap();
+
+        let parsers = create_parsers(&config);
+        assert_eq!(parsers.len(), create_all_parsers().len() + 1);
+        assert!(parsers
+            .iter()
+            .all(|p| p.source_root().is_some_and(|s| !s.host.is_empty())));
+
+        let work = parsers.last().unwrap();
+        assert_eq!(work.assistant(), Assistant::ClaudeCode);
+        assert_eq!(
+            work.root_path(),
+            Some(PathBuf::from("/profiles/work/.claude"))
+        );
+        assert_eq!(
+            work.source_root(),
+            Some(&SourceRoot {
+                label: "work".to_string(),
+                host: "laptop".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parser_for_gemini() {
+        let parser = parser_for(Assistant::Gemini);
+        assert!(parser.is_some());
+        assert_eq!(parser.unwrap().assistant(), Assistant::Gemini);
     }
 }
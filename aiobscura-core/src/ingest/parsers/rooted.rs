@@ -0,0 +1,76 @@
+//! A built-in parser bound to one labeled data root
+//!
+//! Built-in parsers read a single root each. To read an assistant from
+//! several roots, [`create_parsers`](super::create_parsers) creates one
+//! parser per root and wraps it in a [`RootedParser`] carrying the root's
+//! label and host.
+
+use crate::error::Result;
+use crate::ingest::{
+    AssistantParser, ParseContext, ParseResult, SourcePattern, SourceRoot, WatchPath,
+};
+use crate::types::{Assistant, SourceFile};
+use std::path::{Path, PathBuf};
+
+/// Delegates to an inner parser and reports its [`SourceRoot`].
+pub struct RootedParser {
+    inner: Box<dyn AssistantParser>,
+    source: SourceRoot,
+}
+
+impl RootedParser {
+    /// Wrap `inner`, whose root is labeled `source`.
+    pub fn new(inner: Box<dyn AssistantParser>, source: SourceRoot) -> Self {
+        Self { inner, source }
+    }
+}
+
+impl AssistantParser for RootedParser {
+    fn assistant(&self) -> Assistant {
+        self.inner.assistant()
+    }
+
+    fn agent(&self) -> Option<&str> {
+        self.inner.agent()
+    }
+
+    fn version(&self) -> u32 {
+        self.inner.version()
+    }
+
+    fn root_path(&self) -> Option<PathBuf> {
+        self.inner.root_path()
+    }
+
+    fn source_root(&self) -> Option<&SourceRoot> {
+        Some(&self.source)
+    }
+
+    fn is_installed(&self) -> bool {
+        self.inner.is_installed()
+    }
+
+    fn source_patterns(&self) -> Vec<SourcePattern> {
+        self.inner.source_patterns()
+    }
+
+    fn parse(&self, ctx: &ParseContext) -> Result<ParseResult> {
+        self.inner.parse(ctx)
+    }
+
+    fn extract_project_path(&self, file_path: &Path) -> Option<PathBuf> {
+        self.inner.extract_project_path(file_path)
+    }
+
+    fn extract_session_id(&self, file_path: &Path) -> Option<String> {
+        self.inner.extract_session_id(file_path)
+    }
+
+    fn watch_paths(&self) -> Vec<WatchPath> {
+        self.inner.watch_paths()
+    }
+
+    fn discover_files(&self) -> Result<Vec<SourceFile>> {
+        self.inner.discover_files()
+    }
+}
@@ -0,0 +1,467 @@
+//! Out-of-process parser plugins
+//!
+//! Runs an external executable, configured in an `[[agents.plugin]]` section
+//! of `config.toml` (see [`PluginAgentConfig`]), to parse the source files
+//! of an agent that has no built-in parser. Plugins can be written in any
+//! language.
+//!
+//! # Protocol
+//!
+//! The command is run once per parse. It receives one JSON object on stdin:
+//!
+//! ```json
+//! {
+//!   "protocol_version": 1,
+//!   "agent": "shipit",
+//!   "path": "/home/me/.shipit/runs/42.json",
+//!   "checkpoint": {"type": "byte_offset", "offset": 1024},
+//!   "file_size": 2048,
+//!   "modified_at": "2025-04-01T10:00:00Z"
+//! }
+//! ```
+//!
+//! and must print a serialized [`ParseResult`] on stdout, then exit with
+//! status 0. Every field of the result may be left out, but the entities in
+//! it are deserialized as-is: fields that are not an `Option` are required
+//! (see `docs/parser-plugins.md`). Sessions are stored as
+//! [`Assistant::Custom`] with the plugin name in `metadata.agent`, and empty
+//! `source_file_path` fields are filled in with the parsed path. Anything
+//! written to stderr is logged at debug level.
+//!
+//! # Error Handling
+//!
+//! - **Command missing, non-zero exit or timeout**: Returned as
+//!   [`Error::Parse`] with the tail of stderr; the file is retried next sync.
+//! - **Invalid output**: Returned as [`Error::Parse`].
+//!
+//! # Incremental Parsing
+//!
+//! The plugin owns its checkpoint: whatever it returns as `new_checkpoint` is
+//! passed back on the next run. A plugin that returns no checkpoint is handed
+//! [`Checkpoint::None`](crate::types::Checkpoint::None) again, and must then
+//! not re-emit messages it already returned.
+
+use crate::config::PluginAgentConfig;
+use crate::error::{Error, Result};
+use crate::ingest::parser::{AssistantParser, ParseContext, ParseResult, SourcePattern};
+use crate::types::Assistant;
+use serde::Serialize;
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Version of the stdin/stdout protocol sent to plugins.
+pub const PLUGIN_PROTOCOL_VERSION: u32 = 1;
+
+/// How often to poll a running plugin for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Maximum number of stderr bytes quoted in error messages.
+const STDERR_TAIL_BYTES: usize = 1024;
+
+/// Request written to the plugin's stdin.
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    protocol_version: u32,
+    agent: &'a str,
+    #[serde(flatten)]
+    context: &'a ParseContext<'a>,
+}
+
+/// Parser that delegates to an external executable.
+pub struct SubprocessParser {
+    config: PluginAgentConfig,
+    root: PathBuf,
+}
+
+impl SubprocessParser {
+    /// Create a parser from an `[[agents.plugin]]` section.
+    ///
+    /// Returns [`Error::Config`] if the section is invalid. The command is
+    /// not run until the first parse.
+    pub fn new(config: PluginAgentConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            root: config.expanded_root(),
+            config,
+        })
+    }
+
+    /// Agent name from configuration.
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Parse {
+            agent: self.config.name.clone(),
+            message: message.into(),
+        }
+    }
+
+    /// Run the plugin with `input` on stdin, returning its stdout.
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let mut child = Command::new(&self.config.command)
+            .args(&self.config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                self.error(format!(
+                    "failed to run {}: {}",
+                    self.config.command.display(),
+                    e
+                ))
+            })?;
+
+        // Drain both pipes on their own threads so a chatty plugin can't
+        // block on a full pipe while we wait for it.
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // A plugin that exits without reading stdin closes the pipe;
+            // its exit status decides the outcome.
+            if let Err(e) = stdin.write_all(input) {
+                if e.kind() != std::io::ErrorKind::BrokenPipe {
+                    let _ = child.kill();
+                    return Err(e.into());
+                }
+            }
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let Some(status) = wait_timeout(&mut child, timeout)? else {
+            return Err(self.error(format!(
+                "{} timed out after {}s",
+                self.config.command.display(),
+                self.config.timeout_secs
+            )));
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        for line in stderr.lines().map_while(|l| l.ok()) {
+            tracing::debug!(agent = %self.config.name, "{}", line);
+        }
+
+        if !status.success() {
+            let tail =
+                String::from_utf8_lossy(&stderr[stderr.len().saturating_sub(STDERR_TAIL_BYTES)..])
+                    .trim()
+                    .to_string();
+            return Err(self.error(format!(
+                "{} exited with {}: {}",
+                self.config.command.display(),
+                status,
+                tail
+            )));
+        }
+
+        Ok(stdout)
+    }
+
+    /// Stamp sessions with this plugin's identity and fill in source paths
+    /// the plugin left empty.
+    fn adopt(&self, result: &mut ParseResult, path: &Path) {
+        let path = path.to_string_lossy();
+        for session in result.session.iter_mut().chain(&mut result.extra_sessions) {
+            session.assistant = Assistant::Custom;
+            if session.source_file_path.is_empty() {
+                session.source_file_path = path.to_string();
+            }
+            if !session.metadata.is_object() {
+                session.metadata = serde_json::json!({});
+            }
+            if let Some(metadata) = session.metadata.as_object_mut() {
+                metadata.insert("agent".to_string(), self.config.name.clone().into());
+            }
+        }
+        for message in &mut result.messages {
+            if message.source_file_path.is_empty() {
+                message.source_file_path = path.to_string();
+            }
+        }
+    }
+}
+
+/// Read a pipe to the end on a background thread.
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Wait for `child` to exit, killing it after `timeout`.
+///
+/// Returns `None` if the child was killed.
+fn wait_timeout(child: &mut Child, timeout: Duration) -> Result<Option<std::process::ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+impl AssistantParser for SubprocessParser {
+    fn assistant(&self) -> Assistant {
+        Assistant::Custom
+    }
+
+    fn agent(&self) -> Option<&str> {
+        Some(&self.config.name)
+    }
+
+    fn root_path(&self) -> Option<PathBuf> {
+        Some(self.root.clone())
+    }
+
+    fn source_patterns(&self) -> Vec<SourcePattern> {
+        self.config
+            .sources
+            .iter()
+            .map(|source| SourcePattern {
+                pattern: source.pattern.clone(),
+                file_type: source.file_type,
+                description: source
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("{} files", self.config.name)),
+            })
+            .collect()
+    }
+
+    fn parse(&self, ctx: &ParseContext) -> Result<ParseResult> {
+        let request = PluginRequest {
+            protocol_version: PLUGIN_PROTOCOL_VERSION,
+            agent: &self.config.name,
+            context: ctx,
+        };
+        let input = serde_json::to_vec(&request)?;
+        let output = self.run(&input)?;
+
+        let mut result: ParseResult = serde_json::from_slice(&output)
+            .map_err(|e| self.error(format!("invalid plugin output: {}", e)))?;
+        self.adopt(&mut result, ctx.path);
+        Ok(result)
+    }
+
+    fn extract_project_path(&self, _file_path: &Path) -> Option<PathBuf> {
+        // Projects come from the plugin's output
+        None
+    }
+
+    fn extract_session_id(&self, file_path: &Path) -> Option<String> {
+        file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::config::PluginSourceConfig;
+    use crate::types::{Checkpoint, FileType};
+    use tempfile::TempDir;
+
+    fn plugin(script: &str, args: &[&str]) -> SubprocessParser {
+        let mut all_args = vec!["-c".to_string(), script.to_string()];
+        all_args.extend(args.iter().map(|a| a.to_string()));
+        SubprocessParser::new(PluginAgentConfig {
+            name: "shipit".to_string(),
+            command: PathBuf::from("sh"),
+            args: all_args,
+            root: PathBuf::from("/tmp"),
+            sources: vec![PluginSourceConfig {
+                pattern: "*.json".to_string(),
+                file_type: FileType::Json,
+                description: None,
+            }],
+            timeout_secs: 5,
+        })
+        .unwrap()
+    }
+
+    fn context<'a>(path: &'a Path, checkpoint: &'a Checkpoint) -> ParseContext<'a> {
+        ParseContext {
+            path,
+            checkpoint,
+            file_size: 42,
+            modified_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_sends_context_on_stdin() {
+        let temp = TempDir::new().unwrap();
+        let request_path = temp.path().join("request.json");
+        let parser = plugin(
+            r#"cat > "$0"; echo '{}'"#,
+            &[request_path.to_str().unwrap()],
+        );
+
+        let checkpoint = Checkpoint::ByteOffset { offset: 7 };
+        parser
+            .parse(&context(Path::new("/tmp/run.json"), &checkpoint))
+            .unwrap();
+
+        let request: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&request_path).unwrap()).unwrap();
+        assert_eq!(request["protocol_version"], PLUGIN_PROTOCOL_VERSION);
+        assert_eq!(request["agent"], "shipit");
+        assert_eq!(request["path"], "/tmp/run.json");
+        assert_eq!(request["file_size"], 42);
+        assert_eq!(request["checkpoint"]["type"], "byte_offset");
+        assert_eq!(request["checkpoint"]["offset"], 7);
+    }
+
+    #[test]
+    fn test_reads_result_from_stdout() {
+        let parser = plugin(
+            r#"cat >/dev/null; cat <<'EOF'
+{
+  "session": {
+    "id": "shipit:42",
+    "assistant": "claude_code",
+    "started_at": "2025-04-01T10:00:00Z",
+    "status": "stale",
+    "source_file_path": "/tmp/run.json",
+    "metadata": null
+  },
+  "new_checkpoint": {"type": "byte_offset", "offset": 42},
+  "warnings": ["skipped one record"]
+}
+EOF"#,
+            &[],
+        );
+
+        let result = parser
+            .parse(&context(Path::new("/tmp/run.json"), &Checkpoint::None))
+            .unwrap();
+
+        let session = result.session.unwrap();
+        assert_eq!(session.id, "shipit:42");
+        assert_eq!(session.assistant, Assistant::Custom);
+        assert_eq!(session.metadata["agent"], "shipit");
+        assert!(result.messages.is_empty());
+        assert!(matches!(
+            result.new_checkpoint,
+            Checkpoint::ByteOffset { offset: 42 }
+        ));
+        assert_eq!(result.warnings, vec!["skipped one record"]);
+    }
+
+    #[test]
+    fn test_result_entities_need_required_fields() {
+        // The required message fields listed in docs/parser-plugins.md
+        let mut message = serde_json::json!({
+            "id": 0,
+            "session_id": "shipit:42",
+            "thread_id": "shipit:42-main",
+            "seq": 0,
+            "emitted_at": "2025-04-01T10:00:00Z",
+            "observed_at": "2025-04-01T10:00:00Z",
+            "author_role": "human",
+            "message_type": "prompt",
+            "source_file_path": "",
+            "source_offset": 0,
+            "raw_data": {},
+            "metadata": {}
+        });
+        let result: ParseResult =
+            serde_json::from_value(serde_json::json!({ "messages": [message.clone()] })).unwrap();
+        assert_eq!(result.messages.len(), 1);
+        assert!(result.messages[0].content.is_none());
+
+        message.as_object_mut().unwrap().remove("raw_data");
+        let err =
+            serde_json::from_value::<ParseResult>(serde_json::json!({ "messages": [message] }))
+                .unwrap_err();
+        assert!(
+            err.to_string().contains("missing field `raw_data`"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_agent_name_overrides_plugin_metadata() {
+        let parser = plugin(
+            r#"cat >/dev/null; cat <<'EOF'
+{
+  "session": {
+    "id": "shipit:42",
+    "assistant": "custom",
+    "started_at": "2025-04-01T10:00:00Z",
+    "status": "stale",
+    "source_file_path": "",
+    "metadata": {"agent": "other"}
+  }
+}
+EOF"#,
+            &[],
+        );
+
+        let result = parser
+            .parse(&context(Path::new("/tmp/run.json"), &Checkpoint::None))
+            .unwrap();
+        assert_eq!(result.session.unwrap().metadata["agent"], "shipit");
+    }
+
+    #[test]
+    fn test_nonzero_exit_is_error() {
+        let parser = plugin("echo 'bad input' >&2; exit 3", &[]);
+        let err = parser
+            .parse(&context(Path::new("/tmp/run.json"), &Checkpoint::None))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("bad input"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_output_is_error() {
+        let parser = plugin("echo 'not json'", &[]);
+        assert!(parser
+            .parse(&context(Path::new("/tmp/run.json"), &Checkpoint::None))
+            .is_err());
+    }
+
+    #[test]
+    fn test_timeout_kills_plugin() {
+        let mut parser = plugin("sleep 10", &[]);
+        parser.config.timeout_secs = 1;
+
+        let started = Instant::now();
+        let err = parser
+            .parse(&context(Path::new("/tmp/run.json"), &Checkpoint::None))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_missing_command_is_error() {
+        let mut parser = plugin("", &[]);
+        parser.config.command = PathBuf::from("/nonexistent/aiobscura-plugin");
+        assert!(parser
+            .parse(&context(Path::new("/tmp/run.json"), &Checkpoint::None))
+            .is_err());
+    }
+}
@@ -42,7 +42,11 @@ pub mod collector;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod export;
 pub mod format;
+pub mod hooks;
 pub mod ingest;
 pub mod logging;
+pub mod otlp;
+pub mod retention;
 pub mod types;
@@ -0,0 +1,387 @@
+//! OpenTelemetry (OTLP/HTTP) receiver
+//!
+//! Claude Code and Codex can export OpenTelemetry logs and metrics. Those
+//! carry data their session logs lack, such as API latency and tool
+//! permission decisions. The receiver accepts them on a loopback address
+//! and stores them in `telemetry_events` and `telemetry_metrics`, keyed by
+//! the session id the assistant attaches to every record.
+//!
+//! ## Usage
+//!
+//! Enable the receiver for `aiobscura-sync --watch` in
+//! `~/.config/aiobscura/config.toml` (or pass `--otlp`):
+//!
+//! ```toml
+//! [otlp]
+//! enabled = true
+//! listen = "127.0.0.1:4318"
+//! ```
+//!
+//! Then point the assistant's exporter at it, e.g. for Claude Code:
+//!
+//! ```bash
+//! export CLAUDE_CODE_ENABLE_TELEMETRY=1
+//! export OTEL_METRICS_EXPORTER=otlp OTEL_LOGS_EXPORTER=otlp
+//! export OTEL_EXPORTER_OTLP_PROTOCOL=http/protobuf
+//! export OTEL_EXPORTER_OTLP_ENDPOINT=http://127.0.0.1:4318
+//! ```
+
+pub mod proto;
+mod receiver;
+
+pub use receiver::OtlpReceiver;
+
+use crate::types::{Assistant, MetricTemporality, TelemetryEvent, TelemetryMetric};
+use chrono::{DateTime, Utc};
+use proto::{any_value, metric, number_data_point, AnyValue, KeyValue};
+use serde_json::{Map, Value};
+
+/// Attributes that carry the session id: Claude Code uses `session.id`,
+/// Codex `conversation.id`.
+const SESSION_ID_ATTRIBUTES: &[&str] = &["session.id", "conversation.id"];
+
+/// Map exported log records to telemetry events.
+pub fn events_from_logs(
+    request: &proto::ExportLogsServiceRequest,
+    received_at: DateTime<Utc>,
+) -> Vec<TelemetryEvent> {
+    let mut events = Vec::new();
+    for resource_logs in &request.resource_logs {
+        let resource = resource_attributes(resource_logs.resource.as_ref());
+        for record in resource_logs
+            .scope_logs
+            .iter()
+            .flat_map(|scope| &scope.log_records)
+        {
+            let mut attributes = resource.clone();
+            attributes.extend(attributes_to_json(&record.attributes));
+
+            let body = record.body.as_ref().map(any_value_to_json);
+            let raw_name = [
+                Some(record.event_name.as_str()),
+                attributes.get("event.name").and_then(Value::as_str),
+                body.as_ref().and_then(Value::as_str),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|name| !name.is_empty())
+            .unwrap_or("unknown")
+            .to_string();
+            // Claude Code puts the short name in `event.name` and the
+            // prefixed one in the body
+            let prefixed_name = match body.as_ref().and_then(Value::as_str) {
+                Some(body) if body.ends_with(&format!(".{}", raw_name)) => body.to_string(),
+                _ => raw_name,
+            };
+
+            let emitted_at = time_from_nanos(record.time_unix_nano)
+                .or_else(|| {
+                    attributes
+                        .get("event.timestamp")
+                        .and_then(Value::as_str)
+                        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                        .map(|dt| dt.with_timezone(&Utc))
+                })
+                .or_else(|| time_from_nanos(record.observed_time_unix_nano))
+                .unwrap_or(received_at);
+
+            let (assistant, name) = split_assistant_prefix(&prefixed_name, &attributes);
+            events.push(TelemetryEvent {
+                id: 0,
+                session_id: session_id(&attributes),
+                assistant,
+                name,
+                emitted_at,
+                received_at,
+                model: string_attribute(&attributes, "model"),
+                tool_name: string_attribute(&attributes, "tool_name"),
+                decision: string_attribute(&attributes, "decision"),
+                duration_ms: number_attribute(&attributes, "duration_ms").map(|d| d as i64),
+                cost_usd: number_attribute(&attributes, "cost_usd"),
+                attributes: Value::Object(attributes),
+            });
+        }
+    }
+    events
+}
+
+/// Map exported gauge and sum data points to telemetry metrics.
+///
+/// Points are stored as exported, with their sum's temporality: Claude Code
+/// exports cumulative sums by default, whose points must not be added up
+/// (see [`Database::telemetry_metric_total`](crate::db::Database::telemetry_metric_total)).
+///
+/// Histograms and summaries are not exported by either assistant and are
+/// skipped.
+pub fn metrics_from_request(
+    request: &proto::ExportMetricsServiceRequest,
+    received_at: DateTime<Utc>,
+) -> Vec<TelemetryMetric> {
+    let mut metrics = Vec::new();
+    for resource_metrics in &request.resource_metrics {
+        let resource = resource_attributes(resource_metrics.resource.as_ref());
+        for metric in resource_metrics
+            .scope_metrics
+            .iter()
+            .flat_map(|scope| &scope.metrics)
+        {
+            let (data_points, temporality, monotonic) = match &metric.data {
+                Some(metric::Data::Gauge(gauge)) => (&gauge.data_points, None, false),
+                Some(metric::Data::Sum(sum)) => (
+                    &sum.data_points,
+                    match sum.aggregation_temporality {
+                        proto::AGGREGATION_TEMPORALITY_DELTA => Some(MetricTemporality::Delta),
+                        proto::AGGREGATION_TEMPORALITY_CUMULATIVE => {
+                            Some(MetricTemporality::Cumulative)
+                        }
+                        _ => None,
+                    },
+                    sum.is_monotonic,
+                ),
+                None => continue,
+            };
+            for point in data_points {
+                let value = match point.value {
+                    Some(number_data_point::Value::AsDouble(v)) => v,
+                    Some(number_data_point::Value::AsInt(v)) => v as f64,
+                    None => continue,
+                };
+                let mut attributes = resource.clone();
+                attributes.extend(attributes_to_json(&point.attributes));
+
+                let (assistant, name) = split_assistant_prefix(&metric.name, &attributes);
+                metrics.push(TelemetryMetric {
+                    id: 0,
+                    session_id: session_id(&attributes),
+                    assistant,
+                    name,
+                    unit: (!metric.unit.is_empty()).then(|| metric.unit.clone()),
+                    value,
+                    temporality,
+                    monotonic,
+                    started_at: time_from_nanos(point.start_time_unix_nano),
+                    observed_at: time_from_nanos(point.time_unix_nano).unwrap_or(received_at),
+                    received_at,
+                    attributes: Value::Object(attributes),
+                });
+            }
+        }
+    }
+    metrics
+}
+
+fn resource_attributes(resource: Option<&proto::Resource>) -> Map<String, Value> {
+    resource
+        .map(|r| attributes_to_json(&r.attributes))
+        .unwrap_or_default()
+}
+
+fn attributes_to_json(attributes: &[KeyValue]) -> Map<String, Value> {
+    attributes
+        .iter()
+        .map(|kv| {
+            let value = kv.value.as_ref().map(any_value_to_json);
+            (kv.key.clone(), value.unwrap_or(Value::Null))
+        })
+        .collect()
+}
+
+fn any_value_to_json(value: &AnyValue) -> Value {
+    match &value.value {
+        Some(any_value::Value::StringValue(s)) => Value::String(s.clone()),
+        Some(any_value::Value::BoolValue(b)) => Value::Bool(*b),
+        Some(any_value::Value::IntValue(i)) => Value::from(*i),
+        Some(any_value::Value::DoubleValue(d)) => Value::from(*d),
+        Some(any_value::Value::ArrayValue(array)) => {
+            Value::Array(array.values.iter().map(any_value_to_json).collect())
+        }
+        Some(any_value::Value::KvlistValue(list)) => {
+            Value::Object(attributes_to_json(&list.values))
+        }
+        None => Value::Null,
+    }
+}
+
+/// Strip the `claude_code.` / `codex.` prefix from an event or metric name,
+/// falling back to `service.name` to tell which assistant sent it.
+fn split_assistant_prefix(
+    name: &str,
+    attributes: &Map<String, Value>,
+) -> (Option<Assistant>, String) {
+    for (prefix, assistant) in [
+        ("claude_code.", Assistant::ClaudeCode),
+        ("codex.", Assistant::Codex),
+    ] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            return (Some(assistant), rest.to_string());
+        }
+    }
+
+    let service = attributes
+        .get("service.name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_lowercase();
+    let assistant = if service.contains("claude") {
+        Some(Assistant::ClaudeCode)
+    } else if service.contains("codex") {
+        Some(Assistant::Codex)
+    } else {
+        None
+    };
+    (assistant, name.to_string())
+}
+
+fn session_id(attributes: &Map<String, Value>) -> Option<String> {
+    SESSION_ID_ATTRIBUTES
+        .iter()
+        .find_map(|key| string_attribute(attributes, key))
+}
+
+fn string_attribute(attributes: &Map<String, Value>, key: &str) -> Option<String> {
+    attributes
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// A numeric attribute; Claude Code exports numbers as strings.
+fn number_attribute(attributes: &Map<String, Value>, key: &str) -> Option<f64> {
+    match attributes.get(key)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn time_from_nanos(nanos: u64) -> Option<DateTime<Utc>> {
+    (nanos > 0).then(|| DateTime::from_timestamp_nanos(nanos as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_events_from_json() {
+        let request: proto::ExportLogsServiceRequest = serde_json::from_value(serde_json::json!({
+            "resourceLogs": [{
+                "resource": {"attributes": [
+                    {"key": "service.name", "value": {"stringValue": "claude-code"}}
+                ]},
+                "scopeLogs": [{
+                    "scope": {"name": "com.anthropic.claude_code.events"},
+                    "logRecords": [{
+                        "timeUnixNano": "1765015200000000000",
+                        "body": {"stringValue": "claude_code.api_request"},
+                        "attributes": [
+                            {"key": "event.name", "value": {"stringValue": "api_request"}},
+                            {"key": "session.id", "value": {"stringValue": "sess-1"}},
+                            {"key": "model", "value": {"stringValue": "claude-sonnet-4-5"}},
+                            {"key": "duration_ms", "value": {"stringValue": "1834"}},
+                            {"key": "cost_usd", "value": {"doubleValue": 0.0123}},
+                            {"key": "input_tokens", "value": {"intValue": "812"}}
+                        ]
+                    }]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let events = events_from_logs(&request, Utc::now());
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.name, "api_request");
+        assert_eq!(event.assistant, Some(Assistant::ClaudeCode));
+        assert_eq!(event.session_id.as_deref(), Some("sess-1"));
+        assert_eq!(event.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(event.duration_ms, Some(1834));
+        assert_eq!(event.cost_usd, Some(0.0123));
+        assert_eq!(event.attributes["input_tokens"], 812);
+        assert_eq!(event.emitted_at.timestamp(), 1_765_015_200);
+    }
+
+    #[test]
+    fn test_codex_decision_uses_conversation_id() {
+        let request: proto::ExportLogsServiceRequest = serde_json::from_value(serde_json::json!({
+            "resourceLogs": [{
+                "scopeLogs": [{
+                    "logRecords": [{
+                        "attributes": [
+                            {"key": "event.name", "value": {"stringValue": "codex.tool_decision"}},
+                            {"key": "event.timestamp", "value": {"stringValue": "2025-12-06T10:00:00Z"}},
+                            {"key": "conversation.id", "value": {"stringValue": "conv-1"}},
+                            {"key": "tool_name", "value": {"stringValue": "shell"}},
+                            {"key": "decision", "value": {"stringValue": "approved"}}
+                        ]
+                    }]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let event = &events_from_logs(&request, Utc::now())[0];
+        assert_eq!(event.name, "tool_decision");
+        assert_eq!(event.assistant, Some(Assistant::Codex));
+        assert_eq!(event.session_id.as_deref(), Some("conv-1"));
+        assert_eq!(event.tool_name.as_deref(), Some("shell"));
+        assert_eq!(event.decision.as_deref(), Some("approved"));
+        assert_eq!(event.emitted_at.to_rfc3339(), "2025-12-06T10:00:00+00:00");
+    }
+
+    #[test]
+    fn test_metrics_from_sum_and_gauge() {
+        let request: proto::ExportMetricsServiceRequest =
+            serde_json::from_value(serde_json::json!({
+                "resourceMetrics": [{
+                    "scopeMetrics": [{
+                        "metrics": [
+                            {
+                                "name": "claude_code.token.usage",
+                                "unit": "tokens",
+                                "sum": {
+                                    "aggregationTemporality": 2,
+                                    "isMonotonic": true,
+                                    "dataPoints": [{
+                                        "attributes": [
+                                            {"key": "session.id", "value": {"stringValue": "sess-1"}},
+                                            {"key": "type", "value": {"stringValue": "input"}}
+                                        ],
+                                        "startTimeUnixNano": "1765015100000000000",
+                                        "timeUnixNano": 1765015200000000000u64,
+                                        "asInt": "812"
+                                    }]
+                                }
+                            },
+                            {
+                                "name": "claude_code.cost.usage",
+                                "unit": "USD",
+                                "gauge": {"dataPoints": [{"asDouble": 0.5}]}
+                            },
+                            {
+                                "name": "claude_code.latency",
+                                "histogram": {"dataPoints": [{"count": "1"}]}
+                            }
+                        ]
+                    }]
+                }]
+            }))
+            .unwrap();
+
+        let metrics = metrics_from_request(&request, Utc::now());
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].name, "token.usage");
+        assert_eq!(metrics[0].value, 812.0);
+        assert_eq!(metrics[0].unit.as_deref(), Some("tokens"));
+        assert_eq!(metrics[0].session_id.as_deref(), Some("sess-1"));
+        assert_eq!(metrics[0].attributes["type"], "input");
+        assert_eq!(metrics[0].temporality, Some(MetricTemporality::Cumulative));
+        assert!(metrics[0].monotonic);
+        assert_eq!(metrics[0].started_at.unwrap().timestamp(), 1765015100);
+        assert_eq!(metrics[1].name, "cost.usage");
+        assert_eq!(metrics[1].value, 0.5);
+        assert_eq!(metrics[1].temporality, None);
+        assert_eq!(metrics[1].session_id, None);
+    }
+}
@@ -0,0 +1,209 @@
+//! Loopback OTLP/HTTP server
+//!
+//! Implements the two OTLP/HTTP endpoints the assistants export to,
+//! `POST /v1/logs` and `POST /v1/metrics`, accepting both the
+//! `application/x-protobuf` and `application/json` encodings, optionally
+//! gzip-compressed.
+
+use super::proto::{ExportLogsServiceRequest, ExportMetricsServiceRequest};
+use crate::db::Database;
+use crate::error::{Error, Result};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use prost::Message;
+use serde::de::DeserializeOwned;
+use std::io::Read;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest request body accepted, after decompression.
+const MAX_BODY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A running OTLP/HTTP receiver.
+///
+/// Requests are handled on a background thread and written straight to the
+/// database. Dropping the receiver stops the server.
+pub struct OtlpReceiver {
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+    local_addr: SocketAddr,
+}
+
+impl OtlpReceiver {
+    /// Start listening on `addr`, which must resolve to a loopback address.
+    ///
+    /// Use port 0 to pick a free port; [`local_addr`](Self::local_addr)
+    /// reports the one bound.
+    pub fn start(addr: &str, db: Database) -> Result<Self> {
+        let addrs: Vec<SocketAddr> = addr
+            .to_socket_addrs()
+            .map_err(|e| Error::Config(format!("invalid OTLP listen address {}: {}", addr, e)))?
+            .collect();
+        if addrs.is_empty() || addrs.iter().any(|a| !a.ip().is_loopback()) {
+            return Err(Error::Config(format!(
+                "OTLP listen address {} is not a loopback address",
+                addr
+            )));
+        }
+
+        let server = Server::http(&addrs[..])
+            .map_err(|e| Error::Otlp(format!("failed to listen on {}: {}", addr, e)))?;
+        let local_addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| Error::Otlp(format!("{} is not an IP socket", addr)))?;
+        let server = Arc::new(server);
+
+        let thread_server = Arc::clone(&server);
+        let handle = std::thread::Builder::new()
+            .name("otlp-receiver".to_string())
+            .spawn(move || {
+                for request in thread_server.incoming_requests() {
+                    handle_request(&db, request);
+                }
+            })?;
+
+        tracing::info!(addr = %local_addr, "OTLP receiver listening");
+        Ok(Self {
+            server,
+            handle: Some(handle),
+            local_addr,
+        })
+    }
+
+    /// The address the receiver is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for OtlpReceiver {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Which body encoding a request uses.
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    Protobuf,
+    Json,
+}
+
+fn handle_request(db: &Database, mut request: Request) {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let result = if request.method() != &Method::Post {
+        Err((405, "only POST is supported".to_string()))
+    } else {
+        match path.as_str() {
+            "/v1/logs" => ingest::<ExportLogsServiceRequest>(&mut request, |req| {
+                let events = super::events_from_logs(&req, Utc::now());
+                db.insert_telemetry_events(&events).map(|_| events.len())
+            }),
+            "/v1/metrics" => ingest::<ExportMetricsServiceRequest>(&mut request, |req| {
+                let metrics = super::metrics_from_request(&req, Utc::now());
+                db.insert_telemetry_metrics(&metrics).map(|_| metrics.len())
+            }),
+            _ => Err((404, format!("unknown path {}", path))),
+        }
+    };
+
+    let response = match result {
+        Ok(encoding) => {
+            // An empty Export*ServiceResponse is zero bytes in protobuf
+            let (body, content_type) = match encoding {
+                Encoding::Protobuf => ("", "application/x-protobuf"),
+                Encoding::Json => ("{}", "application/json"),
+            };
+            Response::from_string(body).with_header(content_type_header(content_type))
+        }
+        Err((status, message)) => {
+            tracing::debug!(path = %path, status, error = %message, "Rejected OTLP request");
+            Response::from_string(message).with_status_code(status)
+        }
+    };
+    if let Err(e) = request.respond(response) {
+        tracing::debug!(error = %e, "Failed to send OTLP response");
+    }
+}
+
+/// Decode an export request and hand it to `store`, which returns how many
+/// records were written.
+fn ingest<T: Message + Default + DeserializeOwned>(
+    request: &mut Request,
+    store: impl FnOnce(T) -> Result<usize>,
+) -> std::result::Result<Encoding, (u16, String)> {
+    let encoding = match header(request, "Content-Type") {
+        Some(ct) if ct.starts_with("application/x-protobuf") => Encoding::Protobuf,
+        Some(ct) if ct.starts_with("application/json") => Encoding::Json,
+        other => {
+            return Err((
+                415,
+                format!("unsupported content type {}", other.unwrap_or("(none)")),
+            ))
+        }
+    };
+    let gzip = match header(request, "Content-Encoding") {
+        None | Some("identity") => false,
+        Some("gzip") => true,
+        Some(other) => return Err((415, format!("unsupported content encoding {}", other))),
+    };
+
+    let body = read_body(request.as_reader(), gzip).map_err(|e| (400, e.to_string()))?;
+    let decoded = match encoding {
+        Encoding::Protobuf => T::decode(body.as_slice()).map_err(|e| e.to_string()),
+        Encoding::Json => serde_json::from_slice(&body).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| (400, format!("invalid export request: {}", e)))?;
+
+    match store(decoded) {
+        Ok(count) => {
+            tracing::debug!(count, "Stored OTLP telemetry");
+            Ok(encoding)
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to store OTLP telemetry");
+            Err((500, e.to_string()))
+        }
+    }
+}
+
+fn read_body(reader: &mut dyn Read, gzip: bool) -> std::io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    if gzip {
+        GzDecoder::new(reader)
+            .take(MAX_BODY_BYTES + 1)
+            .read_to_end(&mut body)?;
+    } else {
+        reader.take(MAX_BODY_BYTES + 1).read_to_end(&mut body)?;
+    }
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("request body exceeds {} bytes", MAX_BODY_BYTES),
+        ));
+    }
+    Ok(body)
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+fn content_type_header(value: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).expect("valid header")
+}
@@ -0,0 +1,55 @@
+//! Retention pruning of raw message data
+//!
+//! Every message keeps the record it was parsed from in `raw_data`, which
+//! dominates the database's size over time. [`prune`] applies the configured
+//! [`RetentionConfig`] to each session, dropping the raw data and tool result
+//! bodies of messages older than the session's retention period, and the
+//! images only those referenced. Normalized columns, threads, sessions and
+//! analytics stay, as does the image an image message shows.
+
+use crate::config::RetentionConfig;
+use crate::db::{Database, PruneSummary, SessionFilter};
+use crate::error::Result;
+use crate::types::Assistant;
+use chrono::{DateTime, Duration, Utc};
+
+/// Per-session cutoffs for `retention` as of `now`: messages emitted
+/// before a session's cutoff are pruned. Sessions kept forever are left out.
+pub fn session_cutoffs(
+    db: &Database,
+    retention: &RetentionConfig,
+    assistant: Option<Assistant>,
+    agent: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Vec<(String, DateTime<Utc>)>> {
+    let sessions = db.list_sessions(&SessionFilter {
+        assistant,
+        agent: agent.map(String::from),
+        ..Default::default()
+    })?;
+    Ok(sessions
+        .into_iter()
+        .filter_map(|session| {
+            let days = retention.days_for(session.assistant, session.project_id.as_deref())?;
+            Some((session.id, now - Duration::days(i64::from(days))))
+        })
+        .collect())
+}
+
+/// Prune raw data past its retention period, optionally only for one
+/// assistant or custom agent. With `dry_run`, only reports what would be
+/// dropped.
+pub fn prune(
+    db: &Database,
+    retention: &RetentionConfig,
+    assistant: Option<Assistant>,
+    agent: Option<&str>,
+    dry_run: bool,
+) -> Result<PruneSummary> {
+    let cutoffs = session_cutoffs(db, retention, assistant, agent, Utc::now())?;
+    let summary = db.prune_raw_data(&cutoffs, dry_run)?;
+    if !dry_run {
+        db.remove_unreferenced_blobs()?;
+    }
+    Ok(summary)
+}
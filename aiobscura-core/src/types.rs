@@ -112,7 +112,13 @@ pub enum Checkpoint {
     ByteOffset { offset: u64 },
 
     /// For rewritable files (JSON, Markdown): track content hash
-    ContentHash { hash: String },
+    ContentHash {
+        hash: String,
+        /// Leading records already ingested, for rewritten files that only
+        /// grow (e.g. Gemini CLI chat recordings)
+        #[serde(default)]
+        records: u64,
+    },
 
     /// For databases (SQLite): track max rowid or timestamp
     DatabaseCursor {
@@ -135,6 +141,10 @@ pub struct SourceFile {
     pub file_type: FileType,
     /// Which assistant this file is from
     pub assistant: Assistant,
+    /// Custom agent or parser plugin that reads this file
+    /// (see [`AssistantParser::agent`](crate::ingest::AssistantParser::agent))
+    #[serde(default)]
+    pub agent: Option<String>,
     /// When the file was created
     pub created_at: DateTime<Utc>,
     /// When the file was last modified
@@ -145,6 +155,33 @@ pub struct SourceFile {
     pub last_parsed_at: Option<DateTime<Utc>>,
     /// Checkpoint for incremental parsing (type-specific)
     pub checkpoint: Checkpoint,
+    /// Version of the parser that ingested this file
+    /// (see [`AssistantParser::version`](crate::ingest::AssistantParser::version))
+    pub parser_version: u32,
+}
+
+/// A source record that a parser could not interpret, kept for inspection
+/// and retry (the `parse_failures` table).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseFailure {
+    /// Database ID
+    pub id: i64,
+    /// Path to the source file
+    pub source_file_path: String,
+    /// Which assistant the file is from
+    pub assistant: Assistant,
+    /// Byte offset of the record in the source file
+    pub byte_offset: u64,
+    /// Line number, counted from where the failing parse started
+    pub line_number: Option<i32>,
+    /// Raw record text
+    pub raw_text: String,
+    /// Why the record could not be parsed
+    pub error: String,
+    /// Version of the parser that failed
+    pub parser_version: u32,
+    /// When the failure was recorded
+    pub recorded_at: DateTime<Utc>,
 }
 
 // ============================================
@@ -159,6 +196,10 @@ pub enum Assistant {
     Codex,
     Aider,
     Cursor,
+    Gemini,
+    /// Agent parsed by a configuration-defined parser (`[[agents.custom]]`);
+    /// the agent name is in the session metadata
+    Custom,
 }
 
 impl Assistant {
@@ -169,6 +210,8 @@ impl Assistant {
             Assistant::Codex => "Codex",
             Assistant::Aider => "Aider",
             Assistant::Cursor => "Cursor",
+            Assistant::Gemini => "Gemini CLI",
+            Assistant::Custom => "Custom",
         }
     }
 
@@ -179,17 +222,25 @@ impl Assistant {
             Assistant::Codex => "codex",
             Assistant::Aider => "aider",
             Assistant::Cursor => "cursor",
+            Assistant::Gemini => "gemini",
+            Assistant::Custom => "custom",
         }
     }
 
     /// Returns the default path where this assistant stores logs
+    ///
+    /// Aider writes history files into each repository, so its path is the
+    /// home directory that discovery searches below. Custom agents have no
+    /// default; their root comes from configuration.
     pub fn default_log_path(&self) -> Option<PathBuf> {
         let home = dirs::home_dir()?;
         Some(match self {
             Assistant::ClaudeCode => home.join(".claude"),
             Assistant::Codex => home.join(".codex"),
-            Assistant::Aider => home.join(".aider"),
-            Assistant::Cursor => home.join(".cursor"),
+            Assistant::Aider => home,
+            Assistant::Cursor => dirs::config_dir()?.join("Cursor").join("User"),
+            Assistant::Gemini => home.join(".gemini"),
+            Assistant::Custom => return None,
         })
     }
 }
@@ -209,6 +260,8 @@ impl std::str::FromStr for Assistant {
             "codex" | "Codex" => Ok(Assistant::Codex),
             "aider" | "Aider" => Ok(Assistant::Aider),
             "cursor" | "Cursor" => Ok(Assistant::Cursor),
+            "gemini" | "Gemini" => Ok(Assistant::Gemini),
+            "custom" | "Custom" => Ok(Assistant::Custom),
             _ => Err(format!("unknown assistant: {}", s)),
         }
     }
@@ -367,6 +420,16 @@ impl Session {
     pub fn workflow_tag(&self) -> Option<&str> {
         self.metadata.get("workflow_tag").and_then(|v| v.as_str())
     }
+
+    /// Label of the data root this session was read from (`[[agents.root]]`).
+    pub fn source_root(&self) -> Option<&str> {
+        self.metadata.get("source_root").and_then(|v| v.as_str())
+    }
+
+    /// Host label of the data root this session was read from.
+    pub fn source_host(&self) -> Option<&str> {
+        self.metadata.get("source_host").and_then(|v| v.as_str())
+    }
 }
 
 // ============================================
@@ -571,6 +634,8 @@ pub enum MessageType {
     Context,
     /// Error or exception
     Error,
+    /// Context window compaction; content summarizes the conversation before it
+    Compaction,
 }
 
 impl MessageType {
@@ -584,6 +649,7 @@ impl MessageType {
             MessageType::Summary => "summary",
             MessageType::Context => "context",
             MessageType::Error => "error",
+            MessageType::Compaction => "compaction",
         }
     }
 }
@@ -601,6 +667,7 @@ impl std::str::FromStr for MessageType {
             "summary" => Ok(MessageType::Summary),
             "context" => Ok(MessageType::Context),
             "error" => Ok(MessageType::Error),
+            "compaction" => Ok(MessageType::Compaction),
             _ => Err(format!("unknown message type: {}", s)),
         }
     }
@@ -697,6 +764,36 @@ impl std::str::FromStr for ContentType {
     }
 }
 
+/// Reference to an image kept in the blob store instead of the database.
+///
+/// Stored on image messages as `metadata.image`, and in `raw_data` in place
+/// of the base64 payload as `{"type": "blob", ...}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobRef {
+    /// SHA-256 of the decoded bytes (hex), which is also the blob's key
+    pub sha256: String,
+    /// MIME type, e.g. "image/png"
+    pub media_type: String,
+    /// Size of the decoded bytes
+    pub size: u64,
+}
+
+impl BlobRef {
+    /// File extension for the media type ("png", "jpeg", ...).
+    pub fn extension(&self) -> &str {
+        let subtype = self
+            .media_type
+            .split_once('/')
+            .map_or(self.media_type.as_str(), |(_, subtype)| subtype);
+        match subtype {
+            "jpeg" | "jpg" => "jpg",
+            "svg+xml" => "svg",
+            "" => "bin",
+            other => other,
+        }
+    }
+}
+
 /// A message within a session (the core unit of activity)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -736,12 +833,20 @@ pub struct Message {
     pub tool_input: Option<serde_json::Value>,
     /// Result from the tool
     pub tool_result: Option<String>,
+    /// Assistant-assigned id linking a tool_call to its tool_result (or error)
+    pub tool_call_id: Option<String>,
 
     // Token usage (if available)
-    /// Input tokens consumed
+    /// Input tokens consumed, excluding prompt-cache reads and writes
     pub tokens_in: Option<i32>,
-    /// Output tokens generated
+    /// Output tokens generated, including reasoning tokens
     pub tokens_out: Option<i32>,
+    /// Input tokens served from the prompt cache
+    pub tokens_cache_read: Option<i32>,
+    /// Input tokens written to the prompt cache
+    pub tokens_cache_write: Option<i32>,
+    /// Portion of `tokens_out` spent on reasoning
+    pub tokens_reasoning: Option<i32>,
     /// Duration in milliseconds
     pub duration_ms: Option<i32>,
 
@@ -752,6 +857,11 @@ pub struct Message {
     pub source_offset: i64,
     /// Line number in source file (if applicable)
     pub source_line: Option<i32>,
+    /// ID of the source record (Claude `uuid`), shared by all messages parsed from it
+    pub record_id: Option<String>,
+    /// ID of the record this one follows (Claude `parentUuid`); records sharing
+    /// a parent are branches of the conversation
+    pub parent_record_id: Option<String>,
 
     // Lossless capture
     /// Complete original record - NEVER loses data
@@ -779,6 +889,21 @@ impl Message {
             )
     }
 
+    /// The stored image this message shows, if it is an image message whose
+    /// payload was moved to the blob store.
+    pub fn image(&self) -> Option<BlobRef> {
+        self.metadata
+            .get("image")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    /// Backing model id that produced this message, if the parser recorded
+    /// one. Sessions can switch models, so this may differ from the session's
+    /// `backing_model_id`.
+    pub fn model_id(&self) -> Option<&str> {
+        self.metadata.get("model").and_then(|v| v.as_str())
+    }
+
     /// Get a one-line preview of the message content suitable for display.
     ///
     /// For tool calls, shows `<tool_name> argument_preview`.
@@ -852,6 +977,75 @@ impl Message {
     }
 }
 
+/// A tool call paired with its result by `tool_call_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCallOutcome {
+    /// Database ID of the tool_call message
+    pub call_message_id: i64,
+    /// Database ID of the first tool_result or error answering the call
+    pub result_message_id: Option<i64>,
+    /// Assistant-assigned call id
+    pub tool_call_id: String,
+    /// Name of tool called
+    pub tool_name: Option<String>,
+    /// When the call was emitted
+    pub called_at: DateTime<Utc>,
+    /// Result `emitted_at` minus call `emitted_at` (None until answered)
+    pub latency_ms: Option<i64>,
+    /// Whether the result is an error (None until answered)
+    pub is_error: Option<bool>,
+}
+
+impl ToolCallOutcome {
+    /// Pair tool calls in `messages` with their results.
+    ///
+    /// Calls without a `tool_call_id` are skipped. When several results share
+    /// a call id, the earliest one wins. Outcomes are in call order.
+    pub fn pair(messages: &[Message]) -> Vec<ToolCallOutcome> {
+        let mut results: HashMap<&str, &Message> = HashMap::new();
+        for msg in messages {
+            if !matches!(
+                msg.message_type,
+                MessageType::ToolResult | MessageType::Error
+            ) {
+                continue;
+            }
+            if let Some(id) = msg.tool_call_id.as_deref() {
+                results
+                    .entry(id)
+                    .and_modify(|first| {
+                        if (msg.emitted_at, msg.id) < (first.emitted_at, first.id) {
+                            *first = msg;
+                        }
+                    })
+                    .or_insert(msg);
+            }
+        }
+
+        messages
+            .iter()
+            .filter(|msg| msg.message_type == MessageType::ToolCall)
+            .filter_map(|call| {
+                let id = call.tool_call_id.as_deref()?;
+                let result = results.get(id);
+                Some(ToolCallOutcome {
+                    call_message_id: call.id,
+                    result_message_id: result.map(|r| r.id),
+                    tool_call_id: id.to_string(),
+                    tool_name: call.tool_name.clone(),
+                    called_at: call.emitted_at,
+                    latency_ms: result.map(|r| {
+                        r.emitted_at
+                            .signed_duration_since(call.emitted_at)
+                            .num_milliseconds()
+                    }),
+                    is_error: result.map(|r| r.message_type == MessageType::Error),
+                })
+            })
+            .collect()
+    }
+}
+
 // ============================================
 // Plans
 // ============================================
@@ -926,10 +1120,155 @@ pub struct Plan {
     pub metadata: serde_json::Value,
 }
 
+// ============================================
+// Telemetry (OpenTelemetry exports)
+// ============================================
+
+/// An OpenTelemetry log event exported by an assistant (e.g. an API request
+/// or a tool permission decision), received by the OTLP receiver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryEvent {
+    /// Database ID
+    pub id: i64,
+    /// Session the event belongs to (`session.id` or `conversation.id`)
+    pub session_id: Option<String>,
+    /// Which assistant exported the event
+    pub assistant: Option<Assistant>,
+    /// Event name without the assistant prefix (e.g. `api_request`)
+    pub name: String,
+    /// When the event happened
+    pub emitted_at: DateTime<Utc>,
+    /// When the receiver got it
+    pub received_at: DateTime<Utc>,
+    /// Model the event refers to
+    pub model: Option<String>,
+    /// Tool the event refers to
+    pub tool_name: Option<String>,
+    /// Permission decision (`accept`, `reject`, ...)
+    pub decision: Option<String>,
+    /// Duration reported by the event, e.g. API latency
+    pub duration_ms: Option<i64>,
+    /// Cost reported by the event
+    pub cost_usd: Option<f64>,
+    /// All resource and event attributes
+    pub attributes: serde_json::Value,
+}
+
+/// How the data points of an OpenTelemetry sum accumulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricTemporality {
+    /// Each point counts only what happened since the previous one
+    Delta,
+    /// Each point is the running total since the series' start time
+    Cumulative,
+}
+
+impl MetricTemporality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricTemporality::Delta => "delta",
+            MetricTemporality::Cumulative => "cumulative",
+        }
+    }
+}
+
+impl std::str::FromStr for MetricTemporality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "delta" => Ok(MetricTemporality::Delta),
+            "cumulative" => Ok(MetricTemporality::Cumulative),
+            _ => Err(format!("unknown metric temporality: {}", s)),
+        }
+    }
+}
+
+/// A single OpenTelemetry metric data point exported by an assistant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryMetric {
+    /// Database ID
+    pub id: i64,
+    /// Session the data point belongs to
+    pub session_id: Option<String>,
+    /// Which assistant exported the metric
+    pub assistant: Option<Assistant>,
+    /// Metric name without the assistant prefix (e.g. `token.usage`)
+    pub name: String,
+    /// Unit of the value (e.g. `USD`, `tokens`)
+    pub unit: Option<String>,
+    /// Data point value, as exported (see `temporality`)
+    pub value: f64,
+    /// How a sum's points accumulate; `None` for gauges
+    pub temporality: Option<MetricTemporality>,
+    /// Whether the sum only ever increases
+    pub monotonic: bool,
+    /// Start of a cumulative series; a new start time begins a new series
+    pub started_at: Option<DateTime<Utc>>,
+    /// Time of the data point
+    pub observed_at: DateTime<Utc>,
+    /// When the receiver got it
+    pub received_at: DateTime<Utc>,
+    /// All resource and data point attributes
+    pub attributes: serde_json::Value,
+}
+
+// ============================================
+// Hook events (pushed by `aiobscura hook`)
+// ============================================
+
+/// An event an assistant hook pushed to aiobscura, e.g. a Claude Code
+/// `PreToolUse`, `Notification` or `Stop` hook.
+///
+/// Hooks see moments the session log never records, such as the assistant
+/// waiting on a permission prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookEvent {
+    /// Database ID
+    pub id: i64,
+    /// Session the hook fired in
+    pub session_id: String,
+    /// Hook event name (`PreToolUse`, `PostToolUse`, `Notification`, `Stop`, ...)
+    pub event_name: String,
+    /// Tool the hook refers to (tool hooks only)
+    pub tool_name: Option<String>,
+    /// Tool call ID, matching `Message.tool_call_id` (tool hooks only)
+    pub tool_use_id: Option<String>,
+    /// Notification text (e.g. "Claude needs your permission to use Bash")
+    pub message: Option<String>,
+    /// When the hook ran
+    pub received_at: DateTime<Utc>,
+    /// The hook's full JSON input
+    pub payload: serde_json::Value,
+}
+
 // ============================================
 // Metrics (Layer 2 - Derived)
 // ============================================
 
+/// A commit that landed edits a session made, found by the `core.git`
+/// analytics plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCommit {
+    /// Session whose edits the commit contains
+    pub session_id: String,
+    /// Full commit hash
+    pub commit_sha: String,
+    /// Root of the repository the commit is in
+    pub repo_path: PathBuf,
+    /// Committer date
+    pub committed_at: DateTime<Utc>,
+    /// Commit author name
+    pub author_name: Option<String>,
+    /// Commit author email
+    pub author_email: Option<String>,
+    /// First line of the commit message
+    pub summary: String,
+    /// Files the session edited that the commit touched, relative to `repo_path`
+    pub matched_files: Vec<String>,
+}
+
 /// Aggregated metrics for a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMetrics {
@@ -4,7 +4,9 @@
 //! the end-to-end parsing and database storage flow.
 
 use aiobscura_core::db::Database;
-use aiobscura_core::ingest::parsers::{ClaudeCodeParser, CodexParser};
+use aiobscura_core::ingest::parsers::{
+    AiderParser, ClaudeCodeParser, CodexParser, ConfigurableJsonlParser, CursorParser, GeminiParser,
+};
 use aiobscura_core::ingest::{AssistantParser, ParseContext};
 use aiobscura_core::types::{Assistant, AuthorRole, Checkpoint, Message, MessageType};
 use std::path::PathBuf;
@@ -79,6 +81,13 @@ fn test_parse_minimal_session() {
     assert!(assistant_msgs[0].tokens_out.is_some());
     assert_eq!(assistant_msgs[0].tokens_in.unwrap(), 50);
     assert_eq!(assistant_msgs[0].tokens_out.unwrap(), 25);
+    assert_eq!(assistant_msgs[0].tokens_cache_read, Some(10));
+    assert_eq!(assistant_msgs[0].tokens_cache_write, Some(0));
+    assert_eq!(
+        assistant_msgs[0].model_id(),
+        Some("anthropic:claude-opus-4-5-20251101")
+    );
+    assert_eq!(user_msgs[0].model_id(), None);
 }
 
 #[test]
@@ -157,6 +166,16 @@ fn test_parse_malformed_json_recovery() {
         "should have warnings about bad JSON"
     );
 
+    // Bad lines are also returned for quarantine
+    assert_eq!(result.failed_records.len(), 2);
+    assert_eq!(result.failed_records[0].line, Some(2));
+    assert_eq!(
+        result.failed_records[0].raw,
+        "this is not valid json at all"
+    );
+    assert!(result.failed_records[0].offset > 0);
+    assert_eq!(result.failed_records[1].line, Some(4));
+
     // Session should still be created from valid messages
     assert!(result.session.is_some());
 }
@@ -221,6 +240,42 @@ fn test_agent_file_parsing() {
     assert!(!result.messages.is_empty());
 }
 
+#[test]
+fn test_parallel_sync_links_agent_parsed_before_parent() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("projects/-Users-test-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    // `agent-*` sorts before the parent session file in discovery order
+    for name in ["agent-a1234567.jsonl", "with-agent-spawn.jsonl"] {
+        std::fs::copy(fixture_path(name), project_dir.join(name)).unwrap();
+    }
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(ClaudeCodeParser::with_root(
+            temp_dir.path().to_path_buf(),
+        ))],
+    )
+    .with_parallelism(4);
+
+    let result = coordinator.sync_all().expect("sync should succeed");
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files_processed, 2);
+
+    let agent_thread = coordinator
+        .db()
+        .get_thread("test-session-003-agent")
+        .unwrap()
+        .expect("agent thread should exist");
+    assert_eq!(
+        agent_thread.parent_thread_id.as_deref(),
+        Some("test-session-003-main")
+    );
+    assert!(agent_thread.spawned_by_message_id.is_some());
+}
+
 // ============================================
 // Incremental Parsing Tests
 // ============================================
@@ -320,11 +375,13 @@ fn test_full_sync_pipeline() {
         path: PathBuf::from(&session_source_path),
         file_type: aiobscura_core::types::FileType::Jsonl,
         assistant: aiobscura_core::types::Assistant::ClaudeCode,
+        agent: None,
         created_at: chrono::Utc::now(),
         modified_at: chrono::Utc::now(),
         size_bytes: std::fs::metadata(&path).unwrap().len(),
         last_parsed_at: Some(chrono::Utc::now()),
         checkpoint: result.new_checkpoint.clone(),
+        parser_version: 1,
     };
     db.upsert_source_file(&source_file)
         .expect("source file insert should succeed");
@@ -530,6 +587,13 @@ fn test_codex_parse_minimal_session() {
         "should have at least 2 assistant messages"
     );
 
+    // Cached input is split out of input_tokens; reasoning stays in output
+    assert_eq!(assistant_msgs[0].tokens_in, Some(50));
+    assert_eq!(assistant_msgs[0].tokens_cache_read, Some(50));
+    assert_eq!(assistant_msgs[0].tokens_cache_write, None);
+    assert_eq!(assistant_msgs[0].tokens_out, Some(25));
+    assert_eq!(assistant_msgs[0].tokens_reasoning, Some(10));
+
     // Check caller prompt content (CLI invocation)
     assert!(caller_prompt_msgs[0]
         .content
@@ -593,8 +657,10 @@ fn test_codex_parse_with_tool_calls() {
         .unwrap()
         .contains("Exit code: 0"));
 
-    // Check call_id is in metadata
+    // Check call_id is in metadata and links the call to its output
     assert!(shell_call.metadata.get("call_id").is_some());
+    assert_eq!(shell_call.tool_call_id.as_deref(), Some("call_abc123"));
+    assert_eq!(first_result.tool_call_id.as_deref(), Some("call_abc123"));
 }
 
 #[test]
@@ -786,6 +852,2019 @@ fn test_codex_project_creation() {
     assert_eq!(project.name, Some("myproject".to_string()));
 }
 
+// ============================================
+// Aider Parser Tests
+// ============================================
+
+/// Get the path to the Aider fixture repository
+fn aider_fixture_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/aider")
+}
+
+#[test]
+fn test_aider_parse_sessions_and_tools() {
+    let path = aider_fixture_root().join("myrepo/.aider.chat.history.md");
+    let parser = AiderParser::with_root(aider_fixture_root());
+    let ctx = parse_context(&path);
+
+    let result = parser.parse(&ctx).expect("parse should succeed");
+
+    // Two "aider chat started at" headers -> two sessions
+    let session = result.session.as_ref().expect("latest session");
+    assert_eq!(result.extra_sessions.len(), 1);
+    assert_eq!(result.threads.len(), 2);
+    assert_eq!(
+        session.backing_model_id.as_deref(),
+        Some("anthropic:claude-3-5-sonnet-20241022")
+    );
+    assert_eq!(
+        result.extra_sessions[0].backing_model_id.as_deref(),
+        Some("openai:gpt-4o")
+    );
+    assert_eq!(session.assistant, Assistant::Aider);
+
+    // Project is the repository containing the history file
+    let project = result.project.as_ref().expect("project");
+    assert_eq!(project.name, Some("myrepo".to_string()));
+
+    let first_session_id = &result.extra_sessions[0].id;
+    let first: Vec<&Message> = result
+        .messages
+        .iter()
+        .filter(|m| &m.session_id == first_session_id)
+        .collect();
+
+    // Prompt timestamp recovered from .aider.input.history
+    let prompt = first
+        .iter()
+        .find(|m| m.message_type == MessageType::Prompt)
+        .expect("prompt");
+    assert!(prompt.content.as_ref().unwrap().contains("greet function"));
+    assert!(prompt.emitted_at > result.extra_sessions[0].started_at);
+
+    // Response carries token usage from the "Tokens:" line
+    let response = first
+        .iter()
+        .find(|m| m.message_type == MessageType::Response)
+        .expect("response");
+    assert_eq!(response.tokens_in, Some(2100));
+    assert_eq!(response.tokens_out, Some(150));
+
+    // Edit block becomes an Edit tool call with an absolute file path
+    let edit = first
+        .iter()
+        .find(|m| m.tool_name.as_deref() == Some("Edit") && m.message_type == MessageType::ToolCall)
+        .expect("edit tool call");
+    let file_path = edit.tool_input.as_ref().unwrap()["file_path"]
+        .as_str()
+        .unwrap();
+    assert!(file_path.ends_with("myrepo/app.py"));
+
+    // /run becomes a Bash tool call followed by its output
+    let run = first
+        .iter()
+        .position(|m| {
+            m.tool_name.as_deref() == Some("Bash") && m.message_type == MessageType::ToolCall
+        })
+        .expect("run tool call");
+    assert_eq!(
+        first[run].tool_input.as_ref().unwrap()["command"],
+        "pytest -q"
+    );
+    assert_eq!(first[run + 1].message_type, MessageType::ToolResult);
+    assert!(first[run + 1]
+        .tool_result
+        .as_ref()
+        .unwrap()
+        .contains("3 passed"));
+
+    // Other slash commands are context, not prompts
+    let prompts: Vec<_> = result
+        .messages
+        .iter()
+        .filter(|m| m.author_role == AuthorRole::Human && m.message_type == MessageType::Prompt)
+        .collect();
+    assert_eq!(prompts.len(), 2);
+}
+
+#[test]
+fn test_aider_incremental_parsing_continues_open_block() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path().join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    let path = repo.join(".aider.chat.history.md");
+    std::fs::write(
+        &path,
+        "\n# aider chat started at 2025-01-10 09:15:02\n\n> Main model: gpt-4o with diff edit format\n\n#### fix it\n\nWorking on it.\n",
+    )
+    .unwrap();
+
+    let parser = AiderParser::with_root(temp_dir.path().to_path_buf());
+    let result1 = parser
+        .parse(&parse_context(&path))
+        .expect("first parse should succeed");
+    assert_eq!(result1.messages.len(), 3);
+    assert_eq!(
+        result1.messages[2].content.as_deref(),
+        Some("Working on it.")
+    );
+
+    // The response is still being written when the next lines arrive
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(
+        b"Here is the change:\n\nsrc/main.py\n```python\n<<<<<<< SEARCH\nprint('a')\n=======\nprint('b')\n>>>>>>> REPLACE\n```\n\n#### thanks\n",
+    )
+    .unwrap();
+    drop(file);
+
+    let metadata = std::fs::metadata(&path).unwrap();
+    let ctx2 = ParseContext {
+        path: &path,
+        checkpoint: &result1.new_checkpoint,
+        file_size: metadata.len(),
+        modified_at: chrono::Utc::now(),
+    };
+    let result2 = parser
+        .parse(&ctx2)
+        .expect("incremental parse should succeed");
+
+    let summary: Vec<_> = result2
+        .messages
+        .iter()
+        .map(|m| (m.seq, m.message_type))
+        .collect();
+    // Seqs are line numbers: the continuation starts on line 9, after the
+    // response it continues (line 8)
+    assert_eq!(
+        summary,
+        vec![
+            (9, MessageType::Response),
+            (13, MessageType::ToolCall),
+            (20, MessageType::Prompt),
+        ]
+    );
+    assert_eq!(result1.messages[2].seq, 8);
+    let continued = result2.messages[0].content.as_deref().unwrap();
+    assert!(continued.starts_with("Here is the change:"), "{continued}");
+    assert!(!continued.contains("Working on it."));
+    assert_eq!(result2.messages[1].tool_name.as_deref(), Some("Edit"));
+
+    // Other seqs match a parse of the whole file
+    let full = parser
+        .parse(&parse_context(&path))
+        .expect("full parse should succeed");
+    let full_seqs: Vec<_> = full.messages.iter().map(|m| m.seq).collect();
+    assert_eq!(full_seqs, vec![4, 6, 8, 13, 20]);
+}
+
+#[test]
+fn test_aider_incremental_parsing_with_appended_turn() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path().join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    let path = repo.join(".aider.chat.history.md");
+    std::fs::write(
+        &path,
+        "\n# aider chat started at 2025-01-10 09:15:02\n\n> Main model: gpt-4o with whole edit format\n\n#### hello\n\nHi there!\n\n",
+    )
+    .unwrap();
+
+    let parser = AiderParser::with_root(temp_dir.path().to_path_buf());
+    let ctx1 = parse_context(&path);
+    let result1 = parser.parse(&ctx1).expect("first parse should succeed");
+    assert_eq!(result1.messages.len(), 3);
+    let session_id = result1.session.as_ref().unwrap().id.clone();
+    let checkpoint = result1.new_checkpoint.clone();
+
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(b"> Tokens: 1.2k sent, 30 received.\n\n#### and again\n\nHello again!\n\n")
+        .unwrap();
+    drop(file);
+
+    let metadata = std::fs::metadata(&path).unwrap();
+    let ctx2 = ParseContext {
+        path: &path,
+        checkpoint: &checkpoint,
+        file_size: metadata.len(),
+        modified_at: chrono::Utc::now(),
+    };
+    let result2 = parser
+        .parse(&ctx2)
+        .expect("incremental parse should succeed");
+
+    // Only the appended lines are emitted, in the same session, with continued seq
+    assert_eq!(result2.messages.len(), 3);
+    assert_eq!(result2.session.as_ref().unwrap().id, session_id);
+    assert!(result2.extra_sessions.is_empty());
+    assert_eq!(result2.messages[1].seq, 12);
+    assert_eq!(result2.messages[1].session_id, session_id);
+
+    // The response was stored by the first parse, so the usage reported
+    // after it stays on the tokens message
+    let usage = &result2.messages[0];
+    assert_eq!(usage.message_type, MessageType::Context);
+    assert_eq!(usage.seq, 10);
+    assert_eq!((usage.tokens_in, usage.tokens_out), (Some(1200), Some(30)));
+    assert_eq!(result1.messages[2].tokens_in, None);
+    assert_eq!(
+        result2
+            .session
+            .as_ref()
+            .unwrap()
+            .backing_model_id
+            .as_deref(),
+        Some("openai:gpt-4o")
+    );
+
+    // Nothing new after that
+    let checkpoint = result2.new_checkpoint.clone();
+    let ctx3 = ParseContext {
+        path: &path,
+        checkpoint: &checkpoint,
+        file_size: metadata.len(),
+        modified_at: chrono::Utc::now(),
+    };
+    let result3 = parser.parse(&ctx3).expect("third parse should succeed");
+    assert!(result3.messages.is_empty());
+    assert!(result3.session.is_none());
+}
+
+#[test]
+fn test_aider_sync_stores_all_sessions() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+
+    let root = aider_fixture_root();
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(AiderParser::with_root(root.clone()))],
+    );
+    let result = coordinator
+        .sync_file(&root.join("myrepo/.aider.chat.history.md"))
+        .expect("sync should succeed");
+    assert!(result.new_messages > 0);
+
+    let sessions = coordinator
+        .db()
+        .list_sessions(&aiobscura_core::SessionFilter::default())
+        .unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions.iter().all(|s| s.project_id.is_some()));
+
+    let source = coordinator
+        .db()
+        .get_source_file(&root.join("myrepo/.aider.chat.history.md").to_string_lossy())
+        .unwrap()
+        .unwrap();
+    assert_eq!(source.file_type, aiobscura_core::types::FileType::Markdown);
+}
+
+// ============================================
+// Cursor Parser Tests
+// ============================================
+
+/// Create a Cursor user directory with a global and a workspace database.
+fn create_cursor_fixture(root: &std::path::Path) -> PathBuf {
+    let global_dir = root.join("globalStorage");
+    let workspace_dir = root.join("workspaceStorage/abc123");
+    std::fs::create_dir_all(&global_dir).unwrap();
+    std::fs::create_dir_all(&workspace_dir).unwrap();
+
+    std::fs::write(
+        workspace_dir.join("workspace.json"),
+        r#"{"folder": "file:///Users/test/dev/cursor%20app"}"#,
+    )
+    .unwrap();
+    let ws = rusqlite::Connection::open(workspace_dir.join("state.vscdb")).unwrap();
+    ws.execute_batch(
+        r#"
+        CREATE TABLE ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB);
+        INSERT INTO ItemTable VALUES ('composer.composerData',
+            '{"allComposers":[{"composerId":"comp-1"}]}');
+        "#,
+    )
+    .unwrap();
+
+    let global_path = global_dir.join("state.vscdb");
+    let db = rusqlite::Connection::open(&global_path).unwrap();
+    db.execute_batch(
+        r#"
+        CREATE TABLE cursorDiskKV (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB);
+        INSERT INTO cursorDiskKV VALUES ('bubbleId:comp-1:b1',
+            '{"type":1,"text":"Rename foo to bar","createdAt":"2025-03-01T10:00:00Z"}');
+        INSERT INTO cursorDiskKV VALUES ('bubbleId:comp-1:b2',
+            '{"type":2,"text":"Renaming now.","createdAt":"2025-03-01T10:00:05Z","tokenCount":{"inputTokens":1200,"outputTokens":80},"toolFormerData":{"name":"edit_file","rawArgs":"{\"target_file\":\"src/lib.rs\"}","result":"{\"diff\":\"ok\"}","status":"completed","toolCallId":"call-1"}}');
+        INSERT INTO cursorDiskKV VALUES ('composerData:comp-1',
+            '{"composerId":"comp-1","name":"Rename","createdAt":1740823200000,"lastUpdatedAt":1740823205000,"modelConfig":{"modelName":"claude-3.5-sonnet"},"fullConversationHeadersOnly":[{"bubbleId":"b1","type":1},{"bubbleId":"b2","type":2}]}');
+        "#,
+    )
+    .unwrap();
+
+    global_path
+}
+
+#[test]
+fn test_cursor_parse_composer_conversation() {
+    let temp_dir = TempDir::new().unwrap();
+    let global_path = create_cursor_fixture(temp_dir.path());
+    let parser = CursorParser::with_root(temp_dir.path().to_path_buf());
+    let ctx = parse_context(&global_path);
+
+    let result = parser.parse(&ctx).expect("parse should succeed");
+
+    let session = result.session.as_ref().expect("session");
+    assert_eq!(session.id, "comp-1");
+    assert_eq!(session.assistant, Assistant::Cursor);
+    assert_eq!(
+        session.backing_model_id.as_deref(),
+        Some("anthropic:claude-3.5-sonnet")
+    );
+    // Workspace folder resolved through the workspace database
+    assert_eq!(
+        session.metadata["cwd"].as_str(),
+        Some("/Users/test/dev/cursor app")
+    );
+
+    // prompt, response, tool call, tool result
+    assert_eq!(result.messages.len(), 4);
+    assert_eq!(result.messages[0].author_role, AuthorRole::Human);
+    assert_eq!(result.messages[1].tokens_in, Some(1200));
+    assert_eq!(result.messages[2].message_type, MessageType::ToolCall);
+    assert_eq!(result.messages[2].tool_name.as_deref(), Some("edit_file"));
+    assert_eq!(
+        result.messages[2].tool_input.as_ref().unwrap()["target_file"],
+        "src/lib.rs"
+    );
+    assert_eq!(result.messages[3].message_type, MessageType::ToolResult);
+
+    match &result.new_checkpoint {
+        Checkpoint::DatabaseCursor {
+            table,
+            cursor_column,
+            ..
+        } => {
+            assert_eq!(table, "cursorDiskKV");
+            assert_eq!(cursor_column, "rowid");
+        }
+        other => panic!("expected DatabaseCursor checkpoint, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_cursor_incremental_parsing() {
+    let temp_dir = TempDir::new().unwrap();
+    let global_path = create_cursor_fixture(temp_dir.path());
+    let parser = CursorParser::with_root(temp_dir.path().to_path_buf());
+
+    let result1 = parser
+        .parse(&parse_context(&global_path))
+        .expect("first parse should succeed");
+    let checkpoint = result1.new_checkpoint.clone();
+
+    // Unchanged database: nothing new
+    let ctx2 = ParseContext {
+        path: &global_path,
+        checkpoint: &checkpoint,
+        file_size: 0,
+        modified_at: chrono::Utc::now(),
+    };
+    let result2 = parser.parse(&ctx2).expect("second parse should succeed");
+    assert!(result2.messages.is_empty());
+    assert!(result2.warnings.is_empty());
+    assert!(result2.session.is_none());
+
+    // Append a bubble; Cursor rewrites composerData with the new header list
+    let db = rusqlite::Connection::open(&global_path).unwrap();
+    db.execute_batch(
+        r#"
+        INSERT INTO cursorDiskKV VALUES ('bubbleId:comp-1:b3',
+            '{"type":1,"text":"Thanks","createdAt":"2025-03-01T10:01:00Z"}');
+        INSERT INTO cursorDiskKV VALUES ('composerData:comp-1',
+            '{"composerId":"comp-1","createdAt":1740823200000,"lastUpdatedAt":1740823260000,"fullConversationHeadersOnly":[{"bubbleId":"b1"},{"bubbleId":"b2"},{"bubbleId":"b3"}]}');
+        "#,
+    )
+    .unwrap();
+    drop(db);
+
+    let ctx3 = ParseContext {
+        path: &global_path,
+        checkpoint: &checkpoint,
+        file_size: 0,
+        modified_at: chrono::Utc::now(),
+    };
+    let result3 = parser.parse(&ctx3).expect("third parse should succeed");
+    assert_eq!(result3.messages.len(), 1);
+    assert_eq!(result3.messages[0].content.as_deref(), Some("Thanks"));
+    assert_eq!(result3.messages[0].seq, 5);
+}
+
+#[test]
+fn test_cursor_waits_for_unfinished_bubbles() {
+    let temp_dir = TempDir::new().unwrap();
+    let global_path = create_cursor_fixture(temp_dir.path());
+    let parser = CursorParser::with_root(temp_dir.path().to_path_buf());
+    let result1 = parser
+        .parse(&parse_context(&global_path))
+        .expect("first parse should succeed");
+    assert_eq!(result1.messages.len(), 4);
+
+    // A tool call starts; its result is written later
+    let db = rusqlite::Connection::open(&global_path).unwrap();
+    db.execute_batch(
+        r#"
+        INSERT INTO cursorDiskKV VALUES ('bubbleId:comp-1:b3',
+            '{"type":2,"text":"Running the tests.","createdAt":"2025-03-01T10:01:00Z","toolFormerData":{"name":"run_terminal_cmd","rawArgs":"{\"command\":\"cargo test\"}","status":"loading","toolCallId":"call-2"}}');
+        INSERT INTO cursorDiskKV VALUES ('composerData:comp-1',
+            '{"composerId":"comp-1","createdAt":1740823200000,"lastUpdatedAt":1740823260000,"status":"generating","fullConversationHeadersOnly":[{"bubbleId":"b1"},{"bubbleId":"b2"},{"bubbleId":"b3"}]}');
+        "#,
+    )
+    .unwrap();
+
+    let ctx2 = ParseContext {
+        path: &global_path,
+        checkpoint: &result1.new_checkpoint,
+        file_size: 0,
+        modified_at: chrono::Utc::now(),
+    };
+    let result2 = parser.parse(&ctx2).expect("second parse should succeed");
+    assert!(result2.messages.is_empty());
+    assert!(result2.warnings.is_empty());
+
+    // Only the bubble row changes when the result arrives
+    db.execute_batch(
+        r#"
+        INSERT INTO cursorDiskKV VALUES ('bubbleId:comp-1:b3',
+            '{"type":2,"text":"Running the tests.","createdAt":"2025-03-01T10:01:00Z","toolFormerData":{"name":"run_terminal_cmd","rawArgs":"{\"command\":\"cargo test\"}","result":"ok","status":"completed","toolCallId":"call-2"}}');
+        "#,
+    )
+    .unwrap();
+    drop(db);
+
+    let ctx3 = ParseContext {
+        path: &global_path,
+        checkpoint: &result2.new_checkpoint,
+        file_size: 0,
+        modified_at: chrono::Utc::now(),
+    };
+    let result3 = parser.parse(&ctx3).expect("third parse should succeed");
+    let summary: Vec<_> = result3
+        .messages
+        .iter()
+        .map(|m| (m.seq, m.message_type))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (5, MessageType::Response),
+            (6, MessageType::ToolCall),
+            (7, MessageType::ToolResult),
+        ]
+    );
+    assert_eq!(result3.messages[2].tool_result.as_deref(), Some("ok"));
+}
+
+#[test]
+fn test_cursor_sync_does_not_modify_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let cursor_root = temp_dir.path().join("Cursor/User");
+    let global_path = create_cursor_fixture(&cursor_root);
+    let before = std::fs::read(&global_path).unwrap();
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(CursorParser::with_root(cursor_root))],
+    );
+    let result = coordinator.sync_all().expect("sync should succeed");
+    assert!(result.errors.is_empty(), "errors: {:?}", result.errors);
+    assert_eq!(result.messages_inserted, 4);
+
+    let source = coordinator
+        .db()
+        .get_source_file(&global_path.to_string_lossy())
+        .unwrap()
+        .unwrap();
+    assert_eq!(source.file_type, aiobscura_core::types::FileType::Sqlite);
+    assert_eq!(std::fs::read(&global_path).unwrap(), before);
+
+    // Project created from the workspace folder
+    let sessions = coordinator
+        .db()
+        .list_sessions(&aiobscura_core::SessionFilter::default())
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].project_id.is_some());
+}
+
+// ============================================
+// Gemini CLI Parser Tests
+// ============================================
+
+/// Get the path to the Gemini CLI fixture root (stands in for `~/.gemini`)
+fn gemini_fixture_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/gemini")
+}
+
+fn gemini_fixture_session() -> PathBuf {
+    gemini_fixture_root().join(
+        "tmp/05be5fb496bec9caafb2eb39dae11bd06ce57754caf9d66a051476bed61449b7/chats/session-2025-09-18T02-45-3b44bc68.json",
+    )
+}
+
+#[test]
+fn test_gemini_parse_session_with_tools() {
+    let path = gemini_fixture_session();
+    let parser = GeminiParser::with_root(gemini_fixture_root());
+    let ctx = parse_context(&path);
+
+    let result = parser.parse(&ctx).expect("parse should succeed");
+
+    let session = result.session.as_ref().expect("session");
+    assert_eq!(session.id, "3b44bc68-0d5e-4b0f-9a5e-1f2d6c7e8a90");
+    assert_eq!(session.assistant, Assistant::Gemini);
+    assert_eq!(
+        session.backing_model_id.as_deref(),
+        Some("google:gemini-2.5-flash")
+    );
+    assert_eq!(result.threads.len(), 1);
+
+    // Project root recovered by matching the hashed directory name
+    let project = result.project.as_ref().expect("project");
+    assert_eq!(project.path, PathBuf::from("/Users/test/dev/gemini-app"));
+    assert_eq!(project.name, Some("gemini-app".to_string()));
+    assert_eq!(
+        parser.extract_project_path(&path),
+        Some(PathBuf::from("/Users/test/dev/gemini-app"))
+    );
+
+    let types: Vec<MessageType> = result.messages.iter().map(|m| m.message_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            MessageType::Prompt,
+            MessageType::Context,
+            MessageType::Response,
+            MessageType::ToolCall,
+            MessageType::ToolResult,
+            MessageType::ToolCall,
+            MessageType::ToolResult,
+            MessageType::ToolCall,
+            MessageType::ToolResult,
+            MessageType::Context,
+            MessageType::Response,
+        ]
+    );
+    assert_eq!(result.messages[0].author_role, AuthorRole::Human);
+
+    // Usage on the response of a turn with text, cached input apart
+    assert_eq!(result.messages[2].tokens_in, Some(4024));
+    // `output` plus `thoughts`: reasoning is part of `tokens_out`
+    assert_eq!(result.messages[2].tokens_out, Some(274));
+    assert_eq!(result.messages[2].tokens_cache_read, Some(4096));
+    assert_eq!(result.messages[2].tokens_reasoning, Some(210));
+    assert_eq!(result.messages[2].metadata["tokens"]["total"], 8394);
+    // The session switches to flash later; this turn ran on pro
+    assert_eq!(result.messages[2].model_id(), Some("google:gemini-2.5-pro"));
+
+    // Shell call with its output, under the Claude Code tool name
+    assert_eq!(result.messages[5].tool_name.as_deref(), Some("Bash"));
+    assert_eq!(
+        result.messages[5].metadata["gemini_tool_name"],
+        "run_shell_command"
+    );
+    assert_eq!(
+        result.messages[5].tool_input.as_ref().unwrap()["command"],
+        "npm test"
+    );
+    assert_eq!(result.messages[6].tool_result.as_deref(), Some("1 failing"));
+
+    // Turn without text: usage lands on its edit call
+    let edit = &result.messages[7];
+    assert_eq!(edit.tool_name.as_deref(), Some("Edit"));
+    assert_eq!(edit.tokens_in, Some(314));
+
+    let seqs: Vec<i32> = result.messages.iter().map(|m| m.seq).collect();
+    assert_eq!(seqs, (1..=11).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_gemini_incremental_parsing() {
+    let temp_dir = TempDir::new().unwrap();
+    let chats = temp_dir.path().join("tmp/abc/chats");
+    std::fs::create_dir_all(&chats).unwrap();
+    let path = chats.join("session-2025-09-18T02-45-aaaa1111.json");
+    let write = |messages: serde_json::Value, last_updated: String| {
+        let doc = serde_json::json!({
+            "sessionId": "aaaa1111",
+            "projectHash": "abc",
+            "startTime": "2025-09-18T02:45:00Z",
+            "lastUpdated": last_updated,
+            "messages": messages,
+        });
+        std::fs::write(&path, serde_json::to_vec_pretty(&doc).unwrap()).unwrap();
+    };
+    let old = "2025-09-18T02:46:00Z".to_string();
+
+    write(
+        serde_json::json!([
+            {"id": "1", "timestamp": "2025-09-18T02:45:01Z", "type": "user", "content": "hi"},
+            {"id": "2", "timestamp": "2025-09-18T02:45:02Z", "type": "gemini", "content": "hello", "model": "gemini-2.5-pro"}
+        ]),
+        old.clone(),
+    );
+    let parser = GeminiParser::with_root(temp_dir.path().to_path_buf());
+    let result1 = parser
+        .parse(&parse_context(&path))
+        .expect("first parse should succeed");
+    assert_eq!(result1.messages.len(), 2);
+    let checkpoint = result1.new_checkpoint.clone();
+    assert!(matches!(
+        checkpoint,
+        Checkpoint::ContentHash { records: 2, .. }
+    ));
+
+    // Unchanged file: nothing new
+    let ctx2 = ParseContext {
+        path: &path,
+        checkpoint: &checkpoint,
+        file_size: 0,
+        modified_at: chrono::Utc::now(),
+    };
+    let result2 = parser.parse(&ctx2).expect("second parse should succeed");
+    assert!(result2.messages.is_empty());
+    assert!(result2.warnings.is_empty());
+    assert!(result2.session.is_none());
+
+    // New prompt plus a model turn still in progress
+    write(
+        serde_json::json!([
+            {"id": "1", "timestamp": "2025-09-18T02:45:01Z", "type": "user", "content": "hi"},
+            {"id": "2", "timestamp": "2025-09-18T02:45:02Z", "type": "gemini", "content": "hello", "model": "gemini-2.5-pro"},
+            {"id": "3", "timestamp": "2025-09-18T02:46:01Z", "type": "user", "content": "run ls"},
+            {"id": "4", "timestamp": "2025-09-18T02:46:02Z", "type": "gemini", "content": "", "model": "gemini-2.5-pro"}
+        ]),
+        chrono::Utc::now().to_rfc3339(),
+    );
+    let ctx3 = ParseContext {
+        path: &path,
+        checkpoint: &checkpoint,
+        file_size: 0,
+        modified_at: chrono::Utc::now(),
+    };
+    let result3 = parser.parse(&ctx3).expect("third parse should succeed");
+    assert_eq!(result3.messages.len(), 1);
+    assert_eq!(result3.messages[0].content.as_deref(), Some("run ls"));
+    assert_eq!(result3.messages[0].seq, 3);
+    let checkpoint = result3.new_checkpoint.clone();
+
+    // Turn completed with a tool call
+    write(
+        serde_json::json!([
+            {"id": "1", "timestamp": "2025-09-18T02:45:01Z", "type": "user", "content": "hi"},
+            {"id": "2", "timestamp": "2025-09-18T02:45:02Z", "type": "gemini", "content": "hello", "model": "gemini-2.5-pro"},
+            {"id": "3", "timestamp": "2025-09-18T02:46:01Z", "type": "user", "content": "run ls"},
+            {"id": "4", "timestamp": "2025-09-18T02:46:02Z", "type": "gemini", "content": "", "model": "gemini-2.5-pro",
+             "tokens": {"input": 100, "output": 5},
+             "toolCalls": [{"id": "c1", "name": "run_shell_command", "args": {"command": "ls"},
+                            "result": [{"functionResponse": {"id": "c1", "name": "run_shell_command", "response": {"error": "boom"}}}],
+                            "status": "error", "timestamp": "2025-09-18T02:46:03Z"}]}
+        ]),
+        old,
+    );
+    let ctx4 = ParseContext {
+        path: &path,
+        checkpoint: &checkpoint,
+        file_size: 0,
+        modified_at: chrono::Utc::now(),
+    };
+    let result4 = parser.parse(&ctx4).expect("fourth parse should succeed");
+    assert_eq!(result4.messages.len(), 2);
+    assert_eq!(result4.messages[0].message_type, MessageType::ToolCall);
+    assert_eq!(result4.messages[0].seq, 4);
+    assert_eq!(result4.messages[0].tokens_in, Some(100));
+    assert_eq!(result4.messages[1].message_type, MessageType::Error);
+    assert_eq!(result4.messages[1].tool_result.as_deref(), Some("boom"));
+}
+
+#[test]
+fn test_gemini_sync_creates_project() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(GeminiParser::with_root(gemini_fixture_root()))],
+    );
+    let result = coordinator.sync_all().expect("sync should succeed");
+    assert!(result.errors.is_empty(), "errors: {:?}", result.errors);
+    assert_eq!(result.messages_inserted, 11);
+
+    let source = coordinator
+        .db()
+        .get_source_file(&gemini_fixture_session().to_string_lossy())
+        .unwrap()
+        .unwrap();
+    assert_eq!(source.file_type, aiobscura_core::types::FileType::Json);
+    assert!(matches!(
+        source.checkpoint,
+        Checkpoint::ContentHash { records: 5, .. }
+    ));
+
+    let sessions = coordinator
+        .db()
+        .list_sessions(&aiobscura_core::SessionFilter::default())
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+    let project_id = sessions[0].project_id.clone().expect("project");
+    let stats = coordinator
+        .db()
+        .get_project_stats(&project_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.session_count, 1);
+
+    // Re-sync is a no-op
+    let again = coordinator.sync_all().expect("resync should succeed");
+    assert_eq!(again.messages_inserted, 0);
+}
+
+// ============================================
+// Custom (Configuration-Defined) Parser Tests
+// ============================================
+
+/// Build a custom agent config rooted at `root` from a TOML snippet.
+fn custom_agent_config(root: &std::path::Path) -> aiobscura_core::Config {
+    toml::from_str(&format!(
+        r#"
+[[agents.custom]]
+name = "buildbot"
+root = "{}"
+model_provider = "anthropic"
+
+[[agents.custom.sources]]
+pattern = "logs/*.jsonl"
+
+[agents.custom.fields]
+session_id = "/session"
+timestamp = "/ts"
+role = "/who"
+content = "/msg/text"
+tool_name = "/call/name"
+tool_input = "/call/args"
+tool_result = "/call/output"
+tokens_in = "/usage/in"
+tokens_out = "/usage/out"
+model = "/model"
+cwd = "/cwd"
+
+[agents.custom.roles]
+operator = "human"
+bot = "assistant"
+"#,
+        root.display()
+    ))
+    .unwrap()
+}
+
+const CUSTOM_LOG: &str = r#"{"session":"s1","ts":"2025-04-01T09:00:00Z","who":"operator","msg":{"text":"deploy staging"},"cwd":"/srv/deployer"}
+{"session":"s1","ts":1743498005,"who":"bot","msg":{"text":"Deploying."},"model":"claude-sonnet-4","usage":{"in":900,"out":12}}
+{"session":"s1","ts":1743498006000,"who":"bot","call":{"name":"shell","args":"{\"cmd\":\"make deploy\"}"}}
+not json
+{"session":"s1","ts":"2025-04-01T09:00:09Z","who":"tool","call":{"name":"shell","output":"ok"}}
+{"session":"s2","ts":"2025-04-01T10:00:00Z","who":"operator","msg":{"text":"status?"}}
+"#;
+
+#[test]
+fn test_custom_parser_maps_fields() {
+    let temp_dir = TempDir::new().unwrap();
+    let logs = temp_dir.path().join("logs");
+    std::fs::create_dir_all(&logs).unwrap();
+    let path = logs.join("run.jsonl");
+    std::fs::write(&path, CUSTOM_LOG).unwrap();
+
+    let config = custom_agent_config(temp_dir.path());
+    let parser = ConfigurableJsonlParser::new(config.agents.custom[0].clone()).unwrap();
+    let result = parser
+        .parse(&parse_context(&path))
+        .expect("parse should succeed");
+
+    // One malformed line is skipped with a warning
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.messages.len(), 5);
+
+    // Two sessions, namespaced by agent name
+    assert_eq!(result.extra_sessions.len(), 1);
+    let first = &result.extra_sessions[0];
+    assert_eq!(first.id, "buildbot:s1");
+    assert_eq!(first.assistant, Assistant::Custom);
+    assert_eq!(first.metadata["agent"], "buildbot");
+    assert_eq!(
+        first.backing_model_id.as_deref(),
+        Some("anthropic:claude-sonnet-4")
+    );
+    assert_eq!(result.session.as_ref().unwrap().id, "buildbot:s2");
+    // The project is resolved from the cwd when the session is stored
+    assert_eq!(first.metadata["cwd"], "/srv/deployer");
+    assert!(first.project_id.is_none());
+    assert!(result.project.is_none());
+
+    let msgs = &result.messages;
+    assert_eq!(msgs[0].author_role, AuthorRole::Human);
+    assert_eq!(msgs[0].message_type, MessageType::Prompt);
+    assert_eq!(msgs[0].content.as_deref(), Some("deploy staging"));
+
+    assert_eq!(msgs[1].message_type, MessageType::Response);
+    assert_eq!(msgs[1].tokens_in, Some(900));
+    assert_eq!(msgs[1].tokens_out, Some(12));
+    // Epoch seconds and milliseconds
+    assert_eq!(msgs[1].emitted_at.timestamp(), 1743498005);
+    assert_eq!(msgs[2].emitted_at.timestamp(), 1743498006);
+
+    assert_eq!(msgs[2].message_type, MessageType::ToolCall);
+    assert_eq!(msgs[2].tool_name.as_deref(), Some("shell"));
+    assert_eq!(msgs[2].tool_input.as_ref().unwrap()["cmd"], "make deploy");
+
+    assert_eq!(msgs[3].message_type, MessageType::ToolResult);
+    assert_eq!(msgs[3].author_role, AuthorRole::Tool);
+    assert_eq!(msgs[3].tool_result.as_deref(), Some("ok"));
+
+    // raw_data is the full record
+    assert_eq!(msgs[3].raw_data["call"]["output"], "ok");
+    assert_eq!(msgs[4].session_id, "buildbot:s2");
+}
+
+#[test]
+fn test_custom_parser_sync_and_incremental() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let logs = temp_dir.path().join("logs");
+    std::fs::create_dir_all(&logs).unwrap();
+    let path = logs.join("run.jsonl");
+    std::fs::write(&path, CUSTOM_LOG).unwrap();
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_config(
+        db,
+        &custom_agent_config(temp_dir.path()),
+    );
+    let result = coordinator.sync_file(&path).expect("sync should succeed");
+    assert_eq!(result.new_messages, 5);
+
+    let sessions = coordinator
+        .db()
+        .list_sessions(&aiobscura_core::SessionFilter::default())
+        .unwrap();
+    assert_eq!(sessions.len(), 2);
+
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(
+        b"{\"session\":\"s2\",\"ts\":\"2025-04-01T10:00:01Z\",\"who\":\"bot\",\"msg\":{\"text\":\"green\"}}\n",
+    )
+    .unwrap();
+    drop(file);
+
+    let result = coordinator.sync_file(&path).expect("resync should succeed");
+    assert_eq!(result.new_messages, 1);
+    assert_eq!(result.session_id.as_deref(), Some("buildbot:s2"));
+}
+
+#[test]
+fn test_custom_parser_sessions_with_different_cwds() {
+    let temp_dir = TempDir::new().unwrap();
+    let logs = temp_dir.path().join("logs");
+    std::fs::create_dir_all(&logs).unwrap();
+    let path = logs.join("run.jsonl");
+    std::fs::write(
+        &path,
+        r#"{"session":"a","ts":"2025-04-01T09:00:00Z","who":"operator","msg":{"text":"hi"},"cwd":"/srv/frontend"}
+{"session":"b","ts":"2025-04-01T10:00:00Z","who":"operator","msg":{"text":"hi"},"cwd":"/srv/backend"}
+"#,
+    )
+    .unwrap();
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_config(
+        db,
+        &custom_agent_config(temp_dir.path()),
+    );
+    coordinator.sync_file(&path).expect("sync should succeed");
+
+    let db = coordinator.db();
+    for (session_id, dir) in [
+        ("buildbot:a", "/srv/frontend"),
+        ("buildbot:b", "/srv/backend"),
+    ] {
+        let session = db.get_session(session_id).unwrap().unwrap();
+        let project = db
+            .get_project(session.project_id.as_deref().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(project.path, PathBuf::from(dir));
+    }
+}
+
+#[test]
+fn test_custom_agents_are_told_apart() {
+    use aiobscura_core::db::SessionFilter;
+    use aiobscura_core::ingest::parsers::parser_for_agent;
+    use aiobscura_core::ingest::{FailedRecord, IngestCoordinator, ReparseFilter};
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = custom_agent_config(temp_dir.path());
+    let mut deployer = config.agents.custom[0].clone();
+    deployer.name = "deployer".to_string();
+    deployer.sources[0].pattern = "deploys/*.jsonl".to_string();
+    config.agents.custom.push(deployer);
+
+    let mut paths = Vec::new();
+    for dir in ["logs", "deploys"] {
+        std::fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        let path = temp_dir.path().join(dir).join("run.jsonl");
+        std::fs::write(&path, CUSTOM_LOG).unwrap();
+        paths.push(path);
+    }
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_config(db, &config);
+    for path in &paths {
+        coordinator.sync_file(path).expect("sync should succeed");
+    }
+
+    // Both agents are `custom`; the source file records which one read it
+    let db = coordinator.db();
+    for (path, agent) in paths.iter().zip(["buildbot", "deployer"]) {
+        let file = db
+            .get_source_file(&path.to_string_lossy())
+            .unwrap()
+            .unwrap();
+        assert_eq!(file.assistant, Assistant::Custom);
+        assert_eq!(file.agent.as_deref(), Some(agent));
+    }
+
+    let sessions = db
+        .list_sessions(&SessionFilter {
+            agent: Some("deployer".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions.iter().all(|s| s.id.starts_with("deployer:")));
+
+    let filter = ReparseFilter {
+        agent: Some("deployer".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        coordinator.reparse_files(&filter).unwrap(),
+        vec![paths[1].clone()]
+    );
+
+    // Parse failures are listed by the agent of their file
+    for path in &paths {
+        let record = FailedRecord {
+            offset: 0,
+            line: Some(1),
+            raw: "{".to_string(),
+            error: "EOF".to_string(),
+        };
+        db.insert_parse_failures(&path.to_string_lossy(), Assistant::Custom, 1, &[record])
+            .unwrap();
+    }
+    let failures = db.list_parse_failures(None, Some("buildbot"), 10).unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].source_file_path, paths[0].to_string_lossy());
+
+    let parser = parser_for_agent(&config, "deployer").unwrap();
+    assert_eq!(parser.agent(), Some("deployer"));
+    assert!(parser_for_agent(&config, "unknown").is_none());
+}
+
+// ============================================
+// Parser Plugin Tests
+// ============================================
+
+/// Write a plugin script that prints the fixture result on the first parse
+/// and only echoes the checkpoint afterwards.
+#[cfg(unix)]
+fn write_plugin_script(dir: &std::path::Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let result =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plugin/result.json");
+    let script = dir.join("shipit-aiobscura");
+    std::fs::write(
+        &script,
+        format!(
+            r#"#!/bin/sh
+input=$(cat)
+case "$input" in
+  *'"type":"none"'*) cat '{}' ;;
+  *) echo '{{"new_checkpoint":{{"type":"content_hash","hash":"run-42-v1","records":2}}}}' ;;
+esac
+"#,
+            result.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[cfg(unix)]
+#[test]
+fn test_plugin_parser_sync() {
+    let temp_dir = TempDir::new().unwrap();
+    let runs = temp_dir.path().join("runs");
+    std::fs::create_dir_all(&runs).unwrap();
+    let path = runs.join("42.json");
+    std::fs::write(&path, "{}").unwrap();
+
+    let script = write_plugin_script(temp_dir.path());
+    let config: aiobscura_core::Config = toml::from_str(&format!(
+        r#"
+[[agents.plugin]]
+name = "shipit"
+command = '{}'
+root = '{}'
+
+[[agents.plugin.sources]]
+pattern = "runs/*.json"
+file_type = "json"
+"#,
+        script.display(),
+        temp_dir.path().display()
+    ))
+    .unwrap();
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_config(db, &config);
+
+    let result = coordinator.sync_file(&path).expect("sync should succeed");
+    assert_eq!(result.new_messages, 2);
+    assert_eq!(result.session_id.as_deref(), Some("shipit:42"));
+
+    let sessions = coordinator
+        .db()
+        .list_sessions(&aiobscura_core::SessionFilter::default())
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].assistant, Assistant::Custom);
+    assert_eq!(sessions[0].metadata["agent"], "shipit");
+    assert_eq!(sessions[0].source_file_path, path.to_string_lossy());
+
+    // The plugin sees its checkpoint and emits nothing new
+    std::fs::write(&path, "{\"changed\":true}").unwrap();
+    let result = coordinator.sync_file(&path).expect("resync should succeed");
+    assert_eq!(result.new_messages, 0);
+}
+
+// ============================================
+// Tool Call Pairing Tests
+// ============================================
+
+#[test]
+fn test_tool_calls_paired_with_results() {
+    use aiobscura_core::ingest::IngestCoordinator;
+
+    let db = Database::open_in_memory().unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_parsers(
+        db,
+        vec![
+            Box::new(ClaudeCodeParser::with_root(
+                fixture_path("").parent().unwrap().to_path_buf(),
+            )),
+            Box::new(CodexParser::with_root(codex_fixture_path(""))),
+        ],
+    );
+    coordinator
+        .sync_file(&fixture_path("with-tool-calls.jsonl"))
+        .expect("sync should succeed");
+    coordinator
+        .sync_file(&codex_fixture_path("with-tool-calls.jsonl"))
+        .expect("sync should succeed");
+    let db = coordinator.db();
+
+    // Claude: tool_use blocks are answered by tool_result blocks
+    let outcomes = db.get_session_tool_calls("test-session-002").unwrap();
+    assert_eq!(outcomes.len(), 2);
+    let names: Vec<_> = outcomes
+        .iter()
+        .map(|o| o.tool_name.as_deref().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Read", "Bash"]);
+    assert_eq!(
+        outcomes.iter().map(|o| o.latency_ms).collect::<Vec<_>>(),
+        vec![Some(1000), Some(5000)]
+    );
+    assert!(outcomes.iter().all(|o| o.is_error == Some(false)));
+    let messages = db.get_session_messages("test-session-002", 100).unwrap();
+    let result = messages
+        .iter()
+        .find(|m| Some(m.id) == outcomes[0].result_message_id)
+        .unwrap();
+    assert_eq!(result.message_type, MessageType::ToolResult);
+    assert_eq!(
+        result.tool_call_id.as_deref(),
+        Some(&*outcomes[0].tool_call_id)
+    );
+
+    let thread_outcomes = db.get_thread_tool_calls("test-session-002-main").unwrap();
+    assert_eq!(thread_outcomes, outcomes);
+
+    // Codex: function_call is answered by function_call_output
+    let outcomes = db
+        .get_session_tool_calls("019ab86e-2222-3333-4444-555566667777")
+        .unwrap();
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes
+        .iter()
+        .all(|o| o.latency_ms == Some(1000) && o.is_error == Some(false)));
+}
+
+// ============================================
+// Reparse Tests
+// ============================================
+
+#[test]
+fn test_reparse_rebuilds_sessions_shared_across_files() {
+    use aiobscura_core::ingest::{IngestCoordinator, ReparseFilter};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("projects/-Users-test-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    for name in ["agent-a1234567.jsonl", "with-agent-spawn.jsonl"] {
+        std::fs::copy(fixture_path(name), project_dir.join(name)).unwrap();
+    }
+    let agent_file = project_dir.join("agent-a1234567.jsonl");
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(ClaudeCodeParser::with_root(
+            temp_dir.path().to_path_buf(),
+        ))],
+    );
+    coordinator.sync_all().expect("sync should succeed");
+    let db = coordinator.db();
+    let message_count = db.count_messages().unwrap();
+    db.insert_plugin_metric(
+        "core.first_order",
+        "session",
+        Some("test-session-003"),
+        "total_tokens",
+        &serde_json::json!(42),
+        1,
+    )
+    .unwrap();
+
+    let outdated = ReparseFilter {
+        outdated_only: true,
+        ..Default::default()
+    };
+    assert!(coordinator.reparse_files(&outdated).unwrap().is_empty());
+
+    // Simulate the agent file having been ingested by an older parser. Its
+    // session is shared with the parent file, so both are reparsed.
+    db.connection()
+        .unwrap()
+        .execute(
+            "UPDATE source_files SET parser_version = 0 WHERE path = ?",
+            [agent_file.to_string_lossy()],
+        )
+        .unwrap();
+    assert_eq!(coordinator.reparse_files(&outdated).unwrap().len(), 2);
+
+    let result = coordinator
+        .reparse(&outdated, |_, _, _| {})
+        .expect("reparse should succeed");
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files_processed, 2);
+
+    assert_eq!(db.count_messages().unwrap(), message_count);
+    assert!(db
+        .get_plugin_metrics("core.first_order", "session", Some("test-session-003"))
+        .unwrap()
+        .is_empty());
+    let source = db
+        .get_source_file(&agent_file.to_string_lossy())
+        .unwrap()
+        .unwrap();
+    assert_eq!(source.parser_version, 3);
+    let agent_thread = db.get_thread("test-session-003-agent").unwrap().unwrap();
+    assert!(agent_thread.spawned_by_message_id.is_some());
+    assert!(coordinator.reparse_files(&outdated).unwrap().is_empty());
+}
+
+#[test]
+fn test_reparse_keeps_sessions_when_a_file_fails() {
+    use aiobscura_core::ingest::{IngestCoordinator, ReparseFilter};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("projects/-Users-test-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    for name in ["agent-a1234567.jsonl", "with-agent-spawn.jsonl"] {
+        std::fs::copy(fixture_path(name), project_dir.join(name)).unwrap();
+    }
+    let agent_file = project_dir.join("agent-a1234567.jsonl");
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(ClaudeCodeParser::with_root(
+            temp_dir.path().to_path_buf(),
+        ))],
+    );
+    coordinator.sync_all().expect("sync should succeed");
+    let db = coordinator.db();
+    let message_count = db.count_messages().unwrap();
+    let thread_count = db.get_session_threads("test-session-003").unwrap().len();
+    assert!(thread_count > 1);
+
+    // The agent file still exists but can no longer be read
+    std::fs::remove_file(&agent_file).unwrap();
+    std::fs::create_dir(&agent_file).unwrap();
+
+    let all = ReparseFilter::default();
+    assert_eq!(coordinator.reparse_files(&all).unwrap().len(), 2);
+    let err = coordinator
+        .reparse(&all, |_, _, _| {})
+        .expect_err("reparse should fail");
+    assert!(err.to_string().contains("agent-a1234567.jsonl"), "{err}");
+
+    assert_eq!(db.count_messages().unwrap(), message_count);
+    assert_eq!(
+        db.get_session_threads("test-session-003").unwrap().len(),
+        thread_count
+    );
+    assert!(db
+        .get_source_file(&agent_file.to_string_lossy())
+        .unwrap()
+        .is_some());
+}
+
+// ============================================
+// Conversation Structure Tests
+// ============================================
+
+#[test]
+fn test_compaction_and_branches_preserved() {
+    let path = fixture_path("test-session-004.jsonl");
+    let parser = ClaudeCodeParser::new();
+    let result = parser
+        .parse(&parse_context(&path))
+        .expect("parse should succeed");
+
+    let types: Vec<MessageType> = result.messages.iter().map(|m| m.message_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            MessageType::Summary,
+            MessageType::Prompt,
+            MessageType::Response,
+            MessageType::Response,
+            MessageType::Context,
+            MessageType::Compaction,
+            MessageType::Response,
+        ]
+    );
+    assert_eq!(
+        result.messages[0].content.as_deref(),
+        Some("Refactor config loader")
+    );
+
+    // Both responses answer the same prompt: an edit-and-resubmit branch
+    assert_eq!(result.messages[2].record_id.as_deref(), Some("cmp-002"));
+    assert_eq!(
+        result.messages[2].parent_record_id.as_deref(),
+        Some("cmp-001")
+    );
+    assert_eq!(
+        result.messages[3].parent_record_id.as_deref(),
+        Some("cmp-001")
+    );
+
+    // The boundary continues from the leaf before compaction
+    let boundary = &result.messages[4];
+    assert_eq!(boundary.parent_record_id.as_deref(), Some("cmp-003"));
+    assert_eq!(boundary.metadata["trigger"], "auto");
+    assert_eq!(boundary.metadata["pre_tokens"], 155000);
+
+    let compaction = &result.messages[5];
+    assert_eq!(compaction.author_role, AuthorRole::System);
+    assert!(compaction
+        .content
+        .as_deref()
+        .unwrap()
+        .contains("refactoring the config loader"));
+}
+
+#[test]
+fn test_continued_session_linked_across_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("projects/-Users-test-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    // Named after their session ids, as Claude Code does: the leading summary
+    // records carry no sessionId
+    for name in ["test-session-004.jsonl", "test-session-005.jsonl"] {
+        std::fs::copy(fixture_path(name), project_dir.join(name)).unwrap();
+    }
+
+    // Either file may be ingested first
+    let orders = [
+        ["test-session-005.jsonl", "test-session-004.jsonl"],
+        ["test-session-004.jsonl", "test-session-005.jsonl"],
+    ];
+    for (i, order) in orders.iter().enumerate() {
+        let db = Database::open(&temp_dir.path().join(format!("test-{}.db", i))).unwrap();
+        db.migrate().unwrap();
+        let coordinator = aiobscura_core::ingest::IngestCoordinator::with_parsers(
+            db,
+            vec![Box::new(ClaudeCodeParser::with_root(
+                temp_dir.path().to_path_buf(),
+            ))],
+        )
+        .with_parallelism(1);
+
+        let files: Vec<PathBuf> = order.iter().map(|name| project_dir.join(name)).collect();
+        let result = coordinator.sync_files(&files).expect("sync should succeed");
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let db = coordinator.db();
+        assert_eq!(
+            db.get_continued_session_id("test-session-005").unwrap(),
+            Some("test-session-004".to_string())
+        );
+        // A summary of its own leaf does not make a session continue itself
+        assert_eq!(
+            db.get_continued_session_id("test-session-004").unwrap(),
+            None
+        );
+    }
+}
+
+// ============================================
+// Parse Failure Tests
+// ============================================
+
+#[test]
+fn test_parse_failures_are_quarantined_and_retried() {
+    use aiobscura_core::ingest::{IngestCoordinator, ReparseFilter};
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("projects/-Users-test-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let path = project_dir.join("malformed-lines.jsonl");
+    std::fs::copy(fixture_path("malformed-lines.jsonl"), &path).unwrap();
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(ClaudeCodeParser::with_root(
+            temp_dir.path().to_path_buf(),
+        ))],
+    );
+    let result = coordinator.sync_all().expect("sync should succeed");
+    assert_eq!(result.records_failed, 2);
+    let db = coordinator.db();
+
+    let failures = db.list_parse_failures(None, None, 10).unwrap();
+    assert_eq!(failures.len(), 2);
+    let bad_line = failures
+        .iter()
+        .find(|f| f.line_number == Some(2))
+        .expect("line 2 is quarantined");
+    assert_eq!(bad_line.source_file_path, path.to_string_lossy());
+    assert_eq!(bad_line.assistant, Assistant::ClaudeCode);
+    assert_eq!(bad_line.raw_text, "this is not valid json at all");
+    assert_eq!(bad_line.parser_version, 3);
+    assert!(bad_line.error.starts_with("JSON parse error"));
+    assert!(db
+        .list_parse_failures(Some(Assistant::Codex), None, 10)
+        .unwrap()
+        .is_empty());
+
+    let groups = db.summarize_parse_failures(None, None).unwrap();
+    assert_eq!(groups.iter().map(|g| g.count).sum::<usize>(), 2);
+    assert!(groups.iter().all(|g| g.files == 1));
+
+    // Syncing again doesn't duplicate failures
+    coordinator.sync_all().expect("sync should succeed");
+    assert_eq!(db.list_parse_failures(None, None, 10).unwrap().len(), 2);
+
+    // A failure in an appended line is numbered from the start of the file
+    let line_count = std::fs::read_to_string(&path).unwrap().lines().count() as i32;
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(b"appended garbage\n")
+        .unwrap();
+    coordinator.sync_all().expect("sync should succeed");
+    let failures = db.list_parse_failures(None, None, 10).unwrap();
+    assert_eq!(failures.len(), 3);
+    assert!(failures
+        .iter()
+        .any(|f| f.raw_text == "appended garbage" && f.line_number == Some(line_count + 1)));
+
+    // Once the parser (here: the file) can handle the records, retrying
+    // clears the quarantine and recovers the messages.
+    let message_count = db.count_messages().unwrap();
+    let content = std::fs::read_to_string(&path).unwrap();
+    let fixed: Vec<&str> = content
+        .lines()
+        .map(|line| {
+            if serde_json::from_str::<serde_json::Value>(line).is_ok() {
+                line
+            } else {
+                "{\"type\":\"summary\",\"summary\":\"fixed\"}"
+            }
+        })
+        .collect();
+    std::fs::write(&path, fixed.join("\n") + "\n").unwrap();
+
+    let failed = ReparseFilter {
+        failed_only: true,
+        ..Default::default()
+    };
+    assert_eq!(coordinator.reparse_files(&failed).unwrap(), vec![path]);
+    let result = coordinator
+        .reparse(&failed, |_, _, _| {})
+        .expect("reparse should succeed");
+    assert_eq!(result.records_failed, 0);
+    assert!(db.list_parse_failures(None, None, 10).unwrap().is_empty());
+    assert!(db.count_messages().unwrap() >= message_count);
+    assert!(coordinator.reparse_files(&failed).unwrap().is_empty());
+}
+
+// ============================================
+// Multiple Root Tests
+// ============================================
+
+#[test]
+fn test_sessions_record_their_source_root() {
+    use aiobscura_core::db::SessionFilter;
+    use aiobscura_core::ingest::parsers::RootedParser;
+    use aiobscura_core::ingest::{IngestCoordinator, SourceRoot};
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut parsers: Vec<Box<dyn AssistantParser>> = Vec::new();
+    for (label, host, fixture) in [
+        ("default", "laptop", "minimal-session.jsonl"),
+        ("api", "api-devcontainer", "with-tool-calls.jsonl"),
+    ] {
+        let root = temp_dir.path().join(label);
+        let project_dir = root.join("projects/-Users-test-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        std::fs::copy(fixture_path(fixture), project_dir.join(fixture)).unwrap();
+        parsers.push(Box::new(RootedParser::new(
+            Box::new(ClaudeCodeParser::with_root(root)),
+            SourceRoot {
+                label: label.to_string(),
+                host: host.to_string(),
+            },
+        )));
+    }
+
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_parsers(db, parsers);
+    let result = coordinator.sync_all().expect("sync should succeed");
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files_processed, 2);
+
+    let db = coordinator.db();
+    let api = db
+        .list_sessions(&SessionFilter {
+            source_root: Some("api".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(api.len(), 1);
+    assert_eq!(api[0].source_root(), Some("api"));
+    assert_eq!(api[0].source_host(), Some("api-devcontainer"));
+
+    let laptop = db
+        .list_sessions(&SessionFilter {
+            source_host: Some("laptop".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(laptop.len(), 1);
+    assert_eq!(laptop[0].id, "test-session-001");
+    assert_eq!(laptop[0].source_root(), Some("default"));
+}
+
+#[test]
+fn test_reparse_reads_imported_snapshots() {
+    use aiobscura_core::ingest::{import, IngestCoordinator, ReparseFilter};
+
+    let temp_dir = TempDir::new().unwrap();
+    let snapshot = temp_dir.path().join("snapshot");
+    let project_dir = snapshot.join(".claude/projects/-Users-alice-app");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::copy(
+        fixture_path("minimal-session.jsonl"),
+        project_dir.join("minimal-session.jsonl"),
+    )
+    .unwrap();
+    let db_path = temp_dir.path().join("test.db");
+
+    // Import the snapshot as `aiobscura-sync --import` does
+    let db = Database::open(&db_path).unwrap();
+    db.migrate().unwrap();
+    let root =
+        import::prepare_import(&snapshot, &temp_dir.path().join("imports"), "alice").unwrap();
+    let parsers = import::import_parsers(&root, "alice").unwrap();
+    db.record_import(&root, "alice").unwrap();
+    let coordinator = IngestCoordinator::with_parsers(db, parsers);
+    let imported = coordinator.sync_all().expect("import should succeed");
+    assert_eq!(imported.files_processed, 1);
+    let message_count = coordinator.db().count_messages().unwrap();
+    drop(coordinator);
+
+    // A regular coordinator, whose parsers don't read the snapshot
+    let coordinator = IngestCoordinator::with_parsers(
+        Database::open(&db_path).unwrap(),
+        vec![Box::new(ClaudeCodeParser::with_root(
+            temp_dir.path().join("local"),
+        ))],
+    );
+    let result = coordinator
+        .reparse(&ReparseFilter::default(), |_, _, _| {})
+        .expect("reparse should succeed");
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files_processed, 1);
+
+    let db = coordinator.db();
+    assert_eq!(db.count_messages().unwrap(), message_count);
+    let sessions = db
+        .list_sessions(&aiobscura_core::SessionFilter::default())
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].metadata["source_root"], "import");
+    assert_eq!(sessions[0].metadata["source_host"], "alice");
+}
+
+// ============================================
+// Blob Store Tests
+// ============================================
+
+#[test]
+fn test_images_are_moved_to_the_blob_store() {
+    use aiobscura_core::ingest::IngestCoordinator;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("claude/projects/-Users-test-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::copy(
+        fixture_path("with-image.jsonl"),
+        project_dir.join("with-image.jsonl"),
+    )
+    .unwrap();
+
+    let db = Database::open(&temp_dir.path().join("data.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(ClaudeCodeParser::with_root(
+            temp_dir.path().join("claude"),
+        ))],
+    );
+    let result = coordinator.sync_all().expect("sync should succeed");
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+    let db = coordinator.db();
+    let messages = db.get_session_messages("test-session-006", 100).unwrap();
+    assert!(messages
+        .iter()
+        .all(|m| !m.raw_data.to_string().contains("iVBORw0KGgo")));
+
+    let image_message = messages
+        .iter()
+        .find(|m| m.content_type.as_ref().is_some_and(|ct| ct.is_image()))
+        .expect("image prompt");
+    let image = image_message.image().expect("image reference");
+    assert_eq!(image.media_type, "image/png");
+    assert_eq!(image.extension(), "png");
+    let bytes = db.get_blob(&image.sha256).unwrap().expect("stored blob");
+    assert_eq!(bytes.len() as u64, image.size);
+    assert!(bytes.starts_with(b"\x89PNG"));
+    assert!(db
+        .blob_path(&image.sha256)
+        .unwrap()
+        .starts_with(temp_dir.path().join("blobs")));
+
+    // The screenshot returned by the tool is the same image, stored once
+    let tool_result = messages
+        .iter()
+        .find(|m| m.message_type == MessageType::ToolResult)
+        .expect("tool result");
+    assert_eq!(
+        db.get_message_blobs(tool_result.id).unwrap(),
+        vec![image.clone()]
+    );
+    let stored_result = tool_result
+        .tool_result
+        .as_deref()
+        .expect("tool result body");
+    assert!(!stored_result.contains("iVBORw0KGgo"));
+    assert!(stored_result.contains(&image.sha256));
+    let shard = std::fs::read_dir(temp_dir.path().join("blobs"))
+        .unwrap()
+        .count();
+    assert_eq!(shard, 1);
+}
+
+/// Backdate a blob past the grace period of `remove_unreferenced_blobs`.
+fn age_blob(db: &Database, sha256: &str) {
+    let two_hours_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(2 * 60 * 60);
+    std::fs::File::options()
+        .write(true)
+        .open(db.blob_path(sha256).unwrap())
+        .unwrap()
+        .set_modified(two_hours_ago)
+        .unwrap();
+}
+
+#[test]
+fn test_stored_images_are_moved_and_unreferenced_blobs_removed() {
+    use aiobscura_core::ingest::IngestCoordinator;
+    use base64::Engine as _;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("claude/projects/-Users-test-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::copy(
+        fixture_path("with-image.jsonl"),
+        project_dir.join("with-image.jsonl"),
+    )
+    .unwrap();
+
+    let db = Database::open(&temp_dir.path().join("data.db")).unwrap();
+    db.migrate().unwrap();
+    let coordinator = IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(ClaudeCodeParser::with_root(
+            temp_dir.path().join("claude"),
+        ))],
+    );
+    coordinator.sync_all().expect("sync should succeed");
+    let db = coordinator.db();
+    let messages = db.get_session_messages("test-session-006", 100).unwrap();
+    let tool_result = messages
+        .iter()
+        .find(|m| m.message_type == MessageType::ToolResult)
+        .expect("tool result");
+
+    // A tool result stored inline by an older version
+    let data = base64::engine::general_purpose::STANDARD.encode(b"older screenshot");
+    let inline = serde_json::json!([
+        {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": data}}
+    ]);
+    db.connection()
+        .unwrap()
+        .execute(
+            "UPDATE messages SET tool_result = ?2 WHERE id = ?1",
+            rusqlite::params![tool_result.id, inline.to_string()],
+        )
+        .unwrap();
+    assert_eq!(db.externalize_stored_images().unwrap(), 1);
+    assert_eq!(db.externalize_stored_images().unwrap(), 0);
+
+    let stored = db
+        .get_session_messages("test-session-006", 100)
+        .unwrap()
+        .into_iter()
+        .find(|m| m.id == tool_result.id)
+        .expect("tool result");
+    let body = stored.tool_result.expect("tool result body");
+    assert!(!body.contains(&data));
+    let blobs = db.get_message_blobs(tool_result.id).unwrap();
+    assert_eq!(blobs.len(), 2);
+    let older = blobs
+        .iter()
+        .find(|blob| body.contains(&blob.sha256))
+        .expect("reference in the tool result")
+        .clone();
+    assert_eq!(
+        db.get_blob(&older.sha256).unwrap().as_deref(),
+        Some(&b"older screenshot"[..])
+    );
+
+    // Reparsing drops the older screenshot's only reference
+    for blob in &blobs {
+        age_blob(db, &blob.sha256);
+    }
+    coordinator
+        .reparse(&Default::default(), |_, _, _| {})
+        .expect("reparse should succeed");
+    assert_eq!(db.get_blob(&older.sha256).unwrap(), None);
+    let shown = messages
+        .iter()
+        .find_map(|m| m.image())
+        .expect("image prompt");
+    assert!(db.get_blob(&shown.sha256).unwrap().is_some());
+
+    // Recently written blobs may belong to a message not committed yet
+    let fresh = db.blob_store().unwrap().put(b"not committed yet").unwrap();
+    assert_eq!(db.remove_unreferenced_blobs().unwrap(), 0);
+    age_blob(db, &fresh);
+    assert_eq!(db.remove_unreferenced_blobs().unwrap(), 1);
+
+    // Pruning keeps the image the prompt shows
+    age_blob(db, &shown.sha256);
+    let cutoffs = vec![(
+        "test-session-006".to_string(),
+        chrono::Utc::now() + chrono::Duration::days(1),
+    )];
+    db.prune_raw_data(&cutoffs, false).unwrap();
+    assert_eq!(db.remove_unreferenced_blobs().unwrap(), 0);
+    assert!(db.get_blob(&shown.sha256).unwrap().is_some());
+}
+
+// ============================================
+// OTLP Receiver Tests
+// ============================================
+
+/// POST a body to the receiver over a plain HTTP/1.1 connection, returning
+/// the status code.
+fn otlp_post(addr: std::net::SocketAddr, path: &str, content_type: &str, body: &[u8]) -> u16 {
+    use std::io::{Read, Write};
+
+    let mut stream = std::net::TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        addr,
+        content_type,
+        body.len()
+    )
+    .unwrap();
+    stream.write_all(body).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response.split(' ').nth(1).unwrap().parse().unwrap()
+}
+
+#[test]
+fn test_otlp_receiver_stores_logs_and_metrics() {
+    use aiobscura_core::otlp::proto::{
+        any_value, metric, number_data_point, AnyValue, ExportLogsServiceRequest,
+        ExportMetricsServiceRequest, KeyValue, LogRecord, Metric, NumberDataPoint, ResourceLogs,
+        ResourceMetrics, ScopeLogs, ScopeMetrics, Sum, AGGREGATION_TEMPORALITY_CUMULATIVE,
+    };
+    use aiobscura_core::otlp::OtlpReceiver;
+    use prost::Message as _;
+
+    fn string_attr(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let db = Database::open(&db_path).unwrap();
+    db.migrate().unwrap();
+
+    assert!(OtlpReceiver::start("0.0.0.0:0", Database::open(&db_path).unwrap()).is_err());
+    let receiver = OtlpReceiver::start("127.0.0.1:0", Database::open(&db_path).unwrap()).unwrap();
+    let addr = receiver.local_addr();
+
+    // Claude Code tool decision, protobuf-encoded
+    let logs = ExportLogsServiceRequest {
+        resource_logs: vec![ResourceLogs {
+            resource: None,
+            scope_logs: vec![ScopeLogs {
+                scope: None,
+                log_records: vec![LogRecord {
+                    time_unix_nano: 1_765_015_200_000_000_000,
+                    body: Some(AnyValue {
+                        value: Some(any_value::Value::StringValue(
+                            "claude_code.tool_decision".to_string(),
+                        )),
+                    }),
+                    attributes: vec![
+                        string_attr("event.name", "tool_decision"),
+                        string_attr("session.id", "otlp-session"),
+                        string_attr("tool_name", "Bash"),
+                        string_attr("decision", "reject"),
+                    ],
+                    ..Default::default()
+                }],
+            }],
+        }],
+    };
+    assert_eq!(
+        otlp_post(
+            addr,
+            "/v1/logs",
+            "application/x-protobuf",
+            &logs.encode_to_vec()
+        ),
+        200
+    );
+
+    // Codex API request, JSON-encoded
+    let codex_logs = serde_json::json!({
+        "resourceLogs": [{"scopeLogs": [{"logRecords": [{
+            "timeUnixNano": "1765015260000000000",
+            "attributes": [
+                {"key": "event.name", "value": {"stringValue": "codex.api_request"}},
+                {"key": "conversation.id", "value": {"stringValue": "otlp-session"}},
+                {"key": "duration_ms", "value": {"intValue": "950"}}
+            ]
+        }]}]}]
+    });
+    assert_eq!(
+        otlp_post(
+            addr,
+            "/v1/logs",
+            "application/json",
+            codex_logs.to_string().as_bytes()
+        ),
+        200
+    );
+
+    let metrics = ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: None,
+            scope_metrics: vec![ScopeMetrics {
+                scope: None,
+                metrics: vec![Metric {
+                    name: "claude_code.cost.usage".to_string(),
+                    unit: "USD".to_string(),
+                    // Cumulative, as Claude Code exports by default
+                    data: Some(metric::Data::Sum(Sum {
+                        data_points: [(1_765_015_200, 0.42), (1_765_015_260, 0.55)]
+                            .into_iter()
+                            .map(|(secs, usd)| NumberDataPoint {
+                                attributes: vec![string_attr("session.id", "otlp-session")],
+                                start_time_unix_nano: 1_765_015_100_000_000_000,
+                                time_unix_nano: secs * 1_000_000_000,
+                                value: Some(number_data_point::Value::AsDouble(usd)),
+                            })
+                            .collect(),
+                        aggregation_temporality: AGGREGATION_TEMPORALITY_CUMULATIVE,
+                        is_monotonic: true,
+                    })),
+                }],
+            }],
+        }],
+    };
+    assert_eq!(
+        otlp_post(
+            addr,
+            "/v1/metrics",
+            "application/x-protobuf",
+            &metrics.encode_to_vec()
+        ),
+        200
+    );
+
+    assert_eq!(otlp_post(addr, "/v1/logs", "text/plain", b"hi"), 415);
+    assert_eq!(otlp_post(addr, "/v1/logs", "application/json", b"{"), 400);
+    assert_eq!(
+        otlp_post(addr, "/v1/traces", "application/json", b"{}"),
+        404
+    );
+    drop(receiver);
+
+    let events = db.get_session_telemetry_events("otlp-session").unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].name, "tool_decision");
+    assert_eq!(events[0].assistant, Some(Assistant::ClaudeCode));
+    assert_eq!(events[0].tool_name.as_deref(), Some("Bash"));
+    assert_eq!(events[0].decision.as_deref(), Some("reject"));
+    assert_eq!(events[1].name, "api_request");
+    assert_eq!(events[1].assistant, Some(Assistant::Codex));
+    assert_eq!(events[1].duration_ms, Some(950));
+
+    let metrics = db.get_session_telemetry_metrics("otlp-session").unwrap();
+    assert_eq!(metrics.len(), 2);
+    assert_eq!(metrics[0].name, "cost.usage");
+    assert_eq!(metrics[0].unit.as_deref(), Some("USD"));
+    assert_eq!(metrics[0].value, 0.42);
+    assert_eq!(
+        metrics[0].temporality,
+        Some(aiobscura_core::MetricTemporality::Cumulative)
+    );
+    // The running total, not the sum of its points
+    assert_eq!(
+        db.telemetry_metric_total("otlp-session", "cost.usage")
+            .unwrap(),
+        0.55
+    );
+}
+
+// ============================================
+// Cost Estimation Tests
+// ============================================
+
+#[test]
+fn test_cost_estimates_for_project_thread_and_wrapped() {
+    use aiobscura_core::analytics::{
+        ensure_thread_cost, generate_wrapped, list_projects, project_stats, PricingCatalog,
+        WrappedConfig, WrappedPeriod,
+    };
+
+    let temp_dir = TempDir::new().unwrap();
+    let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+    db.migrate().unwrap();
+
+    let coordinator = aiobscura_core::ingest::IngestCoordinator::with_parsers(
+        db,
+        vec![Box::new(CodexParser::with_root(codex_fixture_path("")))],
+    );
+    coordinator
+        .sync_file(&codex_fixture_path("minimal-session.jsonl"))
+        .expect("sync should succeed");
+    let db = coordinator.db();
+
+    let pricing = PricingCatalog::builtin();
+    let projects = list_projects(db, &pricing).unwrap();
+    assert_eq!(projects.len(), 1);
+    let stats = project_stats(db, &projects[0].id, &pricing)
+        .unwrap()
+        .unwrap();
+
+    // gpt-5.1-codex-max is priced as gpt-5
+    let expected = (stats.tokens_in as f64 * 1.25
+        + stats.tokens_cache_read as f64 * 0.125
+        + stats.tokens_out as f64 * 10.0)
+        / 1_000_000.0;
+    assert!(expected > 0.0);
+    assert!((stats.cost.usd - expected).abs() < 1e-12);
+    assert!((projects[0].cost.usd - expected).abs() < 1e-12);
+    assert_eq!(projects[0].cost.unpriced_tokens, 0);
+
+    let session = &db
+        .list_sessions(&aiobscura_core::SessionFilter::default())
+        .unwrap()[0];
+    let thread = &db.get_session_threads(&session.id).unwrap()[0];
+    let thread_cost = ensure_thread_cost(&thread.id, db, &pricing).unwrap();
+    assert!((thread_cost.estimate.usd - expected).abs() < 1e-12);
+
+    let wrapped = generate_wrapped(
+        db,
+        WrappedPeriod::Month(2025, 11),
+        &WrappedConfig::default(),
+    )
+    .unwrap();
+    assert!((wrapped.totals.cost.usd - expected).abs() < 1e-12);
+
+    // Configured prices override the built-in ones
+    let config: aiobscura_core::Config = toml::from_str(
+        r#"
+[[analytics.pricing]]
+model = "openai:gpt-5.1-codex"
+input = 0.0
+output = 0.0
+"#,
+    )
+    .unwrap();
+    let free = PricingCatalog::from_config(&config.analytics);
+    let projects = list_projects(db, &free).unwrap();
+    assert_eq!(projects[0].cost.usd, 0.0);
+    assert_eq!(projects[0].cost.display(), "$0.00");
+
+    // A stored thread cost is recomputed when the prices change
+    let thread_cost = ensure_thread_cost(&thread.id, db, &free).unwrap();
+    assert_eq!(thread_cost.estimate.usd, 0.0);
+    assert_eq!(thread_cost.pricing, Some(free.fingerprint()));
+}
+
 // ============================================
 // Analytics Plugin Framework Tests
 // ============================================
@@ -817,11 +2896,13 @@ fn test_analytics_plugin_framework() {
         path: PathBuf::from(&session_source_path),
         file_type: aiobscura_core::types::FileType::Jsonl,
         assistant: aiobscura_core::types::Assistant::ClaudeCode,
+        agent: None,
         created_at: chrono::Utc::now(),
         modified_at: chrono::Utc::now(),
         size_bytes: std::fs::metadata(&path).unwrap().len(),
         last_parsed_at: Some(chrono::Utc::now()),
         checkpoint: result.new_checkpoint.clone(),
+        parser_version: 1,
     };
     db.upsert_source_file(&source_file).unwrap();
 
@@ -1002,12 +3083,18 @@ fn make_edit_msg(
             "new_string": "bar"
         })),
         tool_result: None,
+        tool_call_id: None,
         tokens_in: None,
         tokens_out: None,
+        tokens_cache_read: None,
+        tokens_cache_write: None,
+        tokens_reasoning: None,
         duration_ms: None,
         source_file_path: source_file_path.to_string(),
         source_offset: 0,
         source_line: None,
+        record_id: None,
+        parent_record_id: None,
         raw_data: serde_json::json!({}),
         metadata: serde_json::json!({}),
     }
@@ -3,7 +3,8 @@
 //! Generate Spotify Wrapped-style summaries of your AI assistant usage.
 
 use aiobscura_core::analytics::{
-    generate_wrapped, TimePatterns, TrendComparison, WrappedConfig, WrappedPeriod, WrappedStats,
+    generate_wrapped, PricingCatalog, TimePatterns, TrendComparison, WrappedConfig, WrappedPeriod,
+    WrappedStats,
 };
 use aiobscura_core::{Config, Database};
 use anyhow::{Context, Result};
@@ -69,6 +70,7 @@ fn main() -> Result<()> {
     let wrapped_config = WrappedConfig {
         fun_mode: !args.serious,
         include_trends: !args.no_trends,
+        pricing: PricingCatalog::from_config(&config.analytics),
         ..Default::default()
     };
 
@@ -132,6 +134,7 @@ fn print_terminal(stats: &WrappedStats, fun_mode: bool) {
         "   Agents:   {:<12} Files: {}",
         stats.totals.agents_spawned, stats.totals.files_modified
     );
+    println!("   Cost:     {}", stats.totals.cost.display());
     println!();
 
     // Top Tools
@@ -300,6 +303,7 @@ fn print_markdown(stats: &WrappedStats, fun_mode: bool) -> Result<()> {
     println!("| Sessions | {} |", stats.totals.sessions);
     println!("| Total Time | {} |", stats.totals.duration_display());
     println!("| Tokens | {} |", stats.totals.tokens_display());
+    println!("| Estimated Cost | {} |", stats.totals.cost.display());
     println!("| Tool Calls | {} |", stats.totals.tool_calls);
     println!("| Agents Spawned | {} |", stats.totals.agents_spawned);
     println!("| Files Modified | {} |", stats.totals.files_modified);
@@ -425,6 +429,11 @@ fn print_json(stats: &WrappedStats) -> Result<()> {
             "duration_secs": stats.totals.total_duration_secs,
             "tokens_in": stats.totals.tokens_in,
             "tokens_out": stats.totals.tokens_out,
+            "tokens_cache_read": stats.totals.tokens_cache_read,
+            "tokens_cache_write": stats.totals.tokens_cache_write,
+            "tokens_reasoning": stats.totals.tokens_reasoning,
+            "cost_usd": stats.totals.cost.usd,
+            "unpriced_tokens": stats.totals.cost.unpriced_tokens,
             "tool_calls": stats.totals.tool_calls,
             "plans": stats.totals.plans,
             "agents_spawned": stats.totals.agents_spawned,
@@ -21,6 +21,14 @@ struct Args {
     #[arg(long)]
     workflow: Option<String>,
 
+    /// Filter sessions by the label of the data root they were read from
+    #[arg(long)]
+    root: Option<String>,
+
+    /// Filter sessions by the host label of the data root they were read from
+    #[arg(long)]
+    host: Option<String>,
+
     /// Show only specific plugin results
     #[arg(short, long)]
     plugin: Option<String>,
@@ -68,13 +76,18 @@ fn main() -> Result<()> {
     }
 
     // Find sessions to analyze
+    let filter = SessionFilter {
+        source_root: args.root.clone(),
+        source_host: args.host.clone(),
+        ..Default::default()
+    };
     let sessions = if let Some(ref session_id) = args.session {
         // Try exact match first
         if let Some(session) = db.get_session(session_id)? {
             vec![session]
         } else {
             // Try partial match
-            let all_sessions = db.list_sessions(&SessionFilter::default())?;
+            let all_sessions = db.list_sessions(&filter)?;
             let matches: Vec<_> = all_sessions
                 .into_iter()
                 .filter(|s| s.id.contains(session_id))
@@ -87,7 +100,7 @@ fn main() -> Result<()> {
         }
     } else {
         // Analyze all sessions
-        db.list_sessions(&SessionFilter::default())?
+        db.list_sessions(&filter)?
     };
 
     let sessions: Vec<_> = if let Some(ref workflow_tag) = args.workflow {
@@ -102,6 +115,8 @@ fn main() -> Result<()> {
     if sessions.is_empty() {
         if let Some(workflow_tag) = &args.workflow {
             println!("No sessions found with workflow tag '{}'.", workflow_tag);
+        } else if args.root.is_some() || args.host.is_some() {
+            println!("No sessions found from the given root/host.");
         } else {
             println!("No sessions found in database.");
             println!("Run 'aiobscura-sync' first to sync AI assistant logs.");
@@ -1,5 +1,6 @@
 //! Application state for the TUI.
 
+mod image;
 mod live;
 mod plan;
 mod project;
@@ -8,12 +9,14 @@ mod wrapped;
 use std::collections::HashMap;
 
 use aiobscura_core::analytics::{
-    generate_wrapped, DashboardStats, FirstOrderSessionMetrics, ProjectRow, ProjectStats,
-    SessionAnalytics, ThreadAnalytics, WrappedConfig, WrappedPeriod, WrappedStats,
+    generate_wrapped, CostEstimate, DashboardStats, FirstOrderSessionMetrics, PricingCatalog,
+    ProjectRow, ProjectStats, SessionAnalytics, ThreadAnalytics, WrappedConfig, WrappedPeriod,
+    WrappedStats,
 };
 use aiobscura_core::db::{EnvironmentHealth, ThreadMetadata};
 use aiobscura_core::{
     ActiveSession, Database, LiveStats, Message, MessageWithContext, Plan, Thread, ThreadType,
+    ToolCallOutcome,
 };
 use anyhow::Result;
 use chrono::Datelike;
@@ -95,6 +98,70 @@ mod tests {
 
         assert_eq!(app.project_table_state.selected(), Some(1));
     }
+
+    #[test]
+    fn read_only_app_shows_analytics_without_computing_them() {
+        use aiobscura_core::{Assistant, Checkpoint, FileType, Session, SessionStatus, SourceFile};
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("test.db");
+        let db = Database::open(&path).expect("open db");
+        db.migrate().expect("migrate");
+        let now = chrono::Utc::now();
+        db.upsert_source_file(&SourceFile {
+            path: PathBuf::from("/tmp/session.jsonl"),
+            file_type: FileType::Jsonl,
+            assistant: Assistant::ClaudeCode,
+            agent: None,
+            created_at: now,
+            modified_at: now,
+            size_bytes: 0,
+            last_parsed_at: None,
+            checkpoint: Checkpoint::None,
+            parser_version: 1,
+        })
+        .expect("insert source file");
+        db.upsert_session(&Session {
+            id: "s1".to_string(),
+            assistant: Assistant::ClaudeCode,
+            backing_model_id: None,
+            project_id: None,
+            started_at: now,
+            last_activity_at: Some(now),
+            status: SessionStatus::Active,
+            source_file_path: "/tmp/session.jsonl".to_string(),
+            metadata: serde_json::json!({}),
+        })
+        .expect("insert session");
+        db.insert_thread(&Thread {
+            id: "s1-main".to_string(),
+            session_id: "s1".to_string(),
+            thread_type: ThreadType::Main,
+            parent_thread_id: None,
+            spawned_by_message_id: None,
+            started_at: now,
+            ended_at: None,
+            last_activity_at: Some(now),
+            metadata: serde_json::json!({}),
+        })
+        .expect("insert thread");
+
+        let read_only = Database::open_read_only(&path).expect("open read-only");
+        let mut app = App::new(read_only).with_read_only(true);
+        app.load_session_analytics("s1");
+        app.load_first_order_metrics("s1");
+        app.load_thread_analytics("s1-main");
+
+        assert_eq!(app.session_analytics_error, None);
+        assert_eq!(app.session_first_order_error, None);
+        assert_eq!(app.thread_analytics_error, None);
+        assert!(app.session_analytics.is_none());
+        assert!(app.thread_cost.is_none());
+        assert!(db
+            .get_session_plugin_metrics("s1")
+            .expect("plugin metrics")
+            .is_empty());
+    }
 }
 
 /// Current view mode
@@ -182,6 +249,21 @@ pub struct App {
     pub thread_analytics: Option<ThreadAnalytics>,
     /// Error message if thread analytics computation failed
     pub thread_analytics_error: Option<String>,
+    /// Estimated cost of current thread (detail view)
+    pub thread_cost: Option<CostEstimate>,
+    /// Outcomes of the current thread's tool calls by call message ID (detail view)
+    pub tool_outcomes: HashMap<i64, ToolCallOutcome>,
+    /// Index into `messages` of the selected image message (detail view)
+    pub selected_image: Option<usize>,
+    /// Scroll to the selected image on the next render
+    pub scroll_to_selected_image: bool,
+    /// Result of the last image action, shown in the footer (detail view)
+    pub image_status: Option<String>,
+    /// Model prices for cost estimates
+    pricing: PricingCatalog,
+    /// Another process owns writes: show cached analytics instead of
+    /// computing and storing them
+    read_only: bool,
 
     /// Wrapped stats for the wrapped view
     pub wrapped_stats: Option<WrappedStats>,
@@ -213,6 +295,10 @@ pub struct App {
     pub project_sessions: Vec<SessionRow>,
     /// Project sessions table selection state
     pub project_sessions_table_state: TableState,
+    /// Data roots (`label@host`) of the current project's sessions
+    pub project_session_sources: Vec<String>,
+    /// Only show sessions from this data root
+    pub project_session_source_filter: Option<String>,
     /// Plans for current project
     pub project_plans: Vec<Plan>,
     /// Project plans table selection state
@@ -281,6 +367,13 @@ impl App {
             session_first_order_error: None,
             thread_analytics: None,
             thread_analytics_error: None,
+            thread_cost: None,
+            tool_outcomes: HashMap::new(),
+            selected_image: None,
+            scroll_to_selected_image: false,
+            image_status: None,
+            pricing: PricingCatalog::builtin(),
+            read_only: false,
 
             wrapped_stats: None,
             wrapped_period: WrappedPeriod::current_year(),
@@ -297,6 +390,8 @@ impl App {
             // Project sub-tab state
             project_sessions: Vec::new(),
             project_sessions_table_state: TableState::default(),
+            project_session_sources: Vec::new(),
+            project_session_source_filter: None,
             project_plans: Vec::new(),
             project_plans_table_state: TableState::default(),
             project_files: Vec::new(),
@@ -322,6 +417,18 @@ impl App {
         }
     }
 
+    /// Use `pricing` for cost estimates instead of the built-in prices.
+    pub fn with_pricing(mut self, pricing: PricingCatalog) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// Show only the analytics already stored, for a read-only database.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
     /// Load environment health stats from the database.
     fn load_environment_health(&mut self) -> Result<()> {
         self.environment_health = self.db.get_environment_health()?;
@@ -337,7 +444,7 @@ impl App {
         self.live_stats_24h = self.db.get_live_stats(24 * 60)?;
         // Load dashboard stats and projects for the dashboard panel.
         self.dashboard_stats = Some(self.db.get_dashboard_stats()?);
-        self.projects = self.db.list_projects_with_stats()?;
+        self.projects = aiobscura_core::analytics::list_projects(&self.db, &self.pricing)?;
         // Load environment health.
         self.load_environment_health()?;
         Ok(())
@@ -480,7 +587,7 @@ impl App {
             }
 
             // Sort main threads by last activity (most recent first)
-            main_threads.sort_by(|a, b| b.thread.started_at.cmp(&a.thread.started_at));
+            main_threads.sort_by_key(|t| std::cmp::Reverse(t.thread.started_at));
 
             // Add main threads with their children
             for main_info in main_threads {
@@ -511,7 +618,7 @@ impl App {
                     let mut children: Vec<&ThreadInfo> =
                         child_indices.iter().map(|&idx| &all_threads[idx]).collect();
                     // Sort children by started_at
-                    children.sort_by(|a, b| a.thread.started_at.cmp(&b.thread.started_at));
+                    children.sort_by_key(|t| t.thread.started_at);
 
                     let child_count = children.len();
                     for (child_idx, child_info) in children.into_iter().enumerate() {
@@ -540,7 +647,7 @@ impl App {
             }
 
             // Add orphan agents at the end of this project group
-            orphan_agents.sort_by(|a, b| b.thread.started_at.cmp(&a.thread.started_at));
+            orphan_agents.sort_by_key(|t| std::cmp::Reverse(t.thread.started_at));
             for orphan_info in orphan_agents {
                 let message_count = orphan_info.message_count;
                 let last_activity = orphan_info
@@ -654,6 +761,15 @@ impl App {
             KeyCode::PageUp | KeyCode::Char('u') => {
                 self.scroll_up_page();
             }
+            KeyCode::Char('i') => {
+                self.select_next_image();
+            }
+            KeyCode::Char('o') => {
+                self.open_selected_image();
+            }
+            KeyCode::Char('s') => {
+                self.save_selected_image();
+            }
             _ => {}
         }
     }
@@ -671,6 +787,7 @@ impl App {
                     Ok(messages) => {
                         self.messages = messages;
                         self.scroll_offset = 0;
+                        self.clear_image_selection();
 
                         // Load metadata for the header
                         self.thread_metadata = self.load_thread_metadata(&thread_id);
@@ -706,9 +823,14 @@ impl App {
 
     /// Load or compute session analytics for the detail view.
     fn load_session_analytics(&mut self, session_id: &str) {
-        match aiobscura_core::analytics::ensure_session_analytics(session_id, &self.db) {
+        let analytics = if self.read_only {
+            self.db.get_session_analytics(session_id)
+        } else {
+            aiobscura_core::analytics::ensure_session_analytics(session_id, &self.db).map(Some)
+        };
+        match analytics {
             Ok(analytics) => {
-                self.session_analytics = Some(analytics);
+                self.session_analytics = analytics;
                 self.session_analytics_error = None;
             }
             Err(e) => {
@@ -721,9 +843,14 @@ impl App {
 
     /// Load or compute first-order session metrics for the detail view.
     fn load_first_order_metrics(&mut self, session_id: &str) {
-        match aiobscura_core::analytics::ensure_first_order_metrics(session_id, &self.db) {
+        let metrics = if self.read_only {
+            self.db.get_session_first_order_metrics(session_id)
+        } else {
+            aiobscura_core::analytics::ensure_first_order_metrics(session_id, &self.db).map(Some)
+        };
+        match metrics {
             Ok(metrics) => {
-                self.session_first_order_metrics = Some(metrics);
+                self.session_first_order_metrics = metrics;
                 self.session_first_order_error = None;
             }
             Err(e) => {
@@ -736,9 +863,14 @@ impl App {
 
     /// Load or compute thread analytics for the detail view.
     fn load_thread_analytics(&mut self, thread_id: &str) {
-        match aiobscura_core::analytics::ensure_thread_analytics(thread_id, &self.db) {
+        let analytics = if self.read_only {
+            self.db.get_thread_analytics(thread_id)
+        } else {
+            aiobscura_core::analytics::ensure_thread_analytics(thread_id, &self.db).map(Some)
+        };
+        match analytics {
             Ok(analytics) => {
-                self.thread_analytics = Some(analytics);
+                self.thread_analytics = analytics;
                 self.thread_analytics_error = None;
             }
             Err(e) => {
@@ -747,6 +879,33 @@ impl App {
                 tracing::warn!(thread_id, error = %e, "Failed to compute thread analytics");
             }
         }
+
+        let cost = if self.read_only {
+            self.db.get_thread_cost_metrics(thread_id)
+        } else {
+            aiobscura_core::analytics::ensure_thread_cost(thread_id, &self.db, &self.pricing)
+                .map(Some)
+        };
+        match cost {
+            Ok(cost) => self.thread_cost = cost.map(|cost| cost.estimate),
+            Err(e) => {
+                self.thread_cost = None;
+                tracing::warn!(thread_id, error = %e, "Failed to compute thread cost");
+            }
+        }
+
+        match self.db.get_thread_tool_calls(thread_id) {
+            Ok(outcomes) => {
+                self.tool_outcomes = outcomes
+                    .into_iter()
+                    .map(|outcome| (outcome.call_message_id, outcome))
+                    .collect();
+            }
+            Err(e) => {
+                self.tool_outcomes.clear();
+                tracing::warn!(thread_id, error = %e, "Failed to load tool call outcomes");
+            }
+        }
     }
 
     /// Close detail view and return to list.
@@ -770,6 +929,9 @@ impl App {
         self.session_first_order_error = None;
         self.thread_analytics = None;
         self.thread_analytics_error = None;
+        self.thread_cost = None;
+        self.tool_outcomes.clear();
+        self.clear_image_selection();
     }
 
     /// Scroll down in detail view.
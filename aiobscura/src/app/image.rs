@@ -0,0 +1,121 @@
+use super::*;
+
+use std::path::{Path, PathBuf};
+
+use aiobscura_core::BlobRef;
+
+impl App {
+    // ========== Image Methods (thread detail view) ==========
+
+    /// Select the next image message in the thread, wrapping around, and
+    /// scroll to it.
+    pub(super) fn select_next_image(&mut self) {
+        let images: Vec<usize> = self
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, msg)| msg.image().is_some())
+            .map(|(idx, _)| idx)
+            .collect();
+        if images.is_empty() {
+            self.image_status = Some("No stored images in this thread".to_string());
+            return;
+        }
+
+        let next = match self.selected_image {
+            Some(current) => images
+                .iter()
+                .copied()
+                .find(|&idx| idx > current)
+                .unwrap_or(images[0]),
+            None => images[0],
+        };
+        self.selected_image = Some(next);
+        self.scroll_to_selected_image = true;
+        let position = images.iter().position(|&idx| idx == next).unwrap_or(0) + 1;
+        self.image_status = Some(format!("Image {}/{}", position, images.len()));
+    }
+
+    /// Open the selected image with the system viewer.
+    pub(super) fn open_selected_image(&mut self) {
+        let Some((image, bytes)) = self.selected_image_bytes() else {
+            return;
+        };
+        let path = std::env::temp_dir().join(image_file_name(&image));
+        let opened = std::fs::write(&path, bytes).and_then(|_| open_with_system_viewer(&path));
+        self.image_status = Some(match opened {
+            Ok(()) => format!("Opened {}", path.display()),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Failed to open image");
+                format!("Could not open image: {}", e)
+            }
+        });
+    }
+
+    /// Save the selected image to the current directory.
+    pub(super) fn save_selected_image(&mut self) {
+        let Some((image, bytes)) = self.selected_image_bytes() else {
+            return;
+        };
+        let path = PathBuf::from(image_file_name(&image));
+        self.image_status = Some(match std::fs::write(&path, bytes) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Could not save image: {}", e),
+        });
+    }
+
+    /// The selected image and its bytes, selecting the first image if none
+    /// is selected yet. Reports why in the status line when unavailable.
+    fn selected_image_bytes(&mut self) -> Option<(BlobRef, Vec<u8>)> {
+        if self.selected_image.is_none() {
+            self.select_next_image();
+        }
+        let image = self
+            .selected_image
+            .and_then(|idx| self.messages.get(idx))
+            .and_then(|msg| msg.image())?;
+
+        match self.db.get_blob(&image.sha256) {
+            Ok(Some(bytes)) => Some((image, bytes)),
+            Ok(None) => {
+                self.image_status = Some("Image is missing from the blob store".to_string());
+                None
+            }
+            Err(e) => {
+                self.image_status = Some(format!("Could not read image: {}", e));
+                None
+            }
+        }
+    }
+
+    /// Reset image selection when leaving the thread.
+    pub(super) fn clear_image_selection(&mut self) {
+        self.selected_image = None;
+        self.scroll_to_selected_image = false;
+        self.image_status = None;
+    }
+}
+
+/// File name for an exported image: `image-<short hash>.<ext>`.
+fn image_file_name(image: &BlobRef) -> String {
+    format!("image-{}.{}", &image.sha256[..12], image.extension())
+}
+
+fn open_with_system_viewer(path: &Path) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    command
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
@@ -77,7 +77,7 @@ impl App {
     /// Open project detail view by ID and name (for quick navigation).
     pub(super) fn open_project_detail_by_id(&mut self, project_id: &str, project_name: &str) {
         // Load project stats
-        match self.db.get_project_stats(project_id) {
+        match aiobscura_core::analytics::project_stats(&self.db, project_id, &self.pricing) {
             Ok(Some(stats)) => self.project_stats = Some(stats),
             Ok(None) => self.project_stats = None,
             Err(e) => {
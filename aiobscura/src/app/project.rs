@@ -77,6 +77,10 @@ impl App {
             KeyCode::Enter => {
                 self.open_project_item();
             }
+            // Cycle the sessions' source filter
+            KeyCode::Char('s') => {
+                self.cycle_project_session_source();
+            }
             _ => {}
         }
     }
@@ -147,9 +151,11 @@ impl App {
                 let project_name = project.name.clone();
 
                 // Load project stats
-                match self.db.get_project_stats(&project_id) {
+                match aiobscura_core::analytics::project_stats(&self.db, &project_id, &self.pricing)
+                {
                     Ok(Some(stats)) => {
                         self.project_stats = Some(stats);
+                        self.project_session_source_filter = None;
                         self.view_mode = ViewMode::ProjectDetail {
                             project_id,
                             project_name,
@@ -226,7 +232,7 @@ impl App {
 
     /// Load projects from the database (for initial startup).
     pub fn load_projects(&mut self) -> Result<()> {
-        self.projects = self.db.list_projects_with_stats()?;
+        self.projects = aiobscura_core::analytics::list_projects(&self.db, &self.pricing)?;
         self.project_table_state = TableState::default();
         if !self.projects.is_empty() {
             self.project_table_state.select(Some(0));
@@ -287,7 +293,7 @@ impl App {
         match &self.view_mode {
             ViewMode::ProjectList => {
                 let selected = self.project_table_state.selected();
-                self.projects = self.db.list_projects_with_stats()?;
+                self.projects = aiobscura_core::analytics::list_projects(&self.db, &self.pricing)?;
                 // Restore selection if valid
                 if let Some(idx) = selected {
                     if idx < self.projects.len() {
@@ -356,6 +362,7 @@ impl App {
     pub(super) fn load_project_sessions(&mut self, project_id: &str) -> Result<()> {
         let summaries = self.db.list_project_sessions(project_id)?;
         self.project_sessions.clear();
+        self.project_session_sources.clear();
 
         for summary in summaries {
             // Calculate duration from started_at to last_activity_at
@@ -365,6 +372,16 @@ impl App {
                 .unwrap_or(0)
                 .max(0);
 
+            let source = match (summary.source_root, summary.source_host) {
+                (Some(root), Some(host)) => Some(format!("{}@{}", root, host)),
+                (root, _) => root,
+            };
+            if let Some(source) = &source {
+                if !self.project_session_sources.contains(source) {
+                    self.project_session_sources.push(source.clone());
+                }
+            }
+
             self.project_sessions.push(SessionRow {
                 id: summary.id,
                 last_activity: summary.last_activity_at,
@@ -372,9 +389,20 @@ impl App {
                 thread_count: summary.thread_count,
                 message_count: summary.message_count,
                 model_name: summary.model_name,
+                source,
             });
         }
 
+        // Apply the source filter, dropping it if no session matches any more
+        self.project_session_sources.sort();
+        match &self.project_session_source_filter {
+            Some(filter) if self.project_session_sources.contains(filter) => {
+                self.project_sessions
+                    .retain(|session| session.source.as_ref() == Some(filter));
+            }
+            _ => self.project_session_source_filter = None,
+        }
+
         // Select first if any
         self.project_sessions_table_state = TableState::default();
         if !self.project_sessions.is_empty() {
@@ -384,13 +412,48 @@ impl App {
         Ok(())
     }
 
+    /// Cycle the Sessions tab through all sessions and each data root's
+    /// sessions in turn.
+    pub(super) fn cycle_project_session_source(&mut self) {
+        let ViewMode::ProjectDetail {
+            project_id,
+            sub_tab: ProjectSubTab::Sessions,
+            ..
+        } = &self.view_mode
+        else {
+            return;
+        };
+        let project_id = project_id.clone();
+
+        let next = match &self.project_session_source_filter {
+            None => 0,
+            Some(current) => match self
+                .project_session_sources
+                .iter()
+                .position(|source| source == current)
+            {
+                Some(idx) => idx + 1,
+                None => self.project_session_sources.len(),
+            },
+        };
+        self.project_session_source_filter = self.project_session_sources.get(next).cloned();
+
+        if let Err(e) = self.load_project_sessions(&project_id) {
+            tracing::warn!(
+                project_id = %project_id,
+                error = %e,
+                "Failed to load project sessions for source filter"
+            );
+        }
+    }
+
     /// Load plans for all sessions in a project.
     pub(super) fn load_project_plans(&mut self, project_id: &str) -> Result<()> {
         self.project_plans = self.db.list_project_plans(project_id)?;
 
         // Sort by modified_at (most recent first)
         self.project_plans
-            .sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+            .sort_by_key(|p| std::cmp::Reverse(p.modified_at));
 
         // Select first if any
         self.project_plans_table_state = TableState::default();
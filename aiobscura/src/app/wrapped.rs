@@ -34,6 +34,14 @@ impl App {
         }
     }
 
+    /// Wrapped generation settings for the TUI.
+    fn wrapped_config(&self) -> WrappedConfig {
+        WrappedConfig {
+            pricing: self.pricing.clone(),
+            ..Default::default()
+        }
+    }
+
     /// Open the wrapped view.
     pub(super) fn open_wrapped_view(&mut self) {
         // Check cache first
@@ -45,7 +53,7 @@ impl App {
         }
 
         // Cache miss - generate and store
-        let config = WrappedConfig::default();
+        let config = self.wrapped_config();
         match generate_wrapped(&self.db, self.wrapped_period, &config) {
             Ok(stats) => {
                 self.wrapped_cache
@@ -125,7 +133,7 @@ impl App {
         }
 
         // Cache miss - generate and store
-        let config = WrappedConfig::default();
+        let config = self.wrapped_config();
         match generate_wrapped(&self.db, self.wrapped_period, &config) {
             Ok(stats) => {
                 self.wrapped_cache
@@ -189,7 +197,7 @@ impl App {
         }
 
         // Cache miss - generate and store
-        let config = WrappedConfig::default();
+        let config = self.wrapped_config();
         match generate_wrapped(&self.db, self.wrapped_period, &config) {
             Ok(stats) => {
                 self.wrapped_cache
@@ -8,11 +8,19 @@ mod process_lock;
 mod thread_row;
 mod ui;
 
-use std::io;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+use aiobscura_core::analytics::PricingCatalog;
+use aiobscura_core::db::SearchFilter;
+use aiobscura_core::export::{self, ExportFormat, ExportOptions};
+use aiobscura_core::hooks;
 use aiobscura_core::ingest::IngestCoordinator;
-use aiobscura_core::{Config, Database};
+use aiobscura_core::{Assistant, Config, Database, MessageType, Project};
 use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, Event},
     execute,
@@ -23,7 +31,115 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use crate::app::App;
 use crate::process_lock::{acquire_ui_guards, UiRunMode};
 
+#[derive(Parser, Debug)]
+#[command(name = "aiobscura")]
+#[command(about = "AI Agent Activity Monitor")]
+#[command(version)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Forward a Claude Code hook event (hook JSON on stdin) to aiobscura
+    Hook,
+    /// Move all sessions of one project into another and delete the first
+    MergeProjects {
+        /// Project to merge away (id or path)
+        from: String,
+        /// Project to keep (id or path)
+        into: String,
+    },
+    /// Search message content, tool inputs and tool results
+    Search {
+        /// Words every hit must contain (`word*` matches by prefix)
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Only search this project (id or path)
+        #[arg(long)]
+        project: Option<String>,
+        /// Only search this assistant (claude_code, codex, aider, cursor, gemini, custom)
+        #[arg(long)]
+        assistant: Option<Assistant>,
+        /// Only search this message type (prompt, response, tool_call, tool_result, ...)
+        #[arg(long = "type")]
+        message_type: Option<MessageType>,
+        /// Maximum number of hits
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+    },
+    /// Export projects, sessions, threads, messages, plugin metrics and
+    /// assessments as Parquet or CSV (schema: docs/export.md)
+    Export {
+        /// Directory to write the dataset to
+        dir: PathBuf,
+        /// File format (parquet, csv)
+        #[arg(long, default_value = "parquet")]
+        format: ExportFormat,
+        /// Only export this project (id or path)
+        #[arg(long)]
+        project: Option<String>,
+        /// Only export sessions active, and messages emitted, on or after
+        /// this date (YYYY-MM-DD, UTC)
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Leave out message text, tool inputs and results, and assessor
+        /// responses
+        #[arg(long)]
+        no_content: bool,
+        /// Append what is new since the export already in DIR
+        #[arg(long)]
+        incremental: bool,
+    },
+}
+
 fn main() -> Result<()> {
+    let args = Args::parse();
+    match args.command {
+        Some(Command::Hook) => return run_hook(),
+        Some(Command::MergeProjects { from, into }) => return run_merge_projects(&from, &into),
+        Some(Command::Search {
+            query,
+            project,
+            assistant,
+            message_type,
+            limit,
+        }) => {
+            return run_search(
+                &query.join(" "),
+                project.as_deref(),
+                SearchFilter {
+                    assistant,
+                    message_type,
+                    limit: Some(limit),
+                    ..Default::default()
+                },
+            )
+        }
+        Some(Command::Export {
+            dir,
+            format,
+            project,
+            since,
+            no_content,
+            incremental,
+        }) => {
+            return run_export(
+                &dir,
+                project.as_deref(),
+                ExportOptions {
+                    format,
+                    since: since.map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc()),
+                    content: !no_content,
+                    incremental,
+                    ..Default::default()
+                },
+            )
+        }
+        None => {}
+    }
+
     // Load configuration
     let config = Config::load().context("failed to load configuration")?;
 
@@ -44,8 +160,14 @@ fn main() -> Result<()> {
     // Open database
     tracing::info!(path = %db_path.display(), "Opening database");
 
-    let db = Database::open(&db_path).context("failed to open database")?;
-    db.migrate().context("failed to run database migrations")?;
+    // The ingest owner has migrated the schema; a read-only TUI never writes
+    let db = if process_guards.mode == UiRunMode::ReadOnly {
+        Database::open_read_only(&db_path).context("failed to open database read-only")?
+    } else {
+        let db = Database::open(&db_path).context("failed to open database")?;
+        db.migrate().context("failed to run database migrations")?;
+        db
+    };
 
     // Create a dedicated sync coordinator only when this process owns ingest.
     let sync_coordinator = if process_guards.mode == UiRunMode::OwnsIngest {
@@ -53,7 +175,7 @@ fn main() -> Result<()> {
         sync_db
             .migrate()
             .context("failed to run sync database migrations")?;
-        let coordinator = IngestCoordinator::new(sync_db);
+        let coordinator = IngestCoordinator::with_config(sync_db, &config);
 
         // Prime the database once at startup so Live view starts from current logs.
         if let Ok(result) = coordinator.sync_all() {
@@ -69,8 +191,18 @@ fn main() -> Result<()> {
         None
     };
 
+    // Accept hook events while this process owns ingest; stops when dropped
+    #[cfg(unix)]
+    let _hook_listener = if sync_coordinator.is_some() {
+        start_hook_listener(&db_path)
+    } else {
+        None
+    };
+
     // Create app and start in Live view (default tab)
-    let mut app = App::new(db);
+    let mut app = App::new(db)
+        .with_pricing(PricingCatalog::from_config(&config.analytics))
+        .with_read_only(process_guards.mode == UiRunMode::ReadOnly);
     app.start_live_view()
         .context("failed to load live messages")?;
 
@@ -82,7 +214,9 @@ fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
 
     // Run the main loop
-    let result = run_app(&mut terminal, &mut app, sync_coordinator.as_ref());
+    let live_sync = sync_coordinator.map(LiveSync::start);
+    let result = run_app(&mut terminal, &mut app, live_sync.as_ref());
+    drop(live_sync);
 
     // Restore terminal
     disable_raw_mode().context("failed to disable raw mode")?;
@@ -95,11 +229,207 @@ fn main() -> Result<()> {
     result
 }
 
+/// Forward the hook event on stdin, to the running sync or TUI process if
+/// there is one, otherwise to the spool file.
+///
+/// Prints nothing on stdout: Claude Code reads some hooks' stdout as
+/// instructions.
+fn run_hook() -> Result<()> {
+    Config::ensure_xdg_env();
+
+    let mut input = Vec::new();
+    io::stdin()
+        .read_to_end(&mut input)
+        .context("failed to read hook input")?;
+    hooks::forward(
+        &input,
+        &Config::hook_socket_path(),
+        &Config::hook_spool_path(),
+    )
+    .context("failed to forward hook event")?;
+    Ok(())
+}
+
+/// Merge project `from` into project `into`.
+fn run_merge_projects(from: &str, into: &str) -> Result<()> {
+    Config::ensure_xdg_env();
+
+    let db = Database::open(&Config::database_path()).context("failed to open database")?;
+    db.migrate().context("failed to run database migrations")?;
+
+    let from = find_project(&db, from)?;
+    let into = find_project(&db, into)?;
+    if from.id == into.id {
+        anyhow::bail!("cannot merge project {} into itself", from.id);
+    }
+
+    let moved = db
+        .merge_projects(&from.id, &into.id)
+        .context("failed to merge projects")?;
+    println!(
+        "Merged {} into {}: {} session(s) moved",
+        from.path.display(),
+        into.path.display(),
+        moved
+    );
+    println!(
+        "To file future sessions of {} under {} as well, add to [projects.merge] in {}:",
+        from.id,
+        into.id,
+        Config::config_path().display()
+    );
+    println!("  \"{}\" = \"{}\"", from.id, into.id);
+    Ok(())
+}
+
+/// Print the messages matching `query`, most relevant first.
+fn run_search(query: &str, project: Option<&str>, mut filter: SearchFilter) -> Result<()> {
+    Config::ensure_xdg_env();
+
+    let db = Database::open(&Config::database_path()).context("failed to open database")?;
+    db.migrate().context("failed to run database migrations")?;
+
+    if let Some(project) = project {
+        filter.project_id = Some(find_project(&db, project)?.id);
+    }
+
+    let hits = db
+        .search_messages(query, &filter)
+        .context("failed to search messages")?;
+    if hits.is_empty() {
+        println!("No messages match {:?}", query);
+        return Ok(());
+    }
+
+    for hit in &hits {
+        let kind = match &hit.tool_name {
+            Some(tool) => format!("{} {}", hit.message_type.as_str(), tool),
+            None => hit.message_type.as_str().to_string(),
+        };
+        println!(
+            "{}  {}  {}  session {} ({} thread)  {}",
+            hit.emitted_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            hit.project_name.as_deref().unwrap_or("(no project)"),
+            hit.assistant.display_name(),
+            hit.session_id,
+            hit.thread_type.as_str(),
+            kind
+        );
+        println!(
+            "    {}",
+            hit.snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+        );
+    }
+    Ok(())
+}
+
+/// Export the dataset to `dir`.
+fn run_export(dir: &Path, project: Option<&str>, mut options: ExportOptions) -> Result<()> {
+    Config::ensure_xdg_env();
+
+    let db = Database::open(&Config::database_path()).context("failed to open database")?;
+    db.migrate().context("failed to run database migrations")?;
+
+    if let Some(project) = project {
+        options.project_id = Some(find_project(&db, project)?.id);
+    }
+
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let part = export::export(&db, dir, &options).context("failed to export dataset")?;
+
+    println!(
+        "Exported part {} ({}) to {}",
+        part.part,
+        options.format,
+        dir.display()
+    );
+    for (table, rows) in &part.rows {
+        println!("  {:<16}{:>8} row(s)", table, rows);
+    }
+    if !part.reparsed_sessions.is_empty() {
+        println!(
+            "  {} reparsed session(s) replace their rows in earlier parts (see manifest.json)",
+            part.reparsed_sessions.len()
+        );
+    }
+    Ok(())
+}
+
+/// Look a project up by id, falling back to its path.
+fn find_project(db: &Database, id_or_path: &str) -> Result<Project> {
+    let project = match db.get_project(id_or_path)? {
+        Some(project) => Some(project),
+        None => db.get_project_by_path(std::path::Path::new(id_or_path))?,
+    };
+    project.with_context(|| format!("no project with id or path {:?}", id_or_path))
+}
+
+#[cfg(unix)]
+fn start_hook_listener(db_path: &std::path::PathBuf) -> Option<hooks::HookListener> {
+    let listener = Database::open(db_path).and_then(|db| {
+        hooks::HookListener::start(&Config::hook_socket_path(), &Config::hook_spool_path(), db)
+    });
+    match listener {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to start hook listener");
+            None
+        }
+    }
+}
+
+/// Syncs every second on a background thread while the Live view is open,
+/// so a slow parse never stalls the UI. Dropping it stops the thread.
+struct LiveSync {
+    active: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl LiveSync {
+    fn start(coordinator: IngestCoordinator) -> Self {
+        let active = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_active, thread_stop) = (Arc::clone(&active), Arc::clone(&stop));
+        let handle = std::thread::spawn(move || {
+            while !thread_stop.load(Ordering::SeqCst) {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                if thread_active.swap(false, Ordering::SeqCst) {
+                    if let Err(e) = coordinator.sync_all() {
+                        tracing::warn!(error = %e, "Live sync iteration failed");
+                    }
+                }
+            }
+        });
+        Self {
+            active,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Ask for a sync. Requests made before it starts are merged into one.
+    fn request(&self) {
+        self.active.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Drop for LiveSync {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
 /// Run the main application loop.
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
-    sync_coordinator: Option<&IngestCoordinator>,
+    live_sync: Option<&LiveSync>,
 ) -> Result<()> {
     // Poll counter for DB change detection (every 10 ticks = ~1 second)
     let mut poll_counter = 0u32;
@@ -113,10 +443,8 @@ fn run_app(
             // In Live view, ingest fresh log data so the dashboard updates
             // even when aiobscura-sync is not running in parallel.
             if app.is_live_view() {
-                if let Some(sync_coordinator) = sync_coordinator {
-                    if let Err(e) = sync_coordinator.sync_all() {
-                        tracing::warn!(error = %e, "Live sync iteration failed");
-                    }
+                if let Some(live_sync) = live_sync {
+                    live_sync.request();
                 }
             }
 
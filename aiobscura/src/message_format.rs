@@ -1,5 +1,6 @@
 //! Shared message formatting helpers for TUI rendering.
 
+use aiobscura_core::format::format_bytes;
 use aiobscura_core::{AuthorRole, Message, MessageType, MessageWithContext};
 use ratatui::style::{Color, Style};
 
@@ -49,6 +50,15 @@ pub fn detail_content(msg: &Message) -> String {
         }
     }
 
+    if let Some(image) = msg.image() {
+        return format!(
+            "[{} · {} · {}]",
+            image.media_type,
+            format_bytes(image.size),
+            &image.sha256[..12]
+        );
+    }
+
     msg.content.clone().unwrap_or_default()
 }
 
@@ -11,22 +11,32 @@
 mod process_lock;
 
 use aiobscura_core::collector::StatefulSyncPublisher;
-use aiobscura_core::ingest::{IngestCoordinator, SyncResult};
-use aiobscura_core::{Config, Database, SessionFilter};
-use anyhow::{Context, Result};
-use clap::{ArgAction, Parser};
+use aiobscura_core::format::format_bytes;
+use aiobscura_core::ingest::{
+    import, parsers, AssistantParser, IngestCoordinator, ProjectResolver, ReparseFilter,
+    SyncResult, WatchPath,
+};
+use aiobscura_core::otlp::OtlpReceiver;
+use aiobscura_core::retention;
+use aiobscura_core::{Assistant, Config, Database, SessionFilter};
+use anyhow::{bail, Context, Result};
+use clap::{ArgAction, ArgGroup, Parser};
 use indicatif::{ProgressBar, ProgressStyle};
+use notify_debouncer_mini::new_debouncer;
+use notify_debouncer_mini::notify::{RecursiveMode, Watcher};
 use process_lock::acquire_sync_guard;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 #[command(name = "aiobscura-sync")]
 #[command(about = "Sync AI assistant logs to the database")]
 #[command(version)]
+#[command(group(ArgGroup::new("mode").args(["reparse", "failures", "prune", "compact"])))]
 struct Args {
     /// Verbose output (-v per-file, -vv per-message)
     #[arg(short, long, action = ArgAction::Count)]
@@ -36,13 +46,92 @@ struct Args {
     #[arg(long)]
     dry_run: bool,
 
+    /// Number of files to parse concurrently (default: `ingest.parallelism`
+    /// from config, or one per CPU)
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
     /// Watch mode - continuously sync instead of one-shot
     #[arg(short, long)]
     watch: bool,
 
-    /// Poll interval in milliseconds (only with --watch)
-    #[arg(long, default_value = "1000")]
-    poll: u64,
+    /// Debounce delay for file change events in milliseconds (only with --watch)
+    #[arg(long, alias = "poll", default_value = "250")]
+    debounce: u64,
+
+    /// Interval between full syncs in seconds (only with --watch)
+    #[arg(long, default_value = "300")]
+    reconcile: u64,
+
+    /// Accept OpenTelemetry exports on `otlp.listen` (only with --watch)
+    #[arg(long, requires = "watch")]
+    otlp: bool,
+
+    /// Re-ingest already synced files from scratch, e.g. after a parser upgrade
+    #[arg(long, conflicts_with = "watch")]
+    reparse: bool,
+
+    /// List records that failed to parse, grouped by error (-v lists recent records)
+    #[arg(long, conflicts_with = "watch")]
+    failures: bool,
+
+    /// Drop raw data older than the `[retention]` policy, then VACUUM
+    /// (with --dry-run, estimate the space freed)
+    #[arg(long, conflicts_with = "watch")]
+    prune: bool,
+
+    /// Move inline images to the blob store and compress raw data and tool
+    /// results stored uncompressed, then VACUUM
+    #[arg(long, conflicts_with_all = ["watch", "dry_run", "assistant", "agent"])]
+    compact: bool,
+
+    /// Train a compression dictionary on local data first and recompress
+    /// everything with it (only with --compact)
+    #[arg(long, requires = "compact")]
+    train_dictionary: bool,
+
+    /// Only reparse, prune or list failures from this assistant (claude_code, codex, aider, cursor, gemini, custom)
+    #[arg(long, requires = "mode")]
+    assistant: Option<Assistant>,
+
+    /// Only reparse, prune or list failures from this custom agent or parser
+    /// plugin (the `name` of its `[[agents.custom]]` or `[[agents.plugin]]`)
+    #[arg(long, value_name = "NAME", requires = "mode")]
+    agent: Option<String>,
+
+    /// Only reparse files modified on or after this date (YYYY-MM-DD, UTC)
+    #[arg(long, requires = "reparse")]
+    since: Option<chrono::NaiveDate>,
+
+    /// Only reparse files ingested by an older parser version
+    #[arg(long, requires = "reparse")]
+    outdated: bool,
+
+    /// Only reparse files with records that failed to parse
+    #[arg(long, requires = "reparse")]
+    failed: bool,
+
+    /// Import another machine's logs from a directory or a .tar.gz/.zip archive
+    #[arg(long, value_name = "ARCHIVE_OR_DIR", requires = "label", conflicts_with_all = ["watch", "mode"])]
+    import: Option<PathBuf>,
+
+    /// Host label recorded on imported sessions (only with --import)
+    #[arg(long, value_name = "HOST", requires = "import")]
+    label: Option<String>,
+}
+
+impl Args {
+    fn reparse_filter(&self) -> ReparseFilter {
+        ReparseFilter {
+            assistant: self.assistant,
+            agent: self.agent.clone(),
+            modified_since: self
+                .since
+                .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc()),
+            outdated_only: self.outdated,
+            failed_only: self.failed,
+        }
+    }
 }
 
 fn main() -> Result<()> {
@@ -72,8 +161,37 @@ fn main() -> Result<()> {
 
     println!("Database: {}", db_path.display());
 
+    if let Some(agent) = &args.agent {
+        if parsers::parser_for_agent(&config, agent).is_none() {
+            eprintln!(
+                "Warning: no custom agent or parser plugin named {:?} in {}",
+                agent,
+                Config::config_path().display()
+            );
+        }
+    }
+
+    if args.failures {
+        return print_parse_failures(&db, args.assistant, args.agent.as_deref(), args.verbose);
+    }
+
+    if args.prune {
+        return run_prune(&db, &config, &args);
+    }
+
+    if args.compact {
+        return run_compact(&db, args.train_dictionary);
+    }
+
+    if let (Some(source), Some(label)) = (&args.import, &args.label) {
+        return run_import(db, &config, &args, source, label);
+    }
+
     // Create coordinator and discover installed assistants
-    let coordinator = IngestCoordinator::new(db);
+    let mut coordinator = IngestCoordinator::with_config(db, &config);
+    if let Some(jobs) = args.jobs {
+        coordinator = coordinator.with_parallelism(jobs);
+    }
     let installed = coordinator.installed_assistants();
 
     println!("Discovered {} installed assistant(s):", installed.len());
@@ -81,8 +199,9 @@ fn main() -> Result<()> {
         match parser.discover_files() {
             Ok(files) => {
                 println!(
-                    "  - {}: {} file(s) at {}",
+                    "  - {}{}: {} file(s) at {}",
                     parser.assistant().display_name(),
+                    root_label(*parser),
                     files.len(),
                     parser
                         .root_path()
@@ -92,8 +211,9 @@ fn main() -> Result<()> {
             }
             Err(e) => {
                 println!(
-                    "  - {}: error discovering files: {}",
+                    "  - {}{}: error discovering files: {}",
                     parser.assistant().display_name(),
+                    root_label(*parser),
                     e
                 );
             }
@@ -101,6 +221,17 @@ fn main() -> Result<()> {
     }
 
     if args.dry_run {
+        if args.reparse {
+            let files = coordinator
+                .reparse_files(&args.reparse_filter())
+                .context("failed to select files to reparse")?;
+            println!("\nWould reparse {} file(s)", files.len());
+            if args.verbose >= 1 {
+                for path in &files {
+                    println!("  {}", shorten_path(path));
+                }
+            }
+        }
         println!("\nDry run - no sync performed");
         tracing::info!("Dry run complete");
         return Ok(());
@@ -139,8 +270,44 @@ fn main() -> Result<()> {
         }
     }
 
-    let result = if args.watch {
-        // Watch mode - continuous polling
+    // Start the OTLP receiver alongside watch mode; it stops when dropped
+    let _otlp_receiver = if args.watch && (args.otlp || config.otlp.enabled) {
+        let otlp_db = Database::open(&db_path).context("failed to open telemetry database")?;
+        let receiver = OtlpReceiver::start(&config.otlp.listen, otlp_db)
+            .context("failed to start OTLP receiver")?;
+        println!("OTLP receiver: http://{}", receiver.local_addr());
+        Some(receiver)
+    } else {
+        None
+    };
+
+    // Accept hook events in watch mode; spooled ones are drained by each full sync
+    #[cfg(unix)]
+    let _hook_listener = if args.watch {
+        let hook_db = Database::open(&db_path).context("failed to open hook database")?;
+        match aiobscura_core::hooks::HookListener::start(
+            &Config::hook_socket_path(),
+            &Config::hook_spool_path(),
+            hook_db,
+        ) {
+            Ok(listener) => {
+                println!("Hook socket: {}", listener.path().display());
+                Some(listener)
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to start hook listener");
+                eprintln!("Hook listener not started: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let result = if args.reparse {
+        run_reparse(&coordinator, &config, &args, &mut publisher)
+    } else if args.watch {
+        // Watch mode - sync on file system events
         run_watch_mode(&coordinator, &config, &args, &mut publisher)
     } else {
         // One-shot sync
@@ -229,7 +396,272 @@ fn run_single_sync(
     Ok(())
 }
 
+/// Re-ingest previously synced files with a progress bar
+fn run_reparse(
+    coordinator: &IngestCoordinator,
+    config: &Config,
+    args: &Args,
+    publisher: &mut Option<StatefulSyncPublisher>,
+) -> Result<()> {
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+
+    let result = coordinator
+        .reparse(&args.reparse_filter(), |current, total, path| {
+            if current == 0 {
+                pb.set_length(total as u64);
+            }
+            pb.set_position(current as u64);
+            pb.set_message(
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("...")
+                    .to_string(),
+            );
+        })
+        .context("reparse failed")?;
+
+    pb.finish_and_clear();
+
+    publish_sync_sessions(config, publisher, &result);
+
+    run_analytics_triggers(coordinator, config, &result, false)?;
+
+    print_sync_result(&result, args.verbose);
+
+    tracing::info!(
+        files_processed = result.files_processed,
+        messages_inserted = result.messages_inserted,
+        "aiobscura-sync reparse complete"
+    );
+
+    Ok(())
+}
+
+/// Import a snapshot of another machine's assistant data
+///
+/// Imported sessions are labeled with the `import` root and `label` as host.
+/// They are neither published to the collector nor mixed with the local
+/// roots' checkpoints, and the hook spool is left for the next regular sync.
+fn run_import(
+    db: Database,
+    config: &Config,
+    args: &Args,
+    source: &Path,
+    label: &str,
+) -> Result<()> {
+    let root = import::prepare_import(source, &Config::imports_dir(), label)
+        .with_context(|| format!("failed to read {}", source.display()))?;
+    let parsers = import::import_parsers(&root, label)
+        .with_context(|| format!("failed to scan {}", root.display()))?;
+    if parsers.is_empty() {
+        bail!("no assistant logs found in {}", source.display());
+    }
+
+    println!("Importing {} as {}:", source.display(), label);
+    for parser in &parsers {
+        println!(
+            "  - {}: {} file(s) at {}",
+            parser.assistant().display_name(),
+            parser.discover_files().map(|f| f.len()).unwrap_or(0),
+            parser
+                .root_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        );
+    }
+
+    if args.dry_run {
+        println!("\nDry run - no import performed");
+        return Ok(());
+    }
+
+    // Reparsing reads the snapshot again
+    db.record_import(&root, label)
+        .context("failed to record import")?;
+
+    let coordinator = IngestCoordinator::with_parsers(db, parsers)
+        .with_parallelism(args.jobs.unwrap_or(config.ingest.parallelism))
+        .with_project_resolver(ProjectResolver::from_config(&config.projects));
+
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+
+    let result = coordinator
+        .sync_all_with_progress(|current, total, path| {
+            if current == 0 {
+                pb.set_length(total as u64);
+            }
+            pb.set_position(current as u64);
+            pb.set_message(
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("...")
+                    .to_string(),
+            );
+        })
+        .context("import failed")?;
+
+    pb.finish_and_clear();
+
+    run_analytics_triggers(&coordinator, config, &result, false)?;
+
+    print_sync_result(&result, args.verbose);
+
+    tracing::info!(
+        label,
+        files_processed = result.files_processed,
+        messages_inserted = result.messages_inserted,
+        "aiobscura-sync import complete"
+    );
+
+    Ok(())
+}
+
+/// Prune raw data past its retention period and reclaim the space
+fn run_prune(db: &Database, config: &Config, args: &Args) -> Result<()> {
+    if config.retention.is_empty() {
+        bail!(
+            "no retention policy configured; set [retention] in {}",
+            Config::config_path().display()
+        );
+    }
+
+    let size_before = db
+        .get_database_size()
+        .context("failed to read database size")?;
+    let summary = retention::prune(
+        db,
+        &config.retention,
+        args.assistant,
+        args.agent.as_deref(),
+        args.dry_run,
+    )
+    .context("failed to prune raw data")?;
+
+    if args.dry_run {
+        println!(
+            "\nWould prune raw data of {} message(s) in {} session(s) from {} source file(s)",
+            summary.messages, summary.sessions, summary.source_files
+        );
+        println!(
+            "Database size: {}, about {} could be freed",
+            format_bytes(size_before),
+            format_bytes(summary.bytes.min(size_before))
+        );
+        println!("\nDry run - nothing pruned");
+        return Ok(());
+    }
+
+    println!(
+        "\nPruned raw data of {} message(s) in {} session(s) from {} source file(s)",
+        summary.messages, summary.sessions, summary.source_files
+    );
+    println!("Vacuuming database...");
+    db.vacuum().context("failed to vacuum database")?;
+    let size_after = db
+        .get_database_size()
+        .context("failed to read database size")?;
+    println!(
+        "Database size: {} -> {}",
+        format_bytes(size_before),
+        format_bytes(size_after)
+    );
+
+    tracing::info!(
+        messages = summary.messages,
+        sessions = summary.sessions,
+        size_before,
+        size_after,
+        "aiobscura-sync prune complete"
+    );
+    Ok(())
+}
+
+/// Move stored images to the blob store and compress stored raw data,
+/// optionally with a newly trained dictionary, and reclaim the space
+fn run_compact(db: &Database, train_dictionary: bool) -> Result<()> {
+    let size_before = db
+        .get_database_size()
+        .context("failed to read database size")?;
+
+    let images = db
+        .externalize_stored_images()
+        .context("failed to move images to the blob store")?;
+    if images > 0 {
+        println!(
+            "\nMoved the images of {} message(s) to the blob store",
+            images
+        );
+    }
+
+    let mut recompress = false;
+    if train_dictionary {
+        println!("\nTraining compression dictionary...");
+        match db
+            .train_compression_dictionary()
+            .context("failed to train compression dictionary")?
+        {
+            Some(id) => {
+                println!("Trained dictionary {}", id);
+                recompress = true;
+            }
+            None => println!("Too few messages to train a dictionary, compressing without one"),
+        }
+    }
+
+    let summary = db
+        .compress_messages(recompress)
+        .context("failed to compress messages")?;
+    println!(
+        "\nCompressed {} message(s): {} -> {}",
+        summary.rows,
+        format_bytes(summary.bytes_before),
+        format_bytes(summary.bytes_after)
+    );
+    println!("Vacuuming database...");
+    db.vacuum().context("failed to vacuum database")?;
+    let size_after = db
+        .get_database_size()
+        .context("failed to read database size")?;
+    println!(
+        "Database size: {} -> {}",
+        format_bytes(size_before),
+        format_bytes(size_after)
+    );
+
+    tracing::info!(
+        rows = summary.rows,
+        size_before,
+        size_after,
+        "aiobscura-sync compact complete"
+    );
+    Ok(())
+}
+
+/// How often watch mode wakes up without file events, to notice Ctrl+C and
+/// run time-based work (reconciliation, inactivity triggers).
+const WATCH_WAKE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often inactivity-based analytics triggers run in watch mode.
+const INACTIVITY_TRIGGER_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Run continuous watch mode
+///
+/// Source directories are watched for file system events, and only the files
+/// reported changed are synced. A full sync runs at startup and then every
+/// `--reconcile` seconds to catch anything the watcher missed (new assistant
+/// directories, dropped events, sources outside the watched paths).
 fn run_watch_mode(
     coordinator: &IngestCoordinator,
     config: &Config,
@@ -246,29 +678,83 @@ fn run_watch_mode(
     })
     .context("failed to set Ctrl+C handler")?;
 
-    let poll_duration = Duration::from_millis(args.poll);
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(Duration::from_millis(args.debounce), tx)
+        .context("failed to create file watcher")?;
+    let mut unwatched = watch_paths(debouncer.watcher(), coordinator.watch_paths());
+
+    let reconcile_interval = Duration::from_secs(args.reconcile.max(1));
 
     println!(
-        "Watch mode active (poll every {}ms). Press Ctrl+C to stop.",
-        args.poll
+        "Watch mode active (full sync every {}s). Press Ctrl+C to stop.",
+        reconcile_interval.as_secs()
     );
     println!();
 
     let mut iteration = 0u64;
-    let mut since_last_inactivity_trigger = Duration::from_secs(60);
+    let mut last_reconcile: Option<Instant> = None;
+    let mut last_inactivity_trigger: Option<Instant> = None;
 
     while running.load(Ordering::SeqCst) {
-        iteration += 1;
+        let mut reconcile = last_reconcile
+            .map(|at| at.elapsed() >= reconcile_interval)
+            .unwrap_or(true);
+        let mut changed = BTreeSet::new();
+
+        if !reconcile {
+            let first = match rx.recv_timeout(WATCH_WAKE_INTERVAL) {
+                Ok(events) => Some(events),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => bail!("file watcher stopped"),
+            };
+            for events in first.into_iter().chain(rx.try_iter()) {
+                match events {
+                    Ok(events) => changed.extend(
+                        events
+                            .iter()
+                            .filter_map(|event| coordinator.source_file_for(&event.path)),
+                    ),
+                    Err(e) => {
+                        // Events may have been dropped; fall back to a full sync
+                        tracing::warn!(error = %e, "File watcher error");
+                        reconcile = true;
+                    }
+                }
+            }
+        }
 
-        // Run sync (checkpoints ensure incremental parsing)
-        let result = coordinator
-            .sync_all_with_progress(|_current, _total, _path| {
-                // Silent progress in watch mode
-            })
-            .context("sync failed")?;
+        let result = if reconcile {
+            iteration += 1;
+            if !unwatched.is_empty() {
+                unwatched = watch_paths(debouncer.watcher(), unwatched);
+            }
+            last_reconcile = Some(Instant::now());
+            coordinator
+                .sync_all_with_progress(|_current, _total, _path| {
+                    // Silent progress in watch mode
+                })
+                .context("sync failed")?
+        } else if !changed.is_empty() {
+            iteration += 1;
+            let paths: Vec<PathBuf> = changed.into_iter().collect();
+            coordinator.sync_files(&paths).context("sync failed")?
+        } else {
+            SyncResult::default()
+        };
 
         publish_sync_sessions(config, publisher, &result);
 
+        for (path, error) in &result.errors {
+            tracing::warn!(path = %path.display(), error = %error, "Failed to sync file");
+        }
+        if result.records_failed > 0 {
+            let timestamp = chrono::Local::now().format("%H:%M:%S");
+            println!(
+                "[{}] {} record(s) failed to parse (see aiobscura-sync --failures)",
+                timestamp, result.records_failed
+            );
+        }
+
         // Only print if there were changes
         if result.messages_inserted > 0 {
             let timestamp = chrono::Local::now().format("%H:%M:%S");
@@ -305,22 +791,20 @@ fn run_watch_mode(
 
             tracing::info!(
                 iteration,
+                full_sync = reconcile,
                 files_processed = result.files_processed,
                 messages_inserted = result.messages_inserted,
                 "watch sync iteration"
             );
         }
 
-        let run_inactivity = since_last_inactivity_trigger >= Duration::from_secs(60);
+        let run_inactivity = last_inactivity_trigger
+            .map(|at| at.elapsed() >= INACTIVITY_TRIGGER_INTERVAL)
+            .unwrap_or(true);
         run_analytics_triggers(coordinator, config, &result, run_inactivity)?;
-        since_last_inactivity_trigger = if run_inactivity {
-            Duration::ZERO
-        } else {
-            since_last_inactivity_trigger.saturating_add(poll_duration)
-        };
-
-        // Sleep until next poll
-        thread::sleep(poll_duration);
+        if run_inactivity {
+            last_inactivity_trigger = Some(Instant::now());
+        }
     }
 
     println!("Watch mode stopped.");
@@ -329,6 +813,39 @@ fn run_watch_mode(
     Ok(())
 }
 
+/// Start watching each path, returning the ones that could not be watched
+/// (e.g. directories that don't exist yet) so they can be retried later.
+fn watch_paths(watcher: &mut dyn Watcher, paths: Vec<WatchPath>) -> Vec<WatchPath> {
+    paths
+        .into_iter()
+        .filter(|watch| {
+            let mode = if watch.recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            match watcher.watch(&watch.path, mode) {
+                Ok(()) => {
+                    tracing::debug!(
+                        path = %watch.path.display(),
+                        recursive = watch.recursive,
+                        "Watching source directory"
+                    );
+                    false
+                }
+                Err(e) => {
+                    tracing::debug!(
+                        path = %watch.path.display(),
+                        error = %e,
+                        "Cannot watch source directory yet; relying on full sync"
+                    );
+                    true
+                }
+            }
+        })
+        .collect()
+}
+
 /// Run automatic analytics triggers for updated or inactive sessions.
 fn run_analytics_triggers(
     coordinator: &IngestCoordinator,
@@ -472,6 +989,15 @@ fn print_sync_result(result: &aiobscura_core::ingest::SyncResult, verbose: u8) {
     println!("  Sessions updated: {}", result.sessions_updated);
     println!("  Messages inserted: {}", result.messages_inserted);
     println!("  Threads created:  {}", result.threads_created);
+    if result.records_failed > 0 {
+        println!(
+            "  Records failed:   {} (see aiobscura-sync --failures)",
+            result.records_failed
+        );
+    }
+    if result.hook_events > 0 {
+        println!("  Hook events:      {}", result.hook_events);
+    }
 
     // -v: Show per-file details, -vv: Show per-message details
     if verbose >= 1 {
@@ -512,6 +1038,86 @@ fn print_sync_result(result: &aiobscura_core::ingest::SyncResult, verbose: u8) {
     }
 }
 
+/// Number of recent failed records listed by `--failures -v`
+const RECENT_FAILURES_LIMIT: usize = 20;
+
+/// Print parse failures grouped by error
+fn print_parse_failures(
+    db: &Database,
+    assistant: Option<Assistant>,
+    agent: Option<&str>,
+    verbose: u8,
+) -> Result<()> {
+    let groups = db
+        .summarize_parse_failures(assistant, agent)
+        .context("failed to load parse failures")?;
+    if groups.is_empty() {
+        println!("\nNo parse failures recorded");
+        return Ok(());
+    }
+
+    let total: usize = groups.iter().map(|g| g.count).sum();
+    println!("\nParse failures ({}):", total);
+    println!(
+        "  {:>6}  {:>5}  {:<12}  ERROR",
+        "COUNT", "FILES", "ASSISTANT"
+    );
+    for group in &groups {
+        println!(
+            "  {:>6}  {:>5}  {:<12}  {}",
+            group.count,
+            group.files,
+            group.assistant.as_str(),
+            truncate(&group.error, 100)
+        );
+    }
+
+    if verbose >= 1 {
+        let failures = db
+            .list_parse_failures(assistant, agent, RECENT_FAILURES_LIMIT)
+            .context("failed to load parse failures")?;
+        println!("\nRecent failures:");
+        for failure in &failures {
+            let line = failure
+                .line_number
+                .map(|line| format!(":{}", line))
+                .unwrap_or_default();
+            println!(
+                "  {}{} @{} (parser v{})",
+                shorten_path(std::path::Path::new(&failure.source_file_path)),
+                line,
+                failure.byte_offset,
+                failure.parser_version
+            );
+            println!("    {}", failure.error);
+            println!("    {}", truncate(failure.raw_text.trim(), 120));
+        }
+    }
+
+    println!("\nRetry after a parser upgrade with: aiobscura-sync --reparse --failed");
+    Ok(())
+}
+
+/// ` [label@host]` for parsers reading an extra `[[agents.root]]`, empty for
+/// default roots.
+fn root_label(parser: &dyn AssistantParser) -> String {
+    match parser.source_root() {
+        Some(source) if source.label != "default" => {
+            format!(" [{}@{}]", source.label, source.host)
+        }
+        _ => String::new(),
+    }
+}
+
+/// Truncate `text` to at most `max` characters, marking the cut with `...`
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max.saturating_sub(3)).collect();
+    format!("{}...", cut)
+}
+
 /// Shorten a path for display by abbreviating the home directory
 fn shorten_path(path: &std::path::Path) -> String {
     if let Ok(home) = std::env::var("HOME") {
@@ -593,7 +1199,6 @@ fn publish_sync_sessions(
 mod tests {
     use super::*;
     use aiobscura_core::ingest::{FileSyncResult, MessageSummary, SkipReason};
-    use std::path::PathBuf;
 
     fn file_result(
         session_id: Option<&str>,
@@ -608,6 +1213,7 @@ mod tests {
             new_checkpoint: aiobscura_core::Checkpoint::ByteOffset { offset: 0 },
             is_new_session: false,
             warnings: vec![],
+            failed_records: 0,
             skip_reason: Some(SkipReason::NoNewContent),
             message_summaries: vec![MessageSummary {
                 role: "assistant".to_string(),
@@ -63,6 +63,8 @@ pub struct SessionRow {
     pub message_count: i64,
     /// Model name (if known)
     pub model_name: Option<String>,
+    /// Data root the session was read from, as `label@host` (if known)
+    pub source: Option<String>,
 }
 
 impl SessionRow {
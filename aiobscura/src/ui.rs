@@ -6,9 +6,10 @@ mod project;
 mod wrapped;
 
 use aiobscura_core::analytics::{TimePatterns, WrappedStats};
-use aiobscura_core::format::format_relative_time;
+use aiobscura_core::format::{format_bytes, format_relative_time};
 use aiobscura_core::{
     ActiveSession, Assistant, Message, MessageType, MessageWithContext, PlanStatus, ThreadType,
+    ToolCallOutcome,
 };
 use chrono::{DateTime, Local, Utc};
 use ratatui::{
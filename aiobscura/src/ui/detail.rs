@@ -322,9 +322,10 @@ fn build_session_analytics_lines(app: &App) -> Vec<Line<'static>> {
         ));
         line3_spans.push(Span::styled(
             format!(
-                " ({} in / {} out)",
+                " ({} in / {} out / {} cached)",
                 format_tokens(metrics.tokens_in),
-                format_tokens(metrics.tokens_out)
+                format_tokens(metrics.tokens_out),
+                format_tokens(metrics.tokens_cache_read + metrics.tokens_cache_write)
             ),
             Style::default().fg(Color::DarkGray),
         ));
@@ -371,6 +372,28 @@ fn build_session_analytics_lines(app: &App) -> Vec<Line<'static>> {
                 Style::default().fg(success_color),
             ));
         }
+        if metrics.compaction_count > 0 {
+            line4_spans.push(Span::raw("  "));
+            line4_spans.push(Span::styled(
+                "Compactions: ",
+                Style::default().fg(LABEL_COLOR),
+            ));
+            line4_spans.push(Span::styled(
+                format!("{}", metrics.compaction_count),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if let Some(latency_ms) = metrics.tool_latency_avg_ms {
+            line4_spans.push(Span::raw("  "));
+            line4_spans.push(Span::styled(
+                "Avg latency: ",
+                Style::default().fg(LABEL_COLOR),
+            ));
+            line4_spans.push(Span::styled(
+                format_duration_ms(latency_ms),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
         lines.push(Line::from(line4_spans));
     }
 
@@ -450,6 +473,14 @@ fn build_thread_analytics_lines(app: &App) -> Vec<Line<'static>> {
             Style::default().fg(Color::Red),
         ));
     }
+    if let Some(cost) = &app.thread_cost {
+        line2_spans.push(Span::raw("  "));
+        line2_spans.push(Span::styled("Cost: ", Style::default().fg(LABEL_COLOR)));
+        line2_spans.push(Span::styled(
+            cost.display(),
+            Style::default().fg(Color::Green),
+        ));
+    }
     lines.push(Line::from(line2_spans));
 
     // Line 3: Hot files for this thread
@@ -795,8 +826,22 @@ fn render_messages(frame: &mut Frame, app: &mut App, area: Rect) {
             )));
         }
 
-        let msg_lines = format_message(msg, idx + 1, total);
+        if app.selected_image == Some(idx) && app.scroll_to_selected_image {
+            app.scroll_offset = lines.len();
+            app.scroll_to_selected_image = false;
+        }
+
+        let msg_lines = format_message(msg, idx + 1, total, app.tool_outcomes.get(&msg.id));
         lines.extend(msg_lines);
+        if app.selected_image == Some(idx) {
+            lines.push(Line::from(vec![
+                Span::styled("  ▲ selected  ", Style::default().fg(Color::Yellow)),
+                Span::styled("o", Style::default().fg(Color::Yellow)),
+                Span::raw(" open  "),
+                Span::styled("s", Style::default().fg(Color::Yellow)),
+                Span::raw(" save"),
+            ]));
+        }
         lines.push(Line::raw("")); // Blank line after content
     }
 
@@ -838,19 +883,27 @@ fn render_messages(frame: &mut Frame, app: &mut App, area: Rect) {
 }
 
 /// Format a single message into display lines.
-fn format_message(msg: &Message, index: usize, total: usize) -> Vec<Line<'static>> {
+///
+/// `outcome` is the paired result of a tool call, if known.
+fn format_message(
+    msg: &Message,
+    index: usize,
+    total: usize,
+    outcome: Option<&ToolCallOutcome>,
+) -> Vec<Line<'static>> {
     let (icon, label, style) = match msg.message_type {
         MessageType::Prompt => ("💬", "Human", Style::default().fg(Color::Cyan).bold()),
         MessageType::Response => ("🤖", "Assistant", Style::default().fg(Color::Green)),
         MessageType::ToolCall => {
             let name = msg.tool_name.as_deref().unwrap_or("unknown");
-            return format_tool_message(name, msg, index, total);
+            return format_tool_message(name, msg, index, total, outcome);
         }
         MessageType::ToolResult => ("📋", "Result", Style::default().fg(Color::DarkGray)),
         MessageType::Error => ("❌", "Error", Style::default().fg(Color::Red)),
         MessageType::Plan => ("📝", "Plan", Style::default().fg(Color::Magenta)),
         MessageType::Summary => ("📊", "Summary", Style::default().fg(Color::Blue)),
         MessageType::Context => ("📎", "Context", Style::default().fg(Color::DarkGray)),
+        MessageType::Compaction => ("🗜", "Compaction", Style::default().fg(Color::Yellow)),
     };
 
     let mut lines = Vec::new();
@@ -902,6 +955,7 @@ fn format_tool_message(
     msg: &Message,
     index: usize,
     total: usize,
+    outcome: Option<&ToolCallOutcome>,
 ) -> Vec<Line<'static>> {
     let mut lines = Vec::new();
 
@@ -909,8 +963,17 @@ fn format_tool_message(
     let time_str = format_message_time(msg.emitted_at);
 
     let counter = format!("[{}/{}]", index, total);
-    // Calculate padding: icon (2) + space + "Tool: " (6) + tool_name + space + counter
-    let header_len = 2 + 1 + 6 + tool_name.chars().count() + 1 + counter.chars().count();
+    let outcome_span = outcome.map(format_tool_outcome);
+    // Calculate padding: icon (2) + space + "Tool: " (6) + tool_name + space + counter + outcome
+    let header_len = 2
+        + 1
+        + 6
+        + tool_name.chars().count()
+        + 1
+        + counter.chars().count()
+        + outcome_span
+            .as_ref()
+            .map_or(0, |span| span.content.chars().count());
     let target_width: usize = 50;
     let padding_needed = target_width.saturating_sub(header_len);
     let padding = " ".repeat(padding_needed);
@@ -926,6 +989,7 @@ fn format_tool_message(
             format!(" {}", counter),
             Style::default().fg(Color::DarkGray),
         ),
+        outcome_span.unwrap_or_default(),
         Span::raw(padding),
         Span::styled(time_str, Style::default().fg(Color::DarkGray)),
     ]));
@@ -948,6 +1012,19 @@ fn format_tool_message(
     lines
 }
 
+/// Format a tool call's outcome for its header: status and latency.
+fn format_tool_outcome(outcome: &ToolCallOutcome) -> Span<'static> {
+    let latency = outcome
+        .latency_ms
+        .map(|ms| format_duration_ms(ms.max(0)))
+        .unwrap_or_default();
+    match outcome.is_error {
+        Some(false) => Span::styled(format!(" ✓ {}", latency), Style::default().fg(Color::Green)),
+        Some(true) => Span::styled(format!(" ✗ {}", latency), Style::default().fg(Color::Red)),
+        None => Span::styled(" … no result", Style::default().fg(Color::DarkGray)),
+    }
+}
+
 /// Render the footer for list view.
 pub(super) fn render_list_footer(frame: &mut Frame, app: &App, area: Rect) {
     let thread_count = app.threads.len();
@@ -990,7 +1067,7 @@ pub(super) fn render_list_footer(frame: &mut Frame, app: &App, area: Rect) {
 fn render_detail_footer(frame: &mut Frame, app: &App, area: Rect) {
     let msg_count = app.messages.len();
 
-    let footer = Line::from(vec![
+    let mut footer_spans = vec![
         Span::styled(" Esc", Style::default().fg(Color::Yellow)),
         Span::raw(" back  "),
         Span::styled("p", Style::default().fg(Color::Yellow)),
@@ -1001,12 +1078,23 @@ fn render_detail_footer(frame: &mut Frame, app: &App, area: Rect) {
         Span::raw(" top/bottom  "),
         Span::styled("u/d", Style::default().fg(Color::Yellow)),
         Span::raw(" page up/down  "),
+        Span::styled("i", Style::default().fg(Color::Yellow)),
+        Span::raw(" next image  "),
         Span::raw("│ "),
         Span::styled(
             format!("{} messages", msg_count),
             Style::default().fg(Color::DarkGray),
         ),
-    ]);
+    ];
+    if let Some(status) = &app.image_status {
+        footer_spans.push(Span::raw(" │ "));
+        footer_spans.push(Span::styled(
+            status.clone(),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+
+    let footer = Line::from(footer_spans);
 
     frame.render_widget(Paragraph::new(footer), area);
 }
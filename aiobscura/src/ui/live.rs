@@ -281,6 +281,7 @@ fn render_environment_health_panel(frame: &mut Frame, health: &EnvironmentHealth
     let known_assistants = [
         (Assistant::ClaudeCode, "Claude"),
         (Assistant::Codex, "Codex"),
+        (Assistant::Gemini, "Gemini"),
     ];
 
     for (assistant, name) in known_assistants {
@@ -313,23 +314,6 @@ fn render_environment_health_panel(frame: &mut Frame, health: &EnvironmentHealth
     frame.render_widget(paragraph, inner);
 }
 
-/// Format bytes as human-readable size (e.g., "42 MB").
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
 /// Render the active sessions panel showing threads with recent activity.
 fn render_active_sessions_panel(frame: &mut Frame, app: &App, area: Rect) {
     let mut lines: Vec<Line> = Vec::new();
@@ -443,6 +427,8 @@ fn format_active_session_line(session: &ActiveSession, is_child: bool) -> Line<'
         aiobscura_core::Assistant::Codex => "Codex",
         aiobscura_core::Assistant::Aider => "Aider",
         aiobscura_core::Assistant::Cursor => "Cursor",
+        aiobscura_core::Assistant::Gemini => "Gemini",
+        aiobscura_core::Assistant::Custom => "Custom",
     };
 
     // Message count
@@ -549,6 +535,8 @@ fn format_live_message(msg: &MessageWithContext) -> Line<'static> {
         Assistant::Codex => ("CX", Color::Yellow),
         Assistant::Aider => ("AI", Color::Magenta),
         Assistant::Cursor => ("CU", Color::White),
+        Assistant::Gemini => ("GM", Color::Blue),
+        Assistant::Custom => ("CT", Color::Gray),
     };
 
     let context_str = format!("[{}/{}]", msg.project_name, msg.thread_name);
@@ -75,7 +75,7 @@ fn render_project_table(frame: &mut Frame, app: &mut App, area: Rect) {
         return;
     }
 
-    let header_cells = ["Project", "Path", "Sessions", "Tokens", "Active"]
+    let header_cells = ["Project", "Path", "Sessions", "Tokens", "Cost", "Active"]
         .into_iter()
         .map(|h| Cell::from(h).style(Style::default().fg(Color::Yellow).bold()));
     let header = Row::new(header_cells).height(1);
@@ -109,6 +109,7 @@ fn render_project_table(frame: &mut Frame, app: &mut App, area: Rect) {
             Cell::from(path_display).style(Style::default().fg(Color::DarkGray)),
             Cell::from(project.session_count.to_string()),
             Cell::from(tokens_display).style(Style::default().fg(WRAPPED_CYAN)),
+            Cell::from(project.cost.display()).style(Style::default().fg(Color::Green)),
             Cell::from(active_display),
         ])
     });
@@ -118,6 +119,7 @@ fn render_project_table(frame: &mut Frame, app: &mut App, area: Rect) {
         Constraint::Length(32), // Path
         Constraint::Length(10), // Sessions
         Constraint::Length(10), // Tokens
+        Constraint::Length(10), // Cost
         Constraint::Length(12), // Active
     ];
 
@@ -195,16 +197,20 @@ fn render_project_overview(
         ),
     ]));
 
-    // Row 4: Tokens | Agents | Plans
+    // Row 4: Tokens | Cost | Agents | Plans
     let tokens_display = format!(
-        "{} in / {} out",
+        "{} in / {} out / {} cached",
         format_tokens(stats.tokens_in),
-        format_tokens(stats.tokens_out)
+        format_tokens(stats.tokens_out),
+        format_tokens(stats.tokens_cache_read + stats.tokens_cache_write)
     );
     lines.push(Line::from(vec![
         Span::styled("Tokens: ", Style::default().fg(LABEL_COLOR)),
         Span::styled(tokens_display, Style::default().fg(WRAPPED_CYAN)),
         Span::raw("    "),
+        Span::styled("Cost: ", Style::default().fg(LABEL_COLOR)),
+        Span::styled(stats.cost.display(), Style::default().fg(Color::Green)),
+        Span::raw("    "),
         Span::styled("Agents: ", Style::default().fg(LABEL_COLOR)),
         Span::styled(
             stats.agents_spawned.to_string(),
@@ -472,6 +478,10 @@ fn render_project_detail_footer(frame: &mut Frame, sub_tab: ProjectSubTab, area:
             spans.push(Span::raw(" open  "));
             spans.push(Span::styled("j/k", Style::default().fg(Color::Yellow)));
             spans.push(Span::raw(" nav  "));
+            if sub_tab == ProjectSubTab::Sessions {
+                spans.push(Span::styled("s", Style::default().fg(Color::Yellow)));
+                spans.push(Span::raw(" source  "));
+            }
         }
         ProjectSubTab::Files => {
             spans.push(Span::styled("j/k", Style::default().fg(Color::Yellow)));
@@ -570,6 +580,7 @@ fn render_project_sessions_content(frame: &mut Frame, app: &mut App, area: Rect)
         "Threads",
         "Msgs",
         "Model",
+        "Source",
     ]
     .into_iter()
     .map(|h| Cell::from(h).style(Style::default().fg(Color::Yellow).bold()));
@@ -605,6 +616,8 @@ fn render_project_sessions_content(frame: &mut Frame, app: &mut App, area: Rect)
             Cell::from(session.thread_count.to_string()).style(thread_style),
             Cell::from(session.message_count.to_string()).style(msg_style),
             Cell::from(model_display).style(Style::default().fg(Color::DarkGray)),
+            Cell::from(session.source.clone().unwrap_or_else(|| "—".to_string()))
+                .style(Style::default().fg(Color::DarkGray)),
         ])
     });
 
@@ -615,6 +628,7 @@ fn render_project_sessions_content(frame: &mut Frame, app: &mut App, area: Rect)
         Constraint::Length(8),  // Threads
         Constraint::Length(6),  // Msgs
         Constraint::Min(10),    // Model
+        Constraint::Min(12),    // Source
     ];
 
     let session_count = app.project_sessions.len();
@@ -631,7 +645,13 @@ fn render_project_sessions_content(frame: &mut Frame, app: &mut App, area: Rect)
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(Style::default().fg(BORDER_PROJECT))
-                .title(format!(" Sessions ({}/{}) ", selected, session_count))
+                .title(match &app.project_session_source_filter {
+                    Some(source) => format!(
+                        " Sessions ({}/{}) · source: {} ",
+                        selected, session_count, source
+                    ),
+                    None => format!(" Sessions ({}/{}) ", selected, session_count),
+                })
                 .title_style(Style::default().fg(BORDER_PROJECT).bold()),
         )
         .row_highlight_style(
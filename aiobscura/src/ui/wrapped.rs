@@ -227,6 +227,14 @@ fn render_wrapped_title_card(frame: &mut Frame, stats: &WrappedStats, area: Rect
                 Style::default().fg(WRAPPED_WHITE).bold(),
             ),
         ]));
+
+        lines.push(Line::from(vec![
+            Span::styled("   ◆ Cost      ", Style::default().fg(WRAPPED_DIM)),
+            Span::styled(
+                stats.totals.cost.display(),
+                Style::default().fg(WRAPPED_LIME).bold(),
+            ),
+        ]));
     }
 
     let block = Block::default()
@@ -1,4 +1,5 @@
-use aiobscura_core::{Assistant, Database, SessionFilter};
+use aiobscura_core::{Assistant, Database, Project, SessionFilter};
+use chrono::Utc;
 use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -57,6 +58,7 @@ fn seed_codex_fixture(home: &Path) {
 
 fn run_bin(env: &CliTestEnv, bin_name: &str, args: &[&str]) -> Output {
     let bin_path = match bin_name {
+        "aiobscura" => PathBuf::from(assert_cmd::cargo::cargo_bin!("aiobscura")),
         "aiobscura-sync" => PathBuf::from(assert_cmd::cargo::cargo_bin!("aiobscura-sync")),
         "aiobscura-analyze" => PathBuf::from(assert_cmd::cargo::cargo_bin!("aiobscura-analyze")),
         "aiobscura-collector" => {
@@ -159,3 +161,278 @@ fn analyze_and_collector_work_on_synced_database() {
     assert!(collector_stdout.contains("Catsyphon Collector Configuration"));
     assert!(collector_stdout.contains("Enabled:         false"));
 }
+
+#[test]
+fn hook_events_are_spooled_and_drained_by_sync() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let env = CliTestEnv::new();
+
+    let input = r#"{"session_id":"hook-session","hook_event_name":"Notification","message":"Claude is waiting for your input"}"#;
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!("aiobscura"))
+        .arg("hook")
+        .env("HOME", &env.home)
+        .env("XDG_DATA_HOME", &env.xdg_data)
+        .env("XDG_CONFIG_HOME", &env.xdg_config)
+        .env("XDG_STATE_HOME", &env.xdg_state)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to execute aiobscura hook");
+    child
+        .stdin
+        .take()
+        .expect("missing stdin")
+        .write_all(input.as_bytes())
+        .expect("failed to write hook input");
+    let output = child.wait_with_output().expect("aiobscura hook failed");
+    assert_success("aiobscura", &["hook"], &output);
+    assert!(output.stdout.is_empty(), "hook must not print to stdout");
+
+    let spool = env.xdg_state.join("aiobscura/hooks.jsonl");
+    assert!(spool.exists(), "event should be spooled without a listener");
+
+    let output = run_bin(&env, "aiobscura-sync", &[]);
+    assert_success("aiobscura-sync", &[], &output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Hook events:      1"));
+    assert!(!spool.exists(), "sync should drain the spool");
+
+    let db = Database::open(&env.db_path()).expect("failed to open db");
+    let events = db
+        .get_session_hook_events("hook-session")
+        .expect("failed to load hook events");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_name, "Notification");
+}
+
+#[test]
+fn merge_projects_moves_sessions_into_target_project() {
+    let env = CliTestEnv::new();
+
+    let output = run_bin(&env, "aiobscura-sync", &[]);
+    assert_success("aiobscura-sync", &[], &output);
+
+    let db = Database::open(&env.db_path()).expect("failed to open db");
+    let sessions = db
+        .list_sessions(&SessionFilter::default())
+        .expect("failed to list sessions");
+    let from = sessions[0].project_id.clone().expect("session project");
+    db.upsert_project(&Project {
+        id: "target-project".to_string(),
+        path: PathBuf::from("/src/target"),
+        name: Some("target".to_string()),
+        created_at: Utc::now(),
+        last_activity_at: None,
+        metadata: serde_json::json!({}),
+    })
+    .expect("failed to insert target project");
+
+    let args = ["merge-projects", from.as_str(), "/src/target"];
+    let output = run_bin(&env, "aiobscura", &args);
+    assert_success("aiobscura", &args, &output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 session(s) moved"));
+
+    assert!(db.get_project(&from).expect("query project").is_none());
+    let session = db
+        .get_session(&sessions[0].id)
+        .expect("query session")
+        .expect("session");
+    assert_eq!(session.project_id.as_deref(), Some("target-project"));
+
+    let output = run_bin(
+        &env,
+        "aiobscura",
+        &["merge-projects", "missing", "target-project"],
+    );
+    assert!(!output.status.success(), "unknown project should fail");
+}
+
+#[test]
+fn import_labels_sessions_and_keeps_local_roots_untouched() {
+    let env = CliTestEnv::new();
+    let snapshot = env.home.join("Downloads/alice-snapshot");
+    seed_codex_fixture(&snapshot);
+
+    let snapshot_arg = snapshot.to_string_lossy().into_owned();
+    let args = ["--import", snapshot_arg.as_str(), "--label", "alice"];
+    let output = run_bin(&env, "aiobscura-sync", &args);
+    assert_success("aiobscura-sync", &args, &output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Importing"));
+
+    let db = Database::open(&env.db_path()).expect("failed to open db");
+    let sessions = db
+        .list_sessions(&SessionFilter::default())
+        .expect("failed to list sessions");
+    assert_eq!(sessions.len(), 1, "expected one imported session");
+    assert_eq!(sessions[0].source_root(), Some("import"));
+    assert_eq!(sessions[0].source_host(), Some("alice"));
+
+    let files = db.list_source_files().expect("failed to list source files");
+    assert!(!files.is_empty());
+    assert!(
+        files.iter().all(|file| file.path.starts_with(&snapshot)),
+        "import should only check in files from the snapshot"
+    );
+
+    let output = run_bin(&env, "aiobscura-sync", &["--label", "alice"]);
+    assert!(!output.status.success(), "--label requires --import");
+}
+
+#[test]
+fn search_finds_synced_messages() {
+    let env = CliTestEnv::new();
+
+    let output = run_bin(&env, "aiobscura-sync", &[]);
+    assert_success("aiobscura-sync", &[], &output);
+
+    let args = ["search", "list", "files", "--type", "prompt"];
+    let output = run_bin(&env, "aiobscura", &args);
+    assert_success("aiobscura", &args, &output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("session 019ab86e-1e83-75b0-b2d7-d335492e7026"),
+        "expected the codex session in:\n{stdout}"
+    );
+    assert!(stdout.contains("«list» the «files»"), "{stdout}");
+    assert_eq!(stdout.matches("session ").count(), 1, "{stdout}");
+
+    let output = run_bin(&env, "aiobscura", &["search", "kubernetes"]);
+    assert_success("aiobscura", &["search", "kubernetes"], &output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No messages match"));
+}
+
+#[test]
+fn prune_drops_old_raw_data_and_keeps_messages() {
+    let env = CliTestEnv::new();
+
+    let output = run_bin(&env, "aiobscura-sync", &[]);
+    assert_success("aiobscura-sync", &[], &output);
+
+    let output = run_bin(&env, "aiobscura-sync", &["--prune"]);
+    assert!(!output.status.success(), "--prune requires a policy");
+
+    let config_dir = env.xdg_config.join("aiobscura");
+    fs::create_dir_all(&config_dir).expect("failed to create config dir");
+    fs::write(config_dir.join("config.toml"), "[retention]\ndays = 30\n")
+        .expect("failed to write config");
+    let session_id = "019ab86e-1e83-75b0-b2d7-d335492e7026";
+
+    let args = ["--prune", "--dry-run"];
+    let output = run_bin(&env, "aiobscura-sync", &args);
+    assert_success("aiobscura-sync", &args, &output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("in 1 session(s) from 1 source file(s)"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("could be freed"), "{stdout}");
+    {
+        let db = Database::open(&env.db_path()).expect("failed to open database");
+        let messages = db.get_session_messages(session_id, 100).unwrap();
+        assert!(messages.iter().all(|m| !m.raw_data.is_null()));
+    }
+
+    let output = run_bin(&env, "aiobscura-sync", &["--prune"]);
+    assert_success("aiobscura-sync", &["--prune"], &output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Pruned raw data of"), "{stdout}");
+    assert!(stdout.contains("Database size:"), "{stdout}");
+
+    let db = Database::open(&env.db_path()).expect("failed to open database");
+    let messages = db.get_session_messages(session_id, 100).unwrap();
+    assert!(!messages.is_empty());
+    assert!(messages.iter().all(|m| m.raw_data.is_null()));
+    assert!(messages.iter().all(|m| m.tool_result.is_none()));
+    assert!(messages.iter().any(|m| m.content.is_some()));
+
+    // Pruned files are neither reparsed nor ingested again
+    let args = ["--reparse", "--dry-run"];
+    let output = run_bin(&env, "aiobscura-sync", &args);
+    assert_success("aiobscura-sync", &args, &output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Would reparse 0 file(s)"));
+
+    let output = run_bin(&env, "aiobscura-sync", &[]);
+    assert_success("aiobscura-sync", &[], &output);
+    assert_eq!(
+        db.get_session_messages(session_id, 100).unwrap().len(),
+        messages.len()
+    );
+}
+
+#[test]
+fn compact_keeps_messages_readable() {
+    let env = CliTestEnv::new();
+
+    let output = run_bin(&env, "aiobscura-sync", &[]);
+    assert_success("aiobscura-sync", &[], &output);
+    let session_id = "019ab86e-1e83-75b0-b2d7-d335492e7026";
+    let before = {
+        let db = Database::open(&env.db_path()).expect("failed to open database");
+        db.get_session_messages(session_id, 100).unwrap()
+    };
+
+    let args = ["--compact", "--train-dictionary"];
+    let output = run_bin(&env, "aiobscura-sync", &args);
+    assert_success("aiobscura-sync", &args, &output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Too few messages to train"), "{stdout}");
+    assert!(stdout.contains("Database size:"), "{stdout}");
+
+    let db = Database::open(&env.db_path()).expect("failed to open database");
+    let after = db.get_session_messages(session_id, 100).unwrap();
+    assert_eq!(after.len(), before.len());
+    for (a, b) in after.iter().zip(&before) {
+        assert_eq!(a.raw_data, b.raw_data);
+        assert_eq!(a.tool_result, b.tool_result);
+    }
+
+    let output = run_bin(&env, "aiobscura-sync", &["--train-dictionary"]);
+    assert!(
+        !output.status.success(),
+        "--train-dictionary requires --compact"
+    );
+}
+
+#[test]
+fn export_writes_dataset_and_appends_incrementally() {
+    let env = CliTestEnv::new();
+
+    let output = run_bin(&env, "aiobscura-sync", &[]);
+    assert_success("aiobscura-sync", &[], &output);
+
+    let dir = env.home.join("export");
+    let dir_arg = dir.to_str().expect("temp path is UTF-8");
+    let args = ["export", dir_arg, "--format", "csv", "--incremental"];
+    let output = run_bin(&env, "aiobscura", &args);
+    assert_success("aiobscura", &args, &output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Exported part 1 (csv)"));
+
+    let messages = fs::read_to_string(dir.join("messages/part-00001.csv")).unwrap();
+    let mut lines = messages.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("id,session_id,thread_id,seq,"));
+    assert!(lines.count() > 0);
+    let sessions = fs::read_to_string(dir.join("sessions/part-00001.csv")).unwrap();
+    assert!(sessions.contains("019ab86e-1e83-75b0-b2d7-d335492e7026"));
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("manifest.json")).unwrap()).unwrap();
+    assert_eq!(manifest["schema_version"], 1);
+    assert!(manifest["watermark"]["message_id"].as_i64().unwrap() > 0);
+
+    // Nothing synced since: the next part is empty
+    let output = run_bin(&env, "aiobscura", &args);
+    assert_success("aiobscura", &args, &output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Exported part 2 (csv)"));
+    assert!(!dir.join("messages/part-00002.csv").exists());
+
+    let args = ["export", dir_arg, "--format", "csv"];
+    let output = run_bin(&env, "aiobscura", &args);
+    assert!(
+        !output.status.success(),
+        "a full export needs a new directory"
+    );
+}